@@ -0,0 +1,38 @@
+//! Deterministic datasets shared by the benchmarks.
+
+use std::fmt::Write;
+
+/// Builds a CSV input of `rows` transactions spread over `clients` clients. Every
+/// `dispute_every`-th row disputes the previous deposit, and every other dispute is
+/// resolved, so dispute lookups hit stored deposits. There are no chargebacks: locked
+/// accounts would turn the rest of the run into rejections.
+pub fn dataset(rows: u32, clients: u16, dispute_every: u32) -> String {
+    let mut csv = String::from("type,client,tx,amount\n");
+    let mut last_deposit = None;
+    for tx in 1..=rows {
+        let client = (tx % u32::from(clients)) as u16 + 1;
+        match last_deposit {
+            Some((client, deposit)) if tx % dispute_every == 0 => {
+                writeln!(csv, "dispute,{client},{deposit},").unwrap();
+                if deposit % 2 == 0 {
+                    writeln!(csv, "resolve,{client},{deposit},").unwrap();
+                }
+                last_deposit = None;
+            }
+            _ if tx % 5 == 0 => {
+                writeln!(csv, "withdrawal,{client},{tx},1.5").unwrap();
+            }
+            _ => {
+                writeln!(
+                    csv,
+                    "deposit,{client},{tx},{}.{:04}",
+                    tx % 1000,
+                    tx % 10_000
+                )
+                .unwrap();
+                last_deposit = Some((client, tx));
+            }
+        }
+    }
+    csv
+}
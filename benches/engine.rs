@@ -0,0 +1,135 @@
+//! Engine throughput: single transactions per type, dispute-heavy streams and
+//! end-to-end runs (parse, process, write) over generated datasets.
+
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use rust_coding_test::{
+    domain::{
+        Authorization, Capture, Chargeback, Deposit, Dispute, Resolve, Transaction, Void,
+        Withdrawal,
+    },
+    engine::PaymentsEngine,
+    output, parsing,
+};
+use rust_decimal::dec;
+
+mod common;
+
+fn parse(input: &str) -> Vec<Transaction> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+    parsing::deserialize_csv(&mut rdr).collect()
+}
+
+/// One client with a deposit (tx 1), a disputed deposit (tx 2) and an authorization (tx 3).
+fn prepared_engine() -> PaymentsEngine {
+    let mut engine = PaymentsEngine::new();
+    engine.process_transactions(
+        [
+            Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+                .unwrap()
+                .into(),
+            Deposit::try_new(1.into(), 2.into(), dec!(10.0))
+                .unwrap()
+                .into(),
+            Dispute::new(1.into(), 2.into()).into(),
+            Authorization::try_new(1.into(), 3.into(), dec!(5.0))
+                .unwrap()
+                .into(),
+        ]
+        .into_iter(),
+    );
+    engine
+}
+
+/// Applies one transaction of each type to a fresh clone of [`prepared_engine`].
+/// Only the `apply` call is measured.
+fn apply(c: &mut Criterion) {
+    let cases: [(&str, Transaction); 8] = [
+        (
+            "deposit",
+            Deposit::try_new(1.into(), 10.into(), dec!(1.0))
+                .unwrap()
+                .into(),
+        ),
+        (
+            "withdrawal",
+            Withdrawal::try_new(1.into(), 10.into(), dec!(1.0))
+                .unwrap()
+                .into(),
+        ),
+        ("dispute", Dispute::new(1.into(), 1.into()).into()),
+        ("resolve", Resolve::new(1.into(), 2.into()).into()),
+        ("chargeback", Chargeback::new(1.into(), 2.into()).into()),
+        (
+            "authorization",
+            Authorization::try_new(1.into(), 10.into(), dec!(1.0))
+                .unwrap()
+                .into(),
+        ),
+        ("capture", Capture::new(1.into(), 3.into()).into()),
+        ("void", Void::new(1.into(), 3.into()).into()),
+    ];
+    let engine = prepared_engine();
+    let mut group = c.benchmark_group("apply");
+    group.throughput(Throughput::Elements(1));
+    for (name, transaction) in cases {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || (engine.clone(), transaction.clone()),
+                |(mut engine, transaction)| engine.apply(transaction),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Whole streams through the engine, from deposit-only to mostly disputes.
+fn process(c: &mut Criterion) {
+    let mut group = c.benchmark_group("process");
+    for (name, dispute_every) in [("few_disputes", 100), ("dispute_heavy", 2)] {
+        let transactions = parse(&common::dataset(100_000, 1_000, dispute_every));
+        group.throughput(Throughput::Elements(transactions.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || transactions.clone(),
+                |transactions| {
+                    let mut engine = PaymentsEngine::new();
+                    engine.process_transactions(transactions.into_iter());
+                    engine
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(format!("{name}_batch"), |b| {
+            b.iter(|| {
+                let mut engine = PaymentsEngine::new();
+                engine.process_batch(&transactions);
+                engine
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Parse, process and write, as the CLI does.
+fn end_to_end(c: &mut Criterion) {
+    let input = common::dataset(100_000, 1_000, 10);
+    let mut group = c.benchmark_group("end_to_end");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("single_tenant", |b| {
+        b.iter(|| {
+            let mut rdr = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(input.as_bytes());
+            let mut engine = PaymentsEngine::new();
+            engine.process_transactions(parsing::deserialize_csv(&mut rdr));
+            output::print_accounts(engine.client_accounts(), std::io::sink()).unwrap();
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, apply, process, end_to_end);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+//! Parsing throughput of the serde and byte-record pipelines.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use rust_coding_test::parsing::{self, ByteRecordParser};
+
+mod common;
+
+fn reader(input: &str) -> csv::Reader<&[u8]> {
+    csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes())
+}
+
+fn parse(c: &mut Criterion) {
+    let input = common::dataset(100_000, 1_000, 10);
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+
+    group.bench_function("serde", |b| {
+        b.iter(|| parsing::deserialize_csv(&mut reader(&input)).count())
+    });
+    group.bench_function("byte_records", |b| {
+        b.iter(|| ByteRecordParser::new(&mut reader(&input)).count())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
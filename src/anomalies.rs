@@ -0,0 +1,209 @@
+//! Anomaly detection for the `anomalies` subcommand: flags statistical outliers while a
+//! run is processed, for the fraud team to review.
+//!
+//! Three kinds are detected, each with its own thresholds in [`AnomalyThresholds`]:
+//! - large deposits, more than a number of standard deviations above the mean of the
+//!   deposits applied before them;
+//! - balance swings, a single transaction moving a client's total by more than a fraction
+//!   of what it was before (in either direction);
+//! - dispute bursts, several disputes of one client within a window of transactions.
+//!
+//! Inputs carry no timestamps, so windows are measured in transactions applied.
+
+use std::collections::{HashMap, VecDeque};
+
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use serde::Serialize;
+
+use crate::{
+    domain::{ClientId, Transaction},
+    engine::{ApplyTransactions, ClientHasher, EngineConfig, Outcome, PaymentsEngine},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnomalyThresholds {
+    /// Standard deviations above the mean for a deposit to count as large.
+    pub large_deposit_sigmas: f64,
+    /// Deposits seen before large deposits are flagged, so the mean is meaningful.
+    pub large_deposit_min_samples: u64,
+    /// Change of a client's total, as a fraction of the total before, that counts as a swing.
+    /// Accounts with no funds before the transaction are skipped.
+    pub balance_swing_ratio: Decimal,
+    /// Disputes of one client within `dispute_window` that count as a burst.
+    pub dispute_burst: usize,
+    /// Window of a dispute burst, in transactions applied.
+    pub dispute_window: u64,
+}
+
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            large_deposit_sigmas: 4.0,
+            large_deposit_min_samples: 30,
+            balance_swing_ratio: Decimal::new(9, 1),
+            dispute_burst: 3,
+            dispute_window: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    LargeDeposit,
+    BalanceSwing,
+    DisputeBurst,
+}
+
+/// One flagged transaction, serialized as a row of the anomaly report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Anomaly {
+    /// Position of the transaction among those applied, starting at 1.
+    pub sequence: u64,
+    pub client: ClientId,
+    pub kind: AnomalyKind,
+    /// The deposit amount, the change of the total, or the number of disputes in the window.
+    pub value: Decimal,
+}
+
+/// Running mean and variance (Welford's algorithm) of the deposits seen so far.
+#[derive(Debug, Clone, Default)]
+struct DepositStats {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl DepositStats {
+    fn add(&mut self, amount: f64) {
+        self.count += 1;
+        let delta = amount - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (amount - self.mean);
+    }
+    fn std_dev(&self) -> f64 {
+        (self.m2 / self.count as f64).sqrt()
+    }
+}
+
+/// Runs a [`PaymentsEngine`] and records the anomalies of the transactions it applies.
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    engine: PaymentsEngine,
+    thresholds: AnomalyThresholds,
+    applied: u64,
+    deposits: DepositStats,
+    /// Sequence numbers of each client's disputes within the current window.
+    recent_disputes: HashMap<ClientId, VecDeque<u64>, ClientHasher>,
+    anomalies: Vec<Anomaly>,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: EngineConfig, thresholds: AnomalyThresholds) -> Self {
+        Self {
+            engine: PaymentsEngine::with_config(config),
+            thresholds,
+            applied: 0,
+            deposits: DepositStats::default(),
+            recent_disputes: HashMap::default(),
+            anomalies: Vec::new(),
+        }
+    }
+    pub fn engine(&self) -> &PaymentsEngine {
+        &self.engine
+    }
+    /// Anomalies in the order they were detected.
+    pub fn anomalies(&self) -> &[Anomaly] {
+        &self.anomalies
+    }
+
+    fn total(&self, client: ClientId) -> Decimal {
+        self.engine
+            .client_accounts()
+            .get(client)
+            .map(|account| account.balance().total())
+            .unwrap_or_default()
+    }
+    /// Compares against the deposits before this one, then adds it to the statistics.
+    fn check_deposit(&mut self, client: ClientId, amount: Decimal) {
+        let value = amount.to_f64().unwrap_or(f64::MAX);
+        let stats = &self.deposits;
+        if stats.count >= self.thresholds.large_deposit_min_samples
+            && value > stats.mean + self.thresholds.large_deposit_sigmas * stats.std_dev()
+        {
+            self.flag(client, AnomalyKind::LargeDeposit, amount);
+        }
+        self.deposits.add(value);
+    }
+    /// Flags the dispute that completes a burst, then starts counting the next one.
+    fn check_disputes(&mut self, client: ClientId) {
+        let disputes = self.recent_disputes.entry(client).or_default();
+        disputes.push_back(self.applied);
+        while let Some(&oldest) = disputes.front()
+            && self.applied - oldest >= self.thresholds.dispute_window
+        {
+            disputes.pop_front();
+        }
+        if disputes.len() >= self.thresholds.dispute_burst {
+            let count = disputes.len();
+            disputes.clear();
+            self.flag(client, AnomalyKind::DisputeBurst, Decimal::from(count));
+        }
+    }
+    fn flag(&mut self, client: ClientId, kind: AnomalyKind, value: Decimal) {
+        self.anomalies.push(Anomaly {
+            sequence: self.applied,
+            client,
+            kind,
+            value,
+        });
+    }
+}
+
+impl ApplyTransactions for AnomalyDetector {
+    type Transaction = Transaction;
+
+    /// Applies `transaction`, checking it for anomalies if it was applied.
+    fn apply(&mut self, transaction: Transaction) -> Outcome {
+        let client = transaction.client_id();
+        let total_before = self.total(client);
+        let deposit = match &transaction {
+            Transaction::Deposit(deposit) => Some(deposit.amount().value()),
+            _ => None,
+        };
+        let is_dispute = matches!(transaction, Transaction::Dispute(_));
+
+        let outcome = self.engine.apply(transaction);
+        if outcome != Outcome::Applied {
+            return outcome;
+        }
+        self.applied += 1;
+
+        if let Some(amount) = deposit {
+            self.check_deposit(client, amount);
+        }
+        let change = self.total(client) - total_before;
+        let swing_limit = total_before.checked_mul(self.thresholds.balance_swing_ratio);
+        if total_before > Decimal::ZERO && swing_limit.is_some_and(|limit| change.abs() > limit) {
+            self.flag(client, AnomalyKind::BalanceSwing, change);
+        }
+        if is_dispute {
+            self.check_disputes(client);
+        }
+        outcome
+    }
+}
+
+/// Writes the anomaly report as CSV: sequence, client, kind, value. The header is written
+/// even without anomalies.
+pub fn write_anomalies(anomalies: &[Anomaly], writer: impl std::io::Write) -> anyhow::Result<()> {
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    wtr.write_record(["sequence", "client", "kind", "value"])?;
+    for anomaly in anomalies {
+        wtr.serialize(anomaly)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
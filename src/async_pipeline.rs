@@ -0,0 +1,130 @@
+//! `async` feature: the CLI pipeline as tokio tasks, for embedding in async services.
+//!
+//! A reader task pulls row-aligned chunks from the input, a parser task turns them into
+//! transactions, and the calling future applies them to a [`PaymentsEngine`]. Each
+//! stage hands over batches through a bounded channel, so IO overlaps with processing
+//! and memory stays bounded.
+//!
+//! Cancellation is cooperative: the engine yields after every batch, so dropping the
+//! future (a `select!` branch losing, `JoinHandle::abort`) stops processing promptly.
+//! The reader and parser tasks stop at their next send once the engine side is gone.
+//!
+//! Rows are split at newlines like [`split_rows`](crate::parsing::split_rows), and each
+//! chunk is parsed on its own, so parse errors report row numbers within their chunk.
+
+use anyhow::Context;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    sync::mpsc,
+};
+
+use crate::{
+    engine::{ClientAccounts, EngineConfig, PaymentsEngine},
+    output::{self, OutputColumns},
+    parsing::{self, TenantTransaction},
+};
+
+/// Bytes read per chunk, before extending it to the end of the row it stops in.
+const CHUNK_BYTES: u64 = 256 * 1024;
+
+/// Chunks (and parsed batches) in flight between two stages.
+const CHANNEL_DEPTH: usize = 4;
+
+/// Reads a CSV document from `input`, processes it and writes the account CSV to `output`,
+/// in the default layout for `config`.
+pub async fn run(
+    input: impl AsyncRead + Unpin + Send + 'static,
+    mut output: impl AsyncWrite + Unpin,
+    config: EngineConfig,
+) -> anyhow::Result<()> {
+    let columns = OutputColumns::for_config(&config);
+    let engine = process(input, config).await?;
+    write_accounts(engine.client_accounts(), &columns, &mut output).await
+}
+
+/// Reads a CSV document from `input` and returns the engine after applying every row.
+/// Invalid rows are skipped and logged, as in the synchronous pipeline.
+pub async fn process(
+    input: impl AsyncRead + Unpin + Send + 'static,
+    config: EngineConfig,
+) -> anyhow::Result<PaymentsEngine> {
+    let (chunk_sender, chunk_receiver) = mpsc::channel(CHANNEL_DEPTH);
+    let (batch_sender, mut batch_receiver) = mpsc::channel(CHANNEL_DEPTH);
+    let reader = tokio::spawn(read_chunks(input, chunk_sender));
+    tokio::spawn(parse_chunks(chunk_receiver, batch_sender));
+
+    let mut engine = PaymentsEngine::with_config(config);
+    while let Some(batch) = batch_receiver.recv().await {
+        engine.process_transactions(
+            batch
+                .into_iter()
+                .map(|tenant_transaction: TenantTransaction| tenant_transaction.transaction),
+        );
+        tokio::task::yield_now().await;
+    }
+    // The parser only stops early if the reader failed.
+    reader.await.context("input reader task failed")??;
+    Ok(engine)
+}
+
+/// Serializes the accounts like [`output::print_accounts_with_columns`] and writes them to
+/// `output`.
+pub async fn write_accounts(
+    client_accounts: &ClientAccounts,
+    columns: &OutputColumns,
+    output: &mut (impl AsyncWrite + Unpin),
+) -> anyhow::Result<()> {
+    let mut buffer = Vec::new();
+    output::print_accounts_with_columns(client_accounts, columns, &mut buffer)?;
+    output.write_all(&buffer).await?;
+    output.flush().await?;
+    Ok(())
+}
+
+/// Sends the header row first, then chunks of whole rows.
+async fn read_chunks(
+    input: impl AsyncRead + Unpin,
+    sender: mpsc::Sender<Vec<u8>>,
+) -> anyhow::Result<()> {
+    let mut input = BufReader::new(input);
+    let mut header = Vec::new();
+    input.read_until(b'\n', &mut header).await?;
+    if header.is_empty() || sender.send(header).await.is_err() {
+        return Ok(());
+    }
+    loop {
+        let mut chunk = Vec::new();
+        (&mut input)
+            .take(CHUNK_BYTES)
+            .read_to_end(&mut chunk)
+            .await?;
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        if chunk.last() != Some(&b'\n') {
+            input.read_until(b'\n', &mut chunk).await?;
+        }
+        if sender.send(chunk).await.is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Parses each chunk behind the header into a batch of transactions.
+async fn parse_chunks(
+    mut receiver: mpsc::Receiver<Vec<u8>>,
+    sender: mpsc::Sender<Vec<TenantTransaction>>,
+) {
+    let Some(header) = receiver.recv().await else {
+        return;
+    };
+    while let Some(chunk) = receiver.recv().await {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(std::io::Read::chain(header.as_slice(), chunk.as_slice()));
+        let batch = parsing::deserialize_tenant_csv(&mut reader).collect();
+        if sender.send(batch).await.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Input authenticity: a detached HMAC-SHA256 signature per input file, checked before any
+//! row is processed so a tampered or unsigned batch is refused as a whole.
+//!
+//! A signature is the lowercase hex HMAC-SHA256 of the file's bytes, under a key shared
+//! with the partner; surrounding whitespace in the signature file is ignored.
+
+use std::io::{self, Read};
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+/// Environment variable holding the shared key. When it's set, the CLI refuses input files
+/// without a valid signature.
+pub const HMAC_KEY_VAR: &str = "PAYMENTS_HMAC_KEY";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthenticityError {
+    #[error("Signature is not a hex-encoded HMAC-SHA256")]
+    MalformedSignature,
+    #[error("Input doesn't match its signature")]
+    SignatureMismatch,
+    #[error("Failed to read the signed input: {0}")]
+    Io(#[from] io::Error),
+}
+
+fn mac_of(mut reader: impl Read, key: &[u8]) -> io::Result<HmacSha256> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(mac),
+            Ok(read) => mac.update(&buffer[..read]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The signature of everything `reader` yields, as written to a signature file.
+pub fn sign(reader: impl Read, key: &[u8]) -> io::Result<String> {
+    Ok(hex::encode(mac_of(reader, key)?.finalize().into_bytes()))
+}
+
+/// Checks `signature` against everything `reader` yields, in constant time.
+pub fn verify(reader: impl Read, key: &[u8], signature: &str) -> Result<(), AuthenticityError> {
+    let signature =
+        hex::decode(signature.trim()).map_err(|_| AuthenticityError::MalformedSignature)?;
+    if signature.len() != Sha256::output_size() {
+        return Err(AuthenticityError::MalformedSignature);
+    }
+    mac_of(reader, key)?
+        .verify_slice(&signature)
+        .map_err(|_| AuthenticityError::SignatureMismatch)
+}
@@ -0,0 +1,142 @@
+//! CBOR transaction input and account output, behind the `cbor` feature.
+//!
+//! Input is a CBOR sequence (RFC 8742): data items written back to back, one per
+//! transaction. Each item is the serde form of [`Transaction`], so the same tagged map as
+//! its JSON form, e.g. `{"type": "deposit", "client": 1, "tx": 1, "amount": "1.0"}`.
+//! Amounts are text strings, or numbers. Output is a CBOR sequence too, one map per
+//! account with the columns of the CSV output.
+
+use std::io::{BufRead, Write};
+
+use ciborium::Value;
+use serde::Serialize;
+use tracing::{error, warn};
+
+use crate::{
+    domain::{Account, ClientId, Transaction},
+    engine::ClientAccounts,
+    output::AccountSink,
+    rejection::RejectionReason,
+};
+
+/// Reads the transactions of a CBOR sequence. Items that aren't valid transactions are
+/// logged and skipped, like invalid CSV rows. Malformed CBOR ends the sequence with an
+/// error logged, since the start of the next item can't be found.
+pub fn deserialize_cbor<R: BufRead>(reader: R) -> CborTransactions<R> {
+    CborTransactions {
+        reader,
+        item: 0,
+        done: false,
+    }
+}
+
+/// Iterator over the transactions of a CBOR sequence, see [`deserialize_cbor`].
+pub struct CborTransactions<R> {
+    reader: R,
+    /// Items read so far.
+    item: usize,
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for CborTransactions<R> {
+    type Item = Transaction;
+
+    fn next(&mut self) -> Option<Transaction> {
+        while !self.done {
+            match self.reader.fill_buf() {
+                Ok([]) => return None,
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Failed to read CBOR input: {e}");
+                    self.done = true;
+                    return None;
+                }
+            }
+            self.item += 1;
+            let value: Value = match ciborium::from_reader(&mut self.reader) {
+                Ok(value) => value,
+                Err(e) => {
+                    error!(item = self.item, "Malformed CBOR, ignoring the rest: {e}");
+                    self.done = true;
+                    return None;
+                }
+            };
+            match value.deserialized::<Transaction>() {
+                Ok(transaction) => return Some(transaction),
+                Err(e) => warn!(
+                    reason = %RejectionReason::MalformedRow,
+                    "Failed to convert CBOR item {} to a transaction: {e}",
+                    self.item
+                ),
+            }
+        }
+        None
+    }
+}
+
+/// An account with its client, as a single map.
+#[derive(Serialize)]
+struct AccountItem<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sequence: Option<u64>,
+    client: ClientId,
+    #[serde(flatten)]
+    account: &'a Account,
+}
+
+/// Writes one item per account, ordered by client ID.
+pub fn print_accounts_cbor(
+    client_accounts: &ClientAccounts,
+    mut writer: impl Write,
+) -> anyhow::Result<()> {
+    for (client, account) in client_accounts.iter_sorted() {
+        let item = AccountItem {
+            sequence: None,
+            client,
+            account,
+        };
+        ciborium::into_writer(&item, &mut writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Sink writing each account as a CBOR item like [`print_accounts_cbor`], with the
+/// checkpoint it belongs to under `sequence`.
+#[derive(Debug)]
+pub struct CborSink<W> {
+    writer: W,
+    sequence: u64,
+}
+
+impl<W: Write> CborSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            sequence: 0,
+        }
+    }
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> AccountSink for CborSink<W> {
+    type Error = ciborium::ser::Error<std::io::Error>;
+
+    fn begin_snapshot(&mut self, sequence: u64) -> Result<(), Self::Error> {
+        self.sequence = sequence;
+        Ok(())
+    }
+    fn account(&mut self, client: ClientId, account: &Account) -> Result<(), Self::Error> {
+        let item = AccountItem {
+            sequence: Some(self.sequence),
+            client,
+            account,
+        };
+        ciborium::into_writer(&item, &mut self.writer)
+    }
+    fn end_snapshot(&mut self) -> Result<(), Self::Error> {
+        Ok(self.writer.flush()?)
+    }
+}
@@ -0,0 +1,102 @@
+//! Chargeback-ratio analytics for the `chargebacks` subcommand.
+//!
+//! Card networks penalize merchants on the ratio of chargebacks to deposits, so each
+//! client with deposits gets that ratio, ranked highest first and flagged above a
+//! threshold.
+
+use std::{collections::HashMap, fmt};
+
+use rust_decimal::Decimal;
+
+use crate::{
+    domain::{ClientId, DisputeState},
+    engine::{ClientHasher, PaymentsEngine},
+};
+
+/// Chargebacks of one client, counted over its deposits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientChargebacks {
+    pub client: ClientId,
+    pub deposits: usize,
+    pub chargebacks: usize,
+    /// `chargebacks / deposits`.
+    pub ratio: Decimal,
+    /// Whether `ratio` is above the report's threshold.
+    pub flagged: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChargebackReport {
+    pub threshold: Decimal,
+    /// Clients with at least one deposit, by ratio then chargeback count (highest first,
+    /// ties by client ID).
+    pub clients: Vec<ClientChargebacks>,
+}
+
+impl ChargebackReport {
+    /// Computes the ratios over `engine`'s deposit history, flagging clients whose ratio
+    /// is above `threshold` (e.g. `0.01` for 1%).
+    pub fn new(engine: &PaymentsEngine, threshold: Decimal) -> Self {
+        let mut counts: HashMap<ClientId, (usize, usize), ClientHasher> = HashMap::default();
+        for deposit in engine.deposit_history().as_map().values() {
+            let (deposits, chargebacks) = counts.entry(deposit.client_id()).or_default();
+            *deposits += 1;
+            if deposit.dispute_state() == DisputeState::ChargedBack {
+                *chargebacks += 1;
+            }
+        }
+
+        let mut clients: Vec<_> = counts
+            .into_iter()
+            .map(|(client, (deposits, chargebacks))| {
+                let ratio = Decimal::from(chargebacks) / Decimal::from(deposits);
+                ClientChargebacks {
+                    client,
+                    deposits,
+                    chargebacks,
+                    ratio,
+                    flagged: ratio > threshold,
+                }
+            })
+            .collect();
+        clients.sort_unstable_by(|a, b| {
+            b.ratio
+                .cmp(&a.ratio)
+                .then(b.chargebacks.cmp(&a.chargebacks))
+                .then(a.client.cmp(&b.client))
+        });
+
+        Self { threshold, clients }
+    }
+    pub fn flagged(&self) -> impl Iterator<Item = &ClientChargebacks> {
+        self.clients.iter().filter(|client| client.flagged)
+    }
+}
+
+impl fmt::Display for ChargebackReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Threshold:  {}", self.threshold)?;
+        writeln!(f, "Flagged:    {}", self.flagged().count())?;
+        if self.clients.is_empty() {
+            return Ok(());
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>7}  {:>10}  {:>11}  {:>8}  flagged",
+            "client", "deposits", "chargebacks", "ratio"
+        )?;
+        for client in &self.clients {
+            writeln!(
+                f,
+                "{:>7}  {:>10}  {:>11}  {:>8}  {}",
+                client.client,
+                client.deposits,
+                client.chargebacks,
+                client.ratio.round_dp(4),
+                if client.flagged { "yes" } else { "no" }
+            )?;
+        }
+        Ok(())
+    }
+}
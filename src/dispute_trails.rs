@@ -0,0 +1,241 @@
+//! Dispute lifecycle export for the `disputes` subcommand: the state transitions of each
+//! disputed deposit, as a Mermaid or Graphviz diagram to explain a dispute trail.
+
+use std::{collections::BTreeMap, fmt};
+
+use rust_decimal::Decimal;
+
+use crate::{
+    domain::{ClientId, DisputeState, Transaction, TransactionId},
+    engine::{ApplyTransactions, EngineConfig, Outcome, PaymentsEngine},
+};
+
+/// An applied transition of a deposit's [`DisputeState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeEvent {
+    Opened,
+    EvidenceSubmitted,
+    Escalated,
+    Resolved,
+    ChargedBack,
+}
+
+/// Every applied transition of one deposit, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisputeTrail {
+    pub client: ClientId,
+    pub tx: TransactionId,
+    pub amount: Decimal,
+    pub events: Vec<DisputeEvent>,
+}
+
+/// Which deposits to record trails for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailFilter {
+    All,
+    Client(ClientId),
+    Transaction(TransactionId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagramFormat {
+    Mermaid,
+    Graphviz,
+}
+
+/// Runs a [`PaymentsEngine`] and records the dispute trails of the deposits selected by a
+/// [`TrailFilter`].
+#[derive(Debug, Clone)]
+pub struct DisputeTrailRecorder {
+    engine: PaymentsEngine,
+    filter: TrailFilter,
+    trails: BTreeMap<TransactionId, DisputeTrail>,
+}
+
+impl DisputeTrailRecorder {
+    pub fn new(config: EngineConfig, filter: TrailFilter) -> Self {
+        Self {
+            engine: PaymentsEngine::with_config(config),
+            filter,
+            trails: BTreeMap::new(),
+        }
+    }
+    pub fn engine(&self) -> &PaymentsEngine {
+        &self.engine
+    }
+    /// Trails of the selected deposits that were disputed at least once, by tx ID.
+    pub fn trails(&self) -> impl Iterator<Item = &DisputeTrail> {
+        self.trails.values()
+    }
+    /// All recorded trails as one diagram, one subgraph per deposit.
+    pub fn diagram(&self, format: DiagramFormat) -> Diagram<'_> {
+        Diagram {
+            trails: self.trails.values().collect(),
+            format,
+        }
+    }
+}
+
+impl ApplyTransactions for DisputeTrailRecorder {
+    type Transaction = Transaction;
+
+    fn apply(&mut self, transaction: Transaction) -> Outcome {
+        let (client, tx, event) = match &transaction {
+            Transaction::Dispute(dispute) => (
+                dispute.client_id(),
+                dispute.disputed_tx_id(),
+                DisputeEvent::Opened,
+            ),
+            Transaction::Evidence(evidence) => (
+                evidence.client_id(),
+                evidence.disputed_tx_id(),
+                DisputeEvent::EvidenceSubmitted,
+            ),
+            Transaction::PreArbitration(pre_arbitration) => (
+                pre_arbitration.client_id(),
+                pre_arbitration.disputed_tx_id(),
+                DisputeEvent::Escalated,
+            ),
+            Transaction::Resolve(resolve) => (
+                resolve.client_id(),
+                resolve.disputed_tx_id(),
+                DisputeEvent::Resolved,
+            ),
+            Transaction::Chargeback(chargeback) => (
+                chargeback.client_id(),
+                chargeback.disputed_tx_id(),
+                DisputeEvent::ChargedBack,
+            ),
+            _ => return self.engine.apply(transaction),
+        };
+        let selected = match self.filter {
+            TrailFilter::All => true,
+            TrailFilter::Client(selected) => client == selected,
+            TrailFilter::Transaction(selected) => tx == selected,
+        };
+        // A chargeback can open the dispute it settles, see `chargeback_opens_dispute`.
+        let state_before = self
+            .engine
+            .deposit_history()
+            .as_map()
+            .get(&tx)
+            .map(|deposit| deposit.dispute_state());
+
+        let outcome = self.engine.apply(transaction);
+        if outcome != Outcome::Applied || !selected {
+            return outcome;
+        }
+        let deposit = &self.engine.deposit_history().as_map()[&tx];
+        let trail = self.trails.entry(tx).or_insert_with(|| DisputeTrail {
+            client,
+            tx,
+            amount: deposit.amount().value(),
+            events: Vec::new(),
+        });
+        if event == DisputeEvent::ChargedBack
+            && matches!(
+                state_before,
+                Some(DisputeState::None | DisputeState::Resolved)
+            )
+        {
+            trail.events.push(DisputeEvent::Opened);
+        }
+        trail.events.push(event);
+        outcome
+    }
+}
+
+/// See [`DisputeTrailRecorder::diagram`].
+#[derive(Debug)]
+pub struct Diagram<'a> {
+    trails: Vec<&'a DisputeTrail>,
+    format: DiagramFormat,
+}
+
+/// A node of a trail: the deposit, then the state after each event.
+fn node_label(event: Option<DisputeEvent>) -> &'static str {
+    match event {
+        None => "deposited",
+        Some(DisputeEvent::Opened) => "open",
+        Some(DisputeEvent::EvidenceSubmitted) => "evidence submitted",
+        Some(DisputeEvent::Escalated) => "pre-arbitration",
+        Some(DisputeEvent::Resolved) => "resolved",
+        Some(DisputeEvent::ChargedBack) => "charged back",
+    }
+}
+
+/// The edge leading to the state after `events[index]`.
+fn edge_label(events: &[DisputeEvent], index: usize) -> &'static str {
+    match events[index] {
+        DisputeEvent::Opened if events[..index].contains(&DisputeEvent::Opened) => "re-dispute",
+        DisputeEvent::Opened => "dispute",
+        DisputeEvent::EvidenceSubmitted => "evidence",
+        DisputeEvent::Escalated => "pre-arbitration",
+        DisputeEvent::Resolved => "resolve",
+        DisputeEvent::ChargedBack => "chargeback",
+    }
+}
+
+impl fmt::Display for Diagram<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.format {
+            DiagramFormat::Mermaid => writeln!(f, "flowchart LR")?,
+            DiagramFormat::Graphviz => {
+                writeln!(f, "digraph disputes {{")?;
+                writeln!(f, "    rankdir=LR;")?;
+            }
+        }
+        for trail in &self.trails {
+            let title = format!(
+                "tx {} (client {}, amount {})",
+                trail.tx, trail.client, trail.amount
+            );
+            let node = |step: usize| format!("tx{}_{step}", trail.tx);
+            let labels = std::iter::once(None).chain(trail.events.iter().copied().map(Some));
+            match self.format {
+                DiagramFormat::Mermaid => {
+                    writeln!(f, "    subgraph tx{}[\"{title}\"]", trail.tx)?;
+                    for (step, event) in labels.enumerate() {
+                        writeln!(f, "        {}[\"{}\"]", node(step), node_label(event))?;
+                    }
+                    for index in 0..trail.events.len() {
+                        writeln!(
+                            f,
+                            "        {} -->|{}| {}",
+                            node(index),
+                            edge_label(&trail.events, index),
+                            node(index + 1)
+                        )?;
+                    }
+                    writeln!(f, "    end")?;
+                }
+                DiagramFormat::Graphviz => {
+                    writeln!(f, "    subgraph cluster_tx{} {{", trail.tx)?;
+                    writeln!(f, "        label=\"{title}\";")?;
+                    for (step, event) in labels.enumerate() {
+                        writeln!(
+                            f,
+                            "        {} [label=\"{}\"];",
+                            node(step),
+                            node_label(event)
+                        )?;
+                    }
+                    for index in 0..trail.events.len() {
+                        writeln!(
+                            f,
+                            "        {} -> {} [label=\"{}\"];",
+                            node(index),
+                            node(index + 1),
+                            edge_label(&trail.events, index)
+                        )?;
+                    }
+                    writeln!(f, "    }}")?;
+                }
+            }
+        }
+        if self.format == DiagramFormat::Graphviz {
+            writeln!(f, "}}")?;
+        }
+        Ok(())
+    }
+}
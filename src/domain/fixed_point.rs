@@ -0,0 +1,97 @@
+//! `fixed-point` feature: amounts and balances as an `i64` count of 1/10000 units.
+//!
+//! The spec caps amounts at [`AMOUNT_MAX_SCALE`] decimal places, so every valid amount
+//! is an exact integer number of units. Integer arithmetic replaces `Decimal` on the
+//! hot path. Values are converted from `Decimal` when parsed and back when read.
+
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
+
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+
+use super::{AMOUNT_MAX_SCALE, DomainError};
+
+const UNITS_PER_WHOLE: i64 = 10_i64.pow(AMOUNT_MAX_SCALE);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FixedPoint(i64);
+
+impl FixedPoint {
+    pub const ZERO: FixedPoint = FixedPoint(0);
+
+    /// `None` on overflow, like `Decimal::checked_add`.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+    /// `None` on overflow, like `Decimal::checked_sub`.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+}
+
+impl TryFrom<Decimal> for FixedPoint {
+    type Error = DomainError;
+
+    fn try_from(value: Decimal) -> Result<Self, Self::Error> {
+        if value.normalize().scale() > AMOUNT_MAX_SCALE {
+            return Err(DomainError::ExcessivePrecision);
+        }
+        value
+            .checked_mul(Decimal::from(UNITS_PER_WHOLE))
+            .and_then(|units| units.to_i64())
+            .map(Self)
+            .ok_or(DomainError::AmountTooLarge)
+    }
+}
+
+/// Normalized, so trailing zeros are dropped (`1.5`, not `1.5000`).
+impl From<FixedPoint> for Decimal {
+    fn from(value: FixedPoint) -> Self {
+        Decimal::new(value.0, AMOUNT_MAX_SCALE).normalize()
+    }
+}
+
+impl fmt::Display for FixedPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Decimal::from(*self).fmt(f)
+    }
+}
+
+/// Overflow panics, like `Decimal` arithmetic does, instead of wrapping in release builds.
+impl Add for FixedPoint {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(
+            self.0
+                .checked_add(rhs.0)
+                .expect("fixed-point addition overflowed"),
+        )
+    }
+}
+
+impl Sub for FixedPoint {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(
+            self.0
+                .checked_sub(rhs.0)
+                .expect("fixed-point subtraction overflowed"),
+        )
+    }
+}
+
+impl AddAssign for FixedPoint {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for FixedPoint {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
@@ -0,0 +1,1508 @@
+//! Core domain types: transactions, accounts, and balances.
+
+use std::fmt;
+
+use derive_more::{Display, From, FromStr, Into, TryInto};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+#[cfg(feature = "cold-tier")]
+pub(crate) use record::DepositRecord;
+
+#[cfg(feature = "fixed-point")]
+mod fixed_point;
+#[cfg(feature = "cold-tier")]
+mod record;
+
+/// Internal storage for amounts and balances. Plain `Decimal` by default. With the
+/// `fixed-point` feature it's an `i64` count of 1/10000 units, and `Decimal` only
+/// appears at the API boundary.
+#[cfg(not(feature = "fixed-point"))]
+type Funds = Decimal;
+#[cfg(feature = "fixed-point")]
+type Funds = fixed_point::FixedPoint;
+
+#[cfg(not(feature = "fixed-point"))]
+fn to_funds(value: Decimal) -> Result<Funds, DomainError> {
+    Ok(value)
+}
+#[cfg(feature = "fixed-point")]
+fn to_funds(value: Decimal) -> Result<Funds, DomainError> {
+    Funds::try_from(value)
+}
+
+#[cfg(not(feature = "fixed-point"))]
+fn to_decimal(funds: Funds) -> Decimal {
+    funds
+}
+#[cfg(feature = "fixed-point")]
+fn to_decimal(funds: Funds) -> Decimal {
+    funds.into()
+}
+
+/// `funds + amount`, or [`DomainError::AmountTooLarge`] if it can't be stored.
+fn add_funds(funds: Funds, amount: Funds) -> Result<Funds, DomainError> {
+    funds.checked_add(amount).ok_or(DomainError::AmountTooLarge)
+}
+
+/// `funds - amount`, or [`DomainError::AmountTooLarge`] if it can't be stored.
+fn sub_funds(funds: Funds, amount: Funds) -> Result<Funds, DomainError> {
+    funds.checked_sub(amount).ok_or(DomainError::AmountTooLarge)
+}
+
+/// Newtype wrapper for client identifiers (valid u16 per spec). Ordered numerically,
+/// which is the order accounts are written in.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    From,
+    Into,
+    Display,
+    FromStr,
+)]
+pub struct ClientId(u16);
+
+/// Newtype wrapper for globally-unique transaction identifiers (valid u32 per spec).
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    From,
+    Into,
+    Display,
+    FromStr,
+)]
+pub struct TransactionId(u32);
+
+/// Newtype wrapper for tenant identifiers. Each tenant gets an isolated engine.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    From,
+    Into,
+    Display,
+    FromStr,
+)]
+pub struct TenantId(u16);
+
+/// Newtype wrapper for partner identifiers: the acquirer or other source a row came from.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    From,
+    Into,
+    Display,
+    FromStr,
+)]
+pub struct PartnerId(u16);
+
+/// Maximum number of decimal places an amount may carry (per spec).
+pub const AMOUNT_MAX_SCALE: u32 = 4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DomainError {
+    #[error("Insufficient funds")]
+    InsufficientFunds,
+    #[error("Amount must be positive")]
+    NonPositiveAmount,
+    #[error("Amount must not be negative")]
+    NegativeAmount,
+    #[error("Amount has more than {AMOUNT_MAX_SCALE} decimal places")]
+    ExcessivePrecision,
+    #[error("Authorization already captured or voided")]
+    AuthorizationAlreadySettled,
+    #[error("Deposit is disputed or charged back")]
+    DepositDisputed,
+    #[error("Refund exceeds the deposit's unrefunded amount")]
+    RefundExceedsDeposit,
+    #[error("Adjustment amount must not be zero")]
+    ZeroAdjustment,
+    #[error("Amount is too large")]
+    AmountTooLarge,
+    #[error("Inconsistent balance: {0}")]
+    InconsistentBalance(&'static str),
+}
+
+/// A non-negative monetary amount with at most [`AMOUNT_MAX_SCALE`] decimal places.
+///
+/// Transaction amounts are always `Amount`s. Balances stay plain `Decimal`s since
+/// `available` may legitimately go negative (see README, assumption 5).
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Display,
+)]
+#[serde(try_from = "Decimal", into = "Decimal")]
+pub struct Amount(Funds);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(Funds::ZERO);
+
+    pub fn value(self) -> Decimal {
+        to_decimal(self.0)
+    }
+    /// Splits `rate` (between 0 and 1) of the amount off, rounded down to 4 decimal
+    /// places. Returns the share and the rest, which add up to the amount.
+    pub fn split(self, rate: Decimal) -> (Amount, Amount) {
+        let share = (self.value() * rate)
+            .round_dp_with_strategy(AMOUNT_MAX_SCALE, RoundingStrategy::ToZero)
+            .clamp(Decimal::ZERO, self.value());
+        let share = funds_or_panic(share);
+        (Amount(share), Amount(self.0 - share))
+    }
+}
+
+impl TryFrom<Decimal> for Amount {
+    type Error = DomainError;
+
+    fn try_from(value: Decimal) -> Result<Self, Self::Error> {
+        if value.is_sign_negative() && !value.is_zero() {
+            return Err(DomainError::NegativeAmount);
+        }
+        // normalize() drops trailing zeros, so "1.50000" is still accepted
+        if value.normalize().scale() > AMOUNT_MAX_SCALE {
+            return Err(DomainError::ExcessivePrecision);
+        }
+        Ok(Self(to_funds(value)?))
+    }
+}
+
+impl From<Amount> for Decimal {
+    fn from(amount: Amount) -> Self {
+        amount.value()
+    }
+}
+
+/// A monetary amount that may be negative, with at most [`AMOUNT_MAX_SCALE`] decimal
+/// places. Only adjustments carry one; every other transaction amount is an [`Amount`].
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Display,
+)]
+#[serde(try_from = "Decimal", into = "Decimal")]
+pub struct SignedAmount(Funds);
+
+impl SignedAmount {
+    pub fn value(self) -> Decimal {
+        to_decimal(self.0)
+    }
+}
+
+impl TryFrom<Decimal> for SignedAmount {
+    type Error = DomainError;
+
+    fn try_from(value: Decimal) -> Result<Self, Self::Error> {
+        if value.normalize().scale() > AMOUNT_MAX_SCALE {
+            return Err(DomainError::ExcessivePrecision);
+        }
+        Ok(Self(to_funds(value)?))
+    }
+}
+
+impl From<Amount> for SignedAmount {
+    fn from(amount: Amount) -> Self {
+        Self(amount.0)
+    }
+}
+
+impl From<SignedAmount> for Decimal {
+    fn from(amount: SignedAmount) -> Self {
+        amount.value()
+    }
+}
+
+/// Checks that a transaction amount is strictly positive and a valid [`Amount`].
+fn validate_amount(amount: Decimal) -> Result<Amount, DomainError> {
+    if amount <= Decimal::ZERO {
+        return Err(DomainError::NonPositiveAmount);
+    }
+    Amount::try_from(amount)
+}
+
+/// Checks that an adjustment amount is non-zero and a valid [`SignedAmount`].
+fn validate_adjustment(amount: Decimal) -> Result<SignedAmount, DomainError> {
+    if amount.is_zero() {
+        return Err(DomainError::ZeroAdjustment);
+    }
+    SignedAmount::try_from(amount)
+}
+
+/// Same as [`deserialize_amount`], for adjustments.
+fn deserialize_adjustment_amount<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SignedAmount, D::Error> {
+    let amount = <Decimal as Deserialize>::deserialize(deserializer)?;
+    validate_adjustment(amount).map_err(serde::de::Error::custom)
+}
+
+/// Serde hook so deserialized movements go through the same validation as `try_new`.
+fn deserialize_amount<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Amount, D::Error> {
+    let amount = <Decimal as Deserialize>::deserialize(deserializer)?;
+    validate_amount(amount).map_err(serde::de::Error::custom)
+}
+
+/// Sum type over all transaction kinds the engine can process.
+///
+/// Serializes as an internally tagged object using the same field names as the CSV
+/// format, e.g. `{"type":"deposit","client":1,"tx":1,"amount":"1.0"}`.
+#[derive(Debug, Clone, From, TryInto, PartialEq, Serialize, Deserialize, Display)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Transaction {
+    Deposit(Deposit),
+    Withdrawal(Withdrawal),
+    Dispute(Dispute),
+    Resolve(Resolve),
+    Chargeback(Chargeback),
+    Authorization(Authorization),
+    Capture(Capture),
+    Void(Void),
+    Refund(Refund),
+    Adjustment(Adjustment),
+    Evidence(Evidence),
+    PreArbitration(PreArbitration),
+}
+
+impl Transaction {
+    /// The client the transaction belongs to.
+    pub fn client_id(&self) -> ClientId {
+        match self {
+            Transaction::Deposit(deposit) => deposit.client_id(),
+            Transaction::Withdrawal(withdrawal) => withdrawal.client_id(),
+            Transaction::Dispute(dispute) => dispute.client_id(),
+            Transaction::Resolve(resolve) => resolve.client_id(),
+            Transaction::Chargeback(chargeback) => chargeback.client_id(),
+            Transaction::Authorization(authorization) => authorization.client_id(),
+            Transaction::Capture(capture) => capture.client_id(),
+            Transaction::Void(void) => void.client_id(),
+            Transaction::Refund(refund) => refund.client_id(),
+            Transaction::Adjustment(adjustment) => adjustment.client_id(),
+            Transaction::Evidence(evidence) => evidence.client_id(),
+            Transaction::PreArbitration(pre_arbitration) => pre_arbitration.client_id(),
+        }
+    }
+    /// The `tx` column of the transaction's row: its own ID for deposits, withdrawals,
+    /// authorizations and adjustments, the tx it refers to for the others.
+    pub fn tx_id(&self) -> TransactionId {
+        match self {
+            Transaction::Deposit(deposit) => deposit.transaction_id(),
+            Transaction::Withdrawal(withdrawal) => withdrawal.transaction_id(),
+            Transaction::Authorization(authorization) => authorization.transaction_id(),
+            Transaction::Adjustment(adjustment) => adjustment.transaction_id(),
+            Transaction::Dispute(dispute) => dispute.disputed_tx_id(),
+            Transaction::Resolve(resolve) => resolve.disputed_tx_id(),
+            Transaction::Chargeback(chargeback) => chargeback.disputed_tx_id(),
+            Transaction::Evidence(evidence) => evidence.disputed_tx_id(),
+            Transaction::PreArbitration(pre_arbitration) => pre_arbitration.disputed_tx_id(),
+            Transaction::Capture(capture) => capture.authorization_tx_id(),
+            Transaction::Void(void) => void.authorization_tx_id(),
+            Transaction::Refund(refund) => refund.deposit_tx_id(),
+        }
+    }
+    /// The related transaction this one was linked to with [`Transaction::with_parent_tx`],
+    /// e.g. the deposit a withdrawal pays out. Only informational: the engine doesn't look
+    /// at it.
+    pub fn parent_tx(&self) -> Option<TransactionId> {
+        match self {
+            Transaction::Deposit(Deposit { tx, .. })
+            | Transaction::Withdrawal(Withdrawal(tx))
+            | Transaction::Authorization(Authorization { tx, .. })
+            | Transaction::Refund(Refund(tx)) => tx.parent_tx,
+            Transaction::Dispute(Dispute(tx))
+            | Transaction::Resolve(Resolve(tx))
+            | Transaction::Chargeback(Chargeback(tx))
+            | Transaction::Capture(Capture(tx))
+            | Transaction::Void(Void(tx))
+            | Transaction::Evidence(Evidence(tx))
+            | Transaction::PreArbitration(PreArbitration(tx)) => tx.parent_tx,
+            Transaction::Adjustment(adjustment) => adjustment.parent_tx,
+        }
+    }
+    /// Links the transaction to a related one, see [`Transaction::parent_tx`].
+    pub fn with_parent_tx(mut self, parent_tx: TransactionId) -> Self {
+        let link = match &mut self {
+            Transaction::Deposit(Deposit { tx, .. })
+            | Transaction::Withdrawal(Withdrawal(tx))
+            | Transaction::Authorization(Authorization { tx, .. })
+            | Transaction::Refund(Refund(tx)) => &mut tx.parent_tx,
+            Transaction::Dispute(Dispute(tx))
+            | Transaction::Resolve(Resolve(tx))
+            | Transaction::Chargeback(Chargeback(tx))
+            | Transaction::Capture(Capture(tx))
+            | Transaction::Void(Void(tx))
+            | Transaction::Evidence(Evidence(tx))
+            | Transaction::PreArbitration(PreArbitration(tx)) => &mut tx.parent_tx,
+            Transaction::Adjustment(adjustment) => &mut adjustment.parent_tx,
+        };
+        *link = Some(parent_tx);
+        self
+    }
+}
+
+/// Lifecycle of a dispute on a deposit. Legal transitions:
+///
+/// ```text
+/// None ──open──▶ Open ──resolve──▶ Resolved ──open──▶ Open ...
+///                  └──charge_back──▶ ChargedBack (final)
+/// ```
+///
+/// Card networks add optional stages between opening and closing a dispute. A dispute in
+/// any of them is still open: it can be resolved or charged back from each.
+///
+/// ```text
+/// Open ──submit_evidence──▶ EvidenceSubmitted ──escalate──▶ PreArbitration
+/// ```
+///
+/// The state can only change through the transition methods, which reject illegal moves.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeState {
+    /// The transaction has never been disputed.
+    #[default]
+    None,
+    /// A dispute is currently open for this transaction.
+    Open,
+    /// The open dispute's evidence was submitted.
+    EvidenceSubmitted,
+    /// The open dispute went to pre-arbitration after evidence was submitted.
+    PreArbitration,
+    /// A dispute was open and has been resolved. The transaction may be disputed again.
+    Resolved,
+    /// A dispute was open but has now been charged back. No further transitions.
+    ChargedBack,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum DisputeTransitionError {
+    #[error("Transaction already disputed")]
+    AlreadyDisputed,
+    #[error("Transaction not disputed")]
+    NotDisputed,
+    #[error("Dispute not in the stage this step follows")]
+    OutOfOrder,
+}
+
+impl DisputeState {
+    /// None/Resolved → Open.
+    pub fn open(&mut self) -> Result<(), DisputeTransitionError> {
+        match self {
+            DisputeState::None | DisputeState::Resolved => {
+                *self = DisputeState::Open;
+                Ok(())
+            }
+            DisputeState::Open
+            | DisputeState::EvidenceSubmitted
+            | DisputeState::PreArbitration
+            | DisputeState::ChargedBack => Err(DisputeTransitionError::AlreadyDisputed),
+        }
+    }
+    /// Open → EvidenceSubmitted.
+    pub fn submit_evidence(&mut self) -> Result<(), DisputeTransitionError> {
+        self.advance(DisputeState::Open, DisputeState::EvidenceSubmitted)
+    }
+    /// EvidenceSubmitted → PreArbitration.
+    pub fn escalate(&mut self) -> Result<(), DisputeTransitionError> {
+        self.advance(
+            DisputeState::EvidenceSubmitted,
+            DisputeState::PreArbitration,
+        )
+    }
+    /// Whether a dispute is open, in whatever stage.
+    pub fn is_open(self) -> bool {
+        matches!(
+            self,
+            DisputeState::Open | DisputeState::EvidenceSubmitted | DisputeState::PreArbitration
+        )
+    }
+    /// Open → Resolved.
+    pub fn resolve(&mut self) -> Result<(), DisputeTransitionError> {
+        self.close(DisputeState::Resolved)
+    }
+    /// Open → ChargedBack.
+    pub fn charge_back(&mut self) -> Result<(), DisputeTransitionError> {
+        self.close(DisputeState::ChargedBack)
+    }
+    /// Open (any stage) → Resolved/ChargedBack.
+    fn close(&mut self, outcome: DisputeState) -> Result<(), DisputeTransitionError> {
+        if !self.is_open() {
+            return Err(DisputeTransitionError::NotDisputed);
+        }
+        *self = outcome;
+        Ok(())
+    }
+    fn advance(
+        &mut self,
+        from: DisputeState,
+        to: DisputeState,
+    ) -> Result<(), DisputeTransitionError> {
+        if !self.is_open() {
+            return Err(DisputeTransitionError::NotDisputed);
+        }
+        if *self != from {
+            return Err(DisputeTransitionError::OutOfOrder);
+        }
+        *self = to;
+        Ok(())
+    }
+}
+
+/// Lifecycle of a two-phase payout: an authorization stays `Pending` until it is
+/// either captured or voided, after which it can't change anymore.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum AuthorizationState {
+    /// Funds are reserved and waiting for capture or void.
+    #[default]
+    Pending,
+    /// The payout went through; the reserved funds left the account.
+    Captured,
+    /// The payout was cancelled; the reserved funds went back to available.
+    Voided,
+}
+
+impl AuthorizationState {
+    fn settle(&mut self, outcome: AuthorizationState) -> Result<(), DomainError> {
+        if *self != AuthorizationState::Pending {
+            return Err(DomainError::AuthorizationAlreadySettled);
+        }
+        *self = outcome;
+        Ok(())
+    }
+}
+
+// Movement transactions carry an amount (deposits, withdrawals & authorizations).
+// The dispute state is engine bookkeeping, not part of the transaction itself, so it isn't serialized.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Deposit {
+    #[serde(skip)]
+    dispute: DisputeState,
+    /// Part of the amount the current dispute couldn't hold, see [`Deposit::held_amount`].
+    #[serde(skip)]
+    shortfall: Amount,
+    #[serde(skip)]
+    refunded: Amount,
+    #[serde(skip)]
+    deposited_at: u64,
+    #[serde(flatten)]
+    tx: MovementTransaction,
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Withdrawal(MovementTransaction);
+/// Reverses (part of) a deposit, referenced by its tx ID in `tx`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Refund(MovementTransaction);
+
+// Dispute-family transactions reference an existing tx by ID (no amount field).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Dispute(DisputeTransaction);
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Resolve(DisputeTransaction);
+#[derive(Debug, Clone, PartialEq, From, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Chargeback(DisputeTransaction);
+/// Marks the evidence of an open dispute as submitted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Evidence(DisputeTransaction);
+/// Moves a dispute whose evidence was submitted to pre-arbitration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PreArbitration(DisputeTransaction);
+
+/// An operator's correction of a client's available funds by a signed amount, with a
+/// reason code for the books. Adjustments aren't deposits, so they can't be disputed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Adjustment {
+    client: ClientId,
+    tx: TransactionId,
+    #[serde(deserialize_with = "deserialize_adjustment_amount")]
+    amount: SignedAmount,
+    reason: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent_tx: Option<TransactionId>,
+}
+
+// Two-phase payouts: an authorization reserves funds, a capture or void referencing it settles them.
+// Like the dispute state, the settlement state is engine bookkeeping and isn't serialized.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Authorization {
+    #[serde(skip)]
+    state: AuthorizationState,
+    #[serde(flatten)]
+    tx: MovementTransaction,
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Capture(DisputeTransaction);
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Void(DisputeTransaction);
+
+impl Deposit {
+    /// Builds a deposit, rejecting non-positive amounts and amounts with more than 4 decimal places.
+    pub fn try_new(
+        client: ClientId,
+        tx: TransactionId,
+        amount: Decimal,
+    ) -> Result<Self, DomainError> {
+        Ok(Self::new(client, tx, validate_amount(amount)?))
+    }
+    pub(crate) fn new(client: ClientId, tx: TransactionId, amount: Amount) -> Self {
+        Self {
+            tx: MovementTransaction::new(client, tx, amount),
+            dispute: DisputeState::None,
+            shortfall: Amount::ZERO,
+            refunded: Amount::ZERO,
+            deposited_at: 0,
+        }
+    }
+    /// Records when the engine applied the deposit, see [`Deposit::deposited_at`].
+    pub(crate) fn at(self, deposited_at: u64) -> Self {
+        Self {
+            deposited_at,
+            ..self
+        }
+    }
+    pub fn amount(&self) -> Amount {
+        self.tx.amount
+    }
+    /// Number of transactions the engine had processed when it applied this deposit,
+    /// counting the deposit itself. 0 for deposits that weren't applied by an engine.
+    pub fn deposited_at(&self) -> u64 {
+        self.deposited_at
+    }
+    /// See [`Transaction::parent_tx`].
+    pub fn parent_tx(&self) -> Option<TransactionId> {
+        self.tx.parent_tx
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.tx.client
+    }
+    pub fn transaction_id(&self) -> TransactionId {
+        self.tx.tx
+    }
+    pub fn dispute_state(&self) -> DisputeState {
+        self.dispute
+    }
+    pub fn open_dispute(&mut self) -> Result<(), DisputeTransitionError> {
+        self.dispute.open()
+    }
+    pub fn resolve_dispute(&mut self) -> Result<(), DisputeTransitionError> {
+        self.dispute.resolve()
+    }
+    pub fn charge_back(&mut self) -> Result<(), DisputeTransitionError> {
+        self.dispute.charge_back()
+    }
+    pub fn submit_evidence(&mut self) -> Result<(), DisputeTransitionError> {
+        self.dispute.submit_evidence()
+    }
+    pub fn escalate_dispute(&mut self) -> Result<(), DisputeTransitionError> {
+        self.dispute.escalate()
+    }
+    /// Part of the amount the latest dispute couldn't hold because available funds were
+    /// capped at zero. Zero unless the engine caps negative balances.
+    pub fn shortfall(&self) -> Amount {
+        self.shortfall
+    }
+    /// What the latest dispute held: the disputable amount minus the shortfall.
+    pub fn held_amount(&self) -> Amount {
+        Amount(self.disputable().0 - self.shortfall.0)
+    }
+    /// Splits the disputable amount of a dispute being opened into what `account`'s
+    /// available funds cover and the shortfall, and records the shortfall. Returns the
+    /// part to hold.
+    pub fn cap_hold(&mut self, account: &Account) -> Amount {
+        let held = account
+            .balance
+            .available
+            .clamp(Funds::ZERO, self.disputable().0);
+        self.shortfall = Amount(self.disputable().0 - held);
+        Amount(held)
+    }
+    pub fn refunded(&self) -> Amount {
+        self.refunded
+    }
+    /// The part of the amount that wasn't refunded, and so can still be disputed.
+    pub fn disputable(&self) -> Amount {
+        Amount(self.tx.amount.0 - self.refunded.0)
+    }
+    /// Whether `amount` can be refunded. Disputed and charged back deposits can't be
+    /// refunded, and refunds can't add up to more than the amount.
+    pub fn check_refund(&self, amount: Amount) -> Result<(), DomainError> {
+        if self.dispute.is_open() || self.dispute == DisputeState::ChargedBack {
+            return Err(DomainError::DepositDisputed);
+        }
+        if amount > self.disputable() {
+            return Err(DomainError::RefundExceedsDeposit);
+        }
+        Ok(())
+    }
+    /// Records a refund of `amount`, see [`Deposit::check_refund`].
+    pub fn refund(&mut self, amount: Amount) -> Result<(), DomainError> {
+        self.check_refund(amount)?;
+        self.refunded = Amount(self.refunded.0 + amount.0);
+        Ok(())
+    }
+}
+
+impl Withdrawal {
+    /// Builds a withdrawal, rejecting non-positive amounts and amounts with more than 4 decimal places.
+    pub fn try_new(
+        client: ClientId,
+        tx: TransactionId,
+        amount: Decimal,
+    ) -> Result<Self, DomainError> {
+        Ok(Self::new(client, tx, validate_amount(amount)?))
+    }
+    pub(crate) fn new(client: ClientId, tx: TransactionId, amount: Amount) -> Self {
+        Self(MovementTransaction::new(client, tx, amount))
+    }
+    pub fn amount(&self) -> Amount {
+        self.0.amount
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.0.client
+    }
+
+    pub fn transaction_id(&self) -> TransactionId {
+        self.0.tx
+    }
+}
+
+impl Adjustment {
+    /// Builds an adjustment, rejecting zero amounts and amounts with more than 4 decimal
+    /// places.
+    pub fn try_new(
+        client: ClientId,
+        tx: TransactionId,
+        amount: Decimal,
+        reason: u16,
+    ) -> Result<Self, DomainError> {
+        Ok(Self::new(client, tx, validate_adjustment(amount)?, reason))
+    }
+    pub(crate) fn new(
+        client: ClientId,
+        tx: TransactionId,
+        amount: SignedAmount,
+        reason: u16,
+    ) -> Self {
+        Self {
+            client,
+            tx,
+            amount,
+            reason,
+            parent_tx: None,
+        }
+    }
+    pub fn amount(&self) -> SignedAmount {
+        self.amount
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.client
+    }
+    pub fn transaction_id(&self) -> TransactionId {
+        self.tx
+    }
+    pub fn reason(&self) -> u16 {
+        self.reason
+    }
+}
+
+impl Refund {
+    /// Builds a refund of `deposit_tx`, rejecting non-positive amounts and amounts with more
+    /// than 4 decimal places.
+    pub fn try_new(
+        client: ClientId,
+        deposit_tx: TransactionId,
+        amount: Decimal,
+    ) -> Result<Self, DomainError> {
+        Ok(Self::new(client, deposit_tx, validate_amount(amount)?))
+    }
+    pub(crate) fn new(client: ClientId, deposit_tx: TransactionId, amount: Amount) -> Self {
+        Self(MovementTransaction::new(client, deposit_tx, amount))
+    }
+    pub fn amount(&self) -> Amount {
+        self.0.amount
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.0.client
+    }
+    pub fn deposit_tx_id(&self) -> TransactionId {
+        self.0.tx
+    }
+}
+
+impl Authorization {
+    /// Builds an authorization, rejecting non-positive amounts and amounts with more than 4 decimal places.
+    pub fn try_new(
+        client: ClientId,
+        tx: TransactionId,
+        amount: Decimal,
+    ) -> Result<Self, DomainError> {
+        Ok(Self::new(client, tx, validate_amount(amount)?))
+    }
+    pub(crate) fn new(client: ClientId, tx: TransactionId, amount: Amount) -> Self {
+        Self {
+            tx: MovementTransaction::new(client, tx, amount),
+            state: AuthorizationState::Pending,
+        }
+    }
+    pub fn amount(&self) -> Amount {
+        self.tx.amount
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.tx.client
+    }
+    pub fn transaction_id(&self) -> TransactionId {
+        self.tx.tx
+    }
+    pub fn state(&self) -> AuthorizationState {
+        self.state
+    }
+    /// Pending → Captured.
+    pub fn capture(&mut self) -> Result<(), DomainError> {
+        self.state.settle(AuthorizationState::Captured)
+    }
+    /// Pending → Voided.
+    pub fn void(&mut self) -> Result<(), DomainError> {
+        self.state.settle(AuthorizationState::Voided)
+    }
+}
+
+impl Capture {
+    pub fn new(client: ClientId, authorization_tx: TransactionId) -> Self {
+        Self(DisputeTransaction::new(client, authorization_tx))
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.0.client_id()
+    }
+    pub fn authorization_tx_id(&self) -> TransactionId {
+        self.0.disputed_transaction_id()
+    }
+}
+
+impl Void {
+    pub fn new(client: ClientId, authorization_tx: TransactionId) -> Self {
+        Self(DisputeTransaction::new(client, authorization_tx))
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.0.client_id()
+    }
+    pub fn authorization_tx_id(&self) -> TransactionId {
+        self.0.disputed_transaction_id()
+    }
+}
+
+impl Dispute {
+    pub fn new(client: ClientId, disputed_tx: TransactionId) -> Self {
+        Self(DisputeTransaction::new(client, disputed_tx))
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.0.client_id()
+    }
+    pub fn disputed_tx_id(&self) -> TransactionId {
+        self.0.disputed_transaction_id()
+    }
+}
+
+impl Resolve {
+    pub fn new(client: ClientId, disputed_tx: TransactionId) -> Self {
+        Self(DisputeTransaction::new(client, disputed_tx))
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.0.client_id()
+    }
+    pub fn disputed_tx_id(&self) -> TransactionId {
+        self.0.disputed_transaction_id()
+    }
+}
+impl Chargeback {
+    pub fn new(client: ClientId, disputed_tx: TransactionId) -> Self {
+        Self(DisputeTransaction::new(client, disputed_tx))
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.0.client_id()
+    }
+    pub fn disputed_tx_id(&self) -> TransactionId {
+        self.0.disputed_transaction_id()
+    }
+}
+
+impl Evidence {
+    pub fn new(client: ClientId, disputed_tx: TransactionId) -> Self {
+        Self(DisputeTransaction::new(client, disputed_tx))
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.0.client_id()
+    }
+    pub fn disputed_tx_id(&self) -> TransactionId {
+        self.0.disputed_transaction_id()
+    }
+}
+impl PreArbitration {
+    pub fn new(client: ClientId, disputed_tx: TransactionId) -> Self {
+        Self(DisputeTransaction::new(client, disputed_tx))
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.0.client_id()
+    }
+    pub fn disputed_tx_id(&self) -> TransactionId {
+        self.0.disputed_transaction_id()
+    }
+}
+
+impl fmt::Display for Deposit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "deposit {}", self.tx)
+    }
+}
+impl fmt::Display for Withdrawal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "withdrawal {}", self.0)
+    }
+}
+impl fmt::Display for Dispute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dispute of {}", self.0)
+    }
+}
+impl fmt::Display for Resolve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "resolve of {}", self.0)
+    }
+}
+impl fmt::Display for Chargeback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "chargeback of {}", self.0)
+    }
+}
+impl fmt::Display for Evidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "evidence for {}", self.0)
+    }
+}
+impl fmt::Display for PreArbitration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pre-arbitration of {}", self.0)
+    }
+}
+impl fmt::Display for Authorization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "authorization {}", self.tx)
+    }
+}
+impl fmt::Display for Capture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "capture of {}", self.0)
+    }
+}
+impl fmt::Display for Void {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "void of {}", self.0)
+    }
+}
+impl fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "adjustment tx {} (client {}, amount {}, reason {}",
+            self.tx, self.client, self.amount, self.reason
+        )?;
+        write_parent_tx(f, self.parent_tx)
+    }
+}
+impl fmt::Display for Refund {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "refund of {}", self.0)
+    }
+}
+
+/// A single client account.
+///
+/// Movements (deposits and withdrawals) are only reachable through [`ActiveAccount`],
+/// which can only be obtained from an unlocked account. That way the compiler,
+/// not a runtime check in every handler, keeps frozen accounts from moving funds.
+/// Dispute-family operations are available on any account.
+///
+/// Serializes as a flat record with the output columns, e.g.
+/// `{"available":"1.5","held":"0","total":"1.5","locked":false,"authorized":"0","pending":"0","reserve":"0"}`,
+/// with the same validation as [`Balance`] on deserialize.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "AccountRepr", try_from = "AccountRepr")]
+pub struct Account {
+    balance: Balance,
+    locked: bool,
+    /// Disputed funds that couldn't be held, see [`Account::shortfall`]. Not serialized.
+    shortfall: Funds,
+}
+
+impl Account {
+    pub fn new(balance: Balance, locked: bool) -> Self {
+        Self {
+            balance,
+            locked,
+            shortfall: Funds::ZERO,
+        }
+    }
+    pub fn balance(&self) -> &Balance {
+        &self.balance
+    }
+    /// Disputed funds that weren't held because available was capped at zero: owed by the
+    /// client while the disputes are open, and for good once they're charged back. Zero
+    /// unless the engine caps negative balances.
+    pub fn shortfall(&self) -> Decimal {
+        to_decimal(self.shortfall)
+    }
+    /// Records the shortfall of a dispute being opened. Fails if the shortfall can't be
+    /// stored.
+    pub fn add_shortfall(&mut self, shortfall: Amount) -> Result<(), DomainError> {
+        self.shortfall = add_funds(self.shortfall, shortfall.0)?;
+        Ok(())
+    }
+    /// Clears the shortfall of a resolved dispute.
+    pub fn clear_shortfall(&mut self, shortfall: Amount) {
+        self.shortfall -= shortfall.0;
+    }
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+    /// Returns a handle allowing movements, or `None` if the account is locked.
+    pub fn active_mut(&mut self) -> Option<ActiveAccount<'_>> {
+        if self.locked {
+            return None;
+        }
+        Some(ActiveAccount(&mut self.balance))
+    }
+    /// Move funds from available to held (dispute). Total stays the same. Fails, holding
+    /// nothing, if available or held can't be stored.
+    pub fn hold(&mut self, amount: Amount) -> Result<(), DomainError> {
+        let total = self.balance.total_funds();
+        self.balance.hold(amount)?;
+        check_invariant(
+            self.balance.total_funds() == total,
+            "total changed during dispute",
+        );
+        check_invariant(
+            self.balance.held >= Funds::ZERO,
+            "held negative after dispute",
+        );
+        Ok(())
+    }
+    /// Move funds from held to available (resolve). Total stays the same.
+    pub fn release(&mut self, amount: Amount) {
+        let total = self.balance.total_funds();
+        self.balance.release(amount);
+        check_invariant(
+            self.balance.total_funds() == total,
+            "total changed during resolve",
+        );
+        check_invariant(
+            self.balance.held >= Funds::ZERO,
+            "held negative after resolve",
+        );
+    }
+    /// Remove held funds (chargeback) and freeze the account.
+    pub fn charge_back(&mut self, amount: Amount) {
+        let available = self.balance.available;
+        self.balance.release(amount);
+        self.balance.remove(amount);
+        self.locked = true;
+        check_invariant(
+            self.balance.available == available,
+            "chargeback removed available funds",
+        );
+        check_invariant(
+            self.balance.held >= Funds::ZERO,
+            "held negative after chargeback",
+        );
+    }
+    /// Lift the freeze of a chargeback, once a temporary freeze expires.
+    pub fn unlock(&mut self) {
+        self.locked = false;
+    }
+    /// Settle a deposit's pending funds into available. Allowed on locked accounts, since
+    /// the funds were received before the lock. Total stays the same.
+    pub fn settle(&mut self, amount: Amount) {
+        self.balance.pending -= amount.0;
+        self.balance.available += amount.0;
+        check_invariant(
+            self.balance.pending >= Funds::ZERO,
+            "pending negative after settlement",
+        );
+    }
+    /// Release funds held back in the reserve into available. Allowed on locked accounts,
+    /// like settlements. Total stays the same.
+    pub fn release_reserve(&mut self, amount: Amount) {
+        self.balance.reserve -= amount.0;
+        self.balance.available += amount.0;
+        check_invariant(
+            self.balance.reserve >= Funds::ZERO,
+            "reserve negative after release",
+        );
+    }
+    /// Apply an operator's adjustment to available. Allowed on locked accounts, and may
+    /// drive available negative: operators correct balances as they see fit. Fails,
+    /// adjusting nothing, if the balance can't be stored.
+    pub fn adjust(&mut self, amount: SignedAmount) -> Result<(), DomainError> {
+        self.balance.update(Balance {
+            available: add_funds(self.balance.available, amount.0)?,
+            ..self.balance.clone()
+        })
+    }
+    /// Settle a captured payout: the authorized funds leave the account.
+    pub fn capture(&mut self, amount: Amount) {
+        self.balance.authorized -= amount.0;
+        check_invariant(
+            self.balance.authorized >= Funds::ZERO,
+            "authorized negative after capture",
+        );
+    }
+    /// Cancel a payout: the authorized funds go back to available. The gross total stays
+    /// the same.
+    pub fn void(&mut self, amount: Amount) {
+        let gross_total = self.balance.gross_funds();
+        self.balance.authorized -= amount.0;
+        self.balance.available += amount.0;
+        check_invariant(
+            self.balance.gross_funds() == gross_total,
+            "gross total changed during void",
+        );
+        check_invariant(
+            self.balance.authorized >= Funds::ZERO,
+            "authorized negative after void",
+        );
+    }
+}
+
+/// Panics in debug builds (so tests catch it) and logs an error in release builds,
+/// where a single bad account shouldn't take down a whole run.
+fn check_invariant(holds: bool, violation: &str) {
+    if holds {
+        return;
+    }
+    if cfg!(debug_assertions) {
+        panic!("Balance invariant violated: {violation}");
+    }
+    error!("Balance invariant violated: {violation}");
+}
+
+/// Mutable view of an unlocked account - the only way to move funds in or out.
+#[derive(Debug)]
+pub struct ActiveAccount<'a>(&'a mut Balance);
+
+impl ActiveAccount<'_> {
+    pub fn available(&self) -> Decimal {
+        self.0.available()
+    }
+    /// Credit funds (deposit). Increases available. Fails, crediting nothing, if the
+    /// balance can't be stored.
+    pub fn deposit(&mut self, amount: Amount) -> Result<(), DomainError> {
+        self.credit(amount, Amount::ZERO, Amount::ZERO)
+    }
+    /// Credit a deposit split between available, pending (not settled yet, see
+    /// [`Account::settle`]) and the rolling reserve (see [`Account::release_reserve`]).
+    /// Fails, crediting nothing, if the balance can't be stored.
+    pub fn credit(
+        &mut self,
+        available: Amount,
+        pending: Amount,
+        reserve: Amount,
+    ) -> Result<(), DomainError> {
+        self.0.update(Balance {
+            available: add_funds(self.0.available, available.0)?,
+            pending: add_funds(self.0.pending, pending.0)?,
+            reserve: add_funds(self.0.reserve, reserve.0)?,
+            ..self.0.clone()
+        })
+    }
+    /// Debit funds (withdrawal). Fails if available < amount.
+    pub fn withdraw(&mut self, amount: Amount) -> Result<(), DomainError> {
+        self.0.try_remove(amount)
+    }
+    /// Debit funds (withdrawal), letting available go down to `-overdraft`. Fails beyond
+    /// that, or with [`DomainError::AmountTooLarge`] if `available + overdraft` can't be stored.
+    pub fn withdraw_with_overdraft(
+        &mut self,
+        amount: Amount,
+        overdraft: Amount,
+    ) -> Result<(), DomainError> {
+        if add_funds(self.0.available, overdraft.0)? < amount.0 {
+            return Err(DomainError::InsufficientFunds);
+        }
+        self.0.remove(amount);
+        Ok(())
+    }
+    /// Reserve funds for a payout (authorization). Fails if available < amount.
+    pub fn authorize(&mut self, amount: Amount) -> Result<(), DomainError> {
+        self.0.try_remove(amount)?;
+        self.0.authorized += amount.0;
+        Ok(())
+    }
+}
+
+/// Tracks a client's funds. Invariant: total = available + held, as in the required output.
+/// `held` is reserved by disputes. The other buckets are left out of `total` and counted by
+/// [`Balance::gross_total`]: `authorized` is reserved by pending payouts, `pending` is
+/// deposited but not yet settled and `reserve` is held back from deposits for a while.
+/// Only readable from outside this module - mutations go through [`Account`].
+///
+/// Mutations that bring funds in fail if the gross total couldn't be stored, so moving
+/// funds between buckets or out of the account can't overflow.
+///
+/// `total` is included when serializing. On deserialize it's optional, but if present it
+/// must match the other fields, and only available funds can be negative.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "BalanceRepr", try_from = "BalanceRepr")]
+pub struct Balance {
+    available: Funds,
+    held: Funds,
+    authorized: Funds,
+    pending: Funds,
+    reserve: Funds,
+}
+
+impl Balance {
+    /// # Panics
+    ///
+    /// With the `fixed-point` feature, if a value has more than [`AMOUNT_MAX_SCALE`]
+    /// decimal places or doesn't fit. Deserializing reports these as errors instead.
+    pub fn new(available: Decimal, held: Decimal) -> Self {
+        Self {
+            available: funds_or_panic(available),
+            held: funds_or_panic(held),
+            authorized: Funds::ZERO,
+            pending: Funds::ZERO,
+            reserve: Funds::ZERO,
+        }
+    }
+    /// # Panics
+    ///
+    /// Same as [`Balance::new`].
+    pub fn with_authorized(mut self, authorized: Decimal) -> Self {
+        self.authorized = funds_or_panic(authorized);
+        self
+    }
+    pub fn available(&self) -> Decimal {
+        to_decimal(self.available)
+    }
+    pub fn held(&self) -> Decimal {
+        to_decimal(self.held)
+    }
+    /// # Panics
+    ///
+    /// Same as [`Balance::new`].
+    pub fn with_pending(mut self, pending: Decimal) -> Self {
+        self.pending = funds_or_panic(pending);
+        self
+    }
+    /// # Panics
+    ///
+    /// Same as [`Balance::new`].
+    pub fn with_reserve(mut self, reserve: Decimal) -> Self {
+        self.reserve = funds_or_panic(reserve);
+        self
+    }
+    pub fn authorized(&self) -> Decimal {
+        to_decimal(self.authorized)
+    }
+    pub fn pending(&self) -> Decimal {
+        to_decimal(self.pending)
+    }
+    pub fn reserve(&self) -> Decimal {
+        to_decimal(self.reserve)
+    }
+    pub fn total(&self) -> Decimal {
+        to_decimal(self.total_funds())
+    }
+    /// `total` plus the authorized, pending and reserve funds: everything the account still
+    /// holds for the client.
+    pub fn gross_total(&self) -> Decimal {
+        to_decimal(self.gross_funds())
+    }
+    fn total_funds(&self) -> Funds {
+        self.available + self.held
+    }
+    fn gross_funds(&self) -> Funds {
+        self.total_funds() + self.authorized + self.pending + self.reserve
+    }
+    /// [`Balance::gross_funds`], or `None` if it or its partial sums can't be stored.
+    fn checked_gross_funds(&self) -> Option<Funds> {
+        [self.held, self.authorized, self.pending, self.reserve]
+            .into_iter()
+            .try_fold(self.available, Funds::checked_add)
+    }
+    /// Replaces the balance with `next`, unless its gross total can't be stored.
+    fn update(&mut self, next: Balance) -> Result<(), DomainError> {
+        if next.checked_gross_funds().is_none() {
+            return Err(DomainError::AmountTooLarge);
+        }
+        *self = next;
+        Ok(())
+    }
+    fn hold(&mut self, amount: Amount) -> Result<(), DomainError> {
+        self.update(Balance {
+            available: sub_funds(self.available, amount.0)?,
+            held: add_funds(self.held, amount.0)?,
+            ..self.clone()
+        })
+    }
+    fn release(&mut self, amount: Amount) {
+        self.held -= amount.0;
+        self.available += amount.0;
+    }
+    fn try_remove(&mut self, amount: Amount) -> Result<(), DomainError> {
+        if self.available >= amount.0 {
+            self.available -= amount.0;
+        } else {
+            return Err(DomainError::InsufficientFunds);
+        }
+        Ok(())
+    }
+    fn remove(&mut self, amount: Amount) {
+        self.available -= amount.0;
+    }
+}
+
+fn funds_or_panic(value: Decimal) -> Funds {
+    to_funds(value).unwrap_or_else(|e| panic!("{value} can't be stored as a balance: {e}"))
+}
+
+/// Serialized form of [`Balance`], carrying the derived `total`.
+#[derive(Serialize, Deserialize)]
+struct BalanceRepr {
+    available: Decimal,
+    held: Decimal,
+    #[serde(default)]
+    total: Option<Decimal>,
+    #[serde(default)]
+    authorized: Decimal,
+    #[serde(default)]
+    pending: Decimal,
+    #[serde(default)]
+    reserve: Decimal,
+}
+
+impl From<Balance> for BalanceRepr {
+    fn from(balance: Balance) -> Self {
+        Self {
+            available: balance.available(),
+            held: balance.held(),
+            total: Some(balance.total()),
+            authorized: balance.authorized(),
+            pending: balance.pending(),
+            reserve: balance.reserve(),
+        }
+    }
+}
+
+impl TryFrom<BalanceRepr> for Balance {
+    type Error = DomainError;
+
+    fn try_from(repr: BalanceRepr) -> Result<Self, Self::Error> {
+        if repr.held < Decimal::ZERO {
+            return Err(DomainError::InconsistentBalance("held is negative"));
+        }
+        if repr.authorized < Decimal::ZERO {
+            return Err(DomainError::InconsistentBalance("authorized is negative"));
+        }
+        if repr.pending < Decimal::ZERO {
+            return Err(DomainError::InconsistentBalance("pending is negative"));
+        }
+        if repr.reserve < Decimal::ZERO {
+            return Err(DomainError::InconsistentBalance("reserve is negative"));
+        }
+        let balance = Balance {
+            available: to_funds(repr.available)?,
+            held: to_funds(repr.held)?,
+            authorized: to_funds(repr.authorized)?,
+            pending: to_funds(repr.pending)?,
+            reserve: to_funds(repr.reserve)?,
+        };
+        if balance.checked_gross_funds().is_none() {
+            return Err(DomainError::AmountTooLarge);
+        }
+        if repr.total.is_some_and(|total| total != balance.total()) {
+            return Err(DomainError::InconsistentBalance(
+                "total doesn't equal the sum of the other funds",
+            ));
+        }
+        Ok(balance)
+    }
+}
+
+/// Serialized form of [`Account`]. Kept flat (rather than nesting [`BalanceRepr`]) so
+/// it can be written as CSV columns - the csv crate doesn't support nested maps.
+#[derive(Serialize, Deserialize)]
+struct AccountRepr {
+    available: Decimal,
+    held: Decimal,
+    #[serde(default)]
+    total: Option<Decimal>,
+    locked: bool,
+    #[serde(default)]
+    authorized: Decimal,
+    #[serde(default)]
+    pending: Decimal,
+    #[serde(default)]
+    reserve: Decimal,
+}
+
+impl From<Account> for AccountRepr {
+    fn from(account: Account) -> Self {
+        let balance = BalanceRepr::from(account.balance);
+        Self {
+            available: balance.available,
+            held: balance.held,
+            total: balance.total,
+            locked: account.locked,
+            authorized: balance.authorized,
+            pending: balance.pending,
+            reserve: balance.reserve,
+        }
+    }
+}
+
+impl TryFrom<AccountRepr> for Account {
+    type Error = DomainError;
+
+    fn try_from(repr: AccountRepr) -> Result<Self, Self::Error> {
+        let balance = Balance::try_from(BalanceRepr {
+            available: repr.available,
+            held: repr.held,
+            total: repr.total,
+            authorized: repr.authorized,
+            pending: repr.pending,
+            reserve: repr.reserve,
+        })?;
+        Ok(Account::new(balance, repr.locked))
+    }
+}
+
+/// Inner struct shared by Deposit, Withdrawal, Authorization and Refund - transactions that
+/// carry an amount. A refund's `tx` is the refunded deposit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct MovementTransaction {
+    client: ClientId,
+    tx: TransactionId,
+    #[serde(deserialize_with = "deserialize_amount")]
+    amount: Amount,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent_tx: Option<TransactionId>,
+}
+impl MovementTransaction {
+    pub fn new(client: ClientId, tx: TransactionId, amount: Amount) -> Self {
+        Self {
+            client,
+            tx,
+            amount,
+            parent_tx: None,
+        }
+    }
+}
+impl fmt::Display for MovementTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tx {} (client {}, amount {}",
+            self.tx, self.client, self.amount
+        )?;
+        write_parent_tx(f, self.parent_tx)
+    }
+}
+
+/// Closes the parenthesis of a transaction's description, mentioning its parent tx.
+fn write_parent_tx(f: &mut fmt::Formatter<'_>, parent_tx: Option<TransactionId>) -> fmt::Result {
+    match parent_tx {
+        Some(parent_tx) => write!(f, ", parent tx {parent_tx})"),
+        None => write!(f, ")"),
+    }
+}
+
+/// Inner struct shared by the dispute family (Dispute, Resolve, Chargeback, Evidence,
+/// PreArbitration), Capture and Void - they reference an existing tx.
+/// The referenced tx is serialized as `tx`, like in the CSV format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct DisputeTransaction {
+    client: ClientId,
+    #[serde(rename = "tx")]
+    disputed_tx: TransactionId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent_tx: Option<TransactionId>,
+}
+
+impl DisputeTransaction {
+    pub fn new(client: ClientId, disputed_tx: TransactionId) -> Self {
+        Self {
+            client,
+            disputed_tx,
+            parent_tx: None,
+        }
+    }
+    pub fn client_id(&self) -> ClientId {
+        self.client
+    }
+    pub fn disputed_transaction_id(&self) -> TransactionId {
+        self.disputed_tx
+    }
+}
+impl fmt::Display for DisputeTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tx {} (client {}", self.disputed_tx, self.client)?;
+        write_parent_tx(f, self.parent_tx)
+    }
+}
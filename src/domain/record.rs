@@ -0,0 +1,47 @@
+//! Serializable form of a [`Deposit`] with its engine bookkeeping, behind the `cold-tier`
+//! feature.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{Amount, Deposit, DisputeState, MovementTransaction, TransactionId};
+
+/// Every field of a [`Deposit`], its engine bookkeeping included, for stores that keep
+/// deposits outside of memory.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct DepositRecord {
+    tx: MovementTransaction,
+    dispute: DisputeState,
+    shortfall: Amount,
+    refunded: Amount,
+    deposited_at: u64,
+}
+
+impl DepositRecord {
+    pub(crate) fn transaction_id(&self) -> TransactionId {
+        self.tx.tx
+    }
+}
+
+impl From<Deposit> for DepositRecord {
+    fn from(deposit: Deposit) -> Self {
+        Self {
+            tx: deposit.tx,
+            dispute: deposit.dispute,
+            shortfall: deposit.shortfall,
+            refunded: deposit.refunded,
+            deposited_at: deposit.deposited_at,
+        }
+    }
+}
+
+impl From<DepositRecord> for Deposit {
+    fn from(record: DepositRecord) -> Self {
+        Self {
+            dispute: record.dispute,
+            shortfall: record.shortfall,
+            refunded: record.refunded,
+            deposited_at: record.deposited_at,
+            tx: record.tx,
+        }
+    }
+}
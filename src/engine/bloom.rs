@@ -0,0 +1,122 @@
+//! Bloom filter over the tx IDs of stored deposits, so lookups of deposits that were never
+//! stored don't reach the history store.
+//!
+//! In noisy inputs most disputes, resolves and chargebacks reference a tx that doesn't
+//! exist. [`BloomFilteredHistory`] wraps any [`DisputeHistoryStore`] and answers those
+//! lookups from the filter alone, which matters when the store is slow (e.g. on disk). A
+//! Bloom filter has no false negatives, so a stored deposit is always looked up; a small
+//! share of lookups of unknown txs still reaches the store.
+
+use crate::{
+    domain::{ClientId, Deposit, TransactionId},
+    engine::DisputeHistoryStore,
+};
+
+/// Set of tx IDs that can answer "definitely not inserted" or "maybe inserted".
+#[derive(Debug, Clone)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    /// Number of bits, a multiple of 64.
+    len: u64,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// Sizes the filter so that after `expected` insertions, roughly `false_positive_rate`
+    /// of the tx IDs that weren't inserted still answer "maybe". More insertions raise the
+    /// rate but never cause false negatives.
+    pub fn new(expected: usize, false_positive_rate: f64) -> Self {
+        let expected = expected.max(1) as f64;
+        let rate = false_positive_rate.clamp(f64::MIN_POSITIVE, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-expected * rate.ln() / (ln2 * ln2)).ceil().max(64.0);
+        let words = (bits / 64.0).ceil() as usize;
+        let len = words as u64 * 64;
+        let hashes = ((len as f64 / expected) * ln2).round().clamp(1.0, 32.0) as u32;
+        Self {
+            bits: vec![0; words],
+            len,
+            hashes,
+        }
+    }
+    pub fn insert(&mut self, tx_id: TransactionId) {
+        for bit in self.bit_indexes(tx_id) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+    /// `false` if `tx_id` was never inserted; `true` if it was, or on a false positive.
+    pub fn may_contain(&self, tx_id: TransactionId) -> bool {
+        self.bit_indexes(tx_id)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Bits of `tx_id`, by double hashing: `h1 + i * h2` for each hash function `i`.
+    fn bit_indexes(&self, tx_id: TransactionId) -> impl Iterator<Item = u64> + use<> {
+        let h1 = mix(u64::from(u32::from(tx_id)));
+        let h2 = mix(h1) | 1;
+        let len = self.len;
+        (0..u64::from(self.hashes)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % len)
+    }
+}
+
+/// SplitMix64 finalizer: spreads sequential tx IDs over the whole range.
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// A [`DisputeHistoryStore`] that only consults `inner` for tx IDs a [`BloomFilter`] of
+/// every inserted deposit may contain.
+#[derive(Debug, Clone)]
+pub struct BloomFilteredHistory<H> {
+    filter: BloomFilter,
+    inner: H,
+    /// Lookups answered by the filter alone.
+    skipped: u64,
+}
+
+impl<H: DisputeHistoryStore> BloomFilteredHistory<H> {
+    /// Wraps an empty store, with a filter sized as in [`BloomFilter::new`].
+    pub fn new(inner: H, expected_deposits: usize, false_positive_rate: f64) -> Self {
+        Self {
+            filter: BloomFilter::new(expected_deposits, false_positive_rate),
+            inner,
+            skipped: 0,
+        }
+    }
+    pub fn inner(&self) -> &H {
+        &self.inner
+    }
+    pub fn into_inner(self) -> H {
+        self.inner
+    }
+    /// How many lookups the filter answered without consulting the store.
+    pub fn skipped_lookups(&self) -> u64 {
+        self.skipped
+    }
+}
+
+impl<H: DisputeHistoryStore> DisputeHistoryStore for BloomFilteredHistory<H> {
+    fn insert(&mut self, deposit: Deposit) {
+        self.filter.insert(deposit.transaction_id());
+        self.inner.insert(deposit);
+    }
+    fn contains(&self, tx_id: &TransactionId) -> bool {
+        self.filter.may_contain(*tx_id) && self.inner.contains(tx_id)
+    }
+    fn get_mut(&mut self, tx_id: &TransactionId, client_id: &ClientId) -> Option<&mut Deposit> {
+        if !self.filter.may_contain(*tx_id) {
+            self.skipped += 1;
+            return None;
+        }
+        self.inner.get_mut(tx_id, client_id)
+    }
+    fn insert_tx_id(&mut self, tx_id: TransactionId) -> bool {
+        self.inner.insert_tx_id(tx_id)
+    }
+    fn contains_tx_id(&self, tx_id: &TransactionId) -> bool {
+        self.inner.contains_tx_id(tx_id)
+    }
+}
@@ -0,0 +1,27 @@
+//! Pre-sizing of the engine's maps, so large runs don't keep rehashing as they grow.
+
+/// Rough size of an input row, e.g. `deposit,1234,56789,12.3456` plus a newline.
+const ESTIMATED_ROW_BYTES: u64 = 24;
+
+/// Number of distinct client IDs.
+const MAX_CLIENTS: usize = u16::MAX as usize + 1;
+
+/// Expected number of entries in the engine's maps. Only affects allocation, never behavior.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityHint {
+    pub clients: usize,
+    pub deposits: usize,
+}
+
+impl CapacityHint {
+    /// Estimates from the size of a CSV input, assuming every row is a deposit - an upper
+    /// bound for deposit history that trades some memory for never rehashing. Clients are
+    /// capped at the number of possible [`ClientId`](crate::domain::ClientId)s.
+    pub fn from_input_size(bytes: u64) -> Self {
+        let rows = usize::try_from(bytes / ESTIMATED_ROW_BYTES).unwrap_or(usize::MAX);
+        Self {
+            clients: rows.min(MAX_CLIENTS),
+            deposits: rows,
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Checkpoint sequence numbers, and the accounts that changed since the last checkpoint for
+//! delta snapshots.
+
+use std::collections::HashMap;
+
+use crate::{
+    domain::{Account, ClientId},
+    engine::ClientAccounts,
+};
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Checkpoints {
+    /// Sequence number of the last snapshot, 0 before the first.
+    sequence: u64,
+    /// State at the last checkpoint of every client touched since (`None` for accounts
+    /// that didn't exist yet). Not tracked until the first delta snapshot.
+    baseline: Option<HashMap<ClientId, Option<Account>>>,
+}
+
+impl Checkpoints {
+    pub(crate) fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub(crate) fn next_sequence(&self) -> u64 {
+        self.sequence + 1
+    }
+
+    /// Remembers the state of `client`'s account before it's first touched after a
+    /// checkpoint. Must be called before the account changes.
+    pub(crate) fn touch(&mut self, client: ClientId, accounts: &ClientAccounts) {
+        if let Some(baseline) = &mut self.baseline {
+            baseline
+                .entry(client)
+                .or_insert_with(|| accounts.get(client).cloned());
+        }
+    }
+
+    /// The accounts that differ from the last checkpoint, in client ID order, or `None`
+    /// if changes aren't tracked yet and every account has to be assumed changed.
+    pub(crate) fn changed<'a>(
+        &self,
+        accounts: &'a ClientAccounts,
+    ) -> Option<Vec<(ClientId, &'a Account)>> {
+        let baseline = self.baseline.as_ref()?;
+        let mut changed: Vec<_> = baseline
+            .iter()
+            .filter_map(|(client, before)| {
+                let account = accounts.get(*client)?;
+                (before.as_ref() != Some(account)).then_some((*client, account))
+            })
+            .collect();
+        changed.sort_unstable_by_key(|(client, _)| *client);
+        Some(changed)
+    }
+
+    /// Ends a checkpoint once its snapshot was sent. `track` starts tracking changes from
+    /// here on; once started, it continues.
+    pub(crate) fn commit(&mut self, track: bool) {
+        self.sequence += 1;
+        if track || self.baseline.is_some() {
+            self.baseline = Some(HashMap::new());
+        }
+    }
+}
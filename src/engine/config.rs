@@ -0,0 +1,125 @@
+//! Engine options for behavior that differs between upstream networks.
+//!
+//! The defaults reproduce the engine's original behavior.
+
+use rust_decimal::Decimal;
+
+use crate::engine::AccountTiers;
+
+/// How a resolve or chargeback is handled when it repeats the outcome the dispute
+/// already has (e.g. a second chargeback for an already charged back tx).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatedSettlement {
+    /// Report the replay as an error (`TransactionNotDisputed`).
+    #[default]
+    Error,
+    /// Treat the replay as a no-op - useful for at-least-once upstream delivery.
+    Ignore,
+}
+
+/// Which disputes, resolves and chargebacks still apply once an account is locked. The
+/// ones not permitted are rejected as `AccountLocked`, like deposits and withdrawals.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LockedAccountDisputes {
+    /// All of them, as on an unlocked account.
+    #[default]
+    Allow,
+    /// Disputes that were open when the account was locked can still be resolved or
+    /// charged back, but no dispute can be opened.
+    SettleOpen,
+    /// None: a locked account is fully inert.
+    Reject,
+}
+
+impl LockedAccountDisputes {
+    /// Whether a transaction that opens a dispute (`opens`), or only settles an open one,
+    /// applies to a locked account.
+    pub(crate) fn permits(self, opens: bool) -> bool {
+        match self {
+            LockedAccountDisputes::Allow => true,
+            LockedAccountDisputes::SettleOpen => !opens,
+            LockedAccountDisputes::Reject => false,
+        }
+    }
+}
+
+/// How a dispute is handled when the client's available funds don't cover the disputed
+/// deposit, e.g. because part of it was already withdrawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NegativeAvailable {
+    /// Hold the whole amount, driving available negative: a debt to the disputing partner.
+    #[default]
+    Allow,
+    /// Hold only what's available, so it stops at zero. The rest is tracked as the
+    /// account's shortfall, see `Account::shortfall`.
+    CapAtZero,
+    /// Reject the dispute as `InsufficientFunds`.
+    Reject,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EngineConfig {
+    pub repeated_settlement: RepeatedSettlement,
+    /// When set, a chargeback for an existing deposit of the same client that has no
+    /// open dispute opens one and charges it back immediately, instead of being rejected.
+    pub chargeback_opens_dispute: bool,
+    pub locked_account_disputes: LockedAccountDisputes,
+    pub negative_available: NegativeAvailable,
+    /// Deposits are credited to `pending` and only become available once this many more
+    /// transactions were processed; withdrawals can't draw on them meanwhile. 0 credits
+    /// deposits to available right away, and `u64::MAX` never settles them.
+    pub settlement_delay: u64,
+    pub rolling_reserve: RollingReserve,
+    /// Disputes opened more than this many transactions after their deposit (counting
+    /// the dispute) are rejected as `DisputeWindowExpired`. `None` accepts disputes of
+    /// deposits of any age.
+    pub dispute_window: Option<u64>,
+    /// Per-client limits, see [`AccountTiers`].
+    pub tiers: AccountTiers,
+    pub chargeback_freeze: ChargebackFreeze,
+    /// When set, a dispute, resolve, chargeback, evidence or pre-arbitration referencing a
+    /// tx no deposit was stored for yet is parked instead of ignored, and retried right
+    /// after that deposit. One still parked after this many more transactions is ignored
+    /// as `TransactionNotFound`; `u64::MAX` keeps them parked. `None` ignores them straight
+    /// away, as the spec says.
+    pub reorder_window: Option<u64>,
+}
+
+impl EngineConfig {
+    /// Whether transactions of different clients don't affect each other through the
+    /// transaction clock, i.e. no settlement delay, rolling reserve, dispute window,
+    /// temporary freeze or reorder window depends on how many transactions were processed
+    /// in between.
+    pub(crate) fn is_clockless(&self) -> bool {
+        self.settlement_delay == 0
+            && self.rolling_reserve.rate.is_zero()
+            && !self.tiers.has_reserve_rates()
+            && self.dispute_window.is_none()
+            && self.chargeback_freeze == ChargebackFreeze::Permanent
+            && self.reorder_window.is_none()
+    }
+}
+
+/// How long a chargeback freezes the account for.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ChargebackFreeze {
+    /// For good.
+    #[default]
+    Permanent,
+    /// Until `horizon` more transactions were processed, counted like
+    /// [`EngineConfig::settlement_delay`] from the chargeback. Another chargeback meanwhile
+    /// extends the freeze. `u64::MAX` never lifts it.
+    Temporary { horizon: u64 },
+}
+
+/// Part of every deposit held back in the account's `reserve` and released to available
+/// after a delay, protecting against chargebacks on recently deposited funds.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RollingReserve {
+    /// Share of each deposit held back, between 0 and 1, rounded down to 4 decimal
+    /// places. 0 disables the reserve.
+    pub rate: Decimal,
+    /// Transactions processed before a deposit's reserve is released, counted like
+    /// [`EngineConfig::settlement_delay`] from the deposit. `u64::MAX` never releases it.
+    pub delay: u64,
+}
@@ -0,0 +1,90 @@
+//! `dense-accounts` feature: [`ClientAccounts`] backed by an array indexed by client ID.
+//!
+//! `ClientId` is a `u16`, so every account fits in a table of at most 65536 slots.
+//! Lookups are a bounds check and an index, with no hashing, which wins when a run
+//! touches a large share of the ID space. The table grows to the highest ID seen, so
+//! small inputs stay small, and iterating it yields accounts already sorted.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::{
+    domain::{Account, ClientId},
+    engine::ClientHasher,
+};
+
+/// Number of distinct client IDs.
+const MAX_CLIENTS: usize = u16::MAX as usize + 1;
+
+/// Maps each client to their account. Accounts are lazily created on first transaction.
+#[derive(Debug, Clone, Default)]
+pub struct ClientAccounts {
+    slots: Vec<Option<Account>>,
+    len: usize,
+    /// Snapshot handed out by [`Self::as_map`], dropped on every mutation.
+    map: OnceLock<HashMap<ClientId, Account, ClientHasher>>,
+}
+
+impl ClientAccounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Reserves slots for client IDs below `capacity`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity.min(MAX_CLIENTS)),
+            ..Self::default()
+        }
+    }
+    /// Exposes the accounts as a map. Built on first use after a mutation, in
+    /// O(accounts), so prefer [`Self::iter`] / [`Self::iter_sorted`].
+    pub fn as_map(&self) -> &HashMap<ClientId, Account, ClientHasher> {
+        self.map.get_or_init(|| {
+            self.iter()
+                .map(|(client_id, account)| (client_id, account.clone()))
+                .collect()
+        })
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn get(&self, client_id: ClientId) -> Option<&Account> {
+        self.slots
+            .get(usize::from(u16::from(client_id)))
+            .and_then(Option::as_ref)
+    }
+    /// Number of client ID slots reserved before the table reallocates.
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+    /// Iterates accounts. Slots are in client ID order, so this is sorted too.
+    pub fn iter(&self) -> impl Iterator<Item = (ClientId, &Account)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let client_id = u16::try_from(index).expect("at most 65536 slots");
+            slot.as_ref()
+                .map(|account| (ClientId::from(client_id), account))
+        })
+    }
+    /// Iterates accounts in ascending client ID order, for deterministic output.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (ClientId, &Account)> {
+        self.iter()
+    }
+    pub fn get_or_create_account_mut(&mut self, client_id: ClientId) -> &mut Account {
+        self.map.take();
+        let index = usize::from(u16::from(client_id));
+        if index >= self.slots.len() {
+            self.slots.resize_with(index + 1, || None);
+        }
+        let slot = &mut self.slots[index];
+        if slot.is_none() {
+            self.len += 1;
+        }
+        slot.get_or_insert_with(Account::default)
+    }
+
+    pub fn get_or_create_account(&mut self, client_id: ClientId) -> &Account {
+        self.get_or_create_account_mut(client_id)
+    }
+}
@@ -1,15 +1,187 @@
-use crate::domain::DomainError;
+use rust_decimal::Decimal;
 
-#[derive(Debug, thiserror::Error)]
+use crate::{
+    domain::{
+        Amount, Authorization, ClientId, Deposit, DisputeState, DisputeTransitionError,
+        TransactionId,
+    },
+    redaction,
+};
+
+/// Every variant carries the client and tx that caused it, so a log line or
+/// report can point at the exact offending row. Client IDs and amounts go through
+/// [`redaction`], so they're masked in messages when it's enabled.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
 pub enum EngineError {
-    #[error("Account is locked")]
-    AccountLocked,
-    #[error("Transaction not found")]
-    TransactionNotFound,
-    #[error("Transaction already disputed")]
-    TransactionAlreadyDisputed,
-    #[error("Transaction not disputed")]
-    TransactionNotDisputed,
-    #[error("Domain error: {0}")]
-    DomainError(#[from] DomainError),
+    #[error(
+        "Account of client {} is locked, rejected tx {tx} of {}",
+        redaction::client(*.client),
+        redaction::amount(*.amount)
+    )]
+    AccountLocked {
+        client: ClientId,
+        tx: TransactionId,
+        amount: Amount,
+    },
+    #[error("Transaction {tx} not found for client {}", redaction::client(*.client))]
+    TransactionNotFound { client: ClientId, tx: TransactionId },
+    #[error(
+        "Transaction {tx} of client {} ({}) already disputed",
+        redaction::client(*.client),
+        redaction::amount(*.amount)
+    )]
+    TransactionAlreadyDisputed {
+        client: ClientId,
+        tx: TransactionId,
+        amount: Amount,
+    },
+    #[error(
+        "Transaction {tx} of client {} ({}) not disputed",
+        redaction::client(*.client),
+        redaction::amount(*.amount)
+    )]
+    TransactionNotDisputed {
+        client: ClientId,
+        tx: TransactionId,
+        amount: Amount,
+    },
+    #[error(
+        "Authorization {tx} of client {} ({}) already captured or voided",
+        redaction::client(*.client),
+        redaction::amount(*.amount)
+    )]
+    AuthorizationAlreadySettled {
+        client: ClientId,
+        tx: TransactionId,
+        amount: Amount,
+    },
+    #[error(
+        "Insufficient funds for client {}: tx {tx} requested {}, available {}",
+        redaction::client(*.client),
+        redaction::amount(*.requested),
+        redaction::amount(*.available)
+    )]
+    InsufficientFunds {
+        client: ClientId,
+        tx: TransactionId,
+        requested: Amount,
+        available: Decimal,
+    },
+    #[error(
+        "Dispute of transaction {tx} of client {} ({}) is at stage {stage:?}, out of order",
+        redaction::client(*.client),
+        redaction::amount(*.amount)
+    )]
+    DisputeStageOutOfOrder {
+        client: ClientId,
+        tx: TransactionId,
+        amount: Amount,
+        stage: DisputeState,
+    },
+    #[error(
+        "Deposit {tx} of client {} ({}) was fully refunded",
+        redaction::client(*.client),
+        redaction::amount(*.amount)
+    )]
+    DepositRefunded {
+        client: ClientId,
+        tx: TransactionId,
+        amount: Amount,
+    },
+    #[error(
+        "Refund of deposit {tx} of client {} requested {}, only {} not refunded yet",
+        redaction::client(*.client),
+        redaction::amount(*.requested),
+        redaction::amount(*.refundable)
+    )]
+    RefundExceedsDeposit {
+        client: ClientId,
+        tx: TransactionId,
+        requested: Amount,
+        refundable: Amount,
+    },
+    #[error(
+        "Dispute of transaction {tx} of client {} ({}) opened {age} transactions after it, \
+         outside the dispute window of {window}",
+        redaction::client(*.client),
+        redaction::amount(*.amount)
+    )]
+    DisputeWindowExpired {
+        client: ClientId,
+        tx: TransactionId,
+        amount: Amount,
+        age: u64,
+        window: u64,
+    },
+    #[error(
+        "Withdrawal {tx} of client {} requested {}, above the tier's limit of {}",
+        redaction::client(*.client),
+        redaction::amount(*.requested),
+        redaction::amount(*.limit)
+    )]
+    WithdrawalLimitExceeded {
+        client: ClientId,
+        tx: TransactionId,
+        requested: Amount,
+        limit: Amount,
+    },
+    #[error(
+        "Transaction {tx} of client {} rejected by middleware: {reason}",
+        redaction::client(*.client)
+    )]
+    RejectedByMiddleware {
+        client: ClientId,
+        tx: TransactionId,
+        reason: String,
+    },
+    #[error(
+        "Transaction {tx} of client {} reuses the ID of an earlier transaction",
+        redaction::client(*.client)
+    )]
+    DuplicateTransactionId { client: ClientId, tx: TransactionId },
+    #[error(
+        "Transaction {tx} of client {} would take a balance beyond the largest storable amount",
+        redaction::client(*.client)
+    )]
+    AmountTooLarge { client: ClientId, tx: TransactionId },
+}
+
+impl EngineError {
+    /// Attaches the disputed deposit's details to a rejected dispute transition.
+    pub(crate) fn dispute_transition(error: DisputeTransitionError, deposit: &Deposit) -> Self {
+        let (client, tx, amount) = (
+            deposit.client_id(),
+            deposit.transaction_id(),
+            deposit.amount(),
+        );
+        match error {
+            DisputeTransitionError::AlreadyDisputed => {
+                EngineError::TransactionAlreadyDisputed { client, tx, amount }
+            }
+            DisputeTransitionError::NotDisputed => {
+                EngineError::TransactionNotDisputed { client, tx, amount }
+            }
+            DisputeTransitionError::OutOfOrder => EngineError::DisputeStageOutOfOrder {
+                client,
+                tx,
+                amount,
+                stage: deposit.dispute_state(),
+            },
+        }
+    }
+    /// A dispute, resolve or chargeback of `deposit` that the locked-account policy forbids.
+    pub(crate) fn locked_dispute(deposit: &Deposit) -> Self {
+        EngineError::AccountLocked {
+            client: deposit.client_id(),
+            tx: deposit.transaction_id(),
+            amount: deposit.amount(),
+        }
+    }
+    pub(crate) fn authorization_settled(authorization: &Authorization) -> Self {
+        EngineError::AuthorizationAlreadySettled {
+            client: authorization.client_id(),
+            tx: authorization.transaction_id(),
+            amount: authorization.amount(),
+        }
+    }
 }
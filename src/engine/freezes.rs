@@ -0,0 +1,38 @@
+//! Accounts frozen by a chargeback until a horizon, see [`ChargebackFreeze::Temporary`].
+//!
+//! [`ChargebackFreeze::Temporary`]: crate::engine::ChargebackFreeze::Temporary
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::domain::ClientId;
+
+/// Temporary freezes in the order they expire. A client frozen again before its freeze
+/// expired has an entry per chargeback; only the last one lifts the freeze.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TemporaryFreezes {
+    queue: VecDeque<(u64, ClientId)>,
+    /// When each frozen client's freeze expires.
+    until: HashMap<ClientId, u64>,
+}
+
+impl TemporaryFreezes {
+    /// Freezes `client` until `due` transactions were processed. The horizon is the same
+    /// for every freeze, so `due` never decreases and the queue stays ordered.
+    pub(crate) fn push(&mut self, due: u64, client: ClientId) {
+        self.queue.push_back((due, client));
+        self.until.insert(client, due);
+    }
+    /// Removes and returns the next client whose freeze expired by `processed` transactions.
+    pub(crate) fn pop_due(&mut self, processed: u64) -> Option<ClientId> {
+        while let Some(&(due, client)) = self.queue.front()
+            && due <= processed
+        {
+            self.queue.pop_front();
+            if self.until.get(&client) == Some(&due) {
+                self.until.remove(&client);
+                return Some(client);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,121 @@
+//! Middleware around [`PaymentsEngine::apply`]: caller-supplied stages that validate,
+//! enrich or filter transactions before the engine sees them, and observe the outcome
+//! after, so policy checks don't need a fork of the engine.
+//!
+//! Stages run in the order they were added before the transaction is applied, and in
+//! reverse order after it, so the first stage wraps all the others.
+
+use tracing::warn;
+
+use crate::{
+    domain::Transaction,
+    engine::{IgnoreReason, Outcome, PaymentsEngine, errors::EngineError},
+};
+
+/// What a stage decides about a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// Pass the transaction (as the stage left it) to the next stage, then the engine.
+    Continue,
+    /// Drop it, reported as ignored with [`IgnoreReason::Filtered`].
+    Filter,
+    /// Reject it, reported as [`EngineError::RejectedByMiddleware`] with this reason.
+    Reject(String),
+}
+
+/// A stage of a [`MiddlewareEngine`]. Both hooks see the engine's state before the
+/// transaction is applied and after, respectively.
+///
+/// Closures taking the transaction and the engine and returning a [`Verdict`] are
+/// middleware with only a `before` hook.
+pub trait Middleware {
+    /// Validates, enriches (by modifying `transaction`) or filters a transaction.
+    fn before(&mut self, transaction: &mut Transaction, engine: &PaymentsEngine) -> Verdict {
+        let _ = (transaction, engine);
+        Verdict::Continue
+    }
+    /// Called for every transaction with its final outcome, including ones a stage
+    /// filtered or rejected.
+    fn after(&mut self, transaction: &Transaction, outcome: &Outcome, engine: &PaymentsEngine) {
+        let _ = (transaction, outcome, engine);
+    }
+}
+
+impl<F> Middleware for F
+where
+    F: FnMut(&mut Transaction, &PaymentsEngine) -> Verdict,
+{
+    fn before(&mut self, transaction: &mut Transaction, engine: &PaymentsEngine) -> Verdict {
+        self(transaction, engine)
+    }
+}
+
+/// A [`PaymentsEngine`] with a chain of [`Middleware`] around every transaction.
+pub struct MiddlewareEngine {
+    engine: PaymentsEngine,
+    middleware: Vec<Box<dyn Middleware>>,
+}
+
+impl MiddlewareEngine {
+    pub fn new(engine: PaymentsEngine) -> Self {
+        Self {
+            engine,
+            middleware: Vec::new(),
+        }
+    }
+    /// Adds a stage after the existing ones.
+    pub fn with(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.push(middleware);
+        self
+    }
+    /// Adds a stage after the existing ones.
+    pub fn push(&mut self, middleware: impl Middleware + 'static) {
+        self.middleware.push(Box::new(middleware));
+    }
+    pub fn engine(&self) -> &PaymentsEngine {
+        &self.engine
+    }
+    pub fn into_engine(self) -> PaymentsEngine {
+        self.engine
+    }
+    /// Runs the `before` hooks, applies the transaction unless one of them stopped it, then
+    /// runs the `after` hooks with the outcome.
+    pub fn apply(&mut self, mut transaction: Transaction) -> Outcome {
+        let mut verdict = Verdict::Continue;
+        for middleware in &mut self.middleware {
+            verdict = middleware.before(&mut transaction, &self.engine);
+            if verdict != Verdict::Continue {
+                break;
+            }
+        }
+        let outcome = match verdict {
+            Verdict::Continue if self.middleware.is_empty() => {
+                return self.engine.apply(transaction);
+            }
+            Verdict::Continue => self.engine.apply(transaction.clone()),
+            Verdict::Filter => Outcome::Ignored(IgnoreReason::Filtered),
+            Verdict::Reject(reason) => Outcome::Rejected(EngineError::RejectedByMiddleware {
+                client: transaction.client_id(),
+                tx: transaction.tx_id(),
+                reason,
+            }),
+        };
+        for middleware in self.middleware.iter_mut().rev() {
+            middleware.after(&transaction, &outcome, &self.engine);
+        }
+        outcome
+    }
+    /// Like [`PaymentsEngine::process_transaction`], through the middleware.
+    pub fn process_transaction(&mut self, transaction: Transaction) -> Result<(), EngineError> {
+        self.apply(transaction).into_result()
+    }
+    /// Like [`PaymentsEngine::process_transactions`], through the middleware.
+    pub fn process_transactions(&mut self, transactions: impl Iterator<Item = Transaction>) {
+        for transaction in transactions {
+            if let Err(e) = self.process_transaction(transaction) {
+                warn!(reason = %e.reason(), "Error processing transaction: {e}");
+            }
+        }
+        self.engine.settle_due();
+    }
+}
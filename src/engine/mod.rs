@@ -1,40 +1,121 @@
 //! Stateful payments engine.
 //!
-//! Processes a stream of transactions and maintains per-client account balances,
-//! a history of deposits (needed for dispute lookups), and a set of currently
-//! disputed transaction IDs.
+//! Processes a stream of transactions and maintains per-client account balances
+//! and a history of deposits (needed for dispute lookups). Each deposit carries
+//! its own dispute state machine.
 
-use tracing::warn;
+use std::collections::HashSet;
+
+use tracing::{info, warn};
 
 use crate::{
     domain::{
-        Account, Chargeback, Deposit, Dispute, DisputeState, Resolve, Transaction, Withdrawal,
+        Account, Adjustment, Amount, Authorization, Capture, Chargeback, ClientId, Deposit,
+        Dispute, DisputeState, DisputeTransitionError, DomainError, Refund, Resolve, Transaction,
+        TransactionId, Void, Withdrawal,
+    },
+    engine::{
+        checkpoints::Checkpoints, errors::EngineError, freezes::TemporaryFreezes,
+        reorder::ParkedReferences, settlement::PendingDeposits,
     },
-    engine::errors::EngineError,
+    output::AccountSink,
+    redaction,
 };
-pub use types::{ClientAccounts, DepositHistory};
+pub use bloom::{BloomFilter, BloomFilteredHistory};
+pub use capacity::CapacityHint;
+pub use config::{
+    ChargebackFreeze, EngineConfig, LockedAccountDisputes, NegativeAvailable, RepeatedSettlement,
+    RollingReserve,
+};
+#[cfg(feature = "dense-accounts")]
+pub use dense_accounts::ClientAccounts;
+pub use middleware::{Middleware, MiddlewareEngine, Verdict};
+pub use outcome::{ApplyTransactions, BatchReport, IgnoreReason, Outcome};
+pub use settlement::{Settlement, SettlementKind};
+pub use store::{DisputeHistoryStore, ReferencedDepositHistory};
+pub use tenants::MultiTenantEngine;
+#[cfg(feature = "cold-tier")]
+pub use tiered_store::{DEFAULT_BLOCK_DEPOSITS, TieredDepositHistory};
+pub use tiers::{AccountTiers, TierError, TierLimits};
+#[cfg(not(feature = "dense-accounts"))]
+pub use types::ClientAccounts;
+pub use types::{AuthorizationHistory, ClientHasher, DepositHistory, TransactionHasher};
 
+mod bloom;
+mod capacity;
+mod checkpoints;
+mod config;
+#[cfg(feature = "dense-accounts")]
+mod dense_accounts;
 pub mod errors;
+mod freezes;
+mod middleware;
+mod outcome;
+mod reorder;
+mod settlement;
+mod store;
+mod tenants;
+#[cfg(feature = "cold-tier")]
+mod tiered_store;
+mod tiers;
+mod tx_ids;
 mod types;
 
-pub struct PaymentsEngine {
+/// Generic over where deposits are kept for dispute lookups; the in-memory
+/// [`DepositHistory`] is the default.
+///
+/// Cloning copies the whole state (accounts and histories), so a clone can be used
+/// for what-if analysis - apply hypothetical transactions to it and inspect the
+/// result while the original engine stays untouched.
+#[derive(Debug, Clone)]
+pub struct PaymentsEngine<H = DepositHistory> {
+    config: EngineConfig,
     client_accounts: ClientAccounts,
     /// Only deposits are stored - they're the only transaction type that can be disputed.
-    deposit_history: DepositHistory,
+    deposit_history: H,
+    /// Pending payouts, looked up by captures and voids.
+    authorization_history: AuthorizationHistory,
+    /// Transactions processed so far, the clock of the settlement delay and dispute window.
+    processed: u64,
+    pending_deposits: PendingDeposits,
+    /// Accounts frozen by a chargeback under [`ChargebackFreeze::Temporary`].
+    temporary_freezes: TemporaryFreezes,
+    /// Disputes rejected as `DisputeWindowExpired`, for the run report.
+    expired_disputes: usize,
+    checkpoints: Checkpoints,
+    /// Transactions waiting for their deposit under [`EngineConfig::reorder_window`].
+    parked: ParkedReferences,
 }
 
-impl PaymentsEngine {
+impl<H> PaymentsEngine<H> {
     pub fn client_accounts(&self) -> &ClientAccounts {
         &self.client_accounts
     }
-}
-
-/// Guard: all operations are rejected on a locked (frozen) account.
-fn check_account_eligibility(account: &Account) -> Result<(), EngineError> {
-    if account.locked {
-        return Err(EngineError::AccountLocked);
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+    pub fn deposit_history(&self) -> &H {
+        &self.deposit_history
+    }
+    /// How many disputes were rejected for being opened outside the dispute window.
+    pub fn expired_disputes(&self) -> usize {
+        self.expired_disputes
+    }
+    /// How many transactions are parked waiting for their deposit, see
+    /// [`EngineConfig::reorder_window`].
+    pub fn parked_transactions(&self) -> usize {
+        self.parked.len()
+    }
+    /// Sequence number of the last snapshot sent by [`PaymentsEngine::flush_accounts`] or
+    /// [`PaymentsEngine::flush_changed_accounts`], 0 before the first.
+    pub fn checkpoint(&self) -> u64 {
+        self.checkpoints.sequence()
+    }
+    /// Moves `client` to a tier defined in [`EngineConfig::tiers`]; its limits apply from
+    /// the next transaction on.
+    pub fn set_client_tier(&mut self, client: ClientId, tier: &str) -> Result<(), TierError> {
+        self.config.tiers.assign(client, tier)
     }
-    Ok(())
 }
 
 impl Default for PaymentsEngine {
@@ -45,113 +126,725 @@ impl Default for PaymentsEngine {
 
 impl PaymentsEngine {
     pub fn new() -> Self {
+        Self::with_config(EngineConfig::default())
+    }
+    pub fn with_config(config: EngineConfig) -> Self {
+        Self::with_store(config, DepositHistory::new())
+    }
+    /// Pre-sizes the account map and deposit history, see [`CapacityHint`].
+    pub fn with_capacity(config: EngineConfig, capacity: CapacityHint) -> Self {
+        let mut engine = Self::with_store(config, DepositHistory::with_capacity(capacity.deposits));
+        engine.client_accounts = ClientAccounts::with_capacity(capacity.clients);
+        engine
+    }
+}
+
+impl<H: DisputeHistoryStore> PaymentsEngine<H> {
+    /// Builds an engine that keeps its deposit history in the given store.
+    pub fn with_store(config: EngineConfig, deposit_history: H) -> Self {
         Self {
+            config,
             client_accounts: ClientAccounts::new(),
-            deposit_history: DepositHistory::new(),
+            deposit_history,
+            authorization_history: AuthorizationHistory::new(),
+            processed: 0,
+            pending_deposits: PendingDeposits::default(),
+            temporary_freezes: TemporaryFreezes::default(),
+            expired_disputes: 0,
+            checkpoints: Checkpoints::default(),
+            parked: ParkedReferences::default(),
         }
     }
-    fn process_transaction(&mut self, transaction: Transaction) -> Result<(), EngineError> {
-        match transaction {
-            Transaction::Deposit(deposit) => self.process_deposit_transaction(deposit)?,
-            Transaction::Withdrawal(withdrawal) => {
-                self.process_withdrawal_transaction(withdrawal)?
-            }
+    /// Applies a single transaction and reports whether it was applied, ignored
+    /// (as the spec requires for e.g. disputes of unknown txs) or rejected.
+    ///
+    /// Tx IDs are globally unique: a deposit, withdrawal, authorization or adjustment
+    /// reusing the ID of an earlier one, applied or not, is rejected as
+    /// `DuplicateTransactionId`, if the store records tx IDs (see
+    /// [`DisputeHistoryStore::insert_tx_id`]).
+    pub fn apply(&mut self, transaction: Transaction) -> Outcome {
+        self.settle_due();
+        self.processed += 1;
+        while let Some(expired) = self.parked.pop_expired(self.processed) {
+            let e = EngineError::TransactionNotFound {
+                client: expired.client_id(),
+                tx: expired.tx_id(),
+            };
+            warn!(reason = %e.reason(), "Deferred transaction expired: {e}");
+        }
+        if let Some(window) = self.config.reorder_window
+            && references_deposit(&transaction)
+            && !self.deposit_history.contains(&transaction.tx_id())
+        {
+            self.parked.park(
+                self.processed.saturating_add(window),
+                transaction.tx_id(),
+                transaction,
+            );
+            return Outcome::Ignored(IgnoreReason::Deferred);
+        }
+        self.dispatch(transaction)
+    }
+    /// Applies a transaction without advancing the clock, then retries the transactions
+    /// parked for it if it's a deposit.
+    fn dispatch(&mut self, transaction: Transaction) -> Outcome {
+        self.checkpoints
+            .touch(transaction.client_id(), &self.client_accounts);
+        if introduces_tx_id(&transaction) && !self.deposit_history.insert_tx_id(transaction.tx_id())
+        {
+            // Like any rejected transaction, it still creates the client's account.
+            self.client_accounts
+                .get_or_create_account_mut(transaction.client_id());
+            return Outcome::Rejected(EngineError::DuplicateTransactionId {
+                client: transaction.client_id(),
+                tx: transaction.tx_id(),
+            });
+        }
+        let deposit_tx = match &transaction {
+            Transaction::Deposit(deposit) => Some(deposit.transaction_id()),
+            _ => None,
+        };
+        let result = match transaction {
+            Transaction::Deposit(deposit) => self.process_deposit_transaction(deposit),
+            Transaction::Withdrawal(withdrawal) => self.process_withdrawal_transaction(withdrawal),
 
-            Transaction::Dispute(dispute) => self.process_dispute_transaction(dispute)?,
-            Transaction::Resolve(resolve) => self.process_resolve_transaction(resolve)?,
-            Transaction::Chargeback(chargeback) => {
-                self.process_chargeback_transaction(chargeback)?
+            Transaction::Dispute(dispute) => self.process_dispute_transaction(dispute),
+            Transaction::Resolve(resolve) => self.process_resolve_transaction(resolve),
+            Transaction::Chargeback(chargeback) => self.process_chargeback_transaction(chargeback),
+            Transaction::Evidence(evidence) => self.advance_dispute(
+                evidence.client_id(),
+                evidence.disputed_tx_id(),
+                Deposit::submit_evidence,
+            ),
+            Transaction::PreArbitration(pre_arbitration) => self.advance_dispute(
+                pre_arbitration.client_id(),
+                pre_arbitration.disputed_tx_id(),
+                Deposit::escalate_dispute,
+            ),
+            Transaction::Authorization(authorization) => {
+                self.process_authorization_transaction(authorization)
             }
+            Transaction::Capture(capture) => self.process_capture_transaction(capture),
+            Transaction::Void(void) => self.process_void_transaction(void),
+            Transaction::Refund(refund) => self.process_refund_transaction(refund),
+            Transaction::Adjustment(adjustment) => self.process_adjustment_transaction(adjustment),
+        };
+        if let Err(EngineError::DisputeWindowExpired { .. }) = result {
+            self.expired_disputes += 1;
         }
-
-        Ok(())
+        if let Some(tx) = deposit_tx
+            && self.deposit_history.contains(&tx)
+        {
+            for parked in self.parked.take(tx) {
+                if let Outcome::Ignored(IgnoreReason::Spec(e)) | Outcome::Rejected(e) =
+                    self.dispatch(parked)
+                {
+                    warn!(reason = %e.reason(), "Error processing deferred transaction: {e}");
+                }
+            }
+        }
+        result.unwrap_or_else(Outcome::from)
+    }
+    /// Applies a single transaction. The error explains which client/tx was rejected and why.
+    /// See [`Outcome::into_result`] for how ignored transactions are reported.
+    pub fn process_transaction(&mut self, transaction: Transaction) -> Result<(), EngineError> {
+        self.apply(transaction).into_result()
     }
 
     fn process_withdrawal_transaction(
         &mut self,
         transaction: Withdrawal,
-    ) -> Result<(), EngineError> {
-        let account = self
+    ) -> Result<Outcome, EngineError> {
+        let (client, tx, amount) = (
+            transaction.client_id(),
+            transaction.transaction_id(),
+            transaction.amount(),
+        );
+        let mut account = self
             .client_accounts
-            .get_or_create_account_mut(transaction.client_id());
-        check_account_eligibility(account)?;
-
-        let amount = transaction.amount();
+            .get_or_create_account_mut(client)
+            .active_mut()
+            .ok_or(EngineError::AccountLocked { client, tx, amount })?;
 
-        account.balance.try_remove(amount)?;
+        let limits = self.config.tiers.limits(client);
+        if let Some(limit) = limits.and_then(|limits| limits.max_withdrawal)
+            && amount > limit
+        {
+            return Err(EngineError::WithdrawalLimitExceeded {
+                client,
+                tx,
+                requested: amount,
+                limit,
+            });
+        }
+        let overdraft = limits.map_or(Amount::ZERO, |limits| limits.overdraft);
+        account
+            .withdraw_with_overdraft(amount, overdraft)
+            .map_err(|e| match e {
+                DomainError::AmountTooLarge => EngineError::AmountTooLarge { client, tx },
+                _ => EngineError::InsufficientFunds {
+                    client,
+                    tx,
+                    requested: amount,
+                    available: account.available(),
+                },
+            })?;
 
-        Ok(())
+        Ok(Outcome::Applied)
     }
-    fn process_deposit_transaction(&mut self, transaction: Deposit) -> Result<(), EngineError> {
-        let account = self
+    fn process_deposit_transaction(
+        &mut self,
+        transaction: Deposit,
+    ) -> Result<Outcome, EngineError> {
+        let mut account = self
             .client_accounts
-            .get_or_create_account_mut(transaction.client_id());
-        check_account_eligibility(account)?;
+            .get_or_create_account_mut(transaction.client_id())
+            .active_mut()
+            .ok_or(EngineError::AccountLocked {
+                client: transaction.client_id(),
+                tx: transaction.transaction_id(),
+                amount: transaction.amount(),
+            })?;
 
-        account.balance.add(transaction.amount());
+        let rate = self
+            .config
+            .tiers
+            .limits(transaction.client_id())
+            .and_then(|limits| limits.reserve_rate)
+            .unwrap_or(self.config.rolling_reserve.rate);
+        let (reserve, amount) = transaction.amount().split(rate);
+        let (available, pending) = if self.config.settlement_delay == 0 {
+            (amount, Amount::ZERO)
+        } else {
+            (Amount::ZERO, amount)
+        };
+        account
+            .credit(available, pending, reserve)
+            .map_err(|_| EngineError::AmountTooLarge {
+                client: transaction.client_id(),
+                tx: transaction.transaction_id(),
+            })?;
+        if reserve != Amount::ZERO {
+            self.pending_deposits.push(
+                self.processed
+                    .saturating_add(self.config.rolling_reserve.delay),
+                Settlement {
+                    client: transaction.client_id(),
+                    tx: transaction.transaction_id(),
+                    amount: reserve,
+                    kind: SettlementKind::Reserve,
+                },
+            );
+        }
+        if self.config.settlement_delay != 0 {
+            self.pending_deposits.push(
+                self.processed.saturating_add(self.config.settlement_delay),
+                Settlement {
+                    client: transaction.client_id(),
+                    tx: transaction.transaction_id(),
+                    amount,
+                    kind: SettlementKind::Pending,
+                },
+            );
+        }
 
         // Record the deposit so it can be referenced later by disputes.
-        self.deposit_history.add_deposit(transaction);
+        self.deposit_history.insert(transaction.at(self.processed));
 
-        Ok(())
+        Ok(Outcome::Applied)
+    }
+    /// Refunds debit available funds like withdrawals, so they're rejected on locked
+    /// accounts. Unlike a chargeback, a refund doesn't lock the account. A deposit that
+    /// hasn't settled yet is settled first.
+    fn process_refund_transaction(&mut self, transaction: Refund) -> Result<Outcome, EngineError> {
+        let (client, tx, amount) = (
+            transaction.client_id(),
+            transaction.deposit_tx_id(),
+            transaction.amount(),
+        );
+        let account = self.client_accounts.get_or_create_account_mut(client);
+        if account.is_locked() {
+            return Err(EngineError::AccountLocked { client, tx, amount });
+        }
+        let deposit = self.deposit_history.try_get_mut(&tx, &client)?;
+
+        deposit.check_refund(amount).map_err(|e| match e {
+            DomainError::RefundExceedsDeposit => EngineError::RefundExceedsDeposit {
+                client,
+                tx,
+                requested: amount,
+                refundable: deposit.disputable(),
+            },
+            _ => EngineError::dispute_transition(DisputeTransitionError::AlreadyDisputed, deposit),
+        })?;
+        let available = account.balance().available() + self.pending_deposits.amount_of(tx);
+        if available < amount.value() {
+            return Err(EngineError::InsufficientFunds {
+                client,
+                tx,
+                requested: amount,
+                available,
+            });
+        }
+        deposit.refund(amount).expect("checked above");
+        while let Some(settlement) = self.pending_deposits.remove(tx) {
+            settle(account, settlement);
+        }
+        account
+            .active_mut()
+            .expect("checked above")
+            .withdraw(amount)
+            .expect("checked above");
+
+        Ok(Outcome::Applied)
     }
-    fn process_dispute_transaction(&mut self, transaction: Dispute) -> Result<(), EngineError> {
+    /// Adjustments are operator corrections, so they apply to locked accounts too and may
+    /// drive available negative. They aren't recorded as deposits, so they can't be
+    /// disputed. Each one is logged at info level under the `audit` target.
+    fn process_adjustment_transaction(
+        &mut self,
+        transaction: Adjustment,
+    ) -> Result<Outcome, EngineError> {
+        self.client_accounts
+            .get_or_create_account_mut(transaction.client_id())
+            .adjust(transaction.amount())
+            .map_err(|_| EngineError::AmountTooLarge {
+                client: transaction.client_id(),
+                tx: transaction.transaction_id(),
+            })?;
+        info!(
+            target: "audit",
+            client = %redaction::client(transaction.client_id()),
+            tx = %transaction.transaction_id(),
+            amount = %redaction::amount(transaction.amount()),
+            reason = transaction.reason(),
+            "Adjustment applied"
+        );
+
+        Ok(Outcome::Applied)
+    }
+    fn process_dispute_transaction(
+        &mut self,
+        transaction: Dispute,
+    ) -> Result<Outcome, EngineError> {
         let account = self
             .client_accounts
             .get_or_create_account_mut(transaction.client_id());
 
-        let disputed_tx = self.deposit_history.try_get_deposit_undisputed_mut(
-            &transaction.disputed_tx_id(),
-            &transaction.client_id(),
+        let disputed_tx = self
+            .deposit_history
+            .try_get_mut(&transaction.disputed_tx_id(), &transaction.client_id())?;
+
+        if account.is_locked() && !self.config.locked_account_disputes.permits(true) {
+            return Err(EngineError::locked_dispute(disputed_tx));
+        }
+        open_dispute(
+            &self.config,
+            self.processed,
+            &mut self.pending_deposits,
+            account,
+            disputed_tx,
         )?;
 
-        account.balance.hold(disputed_tx.amount());
-        disputed_tx.dispute = DisputeState::Open;
-        Ok(())
+        Ok(Outcome::Applied)
+    }
+    /// Moves an open dispute to its next stage (evidence, pre-arbitration). Funds stay held,
+    /// and the locked-account policy treats it like settling an open dispute.
+    fn advance_dispute(
+        &mut self,
+        client: ClientId,
+        tx: TransactionId,
+        advance: fn(&mut Deposit) -> Result<(), DisputeTransitionError>,
+    ) -> Result<Outcome, EngineError> {
+        let account = self.client_accounts.get_or_create_account_mut(client);
+        let disputed_tx = self.deposit_history.try_get_mut(&tx, &client)?;
+
+        if account.is_locked() && !self.config.locked_account_disputes.permits(false) {
+            return Err(EngineError::locked_dispute(disputed_tx));
+        }
+        advance(disputed_tx).map_err(|e| EngineError::dispute_transition(e, disputed_tx))?;
+
+        Ok(Outcome::Applied)
     }
-    fn process_resolve_transaction(&mut self, transaction: Resolve) -> Result<(), EngineError> {
+    fn process_resolve_transaction(
+        &mut self,
+        transaction: Resolve,
+    ) -> Result<Outcome, EngineError> {
         let account = self
             .client_accounts
             .get_or_create_account_mut(transaction.client_id());
 
-        let disputed_tx = self.deposit_history.try_get_deposit_under_dispute_mut(
-            &transaction.disputed_tx_id(),
-            &transaction.client_id(),
-        )?;
+        let disputed_tx = self
+            .deposit_history
+            .try_get_mut(&transaction.disputed_tx_id(), &transaction.client_id())?;
 
-        account.balance.release(disputed_tx.amount());
+        if self.config.repeated_settlement == RepeatedSettlement::Ignore
+            && disputed_tx.dispute_state() == DisputeState::Resolved
+        {
+            return Ok(Outcome::Ignored(IgnoreReason::RepeatedSettlement));
+        }
+        if account.is_locked() && !self.config.locked_account_disputes.permits(false) {
+            return Err(EngineError::locked_dispute(disputed_tx));
+        }
 
-        disputed_tx.dispute = DisputeState::None;
+        disputed_tx
+            .resolve_dispute()
+            .map_err(|e| EngineError::dispute_transition(e, disputed_tx))?;
+        account.release(disputed_tx.held_amount());
+        account.clear_shortfall(disputed_tx.shortfall());
 
-        Ok(())
+        Ok(Outcome::Applied)
     }
     fn process_chargeback_transaction(
         &mut self,
         transaction: Chargeback,
-    ) -> Result<(), EngineError> {
+    ) -> Result<Outcome, EngineError> {
+        let account = self
+            .client_accounts
+            .get_or_create_account_mut(transaction.client_id());
+
+        let disputed_tx = self
+            .deposit_history
+            .try_get_mut(&transaction.disputed_tx_id(), &transaction.client_id())?;
+
+        if disputed_tx.dispute_state() == DisputeState::ChargedBack
+            && self.config.repeated_settlement == RepeatedSettlement::Ignore
+        {
+            return Ok(Outcome::Ignored(IgnoreReason::RepeatedSettlement));
+        }
+        let opens = self.config.chargeback_opens_dispute
+            && matches!(
+                disputed_tx.dispute_state(),
+                DisputeState::None | DisputeState::Resolved
+            );
+        if account.is_locked() && !self.config.locked_account_disputes.permits(opens) {
+            return Err(EngineError::locked_dispute(disputed_tx));
+        }
+        if opens {
+            open_dispute(
+                &self.config,
+                self.processed,
+                &mut self.pending_deposits,
+                account,
+                disputed_tx,
+            )?;
+        }
+
+        disputed_tx
+            .charge_back()
+            .map_err(|e| EngineError::dispute_transition(e, disputed_tx))?;
+        account.charge_back(disputed_tx.held_amount());
+        if let ChargebackFreeze::Temporary { horizon } = self.config.chargeback_freeze {
+            self.temporary_freezes.push(
+                self.processed.saturating_add(horizon),
+                transaction.client_id(),
+            );
+        }
+
+        Ok(Outcome::Applied)
+    }
+
+    fn process_authorization_transaction(
+        &mut self,
+        transaction: Authorization,
+    ) -> Result<Outcome, EngineError> {
+        let (client, tx, amount) = (
+            transaction.client_id(),
+            transaction.transaction_id(),
+            transaction.amount(),
+        );
+        let mut account = self
+            .client_accounts
+            .get_or_create_account_mut(client)
+            .active_mut()
+            .ok_or(EngineError::AccountLocked { client, tx, amount })?;
+
+        account
+            .authorize(amount)
+            .map_err(|_| EngineError::InsufficientFunds {
+                client,
+                tx,
+                requested: amount,
+                available: account.available(),
+            })?;
+
+        // Record the authorization so it can be captured or voided later.
+        self.authorization_history.add_authorization(transaction);
+
+        Ok(Outcome::Applied)
+    }
+    /// Captures settle funds that were already reserved, so they're allowed on locked accounts.
+    fn process_capture_transaction(
+        &mut self,
+        transaction: Capture,
+    ) -> Result<Outcome, EngineError> {
         let account = self
             .client_accounts
             .get_or_create_account_mut(transaction.client_id());
 
-        let disputed_tx = self.deposit_history.try_get_deposit_under_dispute_mut(
-            &transaction.disputed_tx_id(),
+        let authorization = self.authorization_history.try_get_authorization_mut(
+            &transaction.authorization_tx_id(),
             &transaction.client_id(),
         )?;
 
-        account.balance.release(disputed_tx.amount());
-        account.balance.remove(disputed_tx.amount());
-        account.locked = true;
+        authorization
+            .capture()
+            .map_err(|_| EngineError::authorization_settled(authorization))?;
+        account.capture(authorization.amount());
 
-        disputed_tx.dispute = DisputeState::ChargedBack;
+        Ok(Outcome::Applied)
+    }
+    fn process_void_transaction(&mut self, transaction: Void) -> Result<Outcome, EngineError> {
+        let account = self
+            .client_accounts
+            .get_or_create_account_mut(transaction.client_id());
 
-        Ok(())
+        let authorization = self.authorization_history.try_get_authorization_mut(
+            &transaction.authorization_tx_id(),
+            &transaction.client_id(),
+        )?;
+
+        authorization
+            .void()
+            .map_err(|_| EngineError::authorization_settled(authorization))?;
+        account.void(authorization.amount());
+
+        Ok(Outcome::Applied)
     }
 
     pub fn process_transactions(&mut self, transactions: impl Iterator<Item = Transaction>) {
         for transaction in transactions {
             if let Err(e) = self.process_transaction(transaction) {
-                warn!("Error processing transaction: {e}");
+                warn!(reason = %e.reason(), "Error processing transaction: {e}");
+            }
+        }
+        self.settle_due();
+    }
+    /// Applies one transaction, logging it if it isn't applied, for hosts that submit
+    /// transactions as they arrive rather than through an iterator.
+    /// [`PaymentsEngine::flush_accounts`] emits the balances in between.
+    pub fn feed(&mut self, transaction: Transaction) -> Outcome {
+        let outcome = self.apply(transaction);
+        outcome.log_unapplied();
+        outcome
+    }
+    /// Settles what is due, then sends a snapshot of every account to `sink` in client ID
+    /// order. Can be called any number of times between [`PaymentsEngine::feed`]s. Every
+    /// snapshot is a checkpoint with the next sequence number, see
+    /// [`AccountSink::begin_snapshot`].
+    pub fn flush_accounts<S: AccountSink>(&mut self, sink: &mut S) -> Result<(), S::Error> {
+        self.settle_due();
+        sink.begin_snapshot(self.checkpoints.next_sequence())?;
+        for (client, account) in self.client_accounts.iter_sorted() {
+            sink.account(client, account)?;
+        }
+        sink.end_snapshot()?;
+        self.checkpoints.commit(false);
+        Ok(())
+    }
+    /// Same as [`PaymentsEngine::flush_accounts`], but only sends the accounts whose state
+    /// changed since the previous checkpoint of either kind. An account touched by a
+    /// transaction that wasn't applied isn't sent.
+    ///
+    /// Changes are tracked from the first call on, which sends every account; engines that
+    /// only send full snapshots don't pay for the tracking. If `sink` fails, the checkpoint
+    /// doesn't count and the next call sends its changes again.
+    pub fn flush_changed_accounts<S: AccountSink>(&mut self, sink: &mut S) -> Result<(), S::Error> {
+        self.settle_due();
+        sink.begin_snapshot(self.checkpoints.next_sequence())?;
+        match self.checkpoints.changed(&self.client_accounts) {
+            Some(changed) => {
+                for (client, account) in changed {
+                    sink.account(client, account)?;
+                }
+            }
+            None => {
+                for (client, account) in self.client_accounts.iter_sorted() {
+                    sink.account(client, account)?;
+                }
             }
         }
+        sink.end_snapshot()?;
+        self.checkpoints.commit(true);
+        Ok(())
+    }
+    /// Same as [`PaymentsEngine::process_transactions`], but also returns the outcome of
+    /// every transaction, so callers can tell which ones were dropped and why without
+    /// reading the log.
+    pub fn process_transactions_reporting(
+        &mut self,
+        transactions: impl Iterator<Item = Transaction>,
+    ) -> BatchReport {
+        let mut report = BatchReport::default();
+        for transaction in transactions {
+            report.push(self.feed(transaction));
+        }
+        self.settle_due();
+        report
+    }
+    /// Same as [`PaymentsEngine::process_transactions_reporting`] for a batch in memory,
+    /// with the outcomes in input order.
+    ///
+    /// Unless the config makes transactions depend on the transaction clock (a settlement
+    /// delay, rolling reserve or dispute window), the batch is applied grouped by client,
+    /// each client's transactions in input order. A client's transactions then hit the
+    /// same account and deposit entries back to back, while they're still in cache. The
+    /// resulting state is the same as applying the batch in input order, as tx IDs are
+    /// unique. A batch reusing a tx ID is applied in input order, so that the same one of
+    /// the transactions sharing it is rejected as `DuplicateTransactionId`.
+    pub fn process_batch(&mut self, transactions: &[Transaction]) -> BatchReport {
+        if !self.config.is_clockless() || self.reuses_tx_ids(transactions) {
+            return self.process_transactions_reporting(transactions.iter().cloned());
+        }
+        let mut order: Vec<usize> = (0..transactions.len()).collect();
+        order.sort_by_key(|&index| transactions[index].client_id());
+        let mut outcomes: Vec<Option<Outcome>> = transactions.iter().map(|_| None).collect();
+        for index in order {
+            outcomes[index] = Some(self.feed(transactions[index].clone()));
+        }
+        let mut report = BatchReport::default();
+        for outcome in outcomes {
+            report.push(outcome.expect("every transaction was applied"));
+        }
+        self.settle_due();
+        report
+    }
+    fn reuses_tx_ids(&self, transactions: &[Transaction]) -> bool {
+        let mut batch = HashSet::<_, TransactionHasher>::default();
+        transactions
+            .iter()
+            .filter(|transaction| introduces_tx_id(transaction))
+            .any(|transaction| {
+                self.deposit_history.contains_tx_id(&transaction.tx_id())
+                    || !batch.insert(transaction.tx_id())
+            })
+    }
+    /// Moves the deposits whose settlement delay or reserve delay has passed from pending
+    /// or reserve to available, returning them in order, and unlocks the accounts whose
+    /// temporary freeze expired. Processing a transaction does this first, so it's only
+    /// needed to see settlements separately (e.g. to journal them) or after the last one.
+    pub fn settle_due(&mut self) -> Vec<Settlement> {
+        while let Some(client) = self.temporary_freezes.pop_due(self.processed) {
+            self.checkpoints.touch(client, &self.client_accounts);
+            self.client_accounts
+                .get_or_create_account_mut(client)
+                .unlock();
+        }
+        let mut settled = Vec::new();
+        while let Some(settlement) = self.pending_deposits.pop_due(self.processed) {
+            self.checkpoints
+                .touch(settlement.client, &self.client_accounts);
+            settle(
+                self.client_accounts
+                    .get_or_create_account_mut(settlement.client),
+                settlement,
+            );
+            settled.push(settlement);
+        }
+        settled
+    }
+}
+
+/// Whether `transaction` references a deposit by its `tx`, and is parked while that
+/// deposit is missing under [`EngineConfig::reorder_window`]. Refunds reference one too,
+/// but aren't disputes and aren't parked.
+fn references_deposit(transaction: &Transaction) -> bool {
+    matches!(
+        transaction,
+        Transaction::Dispute(_)
+            | Transaction::Resolve(_)
+            | Transaction::Chargeback(_)
+            | Transaction::Evidence(_)
+            | Transaction::PreArbitration(_)
+    )
+}
+
+/// Whether `transaction` has a tx ID of its own, rather than referencing another one.
+fn introduces_tx_id(transaction: &Transaction) -> bool {
+    matches!(
+        transaction,
+        Transaction::Deposit(_)
+            | Transaction::Withdrawal(_)
+            | Transaction::Authorization(_)
+            | Transaction::Adjustment(_)
+    )
+}
+
+fn settle(account: &mut Account, settlement: Settlement) {
+    match settlement.kind {
+        SettlementKind::Pending => account.settle(settlement.amount),
+        SettlementKind::Reserve => account.release_reserve(settlement.amount),
     }
 }
+
+/// Opens a dispute of `deposit` and holds its funds, as far as `policy` lets available
+/// funds go below the disputed amount. A deposit that hasn't settled yet, or whose reserve
+/// wasn't released yet, is settled first.
+fn open_dispute(
+    config: &EngineConfig,
+    processed: u64,
+    pending_deposits: &mut PendingDeposits,
+    account: &mut Account,
+    deposit: &mut Deposit,
+) -> Result<(), EngineError> {
+    if deposit.disputable() == Amount::ZERO {
+        return Err(EngineError::DepositRefunded {
+            client: deposit.client_id(),
+            tx: deposit.transaction_id(),
+            amount: deposit.amount(),
+        });
+    }
+    let age = processed - deposit.deposited_at();
+    if let Some(window) = config.dispute_window
+        && age > window
+    {
+        // Illegal transitions (e.g. a dispute already open) keep their own error.
+        deposit
+            .dispute_state()
+            .open()
+            .map_err(|e| EngineError::dispute_transition(e, deposit))?;
+        return Err(EngineError::DisputeWindowExpired {
+            client: deposit.client_id(),
+            tx: deposit.transaction_id(),
+            amount: deposit.amount(),
+            age,
+            window,
+        });
+    }
+    let policy = config.negative_available;
+    let pending = pending_deposits.amount_of(deposit.transaction_id());
+    let available = account.balance().available() + pending;
+    if policy == NegativeAvailable::Reject && available < deposit.disputable().value() {
+        // Illegal transitions (e.g. a dispute already open) keep their own error.
+        deposit
+            .dispute_state()
+            .open()
+            .map_err(|e| EngineError::dispute_transition(e, deposit))?;
+        return Err(EngineError::InsufficientFunds {
+            client: deposit.client_id(),
+            tx: deposit.transaction_id(),
+            requested: deposit.disputable(),
+            available,
+        });
+    }
+    deposit
+        .dispute_state()
+        .open()
+        .map_err(|e| EngineError::dispute_transition(e, deposit))?;
+    while let Some(settlement) = pending_deposits.remove(deposit.transaction_id()) {
+        settle(account, settlement);
+    }
+    let (client, tx) = (deposit.client_id(), deposit.transaction_id());
+    let too_large = |_| EngineError::AmountTooLarge { client, tx };
+    if policy == NegativeAvailable::CapAtZero {
+        let held = deposit.cap_hold(account);
+        account
+            .add_shortfall(deposit.shortfall())
+            .map_err(too_large)?;
+        account.hold(held).map_err(too_large)?;
+    } else {
+        account.hold(deposit.disputable()).map_err(too_large)?;
+    }
+    deposit.open_dispute().expect("checked above");
+    Ok(())
+}
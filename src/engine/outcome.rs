@@ -0,0 +1,156 @@
+//! What the engine did with a single transaction, or with every transaction of a batch.
+
+use std::collections::HashMap;
+
+use tracing::warn;
+
+use crate::{engine::errors::EngineError, rejection::RejectionReason};
+
+/// Result of [`PaymentsEngine::apply`](crate::engine::PaymentsEngine::apply). Only
+/// `Applied` changes any state.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Applied,
+    /// Dropped on purpose - the spec (or the engine config) says to ignore it.
+    Ignored(IgnoreReason),
+    /// Not allowed in the account's current state: locked account, insufficient
+    /// funds or an authorization that was already settled. Also a transaction reusing
+    /// the tx ID of an earlier one.
+    Rejected(EngineError),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum IgnoreReason {
+    /// The spec's "ignore" cases: the referenced tx doesn't exist for this client, or
+    /// isn't in a dispute state the transaction applies to (e.g. resolving an undisputed tx).
+    Spec(EngineError),
+    /// A resolve or chargeback repeating the dispute's current outcome, with
+    /// [`RepeatedSettlement::Ignore`](crate::engine::RepeatedSettlement::Ignore).
+    RepeatedSettlement,
+    /// Dropped by a [`Middleware`](crate::engine::Middleware) filter before the engine saw it.
+    Filtered,
+    /// Parked until the deposit it references arrives, with
+    /// [`EngineConfig::reorder_window`](crate::engine::EngineConfig::reorder_window). Not
+    /// applied yet, rather than dropped: its retry is logged if that doesn't apply either.
+    Deferred,
+}
+
+impl Outcome {
+    /// The `Result` form used by [`PaymentsEngine::process_transaction`](crate::engine::PaymentsEngine::process_transaction):
+    /// anything that wasn't applied is an error, except ignores the config or a middleware
+    /// asked for.
+    pub fn into_result(self) -> Result<(), EngineError> {
+        match self {
+            Outcome::Applied
+            | Outcome::Ignored(
+                IgnoreReason::RepeatedSettlement | IgnoreReason::Filtered | IgnoreReason::Deferred,
+            ) => Ok(()),
+            Outcome::Ignored(IgnoreReason::Spec(error)) | Outcome::Rejected(error) => Err(error),
+        }
+    }
+    /// Logs why the transaction wasn't applied, for the outcomes
+    /// [`Outcome::into_result`] turns into errors.
+    pub fn log_unapplied(&self) {
+        if let Outcome::Ignored(IgnoreReason::Spec(e)) | Outcome::Rejected(e) = self {
+            warn!(reason = %e.reason(), "Error processing transaction: {e}");
+        }
+    }
+}
+
+/// A wrapper around [`PaymentsEngine`](crate::engine::PaymentsEngine) that applies
+/// transactions one at a time and records something about each on the side.
+pub trait ApplyTransactions {
+    /// What [`ApplyTransactions::apply`] takes: a transaction, possibly with metadata.
+    type Transaction;
+
+    fn apply(&mut self, transaction: Self::Transaction) -> Outcome;
+    /// Like [`PaymentsEngine::process_transactions`](crate::engine::PaymentsEngine::process_transactions),
+    /// logging rejected transactions.
+    fn apply_all(&mut self, transactions: impl Iterator<Item = Self::Transaction>) {
+        for transaction in transactions {
+            self.apply(transaction).log_unapplied();
+        }
+    }
+}
+
+/// Result of [`PaymentsEngine::process_transactions_reporting`](crate::engine::PaymentsEngine::process_transactions_reporting):
+/// the outcome of every transaction, at its index in the batch, and how many were applied,
+/// ignored and rejected.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    outcomes: Vec<Outcome>,
+    applied: usize,
+    ignored: usize,
+    rejected: usize,
+}
+
+impl BatchReport {
+    pub(crate) fn push(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Applied => self.applied += 1,
+            Outcome::Ignored(_) => self.ignored += 1,
+            Outcome::Rejected(_) => self.rejected += 1,
+        }
+        self.outcomes.push(outcome);
+    }
+    /// The outcome of every transaction, in batch order.
+    pub fn outcomes(&self) -> &[Outcome] {
+        &self.outcomes
+    }
+    /// The outcome of the transaction at `index` in the batch.
+    pub fn outcome(&self, index: usize) -> Option<&Outcome> {
+        self.outcomes.get(index)
+    }
+    /// Number of transactions in the batch.
+    pub fn len(&self) -> usize {
+        self.outcomes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.is_empty()
+    }
+    pub fn applied(&self) -> usize {
+        self.applied
+    }
+    pub fn ignored(&self) -> usize {
+        self.ignored
+    }
+    pub fn rejected(&self) -> usize {
+        self.rejected
+    }
+    /// Index and reason of every transaction that wasn't applied, in batch order.
+    pub fn dropped(&self) -> impl Iterator<Item = (usize, RejectionReason)> + '_ {
+        self.outcomes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, outcome)| Some((index, outcome.reason()?)))
+    }
+    /// Number of transactions dropped for each reason.
+    pub fn reason_counts(&self) -> HashMap<RejectionReason, usize> {
+        let mut counts = HashMap::new();
+        for (_, reason) in self.dropped() {
+            *counts.entry(reason).or_default() += 1;
+        }
+        counts
+    }
+}
+
+impl From<EngineError> for Outcome {
+    fn from(error: EngineError) -> Self {
+        match error {
+            EngineError::TransactionNotFound { .. }
+            | EngineError::TransactionAlreadyDisputed { .. }
+            | EngineError::TransactionNotDisputed { .. }
+            | EngineError::DisputeStageOutOfOrder { .. }
+            | EngineError::DepositRefunded { .. } => Outcome::Ignored(IgnoreReason::Spec(error)),
+            EngineError::AccountLocked { .. }
+            | EngineError::AuthorizationAlreadySettled { .. }
+            | EngineError::InsufficientFunds { .. }
+            | EngineError::RefundExceedsDeposit { .. }
+            | EngineError::DisputeWindowExpired { .. }
+            | EngineError::WithdrawalLimitExceeded { .. }
+            | EngineError::RejectedByMiddleware { .. }
+            | EngineError::DuplicateTransactionId { .. }
+            | EngineError::AmountTooLarge { .. } => Outcome::Rejected(error),
+        }
+    }
+}
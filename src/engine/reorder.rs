@@ -0,0 +1,65 @@
+//! Dispute-family transactions parked until the deposit they reference arrives, see
+//! [`EngineConfig::reorder_window`].
+//!
+//! [`EngineConfig::reorder_window`]: crate::engine::EngineConfig::reorder_window
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    domain::{Transaction, TransactionId},
+    engine::TransactionHasher,
+};
+
+/// Parked transactions by the tx they reference, in arrival order, with the clock value
+/// after which they expire.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ParkedReferences {
+    by_tx: HashMap<TransactionId, VecDeque<(u64, Transaction)>, TransactionHasher>,
+    /// Expiry of every parked transaction, in the order they were parked. Entries of
+    /// transactions already taken out are skipped.
+    queue: VecDeque<(u64, TransactionId)>,
+}
+
+impl ParkedReferences {
+    /// Parks `transaction`, referencing `tx`, until `expires` transactions were processed.
+    /// The window is the same for every transaction, so `expires` never decreases and the
+    /// queue stays ordered.
+    pub(crate) fn park(&mut self, expires: u64, tx: TransactionId, transaction: Transaction) {
+        self.queue.push_back((expires, tx));
+        self.by_tx
+            .entry(tx)
+            .or_default()
+            .push_back((expires, transaction));
+    }
+    /// Removes and returns the transactions waiting for `tx`, in arrival order.
+    pub(crate) fn take(&mut self, tx: TransactionId) -> impl Iterator<Item = Transaction> + use<> {
+        self.by_tx
+            .remove(&tx)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(_, transaction)| transaction)
+    }
+    /// Removes and returns the next transaction that expired before `processed`.
+    pub(crate) fn pop_expired(&mut self, processed: u64) -> Option<Transaction> {
+        while let Some(&(expires, tx)) = self.queue.front()
+            && expires < processed
+        {
+            self.queue.pop_front();
+            let Some(waiting) = self.by_tx.get_mut(&tx) else {
+                continue;
+            };
+            if waiting.front().is_some_and(|(front, _)| *front == expires) {
+                let (_, transaction) = waiting.pop_front()?;
+                if waiting.is_empty() {
+                    self.by_tx.remove(&tx);
+                }
+                return Some(transaction);
+            }
+        }
+        None
+    }
+    /// Number of transactions still parked.
+    pub(crate) fn len(&self) -> usize {
+        self.by_tx.values().map(VecDeque::len).sum()
+    }
+}
@@ -0,0 +1,66 @@
+//! Deposits waiting out the settlement delay or the rolling reserve's delay, see
+//! [`EngineConfig::settlement_delay`] and [`EngineConfig::rolling_reserve`].
+//!
+//! [`EngineConfig::settlement_delay`]: crate::engine::EngineConfig::settlement_delay
+//! [`EngineConfig::rolling_reserve`]: crate::engine::EngineConfig::rolling_reserve
+
+use std::collections::VecDeque;
+
+use rust_decimal::Decimal;
+
+use crate::domain::{Amount, ClientId, TransactionId};
+
+/// A deposit's funds moving from pending or reserve to available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settlement {
+    pub client: ClientId,
+    pub tx: TransactionId,
+    pub amount: Amount,
+    pub kind: SettlementKind,
+}
+
+/// Where the funds of a [`Settlement`] were held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettlementKind {
+    Pending,
+    Reserve,
+}
+
+/// Unsettled deposits and unreleased reserves in the order they're due. At most the
+/// larger delay's worth of them are due later than any given point, so lookups by tx are
+/// linear scans. A deposit with both a pending part and a reserve has an entry for each.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PendingDeposits(VecDeque<(u64, Settlement)>);
+
+impl PendingDeposits {
+    /// Queues funds that become available once `due` transactions were processed. Keeps
+    /// the queue ordered by due, since the two delays may differ.
+    pub(crate) fn push(&mut self, due: u64, settlement: Settlement) {
+        let index = self.0.partition_point(|(queued, _)| *queued <= due);
+        self.0.insert(index, (due, settlement));
+    }
+    /// Removes and returns the next deposit due by `processed` transactions.
+    pub(crate) fn pop_due(&mut self, processed: u64) -> Option<Settlement> {
+        let (due, _) = self.0.front()?;
+        if *due > processed {
+            return None;
+        }
+        self.0.pop_front().map(|(_, settlement)| settlement)
+    }
+    /// Funds of the deposit `tx` not available yet, zero if it's fully settled.
+    pub(crate) fn amount_of(&self, tx: TransactionId) -> Decimal {
+        self.0
+            .iter()
+            .filter(|(_, settlement)| settlement.tx == tx)
+            .map(|(_, settlement)| settlement.amount.value())
+            .sum()
+    }
+    /// Removes the next entry of the deposit `tx`, e.g. to settle it early.
+    pub(crate) fn remove(&mut self, tx: TransactionId) -> Option<Settlement> {
+        let index = self
+            .0
+            .iter()
+            .position(|(_, settlement)| settlement.tx == tx)?;
+        self.0.remove(index).map(|(_, settlement)| settlement)
+    }
+}
@@ -0,0 +1,116 @@
+//! Storage abstraction for the deposits that dispute-family transactions reference.
+//!
+//! The engine only talks to [`DisputeHistoryStore`], so alternative backends
+//! (on-disk, tiered, with retention) can be plugged in without touching engine logic.
+
+use std::collections::HashSet;
+
+use crate::{
+    domain::{ClientId, Deposit, Transaction, TransactionId},
+    engine::{DepositHistory, TransactionHasher, errors::EngineError},
+};
+
+pub trait DisputeHistoryStore {
+    /// Records a processed deposit so that later disputes can reference it.
+    fn insert(&mut self, deposit: Deposit);
+    /// Whether a deposit with this tx ID is stored, regardless of its client.
+    fn contains(&self, tx_id: &TransactionId) -> bool;
+    /// Looks up a deposit by tx ID, but only returns it if it belongs to the given client.
+    /// Dispute state is changed through the returned deposit's transition methods.
+    fn get_mut(&mut self, tx_id: &TransactionId, client_id: &ClientId) -> Option<&mut Deposit>;
+    /// Records the tx ID of a deposit, withdrawal, authorization or adjustment, returning
+    /// whether it wasn't recorded yet. The engine rejects a transaction reusing a recorded
+    /// ID as `DuplicateTransactionId`. By default nothing is recorded and every ID is new,
+    /// for stores whose memory mustn't grow with every transaction.
+    fn insert_tx_id(&mut self, _tx_id: TransactionId) -> bool {
+        true
+    }
+    /// Whether [`Self::insert_tx_id`] recorded `tx_id`.
+    fn contains_tx_id(&self, _tx_id: &TransactionId) -> bool {
+        false
+    }
+
+    /// Same as [`Self::get_mut`], reporting a missing deposit as an engine error.
+    fn try_get_mut(
+        &mut self,
+        tx_id: &TransactionId,
+        client_id: &ClientId,
+    ) -> Result<&mut Deposit, EngineError> {
+        self.get_mut(tx_id, client_id)
+            .ok_or(EngineError::TransactionNotFound {
+                client: *client_id,
+                tx: *tx_id,
+            })
+    }
+}
+
+impl DisputeHistoryStore for DepositHistory {
+    fn insert(&mut self, deposit: Deposit) {
+        self.add_deposit(deposit);
+    }
+    fn contains(&self, tx_id: &TransactionId) -> bool {
+        self.as_map().contains_key(tx_id)
+    }
+    fn get_mut(&mut self, tx_id: &TransactionId, client_id: &ClientId) -> Option<&mut Deposit> {
+        self.get_deposit_mut(tx_id, client_id)
+    }
+    fn insert_tx_id(&mut self, tx_id: TransactionId) -> bool {
+        self.add_tx_id(tx_id)
+    }
+    fn contains_tx_id(&self, tx_id: &TransactionId) -> bool {
+        self.has_tx_id(tx_id)
+    }
+}
+
+/// Deposit history for two-pass processing: a first pass over the input collects every
+/// tx ID a dispute, resolve, chargeback or refund references, and only those deposits are
+/// kept. Memory is then O(disputes) instead of O(deposits), with the same results - any other
+/// deposit could never be looked up anyway. To keep that bound, tx IDs aren't recorded, so
+/// a transaction reusing one isn't rejected as `DuplicateTransactionId`.
+#[derive(Debug, Clone)]
+pub struct ReferencedDepositHistory {
+    referenced: HashSet<TransactionId, TransactionHasher>,
+    deposits: DepositHistory,
+}
+
+impl ReferencedDepositHistory {
+    /// Runs the first pass over the same transactions the engine will process.
+    pub fn scan(transactions: impl IntoIterator<Item = Transaction>) -> Self {
+        let referenced = transactions
+            .into_iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Dispute(dispute) => Some(dispute.disputed_tx_id()),
+                Transaction::Resolve(resolve) => Some(resolve.disputed_tx_id()),
+                Transaction::Chargeback(chargeback) => Some(chargeback.disputed_tx_id()),
+                Transaction::Evidence(evidence) => Some(evidence.disputed_tx_id()),
+                Transaction::PreArbitration(pre_arbitration) => {
+                    Some(pre_arbitration.disputed_tx_id())
+                }
+                Transaction::Refund(refund) => Some(refund.deposit_tx_id()),
+                _ => None,
+            })
+            .collect();
+        Self {
+            referenced,
+            deposits: DepositHistory::new(),
+        }
+    }
+    /// The deposits kept so far.
+    pub fn deposits(&self) -> &DepositHistory {
+        &self.deposits
+    }
+}
+
+impl DisputeHistoryStore for ReferencedDepositHistory {
+    fn insert(&mut self, deposit: Deposit) {
+        if self.referenced.contains(&deposit.transaction_id()) {
+            self.deposits.insert(deposit);
+        }
+    }
+    fn contains(&self, tx_id: &TransactionId) -> bool {
+        self.deposits.contains(tx_id)
+    }
+    fn get_mut(&mut self, tx_id: &TransactionId, client_id: &ClientId) -> Option<&mut Deposit> {
+        self.deposits.get_mut(tx_id, client_id)
+    }
+}
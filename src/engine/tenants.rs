@@ -0,0 +1,58 @@
+//! Multi-tenant wrapper around [`PaymentsEngine`].
+//!
+//! Every tenant gets its own engine, so accounts and deposit history never leak
+//! between namespaces - the same client or tx ID can exist under several tenants.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    domain::TenantId,
+    engine::{EngineConfig, PaymentsEngine},
+    parsing::TenantTransaction,
+};
+
+/// Maps each tenant namespace to an isolated engine. `None` is the default
+/// namespace used by rows without a tenant, and sorts first. Engines are lazily created and
+/// all share the same configuration. Cloning branches every tenant at once.
+#[derive(Default, Clone)]
+pub struct MultiTenantEngine {
+    config: EngineConfig,
+    engines: BTreeMap<Option<TenantId>, PaymentsEngine>,
+}
+
+impl MultiTenantEngine {
+    pub fn new() -> Self {
+        Self::with_config(EngineConfig::default())
+    }
+    pub fn with_config(config: EngineConfig) -> Self {
+        Self {
+            config,
+            engines: BTreeMap::new(),
+        }
+    }
+    pub fn engine(&self, tenant: Option<TenantId>) -> Option<&PaymentsEngine> {
+        self.engines.get(&tenant)
+    }
+    /// Iterates engines in tenant order, starting with the default namespace.
+    pub fn engines(&self) -> impl Iterator<Item = (Option<TenantId>, &PaymentsEngine)> {
+        self.engines
+            .iter()
+            .map(|(tenant, engine)| (*tenant, engine))
+    }
+    fn get_or_create_engine_mut(&mut self, tenant: Option<TenantId>) -> &mut PaymentsEngine {
+        self.engines
+            .entry(tenant)
+            .or_insert_with(|| PaymentsEngine::with_config(self.config.clone()))
+    }
+
+    pub fn process_transactions(&mut self, transactions: impl Iterator<Item = TenantTransaction>) {
+        for TenantTransaction {
+            tenant,
+            transaction,
+        } in transactions
+        {
+            self.get_or_create_engine_mut(tenant)
+                .process_transactions(std::iter::once(transaction));
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Deposit history with a hot in-memory tier and a compressed cold tier, behind the
+//! `cold-tier` feature.
+//!
+//! Disputes overwhelmingly reference recent deposits. [`TieredDepositHistory`] keeps the
+//! most recently inserted or looked-up deposits in memory, and moves the oldest ones out a
+//! block at a time: each block is written deflate-compressed to the cold storage (usually
+//! a temporary file). Looking up a cold deposit reads and decompresses its block and moves
+//! the deposit back to the hot tier, so its dispute state can change in place. Memory is
+//! bounded by the hot capacity plus a small index entry per cold deposit. To keep that
+//! bound, tx IDs aren't recorded, so a transaction reusing one isn't rejected as
+//! `DuplicateTransactionId`.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, Read, Seek, SeekFrom, Write},
+};
+
+use flate2::{Compression, read::DeflateDecoder, write::DeflateEncoder};
+use tracing::error;
+
+use crate::{
+    domain::{ClientId, Deposit, DepositRecord, TransactionId},
+    engine::{DisputeHistoryStore, TransactionHasher},
+};
+
+/// Deposits moved to the cold tier at once by default.
+pub const DEFAULT_BLOCK_DEPOSITS: usize = 1024;
+
+/// A [`DisputeHistoryStore`] keeping at most `hot_capacity` deposits in memory, see the
+/// [module docs](self).
+///
+/// The space of a cold deposit isn't reclaimed once it's moved back to the hot tier.
+#[derive(Debug)]
+pub struct TieredDepositHistory<S> {
+    /// Hot deposits with the recency stamp of their entry in `order`.
+    hot: HashMap<TransactionId, (u64, Deposit), TransactionHasher>,
+    /// Hot deposits, least recent first. Entries whose stamp no longer matches the
+    /// deposit's (it was looked up again, or moved out) are skipped.
+    order: VecDeque<(TransactionId, u64)>,
+    next_stamp: u64,
+    hot_capacity: usize,
+    block_deposits: usize,
+    cold: S,
+    /// Where the next block is written.
+    cold_end: u64,
+    /// Offset and length of every block.
+    blocks: Vec<(u64, u64)>,
+    /// Block of every cold deposit.
+    index: HashMap<TransactionId, usize, TransactionHasher>,
+}
+
+impl<S: Read + Write + Seek> TieredDepositHistory<S> {
+    /// Builds an empty history writing its cold tier to `cold`, which must be empty (e.g. a
+    /// fresh temporary file). At least one deposit is kept hot. Deposits are moved out
+    /// [`DEFAULT_BLOCK_DEPOSITS`] at a time.
+    pub fn new(cold: S, hot_capacity: usize) -> Self {
+        Self {
+            hot: HashMap::default(),
+            order: VecDeque::new(),
+            next_stamp: 0,
+            hot_capacity: hot_capacity.max(1),
+            block_deposits: DEFAULT_BLOCK_DEPOSITS,
+            cold,
+            cold_end: 0,
+            blocks: Vec::new(),
+            index: HashMap::default(),
+        }
+    }
+    /// Sets how many deposits are moved to the cold tier at once (at least one).
+    pub fn with_block_deposits(self, block_deposits: usize) -> Self {
+        Self {
+            block_deposits: block_deposits.max(1),
+            ..self
+        }
+    }
+    /// Number of deposits in memory.
+    pub fn hot_len(&self) -> usize {
+        self.hot.len()
+    }
+    /// Number of deposits in the cold tier.
+    pub fn cold_len(&self) -> usize {
+        self.index.len()
+    }
+
+    fn insert_hot(&mut self, deposit: Deposit) {
+        self.push_hot(deposit);
+        if self.hot.len() > self.hot_capacity {
+            self.move_out();
+        }
+    }
+
+    fn push_hot(&mut self, deposit: Deposit) {
+        let (tx, stamp) = (deposit.transaction_id(), self.next_stamp);
+        self.next_stamp += 1;
+        self.hot.insert(tx, (stamp, deposit));
+        self.order.push_back((tx, stamp));
+    }
+
+    /// Moves the least recent block of hot deposits to the cold tier, never the most recent
+    /// one. If the block can't be written, the deposits stay in memory.
+    fn move_out(&mut self) {
+        let mut deposits = Vec::new();
+        while deposits.len() < self.block_deposits
+            && self.hot.len() > 1
+            && let Some((tx, stamp)) = self.order.pop_front()
+        {
+            if self
+                .hot
+                .get(&tx)
+                .is_some_and(|(hot_stamp, _)| *hot_stamp == stamp)
+                && let Some((_, deposit)) = self.hot.remove(&tx)
+            {
+                deposits.push(deposit);
+            }
+        }
+        if deposits.is_empty() {
+            return;
+        }
+        let records: Vec<DepositRecord> = deposits.into_iter().map(DepositRecord::from).collect();
+        match self.write_block(&records) {
+            Ok(block) => {
+                for record in &records {
+                    self.index.insert(record.transaction_id(), block);
+                }
+            }
+            Err(e) => {
+                error!("Failed to move deposits to the cold tier, keeping them in memory: {e}");
+                for record in records {
+                    self.push_hot(Deposit::from(record));
+                }
+            }
+        }
+    }
+
+    fn write_block(&mut self, records: &[DepositRecord]) -> io::Result<usize> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+        serde_json::to_writer(&mut encoder, records)?;
+        let bytes = encoder.finish()?;
+        self.cold.seek(SeekFrom::Start(self.cold_end))?;
+        self.cold.write_all(&bytes)?;
+        let len = bytes.len() as u64;
+        self.blocks.push((self.cold_end, len));
+        self.cold_end += len;
+        Ok(self.blocks.len() - 1)
+    }
+
+    fn read_block(&mut self, block: usize) -> io::Result<Vec<DepositRecord>> {
+        let (offset, len) = self.blocks[block];
+        self.cold.seek(SeekFrom::Start(offset))?;
+        let decoder = DeflateDecoder::new((&mut self.cold).take(len));
+        Ok(serde_json::from_reader(decoder)?)
+    }
+
+    /// Moves a cold deposit back to the hot tier.
+    ///
+    /// # Panics
+    ///
+    /// If its block can't be read back: answering that the deposit doesn't exist would
+    /// silently change the outcome of the dispute looking it up.
+    fn fetch(&mut self, tx_id: &TransactionId) {
+        let Some(block) = self.index.remove(tx_id) else {
+            return;
+        };
+        let record = self
+            .read_block(block)
+            .unwrap_or_else(|e| panic!("Cold tier block {block} unreadable: {e}"))
+            .into_iter()
+            .find(|record| record.transaction_id() == *tx_id)
+            .unwrap_or_else(|| panic!("Cold tier block {block} lacks tx {tx_id}"));
+        self.insert_hot(Deposit::from(record));
+    }
+}
+
+impl<S: Read + Write + Seek> DisputeHistoryStore for TieredDepositHistory<S> {
+    fn insert(&mut self, deposit: Deposit) {
+        self.index.remove(&deposit.transaction_id());
+        self.insert_hot(deposit);
+    }
+    fn contains(&self, tx_id: &TransactionId) -> bool {
+        self.hot.contains_key(tx_id) || self.index.contains_key(tx_id)
+    }
+    fn get_mut(&mut self, tx_id: &TransactionId, client_id: &ClientId) -> Option<&mut Deposit> {
+        self.fetch(tx_id);
+        let (hot_stamp, deposit) = self.hot.get_mut(tx_id)?;
+        if &deposit.client_id() != client_id {
+            return None;
+        }
+        *hot_stamp = self.next_stamp;
+        self.order.push_back((*tx_id, self.next_stamp));
+        self.next_stamp += 1;
+        Some(deposit)
+    }
+}
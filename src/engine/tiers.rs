@@ -0,0 +1,119 @@
+//! Account tiers: per-client limits the engine looks up while processing, see
+//! [`EngineConfig::tiers`].
+//!
+//! Tiers and the clients assigned to them are loaded from JSON (see
+//! [`AccountTiers::from_reader`]) or set through the API. Clients without a tier have no
+//! limits beyond the rest of the configuration.
+//!
+//! [`EngineConfig::tiers`]: crate::engine::EngineConfig::tiers
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Read,
+};
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::domain::{Amount, ClientId};
+
+/// Limits of one tier. The defaults impose none.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TierLimits {
+    /// Largest single withdrawal; larger ones are rejected as `WithdrawalLimitExceeded`.
+    /// `None` is unlimited.
+    pub max_withdrawal: Option<Amount>,
+    /// How far below zero withdrawals may take available funds.
+    pub overdraft: Amount,
+    /// Share of each deposit held in the rolling reserve, between 0 and 1. Overrides
+    /// [`RollingReserve::rate`](crate::engine::RollingReserve::rate) when set.
+    pub reserve_rate: Option<Decimal>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TierError {
+    #[error("Tier {0} is not defined")]
+    UnknownTier(String),
+    #[error("Reserve rate {rate} of tier {tier} is not between 0 and 1")]
+    InvalidReserveRate { tier: String, rate: Decimal },
+    #[error("Invalid tier file: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Named tiers and the tier of each client.
+///
+/// The JSON form maps tier names to their [`TierLimits`] and client IDs to tier names:
+///
+/// ```json
+/// {
+///     "tiers": { "gold": { "max_withdrawal": "5000", "overdraft": "100", "reserve_rate": "0.05" } },
+///     "clients": { "1": "gold" }
+/// }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccountTiers {
+    tiers: BTreeMap<String, TierLimits>,
+    clients: HashMap<ClientId, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AccountTiersRepr {
+    #[serde(default)]
+    tiers: BTreeMap<String, TierLimits>,
+    #[serde(default)]
+    clients: HashMap<ClientId, String>,
+}
+
+impl AccountTiers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Parses and validates tiers in the JSON form above.
+    pub fn from_reader(reader: impl Read) -> Result<Self, TierError> {
+        let repr: AccountTiersRepr = serde_json::from_reader(reader)?;
+        let mut tiers = Self::new();
+        for (name, limits) in repr.tiers {
+            tiers.define(name, limits)?;
+        }
+        for (client, tier) in repr.clients {
+            tiers.assign(client, &tier)?;
+        }
+        Ok(tiers)
+    }
+    /// Adds a tier, or replaces the limits of an existing one (which then apply to its
+    /// clients from the next transaction on).
+    pub fn define(&mut self, name: impl Into<String>, limits: TierLimits) -> Result<(), TierError> {
+        let name = name.into();
+        if let Some(rate) = limits.reserve_rate
+            && !(Decimal::ZERO..=Decimal::ONE).contains(&rate)
+        {
+            return Err(TierError::InvalidReserveRate { tier: name, rate });
+        }
+        self.tiers.insert(name, limits);
+        Ok(())
+    }
+    /// Moves `client` to the defined tier `tier`.
+    pub fn assign(&mut self, client: ClientId, tier: &str) -> Result<(), TierError> {
+        if !self.tiers.contains_key(tier) {
+            return Err(TierError::UnknownTier(tier.to_owned()));
+        }
+        self.clients.insert(client, tier.to_owned());
+        Ok(())
+    }
+    /// Name of `client`'s tier, `None` if it has none.
+    pub fn tier(&self, client: ClientId) -> Option<&str> {
+        self.clients.get(&client).map(String::as_str)
+    }
+    /// Limits of `client`'s tier, `None` if it has none.
+    pub fn limits(&self, client: ClientId) -> Option<&TierLimits> {
+        self.tiers.get(self.tier(client)?)
+    }
+    /// Whether any tier overrides the rolling reserve rate.
+    pub(crate) fn has_reserve_rates(&self) -> bool {
+        self.tiers
+            .values()
+            .any(|limits| limits.reserve_rate.is_some())
+    }
+}
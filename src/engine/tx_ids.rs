@@ -0,0 +1,20 @@
+//! Every tx ID a transaction introduced, so a reused one can be rejected.
+
+use std::collections::HashSet;
+
+use crate::{domain::TransactionId, engine::TransactionHasher};
+
+/// Set of tx IDs. Memory grows with the number of IDs seen, not with their values, so a
+/// single large ID costs no more than a small one.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TransactionIds(HashSet<TransactionId, TransactionHasher>);
+
+impl TransactionIds {
+    pub(crate) fn contains(&self, tx_id: TransactionId) -> bool {
+        self.0.contains(&tx_id)
+    }
+    /// Adds `tx_id`, returning whether it wasn't in the set yet.
+    pub(crate) fn insert(&mut self, tx_id: TransactionId) -> bool {
+        self.0.insert(tx_id)
+    }
+}
@@ -1,13 +1,35 @@
+#[cfg(not(feature = "dense-accounts"))]
+use crate::domain::Account;
 use crate::{
-    domain::{Account, ClientId, Deposit, DisputeState, TransactionId},
-    engine::errors::EngineError,
+    domain::{Authorization, ClientId, Deposit, TransactionId},
+    engine::{errors::EngineError, tx_ids::TransactionIds},
 };
 use std::collections::HashMap;
 
+/// Hasher of [`ClientAccounts`]. With the `fast-hash` feature it's FxHash: client IDs
+/// are small integers and there are at most 65536 of them, so a crafted input can't
+/// degrade lookups much.
+#[cfg(feature = "fast-hash")]
+pub type ClientHasher = rustc_hash::FxBuildHasher;
+#[cfg(not(feature = "fast-hash"))]
+pub type ClientHasher = std::hash::RandomState;
+
+/// Hasher of the tx-keyed histories. Tx IDs come from upstream and the maps grow with
+/// the input, so the `fast-hash` variant stays randomly keyed (aHash) to resist
+/// collision attacks.
+#[cfg(feature = "fast-hash")]
+pub type TransactionHasher = ahash::RandomState;
+#[cfg(not(feature = "fast-hash"))]
+pub type TransactionHasher = std::hash::RandomState;
+
 /// Stores all successfully processed deposits, keyed by transaction ID.
 /// Only deposits are stored because they're the only transaction type that can be disputed.
-#[derive(Debug)]
-pub struct DepositHistory(HashMap<TransactionId, Deposit>);
+/// Also records every tx ID a transaction introduced, so a reused one can be rejected.
+#[derive(Debug, Clone)]
+pub struct DepositHistory {
+    deposits: HashMap<TransactionId, Deposit, TransactionHasher>,
+    tx_ids: TransactionIds,
+}
 
 impl Default for DepositHistory {
     fn default() -> Self {
@@ -17,66 +39,137 @@ impl Default for DepositHistory {
 
 impl DepositHistory {
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self::with_capacity(0)
+    }
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            deposits: HashMap::with_capacity_and_hasher(capacity, Default::default()),
+            tx_ids: TransactionIds::default(),
+        }
     }
 
     pub fn add_deposit(&mut self, deposit: Deposit) {
-        self.0.insert(deposit.transaction_id(), deposit);
+        self.deposits.insert(deposit.transaction_id(), deposit);
+    }
+    /// Records a tx ID, returning whether it wasn't recorded yet.
+    pub fn add_tx_id(&mut self, tx_id: TransactionId) -> bool {
+        self.tx_ids.insert(tx_id)
+    }
+    pub fn has_tx_id(&self, tx_id: &TransactionId) -> bool {
+        self.tx_ids.contains(*tx_id)
     }
     /// Looks up a deposit by tx ID, but only returns it if it belongs to the given client.
     /// This prevents a client from disputing another client's deposit.
     pub fn get_deposit(&self, tx_id: &TransactionId, client_id: &ClientId) -> Option<&Deposit> {
-        self.0.get(tx_id).filter(|tx| &tx.client_id() == client_id)
+        self.deposits
+            .get(tx_id)
+            .filter(|tx| &tx.client_id() == client_id)
     }
-    pub fn try_get_deposit_under_dispute_mut(
+    /// Mutable counterpart of [`Self::get_deposit`]. Dispute transitions are
+    /// enforced by the deposit itself, see [`crate::domain::DisputeState`].
+    pub fn get_deposit_mut(
         &mut self,
         tx_id: &TransactionId,
         client_id: &ClientId,
-    ) -> Result<&mut Deposit, EngineError> {
-        let res = self
-            .0
+    ) -> Option<&mut Deposit> {
+        self.deposits
             .get_mut(tx_id)
-            .filter(|tx| &tx.client_id() == client_id);
-        match res {
-            Some(tx) if tx.dispute == DisputeState::Open => Ok(tx),
-            Some(_) => Err(EngineError::TransactionNotDisputed),
-            None => Err(EngineError::TransactionNotFound),
-        }
+            .filter(|tx| &tx.client_id() == client_id)
+    }
+    pub fn as_map(&self) -> &HashMap<TransactionId, Deposit, TransactionHasher> {
+        &self.deposits
+    }
+}
+
+/// Stores authorizations (pending payouts), keyed by transaction ID, so that
+/// captures and voids can find the reserved amount.
+#[derive(Debug, Clone)]
+pub struct AuthorizationHistory(HashMap<TransactionId, Authorization, TransactionHasher>);
+
+impl Default for AuthorizationHistory {
+    fn default() -> Self {
+        Self::new()
     }
-    pub fn try_get_deposit_undisputed_mut(
+}
+
+impl AuthorizationHistory {
+    pub fn new() -> Self {
+        Self(HashMap::default())
+    }
+
+    pub fn add_authorization(&mut self, authorization: Authorization) {
+        self.0.insert(authorization.transaction_id(), authorization);
+    }
+    /// Looks up an authorization by tx ID, but only if it belongs to the given client.
+    pub fn try_get_authorization_mut(
         &mut self,
         tx_id: &TransactionId,
         client_id: &ClientId,
-    ) -> Result<&mut Deposit, EngineError> {
-        let res = self
-            .0
+    ) -> Result<&mut Authorization, EngineError> {
+        self.0
             .get_mut(tx_id)
-            .filter(|tx| &tx.client_id() == client_id);
-        match res {
-            Some(tx) if tx.dispute == DisputeState::None => Ok(tx),
-            Some(_) => Err(EngineError::TransactionAlreadyDisputed),
-            None => Err(EngineError::TransactionNotFound),
-        }
+            .filter(|tx| &tx.client_id() == client_id)
+            .ok_or(EngineError::TransactionNotFound {
+                client: *client_id,
+                tx: *tx_id,
+            })
     }
 }
 
 /// Maps each client to their account. Accounts are lazily created on first transaction.
-#[derive(Debug)]
-pub struct ClientAccounts(HashMap<ClientId, Account>);
+/// With the `dense-accounts` feature, an array indexed by client ID replaces the map.
+#[cfg(not(feature = "dense-accounts"))]
+#[derive(Debug, Clone)]
+pub struct ClientAccounts(HashMap<ClientId, Account, ClientHasher>);
 
+#[cfg(not(feature = "dense-accounts"))]
 impl Default for ClientAccounts {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(not(feature = "dense-accounts"))]
 impl ClientAccounts {
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self(HashMap::default())
     }
-    pub fn as_map(&self) -> &HashMap<ClientId, Account> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(HashMap::with_capacity_and_hasher(
+            capacity,
+            Default::default(),
+        ))
+    }
+    /// Exposes the underlying map. Prefer [`Self::iter`] / [`Self::iter_sorted`],
+    /// which don't tie callers to the storage layout.
+    pub fn as_map(&self) -> &HashMap<ClientId, Account, ClientHasher> {
         &self.0
     }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn get(&self, client_id: ClientId) -> Option<&Account> {
+        self.0.get(&client_id)
+    }
+    /// Number of accounts that fit before the storage reallocates.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+    /// Iterates accounts in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (ClientId, &Account)> {
+        self.0
+            .iter()
+            .map(|(client_id, account)| (*client_id, account))
+    }
+    /// Iterates accounts in ascending client ID order, for deterministic output.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (ClientId, &Account)> {
+        let mut accounts = self.iter().collect::<Vec<_>>();
+        accounts.sort_unstable_by_key(|(client_id, _)| *client_id);
+        accounts.into_iter()
+    }
     pub fn get_or_create_account_mut(&mut self, client_id: ClientId) -> &mut Account {
         self.0.entry(client_id).or_default()
     }
@@ -0,0 +1,43 @@
+//! Handover from a historical input to a live source, for the CLI's `--live` mode.
+//!
+//! The engine that processed the history keeps running on the live source, so nothing is
+//! rebuilt at the boundary. What can still go wrong is the boundary itself: a live source
+//! that is replayed from a little before the end of the history would apply the tail of
+//! the history twice. [`skip_overlap`] finds the last historical transaction among the
+//! first live ones and drops everything up to it.
+
+use crate::domain::Transaction;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum HandoverError {
+    #[error("Last historical transaction ({last}) not among the first {window} live rows")]
+    NoOverlap { last: Transaction, window: usize },
+}
+
+/// Drops the live transactions up to and including the first one equal to `last`, the last
+/// historical transaction, which must be among the first `window` of them. Reads that many
+/// live transactions at most before returning.
+///
+/// Without a `last` (an empty history) or with a zero `window`, the live source is taken to
+/// start right after the history and nothing is dropped.
+pub fn skip_overlap<I: Iterator<Item = Transaction>>(
+    mut live: I,
+    last: Option<&Transaction>,
+    window: usize,
+) -> Result<I, HandoverError> {
+    let Some(last) = last.filter(|_| window > 0) else {
+        return Ok(live);
+    };
+    if live
+        .by_ref()
+        .take(window)
+        .any(|transaction| &transaction == last)
+    {
+        Ok(live)
+    } else {
+        Err(HandoverError::NoOverlap {
+            last: last.clone(),
+            window,
+        })
+    }
+}
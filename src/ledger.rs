@@ -0,0 +1,210 @@
+//! Double-entry ledger export for the `ledger` subcommand.
+//!
+//! Every applied transaction becomes a balanced journal entry. Each client has five asset
+//! accounts mirroring its balance - `client:<id>:available`, `client:<id>:held`,
+//! `client:<id>:authorized`, `client:<id>:pending` and `client:<id>:reserve` - and money
+//! entering or leaving the clients (deposits, withdrawals, captures, chargebacks) is offset
+//! against the `partner` liability account. Operators' adjustments are offset against the
+//! `adjustments` account instead, so corrections stay apart from partner money.
+//! Settlements of delayed deposits and releases of reserves get entries of their own.
+//! Debits increase a client account, so summing the journal per client account gives the
+//! final balances. Entries of transactions linked to a related tx carry it in `parent_tx`,
+//! so money can be traced across entries.
+
+use std::io;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{
+    domain::{Account, ClientId, Transaction, TransactionId},
+    engine::{EngineConfig, Outcome, PaymentsEngine, SettlementKind},
+};
+
+/// Name of the account that offsets money entering or leaving client accounts.
+pub const PARTNER_ACCOUNT: &str = "partner";
+/// Name of the account that offsets adjustments.
+pub const ADJUSTMENTS_ACCOUNT: &str = "adjustments";
+
+/// One side of a journal entry. Exactly one of `debit` and `credit` is non-zero.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LedgerLine {
+    /// Journal entry the line belongs to: the position of the transaction among those
+    /// applied, starting at 1.
+    pub entry: u64,
+    pub account: String,
+    pub debit: Decimal,
+    pub credit: Decimal,
+    /// Summary of the transaction, e.g. `deposit tx 1 (client 1, amount 1.5)`.
+    pub description: String,
+    /// See [`Transaction::parent_tx`]; empty for settlements.
+    pub parent_tx: Option<TransactionId>,
+}
+
+/// The lines moving `client`'s balance from `before` to `after`. They always balance:
+/// changes of the client accounts are offset by `offset` (usually [`PARTNER_ACCOUNT`]) when
+/// the gross total moved.
+pub fn journal_entry(
+    entry: u64,
+    description: &str,
+    offset: &str,
+    client: ClientId,
+    before: &Account,
+    after: &Account,
+) -> Vec<LedgerLine> {
+    let (before, after) = (before.balance(), after.balance());
+    let changes = [
+        (
+            format!("client:{client}:available"),
+            after.available() - before.available(),
+        ),
+        (
+            format!("client:{client}:held"),
+            after.held() - before.held(),
+        ),
+        (
+            format!("client:{client}:authorized"),
+            after.authorized() - before.authorized(),
+        ),
+        (
+            format!("client:{client}:pending"),
+            after.pending() - before.pending(),
+        ),
+        (
+            format!("client:{client}:reserve"),
+            after.reserve() - before.reserve(),
+        ),
+        (
+            offset.to_string(),
+            before.gross_total() - after.gross_total(),
+        ),
+    ];
+    changes
+        .into_iter()
+        .filter(|(_, change)| !change.is_zero())
+        .map(|(account, change)| LedgerLine {
+            entry,
+            account,
+            debit: change.max(Decimal::ZERO).normalize(),
+            credit: (-change).max(Decimal::ZERO).normalize(),
+            description: description.to_string(),
+            parent_tx: None,
+        })
+        .collect()
+}
+
+/// Runs a [`PaymentsEngine`] and writes the journal of the transactions it applies as CSV:
+/// entry, account, debit, credit, description, parent_tx. The header is written up front, so an empty
+/// journal is still a valid file.
+pub struct LedgerWriter<W: io::Write> {
+    engine: PaymentsEngine,
+    writer: csv::Writer<W>,
+    entries: u64,
+}
+
+impl<W: io::Write> LedgerWriter<W> {
+    pub fn new(config: EngineConfig, writer: W) -> anyhow::Result<Self> {
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(writer);
+        writer.write_record([
+            "entry",
+            "account",
+            "debit",
+            "credit",
+            "description",
+            "parent_tx",
+        ])?;
+        Ok(Self {
+            engine: PaymentsEngine::with_config(config),
+            writer,
+            entries: 0,
+        })
+    }
+    pub fn engine(&self) -> &PaymentsEngine {
+        &self.engine
+    }
+    /// Journals the settlements that are due, each as an entry moving the deposit from
+    /// pending or reserve to available.
+    fn settle_due(&mut self) -> anyhow::Result<()> {
+        for settlement in self.engine.settle_due() {
+            self.entries += 1;
+            let (client, amount) = (settlement.client, settlement.amount.value().normalize());
+            let (from, description) = match settlement.kind {
+                SettlementKind::Pending => (
+                    "pending",
+                    format!("settlement of deposit tx {}", settlement.tx),
+                ),
+                SettlementKind::Reserve => (
+                    "reserve",
+                    format!("reserve release of deposit tx {}", settlement.tx),
+                ),
+            };
+            for (account, debit, credit) in [
+                ("available", amount, Decimal::ZERO),
+                (from, Decimal::ZERO, amount),
+            ] {
+                self.writer.serialize(LedgerLine {
+                    entry: self.entries,
+                    account: format!("client:{client}:{account}"),
+                    debit,
+                    credit,
+                    description: description.clone(),
+                    parent_tx: None,
+                })?;
+            }
+        }
+        Ok(())
+    }
+    /// Applies `transaction`, writing its journal entry if it was applied, after those of
+    /// the settlements due before it.
+    pub fn apply(&mut self, transaction: Transaction) -> anyhow::Result<Outcome> {
+        self.settle_due()?;
+        let client = transaction.client_id();
+        let description = transaction.to_string();
+        let parent_tx = transaction.parent_tx();
+        let offset = match transaction {
+            Transaction::Adjustment(_) => ADJUSTMENTS_ACCOUNT,
+            _ => PARTNER_ACCOUNT,
+        };
+        let before = self
+            .engine
+            .client_accounts()
+            .get(client)
+            .cloned()
+            .unwrap_or_default();
+
+        let outcome = self.engine.apply(transaction);
+        if outcome != Outcome::Applied {
+            return Ok(outcome);
+        }
+        self.entries += 1;
+        let after = self
+            .engine
+            .client_accounts()
+            .get(client)
+            .expect("applied transactions create the account");
+        for line in journal_entry(self.entries, &description, offset, client, &before, after) {
+            self.writer.serialize(LedgerLine { parent_tx, ..line })?;
+        }
+        Ok(outcome)
+    }
+    /// Applies every transaction, logging rejected ones like
+    /// [`PaymentsEngine::process_transactions`]; fails only if writing fails.
+    pub fn apply_all(
+        &mut self,
+        mut transactions: impl Iterator<Item = Transaction>,
+    ) -> anyhow::Result<()> {
+        transactions.try_for_each(|transaction| {
+            self.apply(transaction)?.log_unapplied();
+            Ok(())
+        })
+    }
+    /// Journals the settlements due after the last transaction, flushes the journal and
+    /// returns the engine.
+    pub fn finish(mut self) -> anyhow::Result<PaymentsEngine> {
+        self.settle_due()?;
+        self.writer.flush()?;
+        Ok(self.engine)
+    }
+}
@@ -1,4 +1,26 @@
+pub mod anomalies;
+#[cfg(feature = "async")]
+pub mod async_pipeline;
+pub mod authenticity;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+pub mod chargebacks;
+pub mod dispute_trails;
 pub mod domain;
 pub mod engine;
+pub mod handover;
+pub mod ledger;
 pub mod output;
 pub mod parsing;
+pub mod partners;
+pub mod pipeline;
+pub mod reconcile;
+pub mod redaction;
+pub mod rejection;
+pub mod report;
+pub mod statement;
+pub mod suspicious;
+pub mod testgen;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod volume;
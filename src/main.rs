@@ -1,39 +1,1351 @@
-use std::fs::File;
+use std::{
+    fs::File,
+    io::{Cursor, Read, Seek, Write},
+    num::NonZeroUsize,
+    sync::Arc,
+};
 
-use clap::Parser;
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
-use rust_coding_test::engine::PaymentsEngine;
+use rust_coding_test::anomalies::{self, AnomalyDetector, AnomalyThresholds};
+#[cfg(feature = "async")]
+use rust_coding_test::async_pipeline;
+use rust_coding_test::authenticity::{self, HMAC_KEY_VAR};
+#[cfg(feature = "cbor")]
+use rust_coding_test::cbor;
+use rust_coding_test::chargebacks::ChargebackReport;
+use rust_coding_test::dispute_trails::{DiagramFormat, DisputeTrailRecorder, TrailFilter};
+use rust_coding_test::domain::{ClientId, TransactionId};
+#[cfg(feature = "cold-tier")]
+use rust_coding_test::engine::TieredDepositHistory;
+use rust_coding_test::engine::{
+    AccountTiers, ApplyTransactions, CapacityHint, ChargebackFreeze, ClientAccounts, EngineConfig,
+    LockedAccountDisputes, MultiTenantEngine, NegativeAvailable, PaymentsEngine,
+    ReferencedDepositHistory, RepeatedSettlement, RollingReserve,
+};
+use rust_coding_test::handover;
+use rust_coding_test::ledger::LedgerWriter;
 use rust_coding_test::output;
-use rust_coding_test::parsing;
+use rust_coding_test::parsing::{self, ParseFailureCounts, TenantTransaction};
+use rust_coding_test::partners::PartnerBreakdown;
+use rust_coding_test::pipeline::Pipeline;
+use rust_coding_test::reconcile;
+use rust_coding_test::redaction::{self, REDACTION_KEY_VAR};
+use rust_coding_test::report::RunReport;
+use rust_coding_test::statement::StatementRecorder;
+use rust_coding_test::suspicious::SuspiciousActivityRecorder;
+use rust_coding_test::testgen::Workload;
+use rust_coding_test::volume::{BucketWidth, VolumeAggregator};
+use rust_decimal::Decimal;
 
 fn main() -> anyhow::Result<()> {
     let args = Arguments::parse();
-    if let Some(log_level) = args.log_level {
-        tracing_subscriber::fmt().with_max_level(log_level).init();
+    if let Some(key) = std::env::var_os(REDACTION_KEY_VAR) {
+        redaction::enable(key.as_encoded_bytes());
+    }
+    match args.command {
+        Some(Command::Report(report_args)) => return report(report_args),
+        Some(Command::Statement(statement_args)) => return statement(statement_args),
+        Some(Command::Chargebacks(chargeback_args)) => return chargebacks(chargeback_args),
+        Some(Command::Anomalies(anomaly_args)) => return detect_anomalies(anomaly_args),
+        Some(Command::Volume(volume_args)) => return volume(volume_args),
+        Some(Command::Partners(partner_args)) => return partners(partner_args),
+        Some(Command::Disputes(dispute_args)) => return disputes(dispute_args),
+        Some(Command::Ledger(ledger_args)) => return ledger(ledger_args),
+        Some(Command::Sar(sar_args)) => return suspicious_activity(sar_args),
+        Some(Command::Reconcile(reconcile_args)) => return reconcile(reconcile_args),
+        Some(Command::Compare(compare_args)) => return compare(compare_args),
+        Some(Command::Generate(generate_args)) => return generate(generate_args),
+        None => {}
+    }
+    args.log.init()?;
+
+    let file_path = args
+        .input_file
+        .clone()
+        .expect("required unless a subcommand is given");
+
+    let input_file = InputFile::open(&file_path, args.signature.as_deref())?;
+    #[cfg(not(feature = "compression"))]
+    if is_compressed(&file_path) {
+        anyhow::bail!("{file_path} is compressed, which needs the `compression` feature");
+    }
+
+    let config = args.engine.config();
+
+    if let Some(source) = &args.live {
+        return run_live(&args, &input_file, config, source);
+    }
+    if args.format == InputFormat::Jsonl {
+        return run_jsonl(&args, &input_file, config);
+    }
+    if has_extension(&file_path, "zip") {
+        #[cfg(not(feature = "zip"))]
+        anyhow::bail!("{file_path} is a ZIP archive, which needs the `zip` feature");
+        #[cfg(feature = "zip")]
+        return run_zip(&args, &input_file, config);
+    }
+    if has_extension(&file_path, "cbor") {
+        #[cfg(not(feature = "cbor"))]
+        anyhow::bail!("{file_path} is a CBOR sequence, which needs the `cbor` feature");
+        #[cfg(feature = "cbor")]
+        return run_cbor(&args, &input_file, config);
+    }
+
+    #[cfg(feature = "async")]
+    if args.r#async {
+        if is_compressed(&file_path) {
+            anyhow::bail!("Compressed input isn't supported with --async");
+        }
+        return run_async(&input_file, config);
     }
 
-    let file_path = args.input_file;
+    let capacity = capacity_hint(&args, &input_file)?;
 
-    let file = File::open(file_path)?;
+    // A verified input is already in memory, so `--mmap` and `--io-uring` don't apply.
+    #[cfg(feature = "mmap")]
+    let mapped = if args.mmap && input_file.verified.is_none() {
+        // SAFETY: the input must not change during the run, as documented on `--mmap`.
+        Some(unsafe { parsing::MappedInput::map(&File::open(&file_path)?)? })
+    } else {
+        None
+    };
+    #[allow(unused_mut)] // only reassigned with the optional input features
+    let mut input: Box<dyn Read + Send + '_> = input_file.raw()?;
+    #[cfg(feature = "io-uring")]
+    if args.io_uring && input_file.verified.is_none() {
+        input = parsing::open_uring(&file_path)?;
+    }
+    #[cfg(feature = "mmap")]
+    if let Some(mapped) = &mapped {
+        input = Box::new(mapped.bytes());
+    }
+    let input = decompress(input)?;
 
     let mut rdr = csv::ReaderBuilder::new()
+        // Quarantined rows are written untrimmed; they're trimmed for parsing either way.
+        .trim(if args.quarantine.is_some() {
+            csv::Trim::None
+        } else {
+            csv::Trim::All
+        })
+        .from_reader(input);
+
+    let transaction_iter: Box<dyn Iterator<Item = TenantTransaction> + Send> = if args.byte_records
+    {
+        Box::new(parsing::deserialize_tenant_byte_records(&mut rdr))
+    } else if let Some(path) = &args.quarantine {
+        let quarantine = File::create(path)
+            .with_context(|| format!("Failed to create quarantine file {path}"))?;
+        Box::new(parsing::deserialize_tenant_csv_quarantined(
+            &mut rdr,
+            parsing::Quarantine::new(quarantine),
+        ))
+    } else {
+        Box::new(parsing::deserialize_tenant_csv(&mut rdr))
+    };
+
+    if args.pipelined {
+        Pipeline::default().run(transaction_iter, |transactions| {
+            process(&args, &input_file, config, capacity, transactions)
+        })
+    } else {
+        process(&args, &input_file, config, capacity, transaction_iter)
+    }
+}
+
+fn capacity_hint(args: &Arguments, input_file: &InputFile) -> std::io::Result<CapacityHint> {
+    Ok(if args.presize {
+        CapacityHint::from_input_size(input_file.len()?)
+    } else {
+        CapacityHint::default()
+    })
+}
+
+/// Whether the file name ends in `.<extension>`, ignoring a compression suffix, so that
+/// e.g. `transactions.cbor.zst` is CBOR.
+fn has_extension(file_path: &str, extension: &str) -> bool {
+    let mut path = std::path::Path::new(file_path);
+    if is_compressed(file_path) {
+        path = std::path::Path::new(path.file_stem().unwrap_or_default());
+    }
+    path.extension()
+        .is_some_and(|actual| actual.eq_ignore_ascii_case(extension))
+}
+
+fn is_compressed(file_path: &str) -> bool {
+    std::path::Path::new(file_path)
+        .extension()
+        .is_some_and(|actual| {
+            actual.eq_ignore_ascii_case("gz") || actual.eq_ignore_ascii_case("zst")
+        })
+}
+
+/// An input file, checked against its signature when a key is set in [`HMAC_KEY_VAR`].
+/// A checked file is read once and kept in memory, and every pass over it reads those
+/// bytes rather than the path, so what is parsed is what was verified.
+struct InputFile {
+    path: String,
+    verified: Option<Arc<[u8]>>,
+}
+
+/// The undecompressed bytes of an [`InputFile`].
+trait RawInput: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> RawInput for T {}
+
+impl InputFile {
+    /// Opens `path`, refusing it unless it matches its signature when a key is set. The
+    /// signature defaults to `<path>.sig`.
+    fn open(path: &str, signature: Option<&str>) -> anyhow::Result<Self> {
+        let Some(key) = std::env::var_os(HMAC_KEY_VAR) else {
+            if signature.is_some() {
+                anyhow::bail!("--signature needs the shared key in {HMAC_KEY_VAR}");
+            }
+            return Ok(Self {
+                path: path.to_string(),
+                verified: None,
+            });
+        };
+        let signature_path = signature.map_or_else(|| format!("{path}.sig"), str::to_string);
+        let signature = std::fs::read_to_string(&signature_path)
+            .with_context(|| format!("Refusing {path}: can't read signature {signature_path}"))?;
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        authenticity::verify(bytes.as_slice(), key.as_encoded_bytes(), &signature)
+            .with_context(|| format!("Refusing {path}"))?;
+        Ok(Self {
+            path: path.to_string(),
+            verified: Some(bytes.into()),
+        })
+    }
+    /// Size in bytes, for `--presize`.
+    fn len(&self) -> std::io::Result<u64> {
+        match &self.verified {
+            Some(bytes) => Ok(bytes.len() as u64),
+            None => Ok(std::fs::metadata(&self.path)?.len()),
+        }
+    }
+    /// The file's bytes as stored, possibly compressed.
+    fn raw(&self) -> std::io::Result<Box<dyn RawInput>> {
+        Ok(match &self.verified {
+            Some(bytes) => Box::new(Cursor::new(Arc::clone(bytes))),
+            None => Box::new(File::open(&self.path)?),
+        })
+    }
+    /// The file's transactions, see [`decompress`].
+    fn read(&self) -> anyhow::Result<Box<dyn Read + Send>> {
+        #[cfg(not(feature = "compression"))]
+        if is_compressed(&self.path) {
+            anyhow::bail!(
+                "{} is compressed, which needs the `compression` feature",
+                self.path
+            );
+        }
+        Ok(decompress(self.raw()?)?)
+    }
+}
+
+/// [`InputFile::read`] for a subcommand, with the same signature check as the default
+/// mode, against `<input_file>.sig`.
+fn open_signed_input(file_path: &str) -> anyhow::Result<Box<dyn Read + Send>> {
+    InputFile::open(file_path, None)?.read()
+}
+
+/// Decompresses gzip and zstd input, detected by its magic bytes, with the `compression`
+/// feature. Passes the input through otherwise.
+fn decompress<'a>(
+    input: impl std::io::Read + Send + 'a,
+) -> std::io::Result<Box<dyn std::io::Read + Send + 'a>> {
+    #[cfg(feature = "compression")]
+    return parsing::decompress(std::io::BufReader::new(input));
+    #[cfg(not(feature = "compression"))]
+    Ok(Box::new(input))
+}
+
+/// Processes the CSV members of a ZIP archive in name order as a single input.
+#[cfg(feature = "zip")]
+fn run_zip(args: &Arguments, input_file: &InputFile, config: EngineConfig) -> anyhow::Result<()> {
+    let unsupported = [
+        ("--two-pass", args.two_pass),
+        ("--byte-records", args.byte_records),
+        ("--quarantine", args.quarantine.is_some()),
+        #[cfg(feature = "async")]
+        ("--async", args.r#async),
+        #[cfg(feature = "mmap")]
+        ("--mmap", args.mmap),
+        #[cfg(feature = "io-uring")]
+        ("--io-uring", args.io_uring),
+    ];
+    if let Some((flag, _)) = unsupported.iter().find(|(_, set)| *set) {
+        anyhow::bail!("{flag} isn't supported with ZIP input");
+    }
+
+    let capacity = capacity_hint(args, input_file)?;
+    let input = parsing::ZipInput::new(std::io::BufReader::new(input_file.raw()?))
+        .with_context(|| format!("Failed to open {}", input_file.path))?;
+    let transaction_iter = input.tenant_transactions();
+
+    if args.pipelined {
+        Pipeline::default().run(transaction_iter, |transactions| {
+            process(args, input_file, config, capacity, transactions)
+        })
+    } else {
+        process(args, input_file, config, capacity, transaction_iter)
+    }
+}
+
+/// Processes a CBOR sequence of transactions.
+#[cfg(feature = "cbor")]
+fn run_cbor(args: &Arguments, input_file: &InputFile, config: EngineConfig) -> anyhow::Result<()> {
+    let unsupported = [
+        ("--two-pass", args.two_pass),
+        ("--byte-records", args.byte_records),
+        ("--quarantine", args.quarantine.is_some()),
+        #[cfg(feature = "async")]
+        ("--async", args.r#async),
+        #[cfg(feature = "mmap")]
+        ("--mmap", args.mmap),
+        #[cfg(feature = "io-uring")]
+        ("--io-uring", args.io_uring),
+    ];
+    if let Some((flag, _)) = unsupported.iter().find(|(_, set)| *set) {
+        anyhow::bail!("{flag} isn't supported with CBOR input");
+    }
+
+    let capacity = capacity_hint(args, input_file)?;
+    let transaction_iter = cbor::deserialize_cbor(std::io::BufReader::new(decompress(
+        input_file.raw()?,
+    )?))
+    .map(|transaction| TenantTransaction {
+        tenant: None,
+        transaction,
+    });
+
+    if args.pipelined {
+        Pipeline::default().run(transaction_iter, |transactions| {
+            process(args, input_file, config, capacity, transactions)
+        })
+    } else {
+        process(args, input_file, config, capacity, transaction_iter)
+    }
+}
+
+/// Processes a JSON Lines file of transactions.
+fn run_jsonl(args: &Arguments, input_file: &InputFile, config: EngineConfig) -> anyhow::Result<()> {
+    let unsupported = [
+        ("--two-pass", args.two_pass),
+        ("--byte-records", args.byte_records),
+        ("--quarantine", args.quarantine.is_some()),
+        #[cfg(feature = "async")]
+        ("--async", args.r#async),
+        #[cfg(feature = "mmap")]
+        ("--mmap", args.mmap),
+        #[cfg(feature = "io-uring")]
+        ("--io-uring", args.io_uring),
+    ];
+    if let Some((flag, _)) = unsupported.iter().find(|(_, set)| *set) {
+        anyhow::bail!("{flag} isn't supported with JSON Lines input");
+    }
+
+    let capacity = capacity_hint(args, input_file)?;
+    let transaction_iter =
+        parsing::deserialize_tenant_jsonl(std::io::BufReader::new(decompress(input_file.raw()?)?));
+
+    if args.pipelined {
+        Pipeline::default().run(transaction_iter, |transactions| {
+            process(args, input_file, config, capacity, transactions)
+        })
+    } else {
+        process(args, input_file, config, capacity, transaction_iter)
+    }
+}
+
+/// Processes the input file, then keeps the same engine running on the live `source`
+/// (`-` for stdin, otherwise a TCP address) until it closes. Writes a snapshot of the
+/// changed accounts after the history, every `--snapshot-every` live transactions and at
+/// the end.
+fn run_live(
+    args: &Arguments,
+    input_file: &InputFile,
+    config: EngineConfig,
+    source: &str,
+) -> anyhow::Result<()> {
+    let unsupported = [
+        ("ZIP input", has_extension(&input_file.path, "zip")),
+        ("CBOR input", has_extension(&input_file.path, "cbor")),
+        ("JSON Lines input", args.format == InputFormat::Jsonl),
+        #[cfg(feature = "mmap")]
+        ("--mmap", args.mmap),
+        #[cfg(feature = "io-uring")]
+        ("--io-uring", args.io_uring),
+        #[cfg(feature = "cold-tier")]
+        ("--hot-deposits", args.hot_deposits.is_some()),
+        #[cfg(feature = "cbor")]
+        ("--cbor", args.cbor),
+    ];
+    if let Some((what, _)) = unsupported.iter().find(|(_, set)| *set) {
+        anyhow::bail!("{what} isn't supported with --live");
+    }
+
+    let columns = output::OutputColumns::for_config(&config);
+    let mut engine = PaymentsEngine::with_capacity(config, capacity_hint(args, input_file)?);
+    let mut history = csv::ReaderBuilder::new()
         .trim(csv::Trim::All)
-        .from_reader(file);
+        .from_reader(decompress(input_file.raw()?)?);
+    let mut last = None;
+    engine.process_transactions(
+        parsing::deserialize_csv(&mut history)
+            .inspect(|transaction| last = Some(transaction.clone())),
+    );
+    let mut snapshots = output::SequencedCsv::with_columns(std::io::stdout().lock(), columns);
+    engine.flush_changed_accounts(&mut snapshots)?;
+
+    let live: Box<dyn std::io::Read> = if source == "-" {
+        Box::new(std::io::stdin().lock())
+    } else {
+        Box::new(
+            std::net::TcpStream::connect(source)
+                .with_context(|| format!("Failed to connect to {source}"))?,
+        )
+    };
+    let mut live = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(live);
+    let live = handover::skip_overlap(
+        parsing::deserialize_csv(&mut live),
+        last.as_ref(),
+        args.overlap,
+    )?;
+    for (index, transaction) in live.enumerate() {
+        engine.feed(transaction);
+        if (index + 1) % args.snapshot_every.get() == 0 {
+            engine.flush_changed_accounts(&mut snapshots)?;
+        }
+    }
+    engine.flush_changed_accounts(&mut snapshots)?;
 
-    let transaction_iter = parsing::deserialize_csv(&mut rdr);
+    Ok(())
+}
+
+/// Creates the file of the cold deposit tier in the temporary directory.
+#[cfg(feature = "cold-tier")]
+fn cold_tier_file() -> std::io::Result<(std::path::PathBuf, File)> {
+    let path = std::env::temp_dir().join(format!("payments-cold-tier-{}", std::process::id()));
+    let file = File::options()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    Ok((path, file))
+}
+
+/// Runs the engine selected by `args` over the parsed input and prints the accounts.
+fn process(
+    args: &Arguments,
+    input_file: &InputFile,
+    config: EngineConfig,
+    capacity: CapacityHint,
+    transaction_iter: impl Iterator<Item = TenantTransaction>,
+) -> anyhow::Result<()> {
+    let columns = &args
+        .columns
+        .clone()
+        .unwrap_or_else(|| output::OutputColumns::for_config(&config))
+        .with_gross_total(args.gross_total);
+    if args.multi_tenant {
+        let mut engine = MultiTenantEngine::with_config(config);
+        engine.process_transactions(transaction_iter);
+
+        output::print_tenant_accounts_with_columns(&engine, columns, std::io::stdout())?;
+
+        return Ok(());
+    }
+
+    let transaction_iter =
+        transaction_iter.map(|tenant_transaction| tenant_transaction.transaction);
+
+    if args.two_pass {
+        // First pass: only collect the tx IDs disputes refer to. Invalid rows are
+        // skipped silently here and reported by the second pass. Both read the same
+        // verified bytes when the input is signed.
+        let mut first_pass = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(input_file.read()?);
+        let deposit_history = ReferencedDepositHistory::scan(
+            parsing::ByteRecordParser::new(&mut first_pass)
+                .filter_map(Result::ok)
+                .map(|tenant_transaction| tenant_transaction.transaction),
+        );
+
+        let mut engine = PaymentsEngine::with_store(config, deposit_history);
+        engine.process_transactions(transaction_iter);
+
+        return print_accounts(args, engine.client_accounts(), columns);
+    }
+
+    #[cfg(feature = "cold-tier")]
+    if let Some(hot_deposits) = args.hot_deposits {
+        let (cold_path, cold) = cold_tier_file()?;
+        let mut engine =
+            PaymentsEngine::with_store(config, TieredDepositHistory::new(cold, hot_deposits));
+        engine.process_transactions(transaction_iter);
+        let result = print_accounts(args, engine.client_accounts(), columns);
+        std::fs::remove_file(cold_path)?;
+
+        return result;
+    }
 
-    let mut engine = PaymentsEngine::new();
+    let mut engine = PaymentsEngine::with_capacity(config, capacity);
     engine.process_transactions(transaction_iter);
 
-    let client_accounts = engine.client_accounts();
+    print_accounts(args, engine.client_accounts(), columns)
+}
+
+/// Writes the single-tenant accounts to stdout: as CBOR with `--cbor`, otherwise as CSV
+/// with the selected columns, on `--output-shards` threads.
+fn print_accounts(
+    args: &Arguments,
+    client_accounts: &ClientAccounts,
+    columns: &output::OutputColumns,
+) -> anyhow::Result<()> {
+    #[cfg(feature = "cbor")]
+    if args.cbor {
+        return cbor::print_accounts_cbor(client_accounts, std::io::stdout().lock());
+    }
+    match args.output_shards {
+        Some(shards) => output::print_accounts_sharded_with_columns(
+            client_accounts,
+            columns,
+            std::io::stdout().lock(),
+            shards,
+        ),
+        None => {
+            output::print_accounts_with_columns(client_accounts, columns, std::io::stdout().lock())
+        }
+    }
+}
+
+/// Runs the tokio pipeline (single-tenant), stopping early on Ctrl-C.
+#[cfg(feature = "async")]
+fn run_async(input_file: &InputFile, config: EngineConfig) -> anyhow::Result<()> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let input: Box<dyn tokio::io::AsyncRead + Unpin + Send> = match &input_file.verified {
+            Some(bytes) => Box::new(Cursor::new(Arc::clone(bytes))),
+            None => Box::new(tokio::fs::File::open(&input_file.path).await?),
+        };
+        tokio::select! {
+            result = async_pipeline::run(input, tokio::io::stdout(), config) => result,
+            _ = tokio::signal::ctrl_c() => anyhow::bail!("Cancelled"),
+        }
+    })
+}
+
+/// Prints a [`RunReport`] instead of the account CSV.
+fn report(args: ReportArguments) -> anyhow::Result<()> {
+    args.log.init()?;
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(open_signed_input(&args.input_file)?);
+    let mut engine = PaymentsEngine::with_config(args.engine.config());
+    let mut parse_failures = ParseFailureCounts::default();
+    engine.process_transactions(
+        parsing::try_deserialize_csv(&mut rdr).filter_map(|row| parse_failures.count(row)),
+    );
+
+    print!(
+        "{}",
+        RunReport::new(&engine, args.top).with_parse_failures(parse_failures)
+    );
+
+    Ok(())
+}
+
+/// Prints the [`ChargebackReport`] instead of the account CSV.
+fn chargebacks(args: ChargebackArguments) -> anyhow::Result<()> {
+    args.log.init()?;
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(open_signed_input(&args.input_file)?);
+    let mut engine = PaymentsEngine::with_config(args.engine.config());
+    engine.process_transactions(parsing::deserialize_csv(&mut rdr));
+
+    print!("{}", ChargebackReport::new(&engine, args.threshold));
+
+    Ok(())
+}
+
+/// Writes the anomaly report to `--output`, or stdout without it.
+fn detect_anomalies(args: AnomalyArguments) -> anyhow::Result<()> {
+    args.log.init()?;
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(open_signed_input(&args.input_file)?);
+    let mut detector = AnomalyDetector::new(args.engine.config(), args.thresholds.thresholds());
+    detector.apply_all(parsing::deserialize_csv(&mut rdr));
+
+    match &args.output {
+        Some(path) => anomalies::write_anomalies(detector.anomalies(), File::create(path)?),
+        None => anomalies::write_anomalies(detector.anomalies(), std::io::stdout().lock()),
+    }
+}
+
+/// Writes the volume report to `--output`, or stdout without it.
+fn volume(args: VolumeArguments) -> anyhow::Result<()> {
+    args.log.init()?;
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(open_signed_input(&args.input_file)?);
+    let width = match args.bucket {
+        BucketArgument::Hourly => BucketWidth::Hourly,
+        BucketArgument::Daily => BucketWidth::Daily,
+    };
+    let mut aggregator = VolumeAggregator::new(args.engine.config(), width);
+    aggregator.apply_all(parsing::deserialize_timestamped_csv(&mut rdr));
+    if aggregator.untimestamped() > 0 {
+        tracing::warn!(
+            count = aggregator.untimestamped(),
+            "Deposits and withdrawals without a timestamp left out of the volume report"
+        );
+    }
+
+    match &args.output {
+        Some(path) => aggregator.write_report(File::create(path)?),
+        None => aggregator.write_report(std::io::stdout().lock()),
+    }
+}
+
+/// Writes the partner breakdown to `--output`, or stdout without it.
+fn partners(args: PartnerArguments) -> anyhow::Result<()> {
+    args.log.init()?;
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(open_signed_input(&args.input_file)?);
+    let mut breakdown = PartnerBreakdown::new(args.engine.config());
+    breakdown.apply_all(parsing::deserialize_partner_csv(&mut rdr));
+    breakdown.finish();
+
+    let mut output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    breakdown.write_report(&mut output)?;
+    match &args.liabilities {
+        Some(path) => breakdown.write_liabilities(File::create(path)?),
+        None => {
+            writeln!(output)?;
+            breakdown.write_liabilities(output)
+        }
+    }
+}
+
+/// Prints the dispute trails of the selected deposits as a diagram.
+fn disputes(args: DisputeArguments) -> anyhow::Result<()> {
+    args.log.init()?;
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(open_signed_input(&args.input_file)?);
+    let filter = match (args.client, args.tx) {
+        (Some(client), _) => TrailFilter::Client(client),
+        (_, Some(tx)) => TrailFilter::Transaction(tx),
+        (None, None) => TrailFilter::All,
+    };
+    let format = match args.format {
+        DiagramArgument::Mermaid => DiagramFormat::Mermaid,
+        DiagramArgument::Graphviz => DiagramFormat::Graphviz,
+    };
+    let mut recorder = DisputeTrailRecorder::new(args.engine.config(), filter);
+    recorder.apply_all(parsing::deserialize_csv(&mut rdr));
+
+    print!("{}", recorder.diagram(format));
+
+    Ok(())
+}
+
+/// Writes the double-entry journal to `--output`, or stdout without it.
+fn ledger(args: LedgerArguments) -> anyhow::Result<()> {
+    args.log.init()?;
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(open_signed_input(&args.input_file)?);
+    let output: Box<dyn std::io::Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let mut ledger = LedgerWriter::new(args.engine.config(), output)?;
+    ledger.apply_all(parsing::deserialize_csv(&mut rdr))?;
+    ledger.finish()?;
+
+    Ok(())
+}
+
+/// Writes the suspicious-activity report as JSON to `--output`, or stdout without it.
+fn suspicious_activity(args: SarArguments) -> anyhow::Result<()> {
+    args.log.init()?;
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(open_signed_input(&args.input_file)?);
+    let mut recorder = SuspiciousActivityRecorder::new(args.engine.config());
+    recorder.apply_all(parsing::deserialize_csv(&mut rdr));
+
+    let report = recorder.report();
+    match &args.output {
+        Some(path) => serde_json::to_writer_pretty(File::create(path)?, &report)?,
+        None => {
+            serde_json::to_writer_pretty(std::io::stdout().lock(), &report)?;
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the discrepancies to `--output`, or stdout without it, and exits with status 1
+/// if there are any.
+fn reconcile(args: ReconcileArguments) -> anyhow::Result<()> {
+    args.log.init()?;
+
+    let expected = reconcile::read_expected(InputFile::open(&args.expected, None)?.raw()?)?;
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(open_signed_input(&args.input_file)?);
+    let mut engine = PaymentsEngine::with_config(args.engine.config());
+    engine.process_transactions(parsing::deserialize_csv(&mut rdr));
 
-    output::print_accounts(client_accounts, std::io::stdout())?;
+    let discrepancies = reconcile::reconcile(&expected, engine.client_accounts());
+    match &args.output {
+        Some(path) => reconcile::write_discrepancies(&discrepancies, File::create(path)?)?,
+        None => reconcile::write_discrepancies(&discrepancies, std::io::stdout().lock())?,
+    }
+    if !discrepancies.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Writes the discrepancies between two balance files to `--output`, or stdout without it.
+fn compare(args: CompareArguments) -> anyhow::Result<()> {
+    args.log.init()?;
+
+    let accounts = InputFile::open(&args.accounts, None)?;
+    let against = InputFile::open(&args.against, None)?;
+    let actual = reconcile::read_balances(accounts.raw()?, &args.accounts_columns)
+        .with_context(|| format!("Failed to read {}", args.accounts))?;
+    let expected = reconcile::read_balances(against.raw()?, &args.against_columns)
+        .with_context(|| format!("Failed to read {}", args.against))?;
+
+    let discrepancies = reconcile::compare(&expected, &actual, args.tolerance);
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    if args.json {
+        serde_json::to_writer_pretty(writer, &discrepancies)?;
+    } else {
+        reconcile::write_discrepancies(&discrepancies, writer)?;
+    }
+    if !discrepancies.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Writes a synthetic workload to `--output`, or stdout without it.
+fn generate(args: GenerateArguments) -> anyhow::Result<()> {
+    let workload = Workload {
+        clients: args.clients,
+        rows: args.transactions,
+        dispute_rate: args.dispute_rate,
+        chargeback_share: args.chargeback_share,
+        invalid_rate: args.invalid_rate,
+        seed: args.seed,
+    };
+    match &args.output {
+        Some(path) => workload.write_csv(File::create(path)?)?,
+        None => workload.write_csv(std::io::stdout().lock())?,
+    }
+
+    Ok(())
+}
+
+/// Prints the statements of the requested clients instead of the account CSV.
+fn statement(args: StatementArguments) -> anyhow::Result<()> {
+    args.log.init()?;
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(open_signed_input(&args.input_file)?);
+    let mut recorder = StatementRecorder::new(args.engine.config(), args.client);
+    recorder.apply_all(parsing::deserialize_csv(&mut rdr));
+    recorder.finish();
+
+    for statement in recorder.statements() {
+        match args.format {
+            StatementFormat::Text => println!("{statement}"),
+            StatementFormat::Html => print!("{}", statement.html()),
+        }
+    }
 
     Ok(())
 }
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Arguments {
-    input_file: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// CSV file of transactions, a `.zip` of CSV files read in name order (`zip` feature),
+    /// or a `.cbor` sequence of transactions (`cbor` feature).
+    #[arg(required = true)]
+    input_file: Option<String>,
+    /// Format of the input file. `jsonl` reads one JSON object per line, with the fields of
+    /// a CSV row.
+    #[arg(long, value_enum, default_value_t = InputFormat::Csv)]
+    format: InputFormat,
+    #[command(flatten)]
+    log: LogArguments,
+    #[command(flatten)]
+    engine: EngineArguments,
+    /// Keep a separate account space per value of the optional `tenant` column.
+    #[arg(long)]
+    multi_tenant: bool,
+    /// Parse rows into a reused byte record instead of through serde (faster on large files).
+    #[arg(long)]
+    byte_records: bool,
+    /// Pre-size the engine's maps from the input file size (single-tenant mode only).
+    #[arg(long)]
+    presize: bool,
+    /// Read the input twice and only keep deposits that a dispute refers to (single-tenant
+    /// mode only). Trades a second parse for much less memory on deposit-heavy inputs.
+    #[arg(long)]
+    two_pass: bool,
+    /// Keep at most N deposits in memory and move older ones to a compressed cold tier in a
+    /// temporary file (single-tenant mode only). Disputes of cold deposits read them back.
+    #[cfg(feature = "cold-tier")]
+    #[arg(long, value_name = "N", conflicts_with = "two_pass")]
+    hot_deposits: Option<usize>,
+    /// Parse on a separate thread, overlapping it with processing.
+    #[arg(long)]
+    pipelined: bool,
+    /// Serialize the output on this many threads (single-tenant mode only).
+    #[arg(long)]
+    output_shards: Option<NonZeroUsize>,
+    /// Output columns and their headers, e.g. `client=client_id,available,total`. Defaults
+    /// to client, available, held, total and locked, followed by pending with a settlement
+    /// delay and reserve with a reserve rate.
+    #[arg(long)]
+    columns: Option<output::OutputColumns>,
+    /// Write the gross total as `total`: available + held plus the authorized, pending and
+    /// reserve funds.
+    #[arg(long)]
+    gross_total: bool,
+    /// Read the input file through io_uring (Linux; other platforms use std IO).
+    #[cfg(feature = "io-uring")]
+    #[arg(long)]
+    io_uring: bool,
+    /// Read, parse and write as tokio tasks (single-tenant mode only); Ctrl-C cancels the run.
+    #[cfg(feature = "async")]
+    #[arg(
+        long,
+        conflicts_with_all = ["multi_tenant", "byte_records", "presize", "two_pass", "pipelined", "output_shards", "columns", "gross_total", "live", "quarantine"]
+    )]
+    #[cfg_attr(feature = "cold-tier", arg(conflicts_with = "hot_deposits"))]
+    #[cfg_attr(feature = "io-uring", arg(conflicts_with = "io_uring"))]
+    #[cfg_attr(feature = "mmap", arg(conflicts_with = "mmap"))]
+    #[cfg_attr(feature = "cbor", arg(conflicts_with = "cbor"))]
+    r#async: bool,
+    /// Detached signature of the input file: hex HMAC-SHA256 under the key in
+    /// `PAYMENTS_HMAC_KEY`. Checked whenever the key is set; defaults to `<input_file>.sig`.
+    #[arg(long)]
+    signature: Option<String>,
+    /// Memory-map the input file instead of reading it. It must not be modified during the run.
+    #[cfg(feature = "mmap")]
+    #[arg(long)]
+    mmap: bool,
+    /// After the input file, keep processing CSV transactions from this live source (`-` for
+    /// stdin, or a TCP address) until it closes, writing snapshots of the changed accounts.
+    #[arg(
+        long,
+        value_name = "SOURCE",
+        conflicts_with_all = ["two_pass", "multi_tenant", "byte_records", "pipelined", "columns", "gross_total", "output_shards"]
+    )]
+    live: Option<String>,
+    /// Drop the live transactions up to the last one of the input file, which must be among
+    /// the first N, for live sources replayed from before the end of the file.
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "live")]
+    overlap: usize,
+    /// Write a snapshot of the changed accounts every N live transactions.
+    #[arg(long, value_name = "N", default_value_t = NonZeroUsize::new(1000).unwrap(), requires = "live")]
+    snapshot_every: NonZeroUsize,
+    /// Write the CSV rows that are skipped to this file: their line and rejection reason,
+    /// followed by their fields as read.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["byte_records", "live"])]
+    quarantine: Option<String>,
+    /// Write the accounts as a CBOR sequence instead of CSV (single-tenant mode only).
+    #[cfg(feature = "cbor")]
+    #[arg(long, conflicts_with_all = ["columns", "gross_total", "output_shards", "multi_tenant"])]
+    cbor: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print a run summary (clients, locked accounts, totals, disputes, largest accounts)
+    /// instead of the account CSV.
+    Report(ReportArguments),
+    /// Print per-client statements: every applied transaction with the running balance.
+    Statement(StatementArguments),
+    /// Print per-client chargeback-to-deposit ratios, highest first, flagging those above
+    /// a threshold.
+    Chargebacks(ChargebackArguments),
+    /// Write a CSV report of large deposits, balance swings and dispute bursts.
+    Anomalies(AnomalyArguments),
+    /// Write a CSV report of deposit and withdrawal volume per client per hour or day,
+    /// from the optional `timestamp` column (Unix seconds).
+    Volume(VolumeArguments),
+    /// Write a CSV report of each client's balances and statistics per partner, from the
+    /// optional `partner` column.
+    Partners(PartnerArguments),
+    /// Print the state transitions of disputed deposits as a Mermaid or Graphviz diagram.
+    Disputes(DisputeArguments),
+    /// Write every applied transaction as balanced double-entry journal lines (CSV).
+    Ledger(LedgerArguments),
+    /// Write a JSON suspicious-activity report of frozen accounts and the chargebacks that
+    /// froze them.
+    Sar(SarArguments),
+    /// Compare the resulting accounts against an expected-balance CSV and write the
+    /// discrepancies; exits with status 1 if there are any.
+    Reconcile(ReconcileArguments),
+    /// Compare an account output file against another system's balance export, within a
+    /// tolerance, and write the discrepancies; exits with status 1 if there are any.
+    Compare(CompareArguments),
+    /// Write a synthetic CSV workload for benchmarking and testing.
+    Generate(GenerateArguments),
+}
+
+/// Diagnostics only ever go to stderr or `--log-file`, so stdout carries nothing but the
+/// result.
+#[derive(Args)]
+struct LogArguments {
+    /// Most verbose level to log. Overrides `-v`.
     log_level: Option<tracing::Level>,
+    /// Log rejected rows with `-v`, also info with `-vv`, debug with `-vvv` and everything
+    /// beyond.
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Log nothing.
+    #[arg(short, long, conflicts_with_all = ["log_level", "verbose"])]
+    quiet: bool,
+    /// Append the log to this file instead of writing it to stderr.
+    #[arg(long)]
+    log_file: Option<String>,
+}
+
+impl LogArguments {
+    fn level(&self) -> Option<tracing::Level> {
+        if self.quiet {
+            return None;
+        }
+        self.log_level.or(match self.verbose {
+            0 => None,
+            1 => Some(tracing::Level::WARN),
+            2 => Some(tracing::Level::INFO),
+            3 => Some(tracing::Level::DEBUG),
+            _ => Some(tracing::Level::TRACE),
+        })
+    }
+    /// Installs the logger, if any level is selected.
+    fn init(&self) -> anyhow::Result<()> {
+        let Some(level) = self.level() else {
+            return Ok(());
+        };
+        let logger = tracing_subscriber::fmt().with_max_level(level);
+        match &self.log_file {
+            Some(path) => {
+                let file = File::options().create(true).append(true).open(path)?;
+                logger
+                    .with_ansi(false)
+                    .with_writer(std::sync::Mutex::new(file))
+                    .init();
+            }
+            None => logger.with_writer(std::io::stderr).init(),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+struct ReportArguments {
+    input_file: String,
+    #[command(flatten)]
+    log: LogArguments,
+    #[command(flatten)]
+    engine: EngineArguments,
+    /// Number of largest accounts to list.
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+}
+
+#[derive(Args)]
+struct StatementArguments {
+    input_file: String,
+    #[command(flatten)]
+    log: LogArguments,
+    #[command(flatten)]
+    engine: EngineArguments,
+    /// Client to print a statement for; repeat for several clients.
+    #[arg(long, required = true)]
+    client: Vec<ClientId>,
+    #[arg(long, value_enum, default_value_t = StatementFormat::Text)]
+    format: StatementFormat,
+}
+
+#[derive(Args)]
+struct ChargebackArguments {
+    input_file: String,
+    #[command(flatten)]
+    log: LogArguments,
+    #[command(flatten)]
+    engine: EngineArguments,
+    /// Ratio above which a client is flagged (0.01 is 1%).
+    #[arg(long, default_value = "0.01")]
+    threshold: Decimal,
+}
+
+#[derive(Args)]
+struct AnomalyArguments {
+    input_file: String,
+    #[command(flatten)]
+    log: LogArguments,
+    #[command(flatten)]
+    engine: EngineArguments,
+    #[command(flatten)]
+    thresholds: ThresholdArguments,
+    /// File to write the report to, instead of stdout.
+    #[arg(long, short)]
+    output: Option<String>,
+}
+
+/// See [`AnomalyThresholds`] for the defaults.
+#[derive(Args)]
+struct ThresholdArguments {
+    /// Standard deviations above the mean deposit for a deposit to be flagged.
+    #[arg(long)]
+    large_deposit_sigmas: Option<f64>,
+    /// Deposits seen before large deposits are flagged.
+    #[arg(long)]
+    large_deposit_min_samples: Option<u64>,
+    /// Change of a client's total, as a fraction of the total before, flagged as a swing.
+    #[arg(long)]
+    balance_swing_ratio: Option<Decimal>,
+    /// Disputes of one client within the window flagged as a burst.
+    #[arg(long)]
+    dispute_burst: Option<usize>,
+    /// Window of a dispute burst, in transactions.
+    #[arg(long)]
+    dispute_window: Option<u64>,
+}
+
+impl ThresholdArguments {
+    fn thresholds(&self) -> AnomalyThresholds {
+        let defaults = AnomalyThresholds::default();
+        AnomalyThresholds {
+            large_deposit_sigmas: self
+                .large_deposit_sigmas
+                .unwrap_or(defaults.large_deposit_sigmas),
+            large_deposit_min_samples: self
+                .large_deposit_min_samples
+                .unwrap_or(defaults.large_deposit_min_samples),
+            balance_swing_ratio: self
+                .balance_swing_ratio
+                .unwrap_or(defaults.balance_swing_ratio),
+            dispute_burst: self.dispute_burst.unwrap_or(defaults.dispute_burst),
+            dispute_window: self.dispute_window.unwrap_or(defaults.dispute_window),
+        }
+    }
+}
+
+#[derive(Args)]
+struct VolumeArguments {
+    input_file: String,
+    #[command(flatten)]
+    log: LogArguments,
+    #[command(flatten)]
+    engine: EngineArguments,
+    #[arg(long, value_enum, default_value_t = BucketArgument::Daily)]
+    bucket: BucketArgument,
+    /// File to write the report to, instead of stdout.
+    #[arg(long, short)]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct PartnerArguments {
+    input_file: String,
+    #[command(flatten)]
+    log: LogArguments,
+    #[command(flatten)]
+    engine: EngineArguments,
+    /// File to write the report to, instead of stdout.
+    #[arg(long, short)]
+    output: Option<String>,
+    /// File to write the liability table per partner to, instead of after the report.
+    #[arg(long)]
+    liabilities: Option<String>,
+}
+
+#[derive(Args)]
+struct DisputeArguments {
+    input_file: String,
+    #[command(flatten)]
+    log: LogArguments,
+    #[command(flatten)]
+    engine: EngineArguments,
+    /// Only the deposits of this client (default: every disputed deposit).
+    #[arg(long, conflicts_with = "tx")]
+    client: Option<ClientId>,
+    /// Only this deposit.
+    #[arg(long)]
+    tx: Option<TransactionId>,
+    #[arg(long, value_enum, default_value_t = DiagramArgument::Mermaid)]
+    format: DiagramArgument,
+}
+
+#[derive(Args)]
+struct LedgerArguments {
+    input_file: String,
+    #[command(flatten)]
+    log: LogArguments,
+    #[command(flatten)]
+    engine: EngineArguments,
+    /// File to write the journal to, instead of stdout.
+    #[arg(long, short)]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct SarArguments {
+    input_file: String,
+    #[command(flatten)]
+    log: LogArguments,
+    #[command(flatten)]
+    engine: EngineArguments,
+    /// File to write the report to, instead of stdout.
+    #[arg(long, short)]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct ReconcileArguments {
+    input_file: String,
+    #[command(flatten)]
+    log: LogArguments,
+    #[command(flatten)]
+    engine: EngineArguments,
+    /// Expected balances, in the output format (client, available, held, total, locked).
+    #[arg(long)]
+    expected: String,
+    /// File to write the discrepancies to, instead of stdout.
+    #[arg(long, short)]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct CompareArguments {
+    /// Account output of the engine (CSV).
+    accounts: String,
+    #[command(flatten)]
+    log: LogArguments,
+    /// The other system's balance export (CSV).
+    #[arg(long)]
+    against: String,
+    /// Headers of the export, as `field=header` pairs, e.g. `client=account_id,available=balance`.
+    /// Fields are client, available, held, total and locked; unlisted ones keep their name.
+    #[arg(long, default_value = "")]
+    against_columns: reconcile::ColumnMapping,
+    /// Headers of the account output, for one written with custom `--columns`.
+    #[arg(long, default_value = "")]
+    accounts_columns: reconcile::ColumnMapping,
+    /// Largest difference between two amounts that still counts as a match.
+    #[arg(long, default_value_t = Decimal::ZERO, value_parser = tolerance)]
+    tolerance: Decimal,
+    /// Write the discrepancies as a JSON array instead of CSV.
+    #[arg(long)]
+    json: bool,
+    /// File to write the discrepancies to, instead of stdout.
+    #[arg(long, short)]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct GenerateArguments {
+    /// Client IDs are drawn from 1..=N.
+    #[arg(long, default_value_t = 1000)]
+    clients: u16,
+    /// Rows to generate.
+    #[arg(long, default_value_t = 100_000)]
+    transactions: u32,
+    /// Probability of a row opening a dispute (and of one settling a dispute).
+    #[arg(long, default_value_t = 0.01, value_parser = rate)]
+    dispute_rate: f64,
+    /// Share of settled disputes that are charged back rather than resolved.
+    #[arg(long, default_value_t = 0.2, value_parser = rate)]
+    chargeback_share: f64,
+    /// Probability of a row being invalid.
+    #[arg(long, default_value_t = 0.0, value_parser = rate)]
+    invalid_rate: f64,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// File to write the workload to, instead of stdout.
+    #[arg(long, short)]
+    output: Option<String>,
+}
+
+fn rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(rate),
+        _ => Err(format!("{value} is not a probability between 0 and 1")),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum InputFormat {
+    Csv,
+    Jsonl,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DiagramArgument {
+    Mermaid,
+    Graphviz,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BucketArgument {
+    Hourly,
+    Daily,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StatementFormat {
+    Text,
+    Html,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LockedDisputesArgument {
+    Allow,
+    SettleOpen,
+    Reject,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum NegativeAvailableArgument {
+    Allow,
+    CapAtZero,
+    Reject,
+}
+
+/// Options shared by every mode, see [`EngineConfig`].
+#[derive(Args)]
+struct EngineArguments {
+    /// Treat a repeated resolve/chargeback for the same dispute as a no-op instead of an error.
+    #[arg(long)]
+    ignore_repeated_settlements: bool,
+    /// Let a chargeback without an open dispute open one and charge it back immediately.
+    #[arg(long)]
+    chargeback_opens_dispute: bool,
+    /// Which disputes, resolves and chargebacks still apply to locked accounts.
+    #[arg(long, value_enum, default_value_t = LockedDisputesArgument::Allow)]
+    locked_disputes: LockedDisputesArgument,
+    /// How disputes are handled when available funds don't cover the disputed deposit.
+    #[arg(long, value_enum, default_value_t = NegativeAvailableArgument::Allow)]
+    negative_available: NegativeAvailableArgument,
+    /// Credit deposits as pending until this many more transactions were processed.
+    #[arg(long, default_value_t = 0)]
+    settlement_delay: u64,
+    /// Share of each deposit (0 to 1) held back in the reserve.
+    #[arg(long, default_value_t = Decimal::ZERO, value_parser = reserve_rate)]
+    reserve_rate: Decimal,
+    /// Release a deposit's reserve once this many more transactions were processed.
+    #[arg(long, default_value_t = 0)]
+    reserve_delay: u64,
+    /// Reject disputes opened more than this many transactions after their deposit.
+    #[arg(long)]
+    max_dispute_age: Option<u64>,
+    /// JSON file of account tiers and the clients in them.
+    #[arg(long, value_parser = tiers_file)]
+    tiers: Option<AccountTiers>,
+    /// Unlock accounts frozen by a chargeback once this many more transactions were processed.
+    #[arg(long)]
+    unfreeze_after: Option<u64>,
+    /// Park disputes, resolves and chargebacks of a deposit not seen yet, for up to this
+    /// many transactions, and retry them once it arrives.
+    #[arg(long)]
+    reorder_window: Option<u64>,
+}
+
+fn reserve_rate(value: &str) -> Result<Decimal, String> {
+    match value.parse::<Decimal>() {
+        Ok(rate) if (Decimal::ZERO..=Decimal::ONE).contains(&rate) => Ok(rate),
+        _ => Err(format!("{value} is not a share between 0 and 1")),
+    }
+}
+
+fn tolerance(value: &str) -> Result<Decimal, String> {
+    match value.parse::<Decimal>() {
+        Ok(tolerance) if tolerance >= Decimal::ZERO => Ok(tolerance),
+        _ => Err(format!("{value} is not a non-negative amount")),
+    }
+}
+
+fn tiers_file(path: &str) -> Result<AccountTiers, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {path}: {e}"))?;
+    AccountTiers::from_reader(std::io::BufReader::new(file)).map_err(|e| e.to_string())
+}
+
+impl EngineArguments {
+    fn config(&self) -> EngineConfig {
+        EngineConfig {
+            repeated_settlement: if self.ignore_repeated_settlements {
+                RepeatedSettlement::Ignore
+            } else {
+                RepeatedSettlement::Error
+            },
+            chargeback_opens_dispute: self.chargeback_opens_dispute,
+            locked_account_disputes: match self.locked_disputes {
+                LockedDisputesArgument::Allow => LockedAccountDisputes::Allow,
+                LockedDisputesArgument::SettleOpen => LockedAccountDisputes::SettleOpen,
+                LockedDisputesArgument::Reject => LockedAccountDisputes::Reject,
+            },
+            negative_available: match self.negative_available {
+                NegativeAvailableArgument::Allow => NegativeAvailable::Allow,
+                NegativeAvailableArgument::CapAtZero => NegativeAvailable::CapAtZero,
+                NegativeAvailableArgument::Reject => NegativeAvailable::Reject,
+            },
+            settlement_delay: self.settlement_delay,
+            rolling_reserve: RollingReserve {
+                rate: self.reserve_rate,
+                delay: self.reserve_delay,
+            },
+            dispute_window: self.max_dispute_age,
+            tiers: self.tiers.clone().unwrap_or_default(),
+            chargeback_freeze: match self.unfreeze_after {
+                Some(horizon) => ChargebackFreeze::Temporary { horizon },
+                None => ChargebackFreeze::Permanent,
+            },
+            reorder_window: self.reorder_window,
+        }
+    }
 }
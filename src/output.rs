@@ -1,34 +1,523 @@
 //! Serializes final account state to CSV.
+//!
+//! The columns are fixed unless an [`OutputColumns`] selects which are written, in which
+//! order and under which headers. Long-running hosts emit snapshots through an
+//! [`AccountSink`] instead, see [`PaymentsEngine::flush_accounts`], or only the accounts
+//! that changed since the previous one through
+//! [`PaymentsEngine::flush_changed_accounts`].
+//!
+//! [`PaymentsEngine::flush_accounts`]: crate::engine::PaymentsEngine::flush_accounts
+//! [`PaymentsEngine::flush_changed_accounts`]: crate::engine::PaymentsEngine::flush_changed_accounts
+
+use std::{fmt, num::NonZeroUsize, str::FromStr, thread};
 
 use rust_decimal::Decimal;
 use serde::Serialize;
 
-use crate::{domain::ClientId, engine::ClientAccounts};
+use crate::{
+    domain::{Account, ClientId, TenantId},
+    engine::{ClientAccounts, EngineConfig, MultiTenantEngine},
+};
+
+/// Bytes buffered before the writer is handed a batch - large enough that writing millions
+/// of rows costs a few thousand `write` calls instead of one per 8 KiB default buffer.
+const OUTPUT_BUFFER_BYTES: usize = 1 << 20;
+
+fn writer_builder() -> csv::WriterBuilder {
+    let mut builder = csv::WriterBuilder::new();
+    builder.buffer_capacity(OUTPUT_BUFFER_BYTES);
+    builder
+}
 
-/// Maps directly to the required output columns: client, available, held, total, locked.
+/// Leading column of each row. Followed by [`AccountColumns`].
 #[derive(Debug, Serialize)]
-struct OutputCsv {
+struct ClientColumn {
     client: ClientId,
+}
+
+/// Maps directly to the required output columns: available, held, total, locked.
+/// `pending` (deposits not settled yet), if the run has a settlement delay, and `reserve`
+/// (deposits held back by the rolling reserve), if it has a reserve rate, are appended last
+/// so positional consumers of the required columns aren't affected. `authorized` is only
+/// written when selected through [`OutputColumns`].
+#[derive(Debug, Serialize)]
+struct AccountColumns {
     available: Decimal,
     held: Decimal,
     total: Decimal,
     locked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pending: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reserve: Option<Decimal>,
+}
+
+/// The `total` column: available + held, or the gross total if opted in with
+/// [`OutputColumns::with_gross_total`].
+fn total(account: &Account, gross_total: bool) -> Decimal {
+    if gross_total {
+        account.balance().gross_total()
+    } else {
+        account.balance().total()
+    }
+}
+
+/// Same as [`ClientColumn`], prefixed with the checkpoint a [`SequencedCsv`] row belongs to.
+#[derive(Debug, Serialize)]
+struct SequencedColumns {
+    sequence: u64,
+    client: ClientId,
+}
+
+/// Same as [`ClientColumn`], prefixed with the tenant namespace (empty for the default one).
+#[derive(Debug, Serialize)]
+struct TenantColumns {
+    tenant: Option<TenantId>,
+    client: ClientId,
+}
+
+/// A column of the account output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Column {
+    /// The tenant namespace, empty for the default one and in single-tenant runs.
+    Tenant,
+    Client,
+    Available,
+    Held,
+    Total,
+    Locked,
+    Authorized,
+    Pending,
+    Reserve,
+}
+
+impl Column {
+    pub const ALL: [Column; 9] = [
+        Column::Tenant,
+        Column::Client,
+        Column::Available,
+        Column::Held,
+        Column::Total,
+        Column::Locked,
+        Column::Authorized,
+        Column::Pending,
+        Column::Reserve,
+    ];
+    /// Name of the column, which is also its default header.
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Tenant => "tenant",
+            Column::Client => "client",
+            Column::Available => "available",
+            Column::Held => "held",
+            Column::Total => "total",
+            Column::Locked => "locked",
+            Column::Authorized => "authorized",
+            Column::Pending => "pending",
+            Column::Reserve => "reserve",
+        }
+    }
+    fn value(
+        self,
+        tenant: Option<TenantId>,
+        client: ClientId,
+        account: &Account,
+        gross_total: bool,
+    ) -> String {
+        let balance = account.balance();
+        match self {
+            Column::Tenant => tenant.map(|tenant| tenant.to_string()).unwrap_or_default(),
+            Column::Client => client.to_string(),
+            Column::Available => balance.available().to_string(),
+            Column::Held => balance.held().to_string(),
+            Column::Total => total(account, gross_total).to_string(),
+            Column::Locked => account.is_locked().to_string(),
+            Column::Authorized => balance.authorized().to_string(),
+            Column::Pending => balance.pending().to_string(),
+            Column::Reserve => balance.reserve().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ColumnsError {
+    #[error("Unknown output column {0}")]
+    UnknownColumn(String),
+    #[error("Output column {0} is selected twice")]
+    Duplicate(Column),
+    #[error("Empty header for output column {0}")]
+    EmptyHeader(Column),
+    #[error("No output columns selected")]
+    Empty,
+}
+
+/// Which columns the account output has, in order, and their headers.
+///
+/// The default is the fixed layout described on [`AccountColumns`], after `client`. A
+/// custom selection is parsed from a comma-separated list of column names, each optionally
+/// renamed with `=header`, e.g. `client=client_id,available,total`.
+///
+/// `total` is available + held in either, unless [`OutputColumns::with_gross_total`] opts
+/// in to the gross total.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OutputColumns {
+    /// `None` for the default layout.
+    columns: Option<Vec<(Column, String)>>,
+    /// Whether the default layout has the `pending` and `reserve` columns.
+    pending: bool,
+    reserve: bool,
+    gross_total: bool,
+}
+
+impl OutputColumns {
+    /// The default layout for runs with `config`: `pending` is only written with a
+    /// settlement delay, `reserve` with a reserve rate (global or of a tier).
+    pub fn for_config(config: &EngineConfig) -> Self {
+        Self {
+            pending: config.settlement_delay > 0,
+            reserve: !config.rolling_reserve.rate.is_zero() || config.tiers.has_reserve_rates(),
+            ..Self::default()
+        }
+    }
+    /// Selects `columns`, in order, under their default headers.
+    pub fn new(columns: impl IntoIterator<Item = Column>) -> Result<Self, ColumnsError> {
+        Self::with_headers(
+            columns
+                .into_iter()
+                .map(|column| (column, column.name().to_owned())),
+        )
+    }
+    /// Selects the columns in order, each under the header paired with it.
+    pub fn with_headers(
+        columns: impl IntoIterator<Item = (Column, String)>,
+    ) -> Result<Self, ColumnsError> {
+        let mut selected: Vec<(Column, String)> = Vec::new();
+        for (column, header) in columns {
+            if selected.iter().any(|(other, _)| *other == column) {
+                return Err(ColumnsError::Duplicate(column));
+            }
+            if header.is_empty() {
+                return Err(ColumnsError::EmptyHeader(column));
+            }
+            selected.push((column, header));
+        }
+        if selected.is_empty() {
+            return Err(ColumnsError::Empty);
+        }
+        Ok(Self {
+            columns: Some(selected),
+            ..Self::default()
+        })
+    }
+    /// Writes [`Balance::gross_total`](crate::domain::Balance::gross_total) as `total`,
+    /// instead of available + held.
+    pub fn with_gross_total(mut self, gross_total: bool) -> Self {
+        self.gross_total = gross_total;
+        self
+    }
+    /// Whether this is the default layout.
+    pub fn is_default(&self) -> bool {
+        self.columns.is_none()
+    }
+    fn account_columns(&self, account: &Account) -> AccountColumns {
+        let balance = account.balance();
+        AccountColumns {
+            available: balance.available(),
+            held: balance.held(),
+            total: total(account, self.gross_total),
+            locked: account.is_locked(),
+            pending: self.pending.then(|| balance.pending()),
+            reserve: self.reserve.then(|| balance.reserve()),
+        }
+    }
+    fn header(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .flatten()
+            .map(|(_, header)| header.as_str())
+            .collect()
+    }
+    fn record(&self, tenant: Option<TenantId>, client: ClientId, account: &Account) -> Vec<String> {
+        self.columns
+            .iter()
+            .flatten()
+            .map(|(column, _)| column.value(tenant, client, account, self.gross_total))
+            .collect()
+    }
+}
+
+impl FromStr for OutputColumns {
+    type Err = ColumnsError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let columns = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (name, header) = entry.split_once('=').unwrap_or((entry, entry));
+                let name = name.trim();
+                let column = Column::ALL
+                    .into_iter()
+                    .find(|column| column.name() == name)
+                    .ok_or_else(|| ColumnsError::UnknownColumn(name.to_owned()))?;
+                Ok((column, header.trim().to_owned()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::with_headers(columns)
+    }
+}
+
+/// Receives snapshots of the accounts from [`PaymentsEngine::flush_accounts`], one account
+/// at a time in client ID order.
+///
+/// A `csv::Writer` is a sink writing the default output rows (its header only once, before
+/// the first snapshot). A [`SequencedCsv`] prefixes each row with its snapshot's sequence
+/// number. A `Vec` collects `(client, account)` pairs.
+///
+/// [`PaymentsEngine::flush_accounts`]: crate::engine::PaymentsEngine::flush_accounts
+pub trait AccountSink {
+    type Error;
+
+    /// Called before the first account of each snapshot with its checkpoint sequence
+    /// number, counting from 1.
+    fn begin_snapshot(&mut self, sequence: u64) -> Result<(), Self::Error> {
+        let _ = sequence;
+        Ok(())
+    }
+    fn account(&mut self, client: ClientId, account: &Account) -> Result<(), Self::Error>;
+    /// Called after the last account of each snapshot.
+    fn end_snapshot(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write> AccountSink for csv::Writer<W> {
+    type Error = csv::Error;
+
+    fn account(&mut self, client: ClientId, account: &Account) -> Result<(), Self::Error> {
+        let columns = OutputColumns::default().account_columns(account);
+        self.serialize((ClientColumn { client }, columns))
+    }
+    fn end_snapshot(&mut self) -> Result<(), Self::Error> {
+        Ok(self.flush()?)
+    }
+}
+
+impl AccountSink for Vec<(ClientId, Account)> {
+    type Error = std::convert::Infallible;
+
+    fn account(&mut self, client: ClientId, account: &Account) -> Result<(), Self::Error> {
+        self.push((client, account.clone()));
+        Ok(())
+    }
+}
+
+/// Sink writing the output rows with a leading `sequence` column, the checkpoint each row
+/// belongs to, so consumers of [`PaymentsEngine::flush_changed_accounts`] deltas can tell
+/// them apart. A checkpoint without changes writes no rows.
+///
+/// [`PaymentsEngine::flush_changed_accounts`]: crate::engine::PaymentsEngine::flush_changed_accounts
+#[derive(Debug)]
+pub struct SequencedCsv<W: std::io::Write> {
+    writer: csv::Writer<W>,
+    columns: OutputColumns,
+    sequence: u64,
+    /// Whether the header of custom columns was written.
+    header: bool,
+}
+
+impl<W: std::io::Write> SequencedCsv<W> {
+    /// Writes the default layout.
+    pub fn from_writer(writer: W) -> Self {
+        Self::with_columns(writer, OutputColumns::default())
+    }
+    /// Writes the selected `columns` after `sequence`.
+    pub fn with_columns(writer: W, columns: OutputColumns) -> Self {
+        Self {
+            writer: writer_builder().from_writer(writer),
+            columns,
+            sequence: 0,
+            header: false,
+        }
+    }
+    pub fn into_inner(self) -> csv::Result<W> {
+        self.writer.into_inner().map_err(|e| e.into_error().into())
+    }
+}
+
+impl<W: std::io::Write> AccountSink for SequencedCsv<W> {
+    type Error = csv::Error;
+
+    fn begin_snapshot(&mut self, sequence: u64) -> Result<(), Self::Error> {
+        self.sequence = sequence;
+        Ok(())
+    }
+    fn account(&mut self, client: ClientId, account: &Account) -> Result<(), Self::Error> {
+        if self.columns.is_default() {
+            let sequenced = SequencedColumns {
+                sequence: self.sequence,
+                client,
+            };
+            return self
+                .writer
+                .serialize((sequenced, self.columns.account_columns(account)));
+        }
+        if !std::mem::replace(&mut self.header, true) {
+            let header = self.columns.header();
+            self.writer
+                .write_record(std::iter::once("sequence").chain(header))?;
+        }
+        let sequence = self.sequence.to_string();
+        let record = self.columns.record(None, client, account);
+        self.writer.write_record(
+            std::iter::once(sequence.as_str()).chain(record.iter().map(String::as_str)),
+        )
+    }
+    fn end_snapshot(&mut self) -> Result<(), Self::Error> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Writes one row per account, ordered by client ID.
 pub fn print_accounts(
     client_accounts: &ClientAccounts,
     writer: impl std::io::Write,
 ) -> anyhow::Result<()> {
-    let mut wtr = csv::Writer::from_writer(writer);
-    for (client_id, account) in client_accounts.as_map() {
-        let output_csv = OutputCsv {
-            client: *client_id,
-            available: account.balance.available(),
-            held: account.balance.held(),
-            total: account.balance.total(),
-            locked: account.locked,
-        };
-        wtr.serialize(output_csv)?;
+    print_accounts_with_columns(client_accounts, &OutputColumns::default(), writer)
+}
+
+/// Same as [`print_accounts`], with the selected `columns`. No header is written without
+/// rows, as with the default layout.
+pub fn print_accounts_with_columns(
+    client_accounts: &ClientAccounts,
+    columns: &OutputColumns,
+    writer: impl std::io::Write,
+) -> anyhow::Result<()> {
+    let mut wtr = writer_builder().from_writer(writer);
+    write_rows(&mut wtr, columns, true, client_accounts.iter_sorted())?;
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Writes the rows of single-tenant accounts. The header of custom `columns` precedes them
+/// if `header` is set and there are rows; the default layout's is up to the writer.
+fn write_rows<'a, W: std::io::Write>(
+    wtr: &mut csv::Writer<W>,
+    columns: &OutputColumns,
+    header: bool,
+    rows: impl IntoIterator<Item = (ClientId, &'a Account)>,
+) -> csv::Result<()> {
+    if columns.is_default() {
+        for (client_id, account) in rows {
+            wtr.serialize((
+                ClientColumn { client: client_id },
+                columns.account_columns(account),
+            ))?;
+        }
+        return Ok(());
+    }
+    let mut rows = rows.into_iter().peekable();
+    if header && rows.peek().is_some() {
+        wtr.write_record(columns.header())?;
+    }
+    for (client_id, account) in rows {
+        wtr.write_record(columns.record(None, client_id, account))?;
+    }
+    Ok(())
+}
+
+/// Same output as [`print_accounts`], but the sorted rows are split into `shards` ranges
+/// that are serialized on separate threads into in-memory buffers, then written in order.
+/// Worth it for very large account sets; costs one extra copy of the output in memory.
+pub fn print_accounts_sharded(
+    client_accounts: &ClientAccounts,
+    writer: impl std::io::Write,
+    shards: NonZeroUsize,
+) -> anyhow::Result<()> {
+    print_accounts_sharded_with_columns(client_accounts, &OutputColumns::default(), writer, shards)
+}
+
+/// Same as [`print_accounts_sharded`], with the selected `columns`.
+pub fn print_accounts_sharded_with_columns(
+    client_accounts: &ClientAccounts,
+    columns: &OutputColumns,
+    mut writer: impl std::io::Write,
+    shards: NonZeroUsize,
+) -> anyhow::Result<()> {
+    let rows: Vec<_> = client_accounts.iter_sorted().collect();
+    let shard_len = rows.len().div_ceil(shards.get()).max(1);
+
+    let buffers = thread::scope(|scope| {
+        let handles: Vec<_> = rows
+            .chunks(shard_len)
+            .enumerate()
+            .map(|(index, shard)| {
+                scope.spawn(move || -> anyhow::Result<Vec<u8>> {
+                    // Only the first shard carries the header.
+                    let mut wtr = writer_builder()
+                        .has_headers(index == 0)
+                        .from_writer(Vec::new());
+                    write_rows(&mut wtr, columns, index == 0, shard.iter().copied())?;
+                    Ok(wtr.into_inner().map_err(|e| e.into_error())?)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("output shard panicked"))
+            .collect::<anyhow::Result<Vec<_>>>()
+    })?;
+
+    for buffer in buffers {
+        writer.write_all(&buffer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes one row per account, ordered by tenant (default namespace first) then client ID.
+pub fn print_tenant_accounts(
+    engine: &MultiTenantEngine,
+    writer: impl std::io::Write,
+) -> anyhow::Result<()> {
+    print_tenant_accounts_with_columns(engine, &OutputColumns::default(), writer)
+}
+
+/// Same as [`print_tenant_accounts`], with the selected `columns`.
+pub fn print_tenant_accounts_with_columns(
+    engine: &MultiTenantEngine,
+    columns: &OutputColumns,
+    writer: impl std::io::Write,
+) -> anyhow::Result<()> {
+    let mut wtr = writer_builder().from_writer(writer);
+    if !columns.is_default() {
+        let mut header = true;
+        for (tenant, tenant_engine) in engine.engines() {
+            for (client_id, account) in tenant_engine.client_accounts().iter_sorted() {
+                if std::mem::take(&mut header) {
+                    wtr.write_record(columns.header())?;
+                }
+                wtr.write_record(columns.record(tenant, client_id, account))?;
+            }
+        }
+        wtr.flush()?;
+        return Ok(());
+    }
+    for (tenant, tenant_engine) in engine.engines() {
+        for (client_id, account) in tenant_engine.client_accounts().iter_sorted() {
+            let tenant_columns = TenantColumns {
+                tenant,
+                client: client_id,
+            };
+            wtr.serialize((tenant_columns, columns.account_columns(account)))?;
+        }
     }
     wtr.flush()?;
     Ok(())
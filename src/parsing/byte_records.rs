@@ -0,0 +1,226 @@
+//! Serde-free parsing over a single reused [`ByteRecord`].
+//!
+//! Rows are read into the same buffer and fields are parsed by hand, so no allocation
+//! happens per row. The result is the same as the serde pipeline: rows go through the
+//! same `CsvTransaction` conversion, and amounts are normalized like the serde path
+//! does (`1.50` is read as `1.5`).
+
+use std::{io::Read, str::FromStr};
+
+use csv::ByteRecord;
+use rust_decimal::Decimal;
+
+use crate::{
+    domain::{ClientId, SignedAmount, TenantId, TransactionId},
+    parsing::{
+        CsvTransaction, IntoTransactionError, ParseError, TenantTransaction, TransactionType,
+        skip_invalid,
+    },
+};
+
+/// Same as [`deserialize_tenant_csv`](super::deserialize_tenant_csv), parsed with a
+/// [`ByteRecordParser`].
+pub fn deserialize_tenant_byte_records<D: Read>(
+    reader: &mut csv::Reader<D>,
+) -> impl Iterator<Item = TenantTransaction> {
+    ByteRecordParser::new(reader).filter_map(skip_invalid)
+}
+
+/// Iterator over the rows of a CSV reader, yielding a transaction or the reason the
+/// row was skipped. Columns are located by header name, so their order doesn't matter.
+pub struct ByteRecordParser<'r, D> {
+    reader: &'r mut csv::Reader<D>,
+    record: ByteRecord,
+    /// Resolved from the header when the first row is read.
+    columns: Option<Columns>,
+    /// 1-based index of the last row read.
+    row: usize,
+    /// Set when the header lacks a required column.
+    done: bool,
+}
+
+/// Positions of the known columns in each record.
+struct Columns {
+    r#type: usize,
+    client: usize,
+    tx: usize,
+    amount: Option<usize>,
+    reason: Option<usize>,
+    parent_tx: Option<usize>,
+    tenant: Option<usize>,
+}
+
+impl Columns {
+    fn from_headers(headers: &ByteRecord) -> Result<Self, ParseError> {
+        let position = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header.trim_ascii() == name.as_bytes())
+        };
+        let required = |name: &'static str| position(name).ok_or(ParseError::MissingColumn(name));
+        Ok(Self {
+            r#type: required("type")?,
+            client: required("client")?,
+            tx: required("tx")?,
+            amount: position("amount"),
+            reason: position("reason"),
+            parent_tx: position("parent_tx"),
+            tenant: position("tenant"),
+        })
+    }
+}
+
+impl<'r, D: Read> ByteRecordParser<'r, D> {
+    pub fn new(reader: &'r mut csv::Reader<D>) -> Self {
+        Self {
+            reader,
+            record: ByteRecord::new(),
+            columns: None,
+            row: 0,
+            done: false,
+        }
+    }
+}
+
+impl<D: Read> Iterator for ByteRecordParser<'_, D> {
+    type Item = Result<TenantTransaction, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.reader.read_byte_record(&mut self.record) {
+            Ok(true) => self.row += 1,
+            Ok(false) => return None,
+            Err(e) => {
+                self.row += 1;
+                return Some(Err(e.into()));
+            }
+        }
+
+        let columns = match &self.columns {
+            Some(columns) => columns,
+            None => match self
+                .reader
+                .byte_headers()
+                .map_err(ParseError::from)
+                .and_then(Columns::from_headers)
+            {
+                Ok(columns) => self.columns.insert(columns),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            },
+        };
+        Some(parse_record(&self.record, columns, self.row))
+    }
+}
+
+fn parse_record(
+    record: &ByteRecord,
+    columns: &Columns,
+    row: usize,
+) -> Result<TenantTransaction, ParseError> {
+    let field = |index: usize| record.get(index).unwrap_or_default().trim_ascii();
+    let invalid = |column: &'static str| ParseError::InvalidField { row, column };
+
+    let csv_transaction = CsvTransaction {
+        r#type: parse_type(field(columns.r#type)).ok_or_else(|| invalid("type"))?,
+        client: parse_number::<u16>(field(columns.client))
+            .map(ClientId::from)
+            .ok_or_else(|| invalid("client"))?,
+        tx: parse_number::<u32>(field(columns.tx))
+            .map(TransactionId::from)
+            .ok_or_else(|| invalid("tx"))?,
+        amount: match columns
+            .amount
+            .map(field)
+            .filter(|amount| !amount.is_empty())
+        {
+            Some(amount) => Some(parse_amount(amount, row)?),
+            None => None,
+        },
+        reason: match columns
+            .reason
+            .map(field)
+            .filter(|reason| !reason.is_empty())
+        {
+            Some(reason) => Some(parse_number::<u16>(reason).ok_or_else(|| invalid("reason"))?),
+            None => None,
+        },
+        parent_tx: match columns
+            .parent_tx
+            .map(field)
+            .filter(|parent_tx| !parent_tx.is_empty())
+        {
+            Some(parent_tx) => Some(
+                parse_number::<u32>(parent_tx)
+                    .map(TransactionId::from)
+                    .ok_or_else(|| invalid("parent_tx"))?,
+            ),
+            None => None,
+        },
+        tenant: match columns
+            .tenant
+            .map(field)
+            .filter(|tenant| !tenant.is_empty())
+        {
+            Some(tenant) => Some(
+                parse_number::<u16>(tenant)
+                    .map(TenantId::from)
+                    .ok_or_else(|| invalid("tenant"))?,
+            ),
+            None => None,
+        },
+        // Only the serde pipeline reads timestamps and partners, see
+        // `deserialize_timestamped_csv` and `deserialize_partner_csv`.
+        timestamp: None,
+        partner: None,
+    };
+
+    let tenant = csv_transaction.tenant;
+    let transaction = csv_transaction
+        .try_into()
+        .map_err(|source| ParseError::Invalid { row, source })?;
+    Ok(TenantTransaction {
+        tenant,
+        transaction,
+    })
+}
+
+fn parse_amount(field: &[u8], row: usize) -> Result<SignedAmount, ParseError> {
+    let decimal = std::str::from_utf8(field)
+        .ok()
+        .and_then(|amount| Decimal::from_str(amount).ok())
+        .ok_or(ParseError::InvalidField {
+            row,
+            column: "amount",
+        })?;
+    SignedAmount::try_from(decimal.normalize()).map_err(|e| ParseError::Invalid {
+        row,
+        source: IntoTransactionError::InvalidAmount(e),
+    })
+}
+
+fn parse_type(field: &[u8]) -> Option<TransactionType> {
+    Some(match field {
+        b"deposit" => TransactionType::Deposit,
+        b"withdrawal" => TransactionType::Withdrawal,
+        b"dispute" => TransactionType::Dispute,
+        b"resolve" => TransactionType::Resolve,
+        b"chargeback" => TransactionType::Chargeback,
+        b"authorization" => TransactionType::Authorization,
+        b"capture" => TransactionType::Capture,
+        b"void" => TransactionType::Void,
+        b"refund" => TransactionType::Refund,
+        b"adjustment" => TransactionType::Adjustment,
+        b"evidence" => TransactionType::Evidence,
+        b"pre_arbitration" => TransactionType::PreArbitration,
+        _ => return None,
+    })
+}
+
+fn parse_number<T: FromStr>(field: &[u8]) -> Option<T> {
+    std::str::from_utf8(field).ok()?.parse().ok()
+}
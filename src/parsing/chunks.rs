@@ -0,0 +1,68 @@
+//! Splitting an in-memory CSV document into row-aligned chunks that can be scanned
+//! independently (e.g. in parallel) and then processed in order.
+//!
+//! Rows are split at newlines, so quoted fields must not contain line breaks - which
+//! never happens in transaction files.
+
+use std::io::{Chain, Read};
+
+/// The header row and the data rows of a document, split into consecutive chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowChunks<'a> {
+    header: &'a [u8],
+    body: &'a [u8],
+    chunk_len: usize,
+}
+
+/// Splits `bytes` after its header row into at most `max_chunks` chunks of roughly equal
+/// size, each ending on a row boundary. Concatenating the chunks gives back the body.
+pub fn split_rows(bytes: &[u8], max_chunks: usize) -> RowChunks<'_> {
+    let header_len = bytes
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(bytes.len(), |newline| newline + 1);
+    let (header, body) = bytes.split_at(header_len);
+    RowChunks {
+        header,
+        body,
+        chunk_len: body.len().div_ceil(max_chunks.max(1)).max(1),
+    }
+}
+
+impl<'a> RowChunks<'a> {
+    pub fn header(&self) -> &'a [u8] {
+        self.header
+    }
+
+    /// The chunks in input order. None of them is empty.
+    pub fn chunks(&self) -> impl Iterator<Item = &'a [u8]> {
+        let (body, chunk_len) = (self.body, self.chunk_len);
+        let mut start = 0;
+        std::iter::from_fn(move || {
+            if start >= body.len() {
+                return None;
+            }
+            let end = (start + chunk_len).min(body.len());
+            // extend to the end of the row the cut falls in
+            let end = body[end - 1..]
+                .iter()
+                .position(|byte| *byte == b'\n')
+                .map_or(body.len(), |newline| end + newline);
+            let chunk = &body[start..end];
+            start = end;
+            Some(chunk)
+        })
+    }
+
+    /// One CSV reader per chunk, each seeing the header first, trimmed like the CLI input.
+    /// Feed them to [`deserialize_tenant_csv`](super::deserialize_tenant_csv) or a
+    /// [`ByteRecordParser`](super::ByteRecordParser).
+    pub fn readers(&self) -> impl Iterator<Item = csv::Reader<Chain<&'a [u8], &'a [u8]>>> {
+        let header = self.header;
+        self.chunks().map(move |chunk| {
+            csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(header.chain(chunk))
+        })
+    }
+}
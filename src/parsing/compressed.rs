@@ -0,0 +1,43 @@
+//! Transparent decompression of gzip and zstd input, behind the `compression` feature.
+//!
+//! Daily dumps are too large to decompress to disk first. [`decompress`] recognizes a
+//! compressed input by its magic bytes, whatever the file is called, and decompresses it
+//! as it's read, so the parser sees the plain CSV (or JSON Lines, or CBOR).
+
+use std::io::{self, BufRead, Read};
+
+use flate2::bufread::MultiGzDecoder;
+
+/// Compression formats [`decompress`] recognizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// The format whose magic bytes `header` starts with, `None` for anything else.
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+/// Wraps `reader` in a decoder if its first bytes are those of a gzip or zstd stream, and
+/// returns it as is otherwise. Streams of several concatenated members (gzip) or frames
+/// (zstd) are read to the end. Only the bytes `reader` has buffered are looked at, which
+/// are enough for any [`io::BufReader`] over a file.
+pub fn decompress<'a, R: BufRead + Send + 'a>(
+    mut reader: R,
+) -> io::Result<Box<dyn Read + Send + 'a>> {
+    Ok(match Compression::detect(reader.fill_buf()?) {
+        Some(Compression::Gzip) => Box::new(MultiGzDecoder::new(reader)),
+        Some(Compression::Zstd) => Box::new(zstd::Decoder::with_buffer(reader)?),
+        None => Box::new(reader),
+    })
+}
@@ -0,0 +1,171 @@
+//! Breakdown of parse failures into categories that say what to fix upstream.
+//!
+//! A [`RejectionReason`](crate::rejection::RejectionReason) groups every unparsable row
+//! under `malformed_row`. [`ParseFailure`] tells an unknown transaction type from a
+//! number that doesn't parse or a row with the wrong number of columns, and
+//! [`ParseFailureCounts`] counts them over a run.
+
+use std::collections::BTreeMap;
+
+use derive_more::Display;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::{
+    domain::DomainError,
+    parsing::{IntoTransactionError, ParseError},
+};
+
+/// Category of a [`ParseError`]. Serializes and displays as its snake_case name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseFailure {
+    /// The `type` column isn't a known transaction type.
+    #[display("unknown_type")]
+    UnknownType,
+    /// A client, tx, amount or reason that isn't a number.
+    #[display("malformed_number")]
+    MalformedNumber,
+    #[display("missing_amount")]
+    MissingAmount,
+    /// An amount that parses but isn't allowed: not positive, or more than 4 decimal places.
+    #[display("invalid_amount")]
+    InvalidAmount,
+    #[display("missing_reason")]
+    MissingReason,
+    /// A row with more or fewer fields than the header.
+    #[display("wrong_column_count")]
+    WrongColumnCount,
+    /// The header lacks a required column.
+    #[display("missing_column")]
+    MissingColumn,
+    /// Anything else, e.g. invalid UTF-8 or a read error.
+    #[display("other")]
+    Other,
+}
+
+impl ParseFailure {
+    /// Every category, in report order.
+    pub const ALL: [ParseFailure; 8] = [
+        ParseFailure::UnknownType,
+        ParseFailure::MalformedNumber,
+        ParseFailure::MissingAmount,
+        ParseFailure::InvalidAmount,
+        ParseFailure::MissingReason,
+        ParseFailure::WrongColumnCount,
+        ParseFailure::MissingColumn,
+        ParseFailure::Other,
+    ];
+}
+
+impl ParseError {
+    pub fn failure(&self) -> ParseFailure {
+        match self {
+            ParseError::Malformed(e) => csv_failure(e),
+            ParseError::Invalid { source, .. } => match source {
+                IntoTransactionError::MissingAmountForDeposit
+                | IntoTransactionError::MissingAmountForWithdrawal
+                | IntoTransactionError::MissingAmountForAuthorization
+                | IntoTransactionError::MissingAmountForRefund
+                | IntoTransactionError::MissingAmountForAdjustment => ParseFailure::MissingAmount,
+                IntoTransactionError::MissingReasonForAdjustment => ParseFailure::MissingReason,
+                IntoTransactionError::InvalidAmount(_) => ParseFailure::InvalidAmount,
+            },
+            ParseError::InvalidField { column: "type", .. } => ParseFailure::UnknownType,
+            ParseError::InvalidField { .. } => ParseFailure::MalformedNumber,
+            ParseError::MissingColumn(_) => ParseFailure::MissingColumn,
+            ParseError::Json { source, .. } => json_failure(source),
+        }
+    }
+}
+
+/// Serde reports failures of the row's fields as messages without the column, so they're
+/// told apart by what they say: serde's own wording for unknown enum variants and invalid
+/// values, and the amount validation's [`DomainError`]s.
+fn csv_failure(error: &csv::Error) -> ParseFailure {
+    use csv::DeserializeErrorKind as Kind;
+
+    let csv::ErrorKind::Deserialize { err, .. } = error.kind() else {
+        return match error.kind() {
+            csv::ErrorKind::UnequalLengths { .. } => ParseFailure::WrongColumnCount,
+            _ => ParseFailure::Other,
+        };
+    };
+    match err.kind() {
+        Kind::UnexpectedEndOfRow => ParseFailure::WrongColumnCount,
+        Kind::ParseInt(_) | Kind::ParseFloat(_) | Kind::ParseBool(_) => {
+            ParseFailure::MalformedNumber
+        }
+        Kind::Message(message) => message_failure(message),
+        _ => ParseFailure::Other,
+    }
+}
+
+/// serde_json appends the position to the message (`... at line 1 column 12`), so
+/// messages are matched by their start.
+fn json_failure(error: &serde_json::Error) -> ParseFailure {
+    match error.classify() {
+        serde_json::error::Category::Data => message_failure(&error.to_string()),
+        _ => ParseFailure::Other,
+    }
+}
+
+fn message_failure(message: &str) -> ParseFailure {
+    if message.starts_with("unknown variant") {
+        ParseFailure::UnknownType
+    } else if is_amount_validation(message) {
+        ParseFailure::InvalidAmount
+    } else if message.starts_with("invalid value") || message.starts_with("invalid type") {
+        ParseFailure::MalformedNumber
+    } else if message.starts_with("missing field") {
+        ParseFailure::MissingColumn
+    } else {
+        ParseFailure::Other
+    }
+}
+
+fn is_amount_validation(message: &str) -> bool {
+    [
+        DomainError::NonPositiveAmount,
+        DomainError::NegativeAmount,
+        DomainError::ExcessivePrecision,
+        DomainError::ZeroAdjustment,
+        DomainError::AmountTooLarge,
+    ]
+    .iter()
+    .any(|error| message.starts_with(&error.to_string()))
+}
+
+/// Number of skipped rows per [`ParseFailure`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseFailureCounts(BTreeMap<ParseFailure, usize>);
+
+impl ParseFailureCounts {
+    pub fn record(&mut self, error: &ParseError) {
+        *self.0.entry(error.failure()).or_default() += 1;
+    }
+    /// Passes a parsed row through, or counts and logs a failed one and skips it like
+    /// [`deserialize_csv`](super::deserialize_csv) does. Use it to filter
+    /// [`try_deserialize_csv`](super::try_deserialize_csv).
+    pub fn count<T>(&mut self, row: Result<T, ParseError>) -> Option<T> {
+        match row {
+            Ok(transaction) => Some(transaction),
+            Err(e) => {
+                warn!(reason = %e.reason(), failure = %e.failure(), "{e}");
+                self.record(&e);
+                None
+            }
+        }
+    }
+    pub fn get(&self, failure: ParseFailure) -> usize {
+        self.0.get(&failure).copied().unwrap_or_default()
+    }
+    /// Rows skipped over all categories.
+    pub fn total(&self) -> usize {
+        self.0.values().sum()
+    }
+    /// Categories with at least one failure, in [`ParseFailure::ALL`] order.
+    pub fn iter(&self) -> impl Iterator<Item = (ParseFailure, usize)> + '_ {
+        self.0.iter().map(|(failure, count)| (*failure, *count))
+    }
+}
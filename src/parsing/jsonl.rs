@@ -0,0 +1,101 @@
+//! JSON Lines (newline-delimited JSON) input.
+//!
+//! Every non-blank line is one object with the columns of a CSV row, e.g.
+//! `{"type": "deposit", "client": 1, "tx": 1, "amount": "1.5"}`. Optional columns may be
+//! left out, and amounts may be strings or numbers. Rows go through the same conversion as
+//! CSV rows, so they're validated and rejected the same way.
+
+use std::io::BufRead;
+
+use tracing::error;
+
+use crate::{
+    domain::Transaction,
+    parsing::{CsvTransaction, ParseError, Row, TenantTransaction, skip_invalid},
+};
+
+/// Returns an iterator that lazily deserializes JSON Lines into domain transactions,
+/// skipping and logging the lines that fail to parse or convert, like
+/// [`deserialize_csv`](super::deserialize_csv).
+pub fn deserialize_jsonl<R: BufRead>(reader: R) -> impl Iterator<Item = Transaction> {
+    try_deserialize_jsonl(reader).filter_map(skip_invalid)
+}
+
+/// Same as [`deserialize_jsonl`], but yields an error for every line that fails to parse or
+/// convert instead of skipping it. Errors carry the 1-based line number.
+pub fn try_deserialize_jsonl<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<Transaction, ParseError>> {
+    parse_lines(reader).map(|row| row.map(|row| row.transaction))
+}
+
+/// Same as [`deserialize_jsonl`], but keeps the optional `tenant` field of each line.
+pub fn deserialize_tenant_jsonl<R: BufRead>(reader: R) -> impl Iterator<Item = TenantTransaction> {
+    parse_lines(reader)
+        .filter_map(skip_invalid)
+        .map(|row| TenantTransaction {
+            tenant: row.tenant,
+            transaction: row.transaction,
+        })
+}
+
+fn parse_lines<R: BufRead>(reader: R) -> impl Iterator<Item = Result<Row, ParseError>> {
+    Lines {
+        reader,
+        line: 0,
+        done: false,
+    }
+    .filter_map(|(line, bytes)| {
+        if bytes.trim_ascii().is_empty() {
+            return None;
+        }
+        Some(parse_line(line, &bytes))
+    })
+}
+
+fn parse_line(line: usize, bytes: &[u8]) -> Result<Row, ParseError> {
+    let row = serde_json::from_slice::<CsvTransaction>(bytes)
+        .map_err(|source| ParseError::Json { line, source })?;
+    let tenant = row.tenant;
+    let (timestamp, partner) = (row.timestamp, row.partner);
+    let transaction =
+        Transaction::try_from(row).map_err(|source| ParseError::Invalid { row: line, source })?;
+    Ok(Row {
+        tenant,
+        timestamp,
+        partner,
+        transaction,
+    })
+}
+
+/// The raw lines of the input with their 1-based numbers. Reading bytes rather than
+/// strings leaves invalid UTF-8 to the JSON parser, so it only fails its own line. A read
+/// error ends the input.
+struct Lines<R> {
+    reader: R,
+    line: usize,
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = (usize, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut bytes = Vec::new();
+        match self.reader.read_until(b'\n', &mut bytes) {
+            Ok(0) => None,
+            Ok(_) => {
+                self.line += 1;
+                Some((self.line, bytes))
+            }
+            Err(e) => {
+                error!("Failed to read JSON Lines input, ignoring the rest: {e}");
+                self.done = true;
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Memory-mapped input, behind the `mmap` feature.
+//!
+//! Parsing a mapped file reads straight from the page cache, without a `read` syscall
+//! per buffer refill. Together with [`split_rows`](super::split_rows) it also lets the
+//! whole file be scanned in chunks.
+
+use std::{fs::File, io};
+
+use memmap2::Mmap;
+
+use crate::parsing::{RowChunks, split_rows};
+
+/// A read-only mapping of a whole input file.
+#[derive(Debug)]
+pub struct MappedInput(Mmap);
+
+impl MappedInput {
+    /// Maps `file` into memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the mapping is alive - other
+    /// processes included. Changes would show up in (or invalidate) the mapped bytes.
+    pub unsafe fn map(file: &File) -> io::Result<Self> {
+        // SAFETY: upheld by the caller, see above.
+        unsafe { Mmap::map(file) }.map(Self)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// See [`split_rows`].
+    pub fn split_rows(&self, max_chunks: usize) -> RowChunks<'_> {
+        split_rows(&self.0, max_chunks)
+    }
+}
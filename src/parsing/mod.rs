@@ -0,0 +1,474 @@
+//! CSV deserialization, and serialization back to CSV.
+//!
+//! Parsing happens in two stages:
+//! 1. Serde deserializes each CSV row into a flat `CsvTransaction`.
+//! 2. `TryFrom<CsvTransaction>` converts it into the strongly-typed domain `Transaction`.
+//!
+//! Malformed rows or missing required fields are logged and skipped. [`serialize_csv`]
+//! goes the other way, through `From<&Transaction> for CsvTransaction`.
+//!
+//! [`byte_records`] is an alternative, serde-free first stage for large inputs.
+//! [`split_rows`] cuts an in-memory document into row-aligned chunks, and with the
+//! `mmap` feature `MappedInput` maps an input file to parse it in place. The `io-uring`
+//! feature's [`open_uring`] reads a file with io_uring on Linux, and the `zip` feature's
+//! `ZipInput` reads the CSV members of a ZIP archive as one input. [`ParseFailureCounts`]
+//! breaks skipped rows down by [`ParseFailure`] category. [`deserialize_jsonl`] reads the
+//! same rows as JSON Lines. The `compression` feature's `decompress` reads gzip and zstd
+//! input. [`deserialize_tenant_csv_quarantined`] writes skipped rows to a [`Quarantine`]
+//! file.
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+pub use byte_records::{ByteRecordParser, deserialize_tenant_byte_records};
+pub use chunks::{RowChunks, split_rows};
+#[cfg(feature = "compression")]
+pub use compressed::{Compression, decompress};
+pub use failures::{ParseFailure, ParseFailureCounts};
+pub use jsonl::{deserialize_jsonl, deserialize_tenant_jsonl, try_deserialize_jsonl};
+#[cfg(feature = "mmap")]
+pub use mapped::MappedInput;
+pub use quarantine::{Quarantine, deserialize_tenant_csv_quarantined};
+#[cfg(feature = "io-uring")]
+pub use uring::open_uring;
+#[cfg(feature = "zip")]
+pub use zipped::{ZipInput, ZipTransactions};
+
+pub mod byte_records;
+mod chunks;
+#[cfg(feature = "compression")]
+mod compressed;
+mod failures;
+mod jsonl;
+#[cfg(feature = "mmap")]
+mod mapped;
+mod quarantine;
+#[cfg(feature = "io-uring")]
+mod uring;
+#[cfg(feature = "zip")]
+mod zipped;
+
+use crate::domain::{
+    Adjustment, Authorization, Capture, Chargeback, ClientId, Deposit, Dispute, DomainError,
+    Evidence, PartnerId, PreArbitration, Refund, Resolve, SignedAmount, TenantId, Transaction,
+    TransactionId, Void, Withdrawal,
+};
+
+#[derive(Debug, Clone, Copy, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum TransactionType {
+    Deposit,
+    Withdrawal,
+    Dispute,
+    Resolve,
+    Chargeback,
+    Authorization,
+    Capture,
+    Void,
+    Refund,
+    Adjustment,
+    Evidence,
+    PreArbitration,
+}
+
+/// Flat representation of a single CSV row. `amount` is optional because
+/// dispute/resolve/chargeback rows don't carry one, and signed because adjustments may be
+/// negative. `reason` is an optional column only adjustments use. `parent_tx` is an
+/// optional column linking any row to a related tx. `tenant` is an optional
+/// column - files without it belong to the default namespace. `timestamp` is an
+/// optional column of Unix seconds, only read by [`deserialize_timestamped_csv`]. `partner`
+/// is an optional column naming the row's source, only read by [`deserialize_partner_csv`].
+/// A [`Transaction`] doesn't carry the last three, so they aren't serialized.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CsvTransaction {
+    r#type: TransactionType,
+    client: ClientId,
+    tx: TransactionId,
+    amount: Option<SignedAmount>,
+    #[serde(default)]
+    reason: Option<u16>,
+    #[serde(default)]
+    parent_tx: Option<TransactionId>,
+    #[serde(default, skip_serializing)]
+    tenant: Option<TenantId>,
+    #[serde(default, skip_serializing)]
+    timestamp: Option<u64>,
+    #[serde(default, skip_serializing)]
+    partner: Option<PartnerId>,
+}
+
+/// A domain transaction tagged with the tenant namespace it belongs to.
+/// `None` is the default namespace (rows without a tenant).
+#[derive(Debug, PartialEq)]
+pub struct TenantTransaction {
+    pub tenant: Option<TenantId>,
+    pub transaction: Transaction,
+}
+
+/// A domain transaction with the optional `timestamp` column of its row (Unix seconds).
+#[derive(Debug, PartialEq)]
+pub struct TimestampedTransaction {
+    pub timestamp: Option<u64>,
+    pub transaction: Transaction,
+}
+
+/// A domain transaction with the optional `partner` column of its row.
+#[derive(Debug, PartialEq)]
+pub struct PartnerTransaction {
+    pub partner: Option<PartnerId>,
+    pub transaction: Transaction,
+}
+
+/// Everything kept from a CSV row.
+struct Row {
+    tenant: Option<TenantId>,
+    timestamp: Option<u64>,
+    partner: Option<PartnerId>,
+    transaction: Transaction,
+}
+
+/// Why a CSV row was skipped.
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    /// The row isn't valid CSV or doesn't match the expected columns.
+    #[error("Failed to parse transaction: {0}")]
+    Malformed(#[from] csv::Error),
+    /// The row parsed but isn't a valid transaction (e.g. a deposit without an amount).
+    /// `row` is the 1-based index of the data row, headers excluded.
+    #[error("Failed to convert row {row} to a transaction: {source}")]
+    Invalid {
+        row: usize,
+        source: IntoTransactionError,
+    },
+    /// A field the [`ByteRecordParser`] couldn't parse, e.g. an unknown type or a
+    /// non-numeric client ID.
+    #[error("Failed to parse column {column} of row {row}")]
+    InvalidField { row: usize, column: &'static str },
+    /// The header lacks a required column, so no row can be parsed by the [`ByteRecordParser`].
+    #[error("Missing column {0}")]
+    MissingColumn(&'static str),
+    /// A line of [JSON Lines](deserialize_jsonl) input that isn't valid JSON or doesn't match
+    /// the expected fields. `line` is 1-based.
+    #[error("Failed to parse line {line}: {source}")]
+    Json {
+        line: usize,
+        source: serde_json::Error,
+    },
+}
+
+/// Returns an iterator that lazily deserializes CSV rows into domain transactions,
+/// skipping any rows that fail to parse or convert. Built on [`try_deserialize_csv`],
+/// logging each skipped row as a warning.
+pub fn deserialize_csv<D: std::io::Read>(
+    reader: &mut csv::Reader<D>,
+) -> impl Iterator<Item = Transaction> {
+    try_deserialize_csv(reader).filter_map(skip_invalid)
+}
+
+/// Same as [`deserialize_csv`], but yields an error for every row that fails to parse or
+/// convert instead of skipping it, so the caller decides what to do. Errors of rows that
+/// parsed but aren't valid transactions carry the row's index; CSV errors carry its
+/// position.
+pub fn try_deserialize_csv<D: std::io::Read>(
+    reader: &mut csv::Reader<D>,
+) -> impl Iterator<Item = Result<Transaction, ParseError>> {
+    parse_rows(reader).map(|row| row.map(|row| row.transaction))
+}
+
+/// Same as [`deserialize_csv`], but keeps the optional `tenant` column of each row.
+pub fn deserialize_tenant_csv<D: std::io::Read>(
+    reader: &mut csv::Reader<D>,
+) -> impl Iterator<Item = TenantTransaction> {
+    parse_rows(reader)
+        .filter_map(skip_invalid)
+        .map(|row| TenantTransaction {
+            tenant: row.tenant,
+            transaction: row.transaction,
+        })
+}
+
+/// Same as [`deserialize_csv`], but keeps the optional `timestamp` column of each row.
+pub fn deserialize_timestamped_csv<D: std::io::Read>(
+    reader: &mut csv::Reader<D>,
+) -> impl Iterator<Item = TimestampedTransaction> {
+    parse_rows(reader)
+        .filter_map(skip_invalid)
+        .map(|row| TimestampedTransaction {
+            timestamp: row.timestamp,
+            transaction: row.transaction,
+        })
+}
+
+/// Same as [`deserialize_csv`], but keeps the optional `partner` column of each row.
+pub fn deserialize_partner_csv<D: std::io::Read>(
+    reader: &mut csv::Reader<D>,
+) -> impl Iterator<Item = PartnerTransaction> {
+    parse_rows(reader)
+        .filter_map(skip_invalid)
+        .map(|row| PartnerTransaction {
+            partner: row.partner,
+            transaction: row.transaction,
+        })
+}
+
+/// Writes `transactions` as CSV rows that [`deserialize_csv`] reads back into the same
+/// transactions, with columns `type,client,tx,amount,reason,parent_tx`. The header is
+/// written before the first row if `writer` has headers enabled.
+pub fn serialize_csv<W: std::io::Write>(
+    writer: &mut csv::Writer<W>,
+    transactions: impl IntoIterator<Item = Transaction>,
+) -> csv::Result<()> {
+    for transaction in transactions {
+        writer.serialize(CsvTransaction::from(&transaction))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub(crate) fn skip_invalid<T>(result: Result<T, ParseError>) -> Option<T> {
+    match result {
+        Ok(transaction) => Some(transaction),
+        Err(e) => {
+            // skipping malformed or invalid transaction and logging the error
+            warn!(reason = %e.reason(), "{e}");
+            None
+        }
+    }
+}
+
+/// Parses an in-memory CSV document (same format and trimming as the CLI input),
+/// returning the valid transactions and an error for every skipped row.
+///
+/// Never panics, whatever the input - it's meant for untrusted uploads and as a
+/// fuzzing target.
+pub fn parse_bytes(bytes: &[u8]) -> (Vec<Transaction>, Vec<ParseError>) {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(bytes);
+
+    let mut transactions = Vec::new();
+    let mut errors = Vec::new();
+    for result in parse_rows(&mut reader) {
+        match result {
+            Ok(row) => transactions.push(row.transaction),
+            Err(e) => errors.push(e),
+        }
+    }
+    (transactions, errors)
+}
+
+fn parse_rows<D: std::io::Read>(
+    reader: &mut csv::Reader<D>,
+) -> impl Iterator<Item = Result<Row, ParseError>> {
+    reader
+        .deserialize::<CsvTransaction>()
+        .enumerate()
+        .map(|(index, result)| parse_row(index, result))
+}
+
+/// Converts the deserialized data row at 0-based `index`.
+fn parse_row(index: usize, result: csv::Result<CsvTransaction>) -> Result<Row, ParseError> {
+    let csv_transaction = result?;
+    let (tenant, timestamp, partner) = (
+        csv_transaction.tenant,
+        csv_transaction.timestamp,
+        csv_transaction.partner,
+    );
+    let transaction =
+        Transaction::try_from(csv_transaction).map_err(|source| ParseError::Invalid {
+            row: index + 1,
+            source,
+        })?;
+    Ok(Row {
+        tenant,
+        timestamp,
+        partner,
+        transaction,
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum IntoTransactionError {
+    #[error("Missing amount for deposit")]
+    MissingAmountForDeposit,
+    #[error("Missing amount for withdrawal")]
+    MissingAmountForWithdrawal,
+    #[error("Missing amount for authorization")]
+    MissingAmountForAuthorization,
+    #[error("Missing amount for refund")]
+    MissingAmountForRefund,
+    #[error("Missing amount for adjustment")]
+    MissingAmountForAdjustment,
+    #[error("Missing reason for adjustment")]
+    MissingReasonForAdjustment,
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(#[from] DomainError),
+}
+
+impl From<&Transaction> for CsvTransaction {
+    fn from(transaction: &Transaction) -> Self {
+        let (r#type, tx, amount, reason) = match transaction {
+            Transaction::Deposit(deposit) => (
+                TransactionType::Deposit,
+                deposit.transaction_id(),
+                Some(deposit.amount().into()),
+                None,
+            ),
+            Transaction::Withdrawal(withdrawal) => (
+                TransactionType::Withdrawal,
+                withdrawal.transaction_id(),
+                Some(withdrawal.amount().into()),
+                None,
+            ),
+            Transaction::Dispute(dispute) => (
+                TransactionType::Dispute,
+                dispute.disputed_tx_id(),
+                None,
+                None,
+            ),
+            Transaction::Resolve(resolve) => (
+                TransactionType::Resolve,
+                resolve.disputed_tx_id(),
+                None,
+                None,
+            ),
+            Transaction::Chargeback(chargeback) => (
+                TransactionType::Chargeback,
+                chargeback.disputed_tx_id(),
+                None,
+                None,
+            ),
+            Transaction::Authorization(authorization) => (
+                TransactionType::Authorization,
+                authorization.transaction_id(),
+                Some(authorization.amount().into()),
+                None,
+            ),
+            Transaction::Capture(capture) => (
+                TransactionType::Capture,
+                capture.authorization_tx_id(),
+                None,
+                None,
+            ),
+            Transaction::Void(void) => (
+                TransactionType::Void,
+                void.authorization_tx_id(),
+                None,
+                None,
+            ),
+            Transaction::Refund(refund) => (
+                TransactionType::Refund,
+                refund.deposit_tx_id(),
+                Some(refund.amount().into()),
+                None,
+            ),
+            Transaction::Adjustment(adjustment) => (
+                TransactionType::Adjustment,
+                adjustment.transaction_id(),
+                Some(adjustment.amount()),
+                Some(adjustment.reason()),
+            ),
+            Transaction::Evidence(evidence) => (
+                TransactionType::Evidence,
+                evidence.disputed_tx_id(),
+                None,
+                None,
+            ),
+            Transaction::PreArbitration(pre_arbitration) => (
+                TransactionType::PreArbitration,
+                pre_arbitration.disputed_tx_id(),
+                None,
+                None,
+            ),
+        };
+        Self {
+            r#type,
+            client: transaction.client_id(),
+            tx,
+            amount,
+            reason,
+            parent_tx: transaction.parent_tx(),
+            tenant: None,
+            timestamp: None,
+            partner: None,
+        }
+    }
+}
+
+impl TryFrom<CsvTransaction> for Transaction {
+    type Error = IntoTransactionError;
+
+    fn try_from(value: CsvTransaction) -> Result<Self, Self::Error> {
+        let transaction = match value.r#type {
+            TransactionType::Deposit => Ok(Transaction::Deposit(Deposit::try_new(
+                value.client,
+                value.tx,
+                value
+                    .amount
+                    .ok_or(IntoTransactionError::MissingAmountForDeposit)?
+                    .value(),
+            )?)),
+            TransactionType::Withdrawal => Ok(Transaction::Withdrawal(Withdrawal::try_new(
+                value.client,
+                value.tx,
+                value
+                    .amount
+                    .ok_or(IntoTransactionError::MissingAmountForWithdrawal)?
+                    .value(),
+            )?)),
+            TransactionType::Dispute => {
+                Ok(Transaction::Dispute(Dispute::new(value.client, value.tx)))
+            }
+            TransactionType::Resolve => {
+                Ok(Transaction::Resolve(Resolve::new(value.client, value.tx)))
+            }
+            TransactionType::Chargeback => Ok(Transaction::Chargeback(Chargeback::new(
+                value.client,
+                value.tx,
+            ))),
+            TransactionType::Authorization => {
+                Ok(Transaction::Authorization(Authorization::try_new(
+                    value.client,
+                    value.tx,
+                    value
+                        .amount
+                        .ok_or(IntoTransactionError::MissingAmountForAuthorization)?
+                        .value(),
+                )?))
+            }
+            TransactionType::Capture => {
+                Ok(Transaction::Capture(Capture::new(value.client, value.tx)))
+            }
+            TransactionType::Void => Ok(Transaction::Void(Void::new(value.client, value.tx))),
+            TransactionType::Refund => Ok(Transaction::Refund(Refund::try_new(
+                value.client,
+                value.tx,
+                value
+                    .amount
+                    .ok_or(IntoTransactionError::MissingAmountForRefund)?
+                    .value(),
+            )?)),
+            TransactionType::Adjustment => Ok(Transaction::Adjustment(Adjustment::try_new(
+                value.client,
+                value.tx,
+                value
+                    .amount
+                    .ok_or(IntoTransactionError::MissingAmountForAdjustment)?
+                    .value(),
+                value
+                    .reason
+                    .ok_or(IntoTransactionError::MissingReasonForAdjustment)?,
+            )?)),
+            TransactionType::Evidence => {
+                Ok(Transaction::Evidence(Evidence::new(value.client, value.tx)))
+            }
+            TransactionType::PreArbitration => Ok(Transaction::PreArbitration(
+                PreArbitration::new(value.client, value.tx),
+            )),
+        };
+        match value.parent_tx {
+            Some(parent_tx) => transaction.map(|transaction| transaction.with_parent_tx(parent_tx)),
+            None => transaction,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Quarantine file of the CSV rows that were skipped, so they can be fixed and processed
+//! later.
+//!
+//! [`deserialize_tenant_csv_quarantined`] parses like
+//! [`deserialize_tenant_csv`](super::deserialize_tenant_csv), and also writes every row it
+//! skips to a [`Quarantine`]: the row's line in the input and its
+//! [`RejectionReason`](crate::rejection::RejectionReason), followed by its fields as read.
+
+use std::io::{Read, Write};
+
+use csv::ByteRecord;
+use tracing::{error, warn};
+
+use crate::parsing::{CsvTransaction, ParseError, TenantTransaction, parse_row};
+
+/// CSV writer of skipped rows. Its header is `line,reason` followed by the input's header,
+/// written with the first row; nothing is written if no row is skipped. Rows are flushed as
+/// they're written, so the file is complete even if the run stops early.
+#[derive(Debug)]
+pub struct Quarantine<W: Write> {
+    writer: csv::Writer<W>,
+    /// Rows written so far.
+    rows: usize,
+}
+
+impl<W: Write> Quarantine<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            // Rows with the wrong number of fields are quarantined as they are.
+            writer: csv::WriterBuilder::new().flexible(true).from_writer(writer),
+            rows: 0,
+        }
+    }
+
+    fn write(&mut self, headers: &ByteRecord, record: &ByteRecord, error: &ParseError) {
+        let line = record.position().map_or(0, csv::Position::line);
+        if let Err(e) = self.try_write(headers, line, record, error) {
+            error!(line, "Failed to quarantine row: {e}");
+        }
+    }
+
+    fn try_write(
+        &mut self,
+        headers: &ByteRecord,
+        line: u64,
+        record: &ByteRecord,
+        error: &ParseError,
+    ) -> csv::Result<()> {
+        if self.rows == 0 {
+            self.writer
+                .write_record([&b"line"[..], b"reason"].into_iter().chain(headers))?;
+        }
+        let (line, reason) = (line.to_string(), error.reason().to_string());
+        self.writer.write_record(
+            [line.as_bytes(), reason.as_bytes()]
+                .into_iter()
+                .chain(record),
+        )?;
+        self.writer.flush()?;
+        self.rows += 1;
+        Ok(())
+    }
+}
+
+/// Same as [`deserialize_tenant_csv`](super::deserialize_tenant_csv), but also writes every
+/// skipped row to `quarantine`. Fields are trimmed for parsing and quarantined as `reader`
+/// returns them, so a reader without trimming quarantines them verbatim.
+pub fn deserialize_tenant_csv_quarantined<D: Read, W: Write>(
+    reader: &mut csv::Reader<D>,
+    quarantine: Quarantine<W>,
+) -> impl Iterator<Item = TenantTransaction> {
+    let headers = reader.byte_headers().cloned().unwrap_or_default();
+    let mut trimmed_headers = headers.clone();
+    trimmed_headers.trim();
+    QuarantinedRows {
+        headers,
+        trimmed_headers,
+        reader,
+        record: ByteRecord::new(),
+        index: 0,
+        quarantine,
+    }
+}
+
+struct QuarantinedRows<'r, D, W: Write> {
+    reader: &'r mut csv::Reader<D>,
+    /// As read, for the quarantine file, and trimmed, for parsing.
+    headers: ByteRecord,
+    trimmed_headers: ByteRecord,
+    record: ByteRecord,
+    /// 0-based index of the next data row.
+    index: usize,
+    quarantine: Quarantine<W>,
+}
+
+impl<D: Read, W: Write> Iterator for QuarantinedRows<'_, D, W> {
+    type Item = TenantTransaction;
+
+    fn next(&mut self) -> Option<TenantTransaction> {
+        loop {
+            // The record is read even when it has the wrong number of fields.
+            let read = self.reader.read_byte_record(&mut self.record);
+            let result = match read {
+                Ok(false) => return None,
+                Ok(true) => {
+                    let mut trimmed = self.record.clone();
+                    trimmed.trim();
+                    trimmed.deserialize::<CsvTransaction>(Some(&self.trimmed_headers))
+                }
+                Err(e) if !matches!(e.kind(), csv::ErrorKind::UnequalLengths { .. }) => {
+                    // An IO or UTF-8 error: the record can't be trusted, or read past.
+                    error!("Failed to read CSV input, ignoring the rest: {e}");
+                    return None;
+                }
+                Err(e) => Err(e),
+            };
+            let index = self.index;
+            self.index += 1;
+            match parse_row(index, result) {
+                Ok(row) => {
+                    return Some(TenantTransaction {
+                        tenant: row.tenant,
+                        transaction: row.transaction,
+                    });
+                }
+                Err(e) => {
+                    warn!(reason = %e.reason(), "{e}");
+                    self.quarantine.write(&self.headers, &self.record, &e);
+                }
+            }
+        }
+    }
+}
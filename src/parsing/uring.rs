@@ -0,0 +1,198 @@
+//! io_uring input, behind the `io-uring` feature.
+//!
+//! [`open_uring`] reads a file through an io_uring with several block reads in flight,
+//! so the kernel is already fetching the next blocks while the current one is parsed.
+//! On other platforms, or kernels where io_uring is unavailable or disabled, it falls
+//! back to a plain [`File`].
+
+use std::{fs::File, io, path::Path};
+
+/// Opens `path` for sequential reading, through io_uring where possible.
+pub fn open_uring(path: impl AsRef<Path>) -> io::Result<Box<dyn io::Read + Send>> {
+    let file = File::open(path)?;
+    #[cfg(target_os = "linux")]
+    return Ok(ring::wrap(file));
+    #[cfg(not(target_os = "linux"))]
+    Ok(Box::new(file))
+}
+
+#[cfg(target_os = "linux")]
+mod ring {
+    use std::{fs::File, io, os::fd::AsRawFd};
+
+    use io_uring::{IoUring, opcode, types};
+    use tracing::debug;
+
+    /// Size of each block read.
+    const BLOCK_BYTES: usize = 1 << 20;
+
+    /// Block reads kept in flight.
+    const SLOTS: usize = 4;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum State {
+        InFlight,
+        /// `eof` is set when the file ended within this block.
+        Ready {
+            eof: bool,
+        },
+    }
+
+    #[derive(Debug)]
+    struct Slot {
+        buffer: Box<[u8]>,
+        /// File offset of `buffer[0]`.
+        offset: u64,
+        filled: usize,
+        consumed: usize,
+        state: State,
+    }
+
+    /// Wraps `file` in a [`UringReader`], or returns it as is if io_uring can't be set up.
+    pub fn wrap(file: File) -> Box<dyn io::Read + Send> {
+        match IoUring::new(SLOTS as u32) {
+            Ok(ring) => Box::new(UringReader::new(ring, file)),
+            Err(e) => {
+                debug!("io_uring unavailable, falling back to std IO: {e}");
+                Box::new(file)
+            }
+        }
+    }
+
+    /// Reads a file front to back with [`SLOTS`] consecutive blocks requested ahead.
+    /// Slots are consumed round-robin; an exhausted slot is resubmitted for the block
+    /// after the last requested one. The first reads are submitted on the first `read`.
+    struct UringReader {
+        ring: IoUring,
+        slots: Vec<Slot>,
+        file: File,
+        started: bool,
+        current: usize,
+        next_offset: u64,
+        in_flight: usize,
+    }
+
+    impl UringReader {
+        fn new(ring: IoUring, file: File) -> Self {
+            let slots = (0..SLOTS)
+                .map(|_| Slot {
+                    buffer: vec![0; BLOCK_BYTES].into_boxed_slice(),
+                    offset: 0,
+                    filled: 0,
+                    consumed: 0,
+                    state: State::Ready { eof: false },
+                })
+                .collect();
+            Self {
+                ring,
+                slots,
+                file,
+                started: false,
+                current: 0,
+                next_offset: 0,
+                in_flight: 0,
+            }
+        }
+
+        fn request_next_block(&mut self, index: usize) -> io::Result<()> {
+            let slot = &mut self.slots[index];
+            slot.offset = self.next_offset;
+            slot.filled = 0;
+            slot.consumed = 0;
+            self.next_offset += BLOCK_BYTES as u64;
+            self.submit_read(index)
+        }
+
+        /// Reads into the unfilled tail of the slot's buffer.
+        fn submit_read(&mut self, index: usize) -> io::Result<()> {
+            let fd = types::Fd(self.file.as_raw_fd());
+            let slot = &mut self.slots[index];
+            let tail = &mut slot.buffer[slot.filled..];
+            let entry = opcode::Read::new(fd, tail.as_mut_ptr(), tail.len() as u32)
+                .offset(slot.offset + slot.filled as u64)
+                .build()
+                .user_data(index as u64);
+            // SAFETY: the buffer is heap-allocated, owned by `self` and not touched
+            // until this read completes; `Drop` waits for every read in flight.
+            unsafe { self.ring.submission().push(&entry) }
+                .map_err(|_| io::Error::other("io_uring submission queue full"))?;
+            slot.state = State::InFlight;
+            self.in_flight += 1;
+            self.ring.submit()?;
+            Ok(())
+        }
+
+        /// Waits for at least one completion and records every available one. All of
+        /// them are accounted for before the first error is returned.
+        fn reap(&mut self) -> io::Result<()> {
+            self.ring.submit_and_wait(1)?;
+            let completions: Vec<_> = self
+                .ring
+                .completion()
+                .map(|cqe| (cqe.user_data() as usize, cqe.result()))
+                .collect();
+            let mut first_error = None;
+            for (index, result) in completions {
+                self.in_flight -= 1;
+                let Ok(read) = usize::try_from(result) else {
+                    first_error.get_or_insert(io::Error::from_raw_os_error(-result));
+                    continue;
+                };
+                let slot = &mut self.slots[index];
+                slot.filled += read;
+                if read == 0 || slot.filled == slot.buffer.len() {
+                    slot.state = State::Ready { eof: read == 0 };
+                } else if let Err(e) = self.submit_read(index) {
+                    // Short read: ask for the rest, so every block is full unless at EOF.
+                    first_error.get_or_insert(e);
+                }
+            }
+            first_error.map_or(Ok(()), Err)
+        }
+    }
+
+    impl io::Read for UringReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if !self.started {
+                self.started = true;
+                for index in 0..SLOTS {
+                    self.request_next_block(index)?;
+                }
+            }
+            loop {
+                let index = self.current;
+                let slot = &mut self.slots[index];
+                match slot.state {
+                    State::InFlight => self.reap()?,
+                    State::Ready { .. } if slot.consumed < slot.filled => {
+                        let available = &slot.buffer[slot.consumed..slot.filled];
+                        let len = available.len().min(buf.len());
+                        buf[..len].copy_from_slice(&available[..len]);
+                        slot.consumed += len;
+                        return Ok(len);
+                    }
+                    State::Ready { eof: true } => return Ok(0),
+                    State::Ready { eof: false } => {
+                        self.request_next_block(index)?;
+                        self.current = (index + 1) % SLOTS;
+                    }
+                }
+            }
+        }
+    }
+
+    impl Drop for UringReader {
+        fn drop(&mut self) {
+            while self.in_flight > 0 {
+                if let Err(e) = self.ring.submit_and_wait(1)
+                    && e.kind() != io::ErrorKind::Interrupted
+                {
+                    // The kernel may still write into the buffers, so they must outlive us.
+                    std::mem::forget(std::mem::take(&mut self.slots));
+                    return;
+                }
+                self.in_flight -= self.ring.completion().count();
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! ZIP archive input, behind the `zip` feature.
+//!
+//! Partners deliver a day of transactions as a `.zip` of CSV files. [`ZipInput`] reads
+//! the CSV members in name order as a single input: each member has its own header, and
+//! its rows follow those of the previous member. Members are decompressed into memory one
+//! at a time, so only the largest member has to fit, not the whole archive.
+
+use std::{
+    io::{self, Cursor, Read, Seek},
+    path::Path,
+};
+
+use tracing::warn;
+use zip::ZipArchive;
+
+use crate::parsing::{CsvTransaction, ParseError, TenantTransaction, parse_row};
+
+/// An opened archive and the names of its CSV members, in the order they're read.
+pub struct ZipInput<R> {
+    archive: ZipArchive<R>,
+    members: Vec<String>,
+}
+
+impl<R: Read + Seek> ZipInput<R> {
+    /// Opens an archive. Members whose name ends in `.csv` (in any case) are read, in
+    /// byte-wise name order; directories and other files are left out. Fails if `reader`
+    /// isn't a ZIP archive or has no CSV member.
+    pub fn new(reader: R) -> io::Result<Self> {
+        let archive = ZipArchive::new(reader)?;
+        let mut members: Vec<String> = archive
+            .file_names()
+            .filter(|name| is_csv(name))
+            .map(str::to_string)
+            .collect();
+        if members.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the archive has no CSV member",
+            ));
+        }
+        members.sort_unstable();
+        Ok(Self { archive, members })
+    }
+
+    pub fn members(&self) -> &[String] {
+        &self.members
+    }
+
+    /// The transactions of every member, like [`deserialize_tenant_csv`] with the CLI's
+    /// trimming. Invalid rows are logged with their member and skipped; so is a member
+    /// that can't be decompressed, as a malformed row.
+    ///
+    /// [`deserialize_tenant_csv`]: super::deserialize_tenant_csv
+    pub fn tenant_transactions(self) -> ZipTransactions<R> {
+        ZipTransactions {
+            input: self,
+            next_member: 0,
+            current: None,
+        }
+    }
+}
+
+/// Iterator over the transactions of a [`ZipInput`], see [`ZipInput::tenant_transactions`].
+pub struct ZipTransactions<R> {
+    input: ZipInput<R>,
+    next_member: usize,
+    /// Reader over the member being parsed and the number of its rows read so far.
+    current: Option<(csv::Reader<Cursor<Vec<u8>>>, usize)>,
+}
+
+impl<R: Read + Seek> Iterator for ZipTransactions<R> {
+    type Item = TenantTransaction;
+
+    fn next(&mut self) -> Option<TenantTransaction> {
+        loop {
+            let Some((reader, rows)) = &mut self.current else {
+                let member = self.input.members.get(self.next_member)?;
+                self.next_member += 1;
+                match read_member(&mut self.input.archive, member) {
+                    Ok(bytes) => {
+                        let reader = csv::ReaderBuilder::new()
+                            .trim(csv::Trim::All)
+                            .from_reader(Cursor::new(bytes));
+                        self.current = Some((reader, 0));
+                    }
+                    Err(e) => {
+                        let e = ParseError::Malformed(e.into());
+                        warn!(member, reason = %e.reason(), "{e}");
+                    }
+                }
+                continue;
+            };
+            let Some(result) = reader.deserialize::<CsvTransaction>().next() else {
+                self.current = None;
+                continue;
+            };
+            *rows += 1;
+            match parse_row(*rows - 1, result) {
+                Ok(row) => {
+                    return Some(TenantTransaction {
+                        tenant: row.tenant,
+                        transaction: row.transaction,
+                    });
+                }
+                Err(e) => {
+                    let member = &self.input.members[self.next_member - 1];
+                    warn!(member, reason = %e.reason(), "{e}");
+                }
+            }
+        }
+    }
+}
+
+fn is_csv(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+}
+
+fn read_member<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    archive.by_name(name)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
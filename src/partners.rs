@@ -0,0 +1,275 @@
+//! Per-partner breakdown for the `partners` subcommand: the balances and statistics of each
+//! client per partner (acquirer) the rows came from, for inputs with a `partner` column,
+//! and an aggregate liability account per partner.
+//!
+//! A row without a partner that references another tx (a dispute, capture, refund, ...)
+//! is attributed to the partner of the referenced tx, so a chargeback reduces the
+//! liability to the partner that sent the deposit. Other rows without one are attributed
+//! to no partner. Funds a settlement or reserve release moves to available are attributed
+//! to the partner of the deposit they belong to.
+
+use std::collections::{BTreeMap, HashMap};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{
+    domain::{ClientId, PartnerId, Transaction, TransactionId},
+    engine::{ApplyTransactions, EngineConfig, Outcome, PaymentsEngine},
+    parsing::PartnerTransaction,
+};
+
+/// The part of one client's account that came through one partner, and what was applied
+/// through it. Balances are the sums of the changes the partner's transactions made.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PartnerBalance {
+    pub available: Decimal,
+    pub held: Decimal,
+    pub total: Decimal,
+    pub deposits: Decimal,
+    pub deposit_count: usize,
+    pub withdrawals: Decimal,
+    pub withdrawal_count: usize,
+    pub chargebacks: usize,
+}
+
+/// Aggregate liability account of one partner over all clients: what came in and went out
+/// through it, and what the clients still hold through it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PartnerLiability {
+    pub deposited: Decimal,
+    pub withdrawn: Decimal,
+    /// Funds removed from the clients by chargebacks.
+    pub charged_back: Decimal,
+    /// The sum of the clients' totals through the partner: the liability.
+    pub balance: Decimal,
+}
+
+/// A row of the liability table.
+#[derive(Debug, Serialize)]
+struct LiabilityRow {
+    partner: Option<PartnerId>,
+    deposited: Decimal,
+    withdrawn: Decimal,
+    charged_back: Decimal,
+    balance: Decimal,
+}
+
+/// A row of the partner report.
+#[derive(Debug, Serialize)]
+struct PartnerRow {
+    partner: Option<PartnerId>,
+    client: ClientId,
+    available: Decimal,
+    held: Decimal,
+    total: Decimal,
+    deposits: Decimal,
+    deposit_count: usize,
+    withdrawals: Decimal,
+    withdrawal_count: usize,
+    chargebacks: usize,
+}
+
+/// Runs a [`PaymentsEngine`] and attributes what every applied transaction changed to the
+/// partner it came through.
+#[derive(Debug, Clone)]
+pub struct PartnerBreakdown {
+    engine: PaymentsEngine,
+    /// Keyed by partner, then client, which is also the report order.
+    balances: BTreeMap<(Option<PartnerId>, ClientId), PartnerBalance>,
+    liabilities: BTreeMap<Option<PartnerId>, PartnerLiability>,
+    /// Partner of every applied tx that can be referenced later.
+    partners: HashMap<TransactionId, PartnerId>,
+}
+
+impl PartnerBreakdown {
+    pub fn new(config: EngineConfig) -> Self {
+        Self {
+            engine: PaymentsEngine::with_config(config),
+            balances: BTreeMap::new(),
+            liabilities: BTreeMap::new(),
+            partners: HashMap::new(),
+        }
+    }
+    pub fn engine(&self) -> &PaymentsEngine {
+        &self.engine
+    }
+    pub fn balances(&self) -> &BTreeMap<(Option<PartnerId>, ClientId), PartnerBalance> {
+        &self.balances
+    }
+    /// The liability account of each partner, updated with the client balances.
+    pub fn liabilities(&self) -> &BTreeMap<Option<PartnerId>, PartnerLiability> {
+        &self.liabilities
+    }
+    /// Credits the settlements that are due to the partners of their deposits: the funds
+    /// move to available, and so into the partner's total.
+    fn settle_due(&mut self) {
+        for settlement in self.engine.settle_due() {
+            let partner = self.partners.get(&settlement.tx).copied();
+            let amount = settlement.amount.value();
+            let balance = self
+                .balances
+                .entry((partner, settlement.client))
+                .or_default();
+            balance.available += amount;
+            balance.total += amount;
+            self.liabilities.entry(partner).or_default().balance += amount;
+        }
+    }
+    /// Credits the settlements due after the last transaction.
+    pub fn finish(&mut self) {
+        self.settle_due();
+    }
+    /// Writes one CSV row per partner and client, ordered by partner (rows without one
+    /// first) then client. The header is written even without rows.
+    pub fn write_report(&self, writer: impl std::io::Write) -> anyhow::Result<()> {
+        let mut wtr = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(writer);
+        wtr.write_record([
+            "partner",
+            "client",
+            "available",
+            "held",
+            "total",
+            "deposits",
+            "deposit_count",
+            "withdrawals",
+            "withdrawal_count",
+            "chargebacks",
+        ])?;
+        for ((partner, client), balance) in &self.balances {
+            wtr.serialize(PartnerRow {
+                partner: *partner,
+                client: *client,
+                available: balance.available.normalize(),
+                held: balance.held.normalize(),
+                total: balance.total.normalize(),
+                deposits: balance.deposits.normalize(),
+                deposit_count: balance.deposit_count,
+                withdrawals: balance.withdrawals.normalize(),
+                withdrawal_count: balance.withdrawal_count,
+                chargebacks: balance.chargebacks,
+            })?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+    /// Writes the liability table, one CSV row per partner in the report's order. The
+    /// header is written even without rows.
+    pub fn write_liabilities(&self, writer: impl std::io::Write) -> anyhow::Result<()> {
+        let mut wtr = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(writer);
+        wtr.write_record([
+            "partner",
+            "deposited",
+            "withdrawn",
+            "charged_back",
+            "balance",
+        ])?;
+        for (partner, liability) in &self.liabilities {
+            wtr.serialize(LiabilityRow {
+                partner: *partner,
+                deposited: liability.deposited.normalize(),
+                withdrawn: liability.withdrawn.normalize(),
+                charged_back: liability.charged_back.normalize(),
+                balance: liability.balance.normalize(),
+            })?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl ApplyTransactions for PartnerBreakdown {
+    type Transaction = PartnerTransaction;
+
+    /// Applies `transaction` and attributes what it changed to its partner, after the
+    /// settlements due before it.
+    fn apply(&mut self, partner_transaction: PartnerTransaction) -> Outcome {
+        self.settle_due();
+        let PartnerTransaction {
+            partner,
+            transaction,
+        } = partner_transaction;
+        let client = transaction.client_id();
+        let (own_tx, referenced_tx) = txs_of(&transaction);
+        let partner = partner.or_else(|| {
+            referenced_tx.and_then(|referenced| self.partners.get(&referenced).copied())
+        });
+        let movement = match &transaction {
+            Transaction::Deposit(deposit) => Some((true, deposit.amount().value())),
+            Transaction::Withdrawal(withdrawal) => Some((false, withdrawal.amount().value())),
+            _ => None,
+        };
+        let is_chargeback = matches!(transaction, Transaction::Chargeback(_));
+        let before = self
+            .engine
+            .client_accounts()
+            .get(client)
+            .cloned()
+            .unwrap_or_default();
+
+        let outcome = self.engine.apply(transaction);
+        if outcome != Outcome::Applied {
+            return outcome;
+        }
+        if let (Some(tx), Some(partner)) = (own_tx, partner) {
+            self.partners.insert(tx, partner);
+        }
+        let (before, after) = (
+            before.balance(),
+            self.engine
+                .client_accounts()
+                .get(client)
+                .expect("applied transactions create the account")
+                .balance(),
+        );
+        let total_change = after.total() - before.total();
+        let balance = self.balances.entry((partner, client)).or_default();
+        let liability = self.liabilities.entry(partner).or_default();
+        balance.available += after.available() - before.available();
+        balance.held += after.held() - before.held();
+        balance.total += total_change;
+        liability.balance += total_change;
+        match movement {
+            Some((true, amount)) => {
+                balance.deposits += amount;
+                balance.deposit_count += 1;
+                liability.deposited += amount;
+            }
+            Some((false, amount)) => {
+                balance.withdrawals += amount;
+                balance.withdrawal_count += 1;
+                liability.withdrawn += amount;
+            }
+            None => {}
+        }
+        if is_chargeback {
+            balance.chargebacks += 1;
+            liability.charged_back -= total_change;
+        }
+        outcome
+    }
+}
+
+/// The tx a transaction creates, which later rows can reference, and the tx it references.
+fn txs_of(transaction: &Transaction) -> (Option<TransactionId>, Option<TransactionId>) {
+    match transaction {
+        Transaction::Deposit(deposit) => (Some(deposit.transaction_id()), None),
+        Transaction::Withdrawal(withdrawal) => (Some(withdrawal.transaction_id()), None),
+        Transaction::Authorization(authorization) => (Some(authorization.transaction_id()), None),
+        Transaction::Adjustment(adjustment) => (Some(adjustment.transaction_id()), None),
+        Transaction::Dispute(dispute) => (None, Some(dispute.disputed_tx_id())),
+        Transaction::Resolve(resolve) => (None, Some(resolve.disputed_tx_id())),
+        Transaction::Chargeback(chargeback) => (None, Some(chargeback.disputed_tx_id())),
+        Transaction::Evidence(evidence) => (None, Some(evidence.disputed_tx_id())),
+        Transaction::PreArbitration(pre_arbitration) => {
+            (None, Some(pre_arbitration.disputed_tx_id()))
+        }
+        Transaction::Capture(capture) => (None, Some(capture.authorization_tx_id())),
+        Transaction::Void(void) => (None, Some(void.authorization_tx_id())),
+        Transaction::Refund(refund) => (None, Some(refund.deposit_tx_id())),
+    }
+}
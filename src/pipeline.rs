@@ -0,0 +1,58 @@
+//! Overlapping parsing with processing.
+//!
+//! The CLI is otherwise strictly sequential: each row is parsed and then applied before
+//! the next one is read. [`Pipeline`] moves the producing iterator (typically the parser)
+//! to its own thread and hands its items over a bounded channel, so the two stages run
+//! in parallel while items still arrive in input order.
+
+use std::{sync::mpsc, thread};
+
+/// Shape of the channel between the producer thread and the consumer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pipeline {
+    /// Items per message. Batching keeps channel synchronization out of the per-row cost.
+    pub batch_size: usize,
+    /// Batches in flight before the producer blocks, which bounds memory when the
+    /// consumer is the slower stage.
+    pub depth: usize,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self {
+            batch_size: 1024,
+            depth: 16,
+        }
+    }
+}
+
+impl Pipeline {
+    /// Drives `items` on a separate thread and calls `consume` on the current thread with
+    /// an iterator over the same items, in order. Returns what `consume` returns.
+    ///
+    /// If `consume` returns before the iterator is exhausted, the producer stops at its
+    /// next send. A panic on the producer thread is propagated once `consume` returns.
+    pub fn run<T, R>(
+        self,
+        items: impl Iterator<Item = T> + Send,
+        consume: impl FnOnce(&mut dyn Iterator<Item = T>) -> R,
+    ) -> R
+    where
+        T: Send,
+    {
+        let batch_size = self.batch_size.max(1);
+        let (sender, receiver) = mpsc::sync_channel::<Vec<T>>(self.depth);
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                let mut items = items;
+                loop {
+                    let batch: Vec<T> = items.by_ref().take(batch_size).collect();
+                    if batch.is_empty() || sender.send(batch).is_err() {
+                        break;
+                    }
+                }
+            });
+            consume(&mut receiver.into_iter().flatten())
+        })
+    }
+}
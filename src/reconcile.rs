@@ -0,0 +1,309 @@
+//! Reconciliation for the `reconcile` subcommand: compares the accounts after a run against
+//! an expected-balance file in the output format, reporting every difference per client.
+//!
+//! The `compare` subcommand diffs two balance files instead, typically the engine's output
+//! against another system's export: [`read_balances`] reads either under a
+//! [`ColumnMapping`], and [`compare`] allows amounts to differ by a tolerance.
+
+use std::{collections::BTreeMap, str::FromStr};
+
+use anyhow::{Context, bail};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{domain::ClientId, engine::ClientAccounts};
+
+/// A row of the expected-balance file. Extra columns (e.g. `authorized`) are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ExpectedBalance {
+    pub client: ClientId,
+    pub available: Decimal,
+    pub held: Decimal,
+    pub total: Decimal,
+    pub locked: bool,
+}
+
+/// One difference, serialized as a row of the discrepancy report. `field` is `account`
+/// when the client is only on one side, with `present`/`missing` as values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Discrepancy {
+    pub client: ClientId,
+    pub field: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Reads an expected-balance CSV (same trimming as the input). Fails on malformed rows and
+/// repeated clients, since a reconciliation against a partial file would be meaningless.
+pub fn read_expected(
+    reader: impl std::io::Read,
+) -> anyhow::Result<BTreeMap<ClientId, ExpectedBalance>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let mut expected = BTreeMap::new();
+    for (index, row) in reader.deserialize::<ExpectedBalance>().enumerate() {
+        let row = row.with_context(|| format!("Invalid expected balance in row {}", index + 1))?;
+        let client = row.client;
+        if expected.insert(client, row).is_some() {
+            bail!("Client {client} appears more than once in the expected balances");
+        }
+    }
+    Ok(expected)
+}
+
+/// Differences between `expected` and `accounts`, ordered by client, then field
+/// (available, held, total, locked).
+pub fn reconcile(
+    expected: &BTreeMap<ClientId, ExpectedBalance>,
+    accounts: &ClientAccounts,
+) -> Vec<Discrepancy> {
+    let mut discrepancies = Vec::new();
+    let mut actual: BTreeMap<_, _> = accounts.iter().collect();
+    for (client, expected) in expected {
+        let Some(account) = actual.remove(client) else {
+            discrepancies.push(Discrepancy {
+                client: *client,
+                field: "account",
+                expected: "present".to_string(),
+                actual: "missing".to_string(),
+            });
+            continue;
+        };
+        let balance = account.balance();
+        let fields = [
+            ("available", expected.available, balance.available()),
+            ("held", expected.held, balance.held()),
+            ("total", expected.total, balance.total()),
+        ];
+        for (field, expected, actual) in fields {
+            if expected != actual {
+                discrepancies.push(Discrepancy {
+                    client: *client,
+                    field,
+                    expected: expected.to_string(),
+                    actual: actual.to_string(),
+                });
+            }
+        }
+        if expected.locked != account.is_locked() {
+            discrepancies.push(Discrepancy {
+                client: *client,
+                field: "locked",
+                expected: expected.locked.to_string(),
+                actual: account.is_locked().to_string(),
+            });
+        }
+    }
+    discrepancies.extend(actual.into_keys().map(|client| Discrepancy {
+        client,
+        field: "account",
+        expected: "missing".to_string(),
+        actual: "present".to_string(),
+    }));
+    discrepancies.sort_by_key(|discrepancy| discrepancy.client);
+    discrepancies
+}
+
+/// Writes the discrepancy report as CSV: client, field, expected, actual. The header is
+/// written even without discrepancies.
+pub fn write_discrepancies(
+    discrepancies: &[Discrepancy],
+    writer: impl std::io::Write,
+) -> anyhow::Result<()> {
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    wtr.write_record(["client", "field", "expected", "actual"])?;
+    for discrepancy in discrepancies {
+        wtr.serialize(discrepancy)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Fields [`compare`] can compare, in report order.
+const FIELDS: [&str; 4] = ["available", "held", "total", "locked"];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MappingError {
+    #[error("Unknown balance field {0}")]
+    UnknownField(String),
+    #[error("Balance field {0} is mapped twice")]
+    Duplicate(&'static str),
+    #[error("Empty header for balance field {0}")]
+    EmptyHeader(&'static str),
+}
+
+/// Header of each balance field in a file, for exports that name them differently. Parsed
+/// from a comma-separated list of `field=header`, e.g. `client=account_id,available=balance`;
+/// fields that aren't listed keep their own name. The fields are `client`, `available`,
+/// `held`, `total` and `locked`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnMapping(BTreeMap<&'static str, String>);
+
+impl ColumnMapping {
+    pub fn header(&self, field: &'static str) -> &str {
+        self.0.get(field).map_or(field, String::as_str)
+    }
+}
+
+impl FromStr for ColumnMapping {
+    type Err = MappingError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut mapping = BTreeMap::new();
+        for entry in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (name, header) = entry.split_once('=').unwrap_or((entry, entry));
+            let name = name.trim();
+            let field = std::iter::once("client")
+                .chain(FIELDS)
+                .find(|field| *field == name)
+                .ok_or_else(|| MappingError::UnknownField(name.to_owned()))?;
+            let header = header.trim();
+            if header.is_empty() {
+                return Err(MappingError::EmptyHeader(field));
+            }
+            if mapping.insert(field, header.to_owned()).is_some() {
+                return Err(MappingError::Duplicate(field));
+            }
+        }
+        Ok(Self(mapping))
+    }
+}
+
+/// The balances of a client in a balance file. Fields the file doesn't have are `None`
+/// and aren't compared.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Balances {
+    pub available: Option<Decimal>,
+    pub held: Option<Decimal>,
+    pub total: Option<Decimal>,
+    pub locked: Option<bool>,
+}
+
+/// Reads a balance file (CSV, same trimming as the input) with its headers mapped by
+/// `mapping`. Only the client column is required; other columns are ignored. `locked`
+/// accepts `true`/`false` in any case, and `1`/`0`. Fails on malformed rows and repeated
+/// clients, like [`read_expected`].
+pub fn read_balances(
+    reader: impl std::io::Read,
+    mapping: &ColumnMapping,
+) -> anyhow::Result<BTreeMap<ClientId, Balances>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let headers = reader.headers()?.clone();
+    let position = |field| {
+        headers
+            .iter()
+            .position(|header| header == mapping.header(field))
+    };
+    let Some(client_column) = position("client") else {
+        bail!("No {} column in the balances", mapping.header("client"));
+    };
+    let columns = FIELDS.map(position);
+
+    let mut balances = BTreeMap::new();
+    for (index, record) in reader.records().enumerate() {
+        let row = index + 1;
+        let record = record.with_context(|| format!("Invalid balances in row {row}"))?;
+        let field = |column: Option<usize>| column.and_then(|column| record.get(column));
+        let client: u16 = field(Some(client_column))
+            .unwrap_or_default()
+            .parse()
+            .with_context(|| format!("Invalid client in row {row}"))?;
+        let amount = |index: usize| -> anyhow::Result<Option<Decimal>> {
+            field(columns[index])
+                .map(|value| {
+                    Decimal::from_str(value)
+                        .with_context(|| format!("Invalid {} in row {row}", FIELDS[index]))
+                })
+                .transpose()
+        };
+        let locked = field(columns[3])
+            .map(|value| match value.to_ascii_lowercase().as_str() {
+                "true" | "1" => Ok(true),
+                "false" | "0" => Ok(false),
+                _ => Err(anyhow::anyhow!("Invalid locked in row {row}")),
+            })
+            .transpose()?;
+        let client = ClientId::from(client);
+        let row_balances = Balances {
+            available: amount(0)?,
+            held: amount(1)?,
+            total: amount(2)?,
+            locked,
+        };
+        if balances.insert(client, row_balances).is_some() {
+            bail!("Client {client} appears more than once in the balances");
+        }
+    }
+    Ok(balances)
+}
+
+/// Differences between another system's balances (`expected`) and the engine's (`actual`),
+/// ordered like [`reconcile`]'s. Amounts that differ by at most `tolerance` match. Only
+/// fields both files have are compared.
+pub fn compare(
+    expected: &BTreeMap<ClientId, Balances>,
+    actual: &BTreeMap<ClientId, Balances>,
+    tolerance: Decimal,
+) -> Vec<Discrepancy> {
+    let mut discrepancies = Vec::new();
+    for (client, expected) in expected {
+        let Some(actual) = actual.get(client) else {
+            discrepancies.push(Discrepancy {
+                client: *client,
+                field: "account",
+                expected: "present".to_string(),
+                actual: "missing".to_string(),
+            });
+            continue;
+        };
+        let amounts = [
+            (FIELDS[0], expected.available, actual.available),
+            (FIELDS[1], expected.held, actual.held),
+            (FIELDS[2], expected.total, actual.total),
+        ];
+        for (field, expected, actual) in amounts {
+            if let (Some(expected), Some(actual)) = (expected, actual)
+                && (expected - actual).abs() > tolerance
+            {
+                discrepancies.push(Discrepancy {
+                    client: *client,
+                    field,
+                    expected: expected.to_string(),
+                    actual: actual.to_string(),
+                });
+            }
+        }
+        if let (Some(expected), Some(actual)) = (expected.locked, actual.locked)
+            && expected != actual
+        {
+            discrepancies.push(Discrepancy {
+                client: *client,
+                field: FIELDS[3],
+                expected: expected.to_string(),
+                actual: actual.to_string(),
+            });
+        }
+    }
+    discrepancies.extend(
+        actual
+            .keys()
+            .filter(|client| !expected.contains_key(client))
+            .map(|client| Discrepancy {
+                client: *client,
+                field: "account",
+                expected: "missing".to_string(),
+                actual: "present".to_string(),
+            }),
+    );
+    discrepancies.sort_by_key(|discrepancy| discrepancy.client);
+    discrepancies
+}
@@ -0,0 +1,89 @@
+//! PII-safe log and error messages.
+//!
+//! Once [`enable`]d, client IDs and amounts in engine error messages (and so in the
+//! warnings logged for rejected transactions) are masked: client IDs become a keyed hash,
+//! stable for a given key so log lines about the same client can still be correlated, and
+//! amounts become the power-of-ten range they fall in. The key matters: client IDs are
+//! 16-bit, so an unkeyed hash could be reversed by trying all of them.
+//!
+//! Reports and outputs are unaffected - they're meant to contain the figures.
+
+use std::{fmt, sync::OnceLock};
+
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use sha2::Sha256;
+
+use crate::domain::ClientId;
+
+/// Environment variable holding the redaction key. The CLI enables redaction when it's set.
+pub const REDACTION_KEY_VAR: &str = "PAYMENTS_REDACTION_KEY";
+
+static KEY: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// Masks client IDs and amounts in messages for the rest of the process. Only the first
+/// key is kept; returns whether this call's key is the one in use.
+pub fn enable(key: &[u8]) -> bool {
+    KEY.get_or_init(|| key.to_vec()) == key
+}
+
+pub fn is_enabled() -> bool {
+    KEY.get().is_some()
+}
+
+/// A client ID in a message: itself, or `client#` and 8 hex digits of its keyed hash.
+pub fn client(client: ClientId) -> Redacted {
+    Redacted::Client(client)
+}
+
+/// An amount in a message: itself, or its range, e.g. `[10, 100)`.
+pub fn amount(amount: impl Into<Decimal>) -> Redacted {
+    Redacted::Amount(amount.into())
+}
+
+/// See [`client`] and [`amount`]. Whether to mask is decided when formatting.
+#[derive(Debug, Clone, Copy)]
+pub enum Redacted {
+    Client(ClientId),
+    Amount(Decimal),
+}
+
+impl fmt::Display for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(key) = KEY.get() else {
+            return match self {
+                Redacted::Client(client) => write!(f, "{client}"),
+                Redacted::Amount(amount) => write!(f, "{amount}"),
+            };
+        };
+        match *self {
+            Redacted::Client(client) => {
+                let mut mac =
+                    Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+                mac.update(&u16::from(client).to_be_bytes());
+                let hash = mac.finalize().into_bytes();
+                write!(f, "client#{}", hex::encode(&hash[..4]))
+            }
+            Redacted::Amount(amount) => write_range(f, amount),
+        }
+    }
+}
+
+/// `[10^n, 10^(n+1))` holding the magnitude of `amount`, signed; `0` stays as is.
+fn write_range(f: &mut fmt::Formatter<'_>, amount: Decimal) -> fmt::Result {
+    if amount.is_zero() {
+        return write!(f, "0");
+    }
+    let sign = if amount.is_sign_negative() { "-" } else { "" };
+    let magnitude = amount.abs();
+    let mut lower = Decimal::new(1, 4);
+    while let Some(next) = lower.checked_mul(Decimal::TEN)
+        && next <= magnitude
+    {
+        lower = next;
+    }
+    match lower.checked_mul(Decimal::TEN) {
+        Some(upper) => write!(f, "{sign}[{}, {})", lower.normalize(), upper.normalize()),
+        None => write!(f, "{sign}[{}, ∞)", lower.normalize()),
+    }
+}
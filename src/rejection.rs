@@ -0,0 +1,209 @@
+//! Stable taxonomy of the reasons a row or transaction isn't applied.
+//!
+//! Every reason has a snake_case name and a numeric code. Use these (not error
+//! messages, which may change) to correlate logs, reports, API responses and
+//! metrics labels across systems.
+//!
+//! | Code | Name | Meaning |
+//! |---|---|---|
+//! | 100 | `malformed_row` | The row isn't valid CSV (or JSON) or doesn't match the expected columns |
+//! | 101 | `missing_amount` | A deposit, withdrawal, authorization, refund or adjustment without an amount |
+//! | 102 | `invalid_amount` | A non-positive amount (zero for adjustments) or one with more than 4 decimal places |
+//! | 103 | `missing_reason` | An adjustment without a reason code |
+//! | 200 | `transaction_not_found` | The referenced tx doesn't exist for this client |
+//! | 201 | `already_disputed` | A dispute or refund of a tx that is already disputed or charged back |
+//! | 202 | `not_disputed` | A resolve, chargeback, evidence or pre-arbitration for a tx without an open dispute |
+//! | 203 | `repeated_settlement` | A repeated resolve/chargeback, ignored by configuration |
+//! | 204 | `deposit_refunded` | A dispute of a deposit that was fully refunded |
+//! | 205 | `dispute_stage_out_of_order` | Evidence or pre-arbitration for an open dispute not at the preceding stage |
+//! | 206 | `filtered` | A transaction a middleware filtered out before the engine saw it |
+//! | 207 | `deferred` | A dispute-family transaction parked until the deposit it references arrives |
+//! | 300 | `account_locked` | A movement (or refund) on a locked account |
+//! | 301 | `insufficient_funds` | A withdrawal, authorization or refund above the available funds |
+//! | 302 | `authorization_already_settled` | A capture or void of an authorization that was already settled |
+//! | 303 | `refund_exceeds_deposit` | A refund above the part of the deposit not refunded yet |
+//! | 304 | `dispute_window_expired` | A dispute opened after the configured dispute window |
+//! | 305 | `withdrawal_limit_exceeded` | A withdrawal above the limit of the client's tier |
+//! | 306 | `rejected_by_middleware` | A transaction a middleware's policy check rejected |
+//! | 307 | `duplicate_transaction_id` | A deposit, withdrawal, authorization or adjustment reusing an earlier tx ID |
+//! | 308 | `amount_too_large` | A deposit, adjustment or dispute that would take a balance beyond the largest storable amount |
+//!
+//! Codes in the 1xx range are parsing failures, 2xx are transactions the engine ignores
+//! and 3xx are transactions it rejects (see [`Outcome`](crate::engine::Outcome)).
+//!
+//! Names and codes are never changed or reused. Adding a reason or changing what one
+//! covers bumps [`TAXONOMY_VERSION`].
+
+use derive_more::Display;
+use serde::Serialize;
+
+use crate::{
+    engine::{IgnoreReason, Outcome, errors::EngineError},
+    parsing::{IntoTransactionError, ParseError},
+};
+
+/// Version of the table above.
+pub const TAXONOMY_VERSION: u32 = 10;
+
+/// Serializes and displays as its name, e.g. `insufficient_funds`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    #[display("malformed_row")]
+    MalformedRow,
+    #[display("missing_amount")]
+    MissingAmount,
+    #[display("invalid_amount")]
+    InvalidAmount,
+    #[display("missing_reason")]
+    MissingReason,
+    #[display("transaction_not_found")]
+    TransactionNotFound,
+    #[display("already_disputed")]
+    AlreadyDisputed,
+    #[display("not_disputed")]
+    NotDisputed,
+    #[display("repeated_settlement")]
+    RepeatedSettlement,
+    #[display("deposit_refunded")]
+    DepositRefunded,
+    #[display("dispute_stage_out_of_order")]
+    DisputeStageOutOfOrder,
+    #[display("filtered")]
+    Filtered,
+    #[display("deferred")]
+    Deferred,
+    #[display("account_locked")]
+    AccountLocked,
+    #[display("insufficient_funds")]
+    InsufficientFunds,
+    #[display("authorization_already_settled")]
+    AuthorizationAlreadySettled,
+    #[display("refund_exceeds_deposit")]
+    RefundExceedsDeposit,
+    #[display("dispute_window_expired")]
+    DisputeWindowExpired,
+    #[display("withdrawal_limit_exceeded")]
+    WithdrawalLimitExceeded,
+    #[display("rejected_by_middleware")]
+    RejectedByMiddleware,
+    #[display("duplicate_transaction_id")]
+    DuplicateTransactionId,
+    #[display("amount_too_large")]
+    AmountTooLarge,
+}
+
+impl RejectionReason {
+    /// Every reason, in code order - e.g. to pre-register metrics labels.
+    pub const ALL: [RejectionReason; 21] = [
+        RejectionReason::MalformedRow,
+        RejectionReason::MissingAmount,
+        RejectionReason::InvalidAmount,
+        RejectionReason::MissingReason,
+        RejectionReason::TransactionNotFound,
+        RejectionReason::AlreadyDisputed,
+        RejectionReason::NotDisputed,
+        RejectionReason::RepeatedSettlement,
+        RejectionReason::DepositRefunded,
+        RejectionReason::DisputeStageOutOfOrder,
+        RejectionReason::Filtered,
+        RejectionReason::Deferred,
+        RejectionReason::AccountLocked,
+        RejectionReason::InsufficientFunds,
+        RejectionReason::AuthorizationAlreadySettled,
+        RejectionReason::RefundExceedsDeposit,
+        RejectionReason::DisputeWindowExpired,
+        RejectionReason::WithdrawalLimitExceeded,
+        RejectionReason::RejectedByMiddleware,
+        RejectionReason::DuplicateTransactionId,
+        RejectionReason::AmountTooLarge,
+    ];
+
+    pub fn code(self) -> u16 {
+        match self {
+            RejectionReason::MalformedRow => 100,
+            RejectionReason::MissingAmount => 101,
+            RejectionReason::InvalidAmount => 102,
+            RejectionReason::MissingReason => 103,
+            RejectionReason::TransactionNotFound => 200,
+            RejectionReason::AlreadyDisputed => 201,
+            RejectionReason::NotDisputed => 202,
+            RejectionReason::RepeatedSettlement => 203,
+            RejectionReason::DepositRefunded => 204,
+            RejectionReason::DisputeStageOutOfOrder => 205,
+            RejectionReason::Filtered => 206,
+            RejectionReason::Deferred => 207,
+            RejectionReason::AccountLocked => 300,
+            RejectionReason::InsufficientFunds => 301,
+            RejectionReason::AuthorizationAlreadySettled => 302,
+            RejectionReason::RefundExceedsDeposit => 303,
+            RejectionReason::DisputeWindowExpired => 304,
+            RejectionReason::WithdrawalLimitExceeded => 305,
+            RejectionReason::RejectedByMiddleware => 306,
+            RejectionReason::DuplicateTransactionId => 307,
+            RejectionReason::AmountTooLarge => 308,
+        }
+    }
+}
+
+impl EngineError {
+    pub fn reason(&self) -> RejectionReason {
+        match self {
+            EngineError::AccountLocked { .. } => RejectionReason::AccountLocked,
+            EngineError::TransactionNotFound { .. } => RejectionReason::TransactionNotFound,
+            EngineError::TransactionAlreadyDisputed { .. } => RejectionReason::AlreadyDisputed,
+            EngineError::TransactionNotDisputed { .. } => RejectionReason::NotDisputed,
+            EngineError::AuthorizationAlreadySettled { .. } => {
+                RejectionReason::AuthorizationAlreadySettled
+            }
+            EngineError::InsufficientFunds { .. } => RejectionReason::InsufficientFunds,
+            EngineError::DepositRefunded { .. } => RejectionReason::DepositRefunded,
+            EngineError::DisputeStageOutOfOrder { .. } => RejectionReason::DisputeStageOutOfOrder,
+            EngineError::RefundExceedsDeposit { .. } => RejectionReason::RefundExceedsDeposit,
+            EngineError::DisputeWindowExpired { .. } => RejectionReason::DisputeWindowExpired,
+            EngineError::WithdrawalLimitExceeded { .. } => RejectionReason::WithdrawalLimitExceeded,
+            EngineError::RejectedByMiddleware { .. } => RejectionReason::RejectedByMiddleware,
+            EngineError::DuplicateTransactionId { .. } => RejectionReason::DuplicateTransactionId,
+            EngineError::AmountTooLarge { .. } => RejectionReason::AmountTooLarge,
+        }
+    }
+}
+
+impl ParseError {
+    pub fn reason(&self) -> RejectionReason {
+        match self {
+            ParseError::Malformed(_)
+            | ParseError::InvalidField { .. }
+            | ParseError::MissingColumn(_)
+            | ParseError::Json { .. } => RejectionReason::MalformedRow,
+            ParseError::Invalid { source, .. } => match source {
+                IntoTransactionError::MissingAmountForDeposit
+                | IntoTransactionError::MissingAmountForWithdrawal
+                | IntoTransactionError::MissingAmountForAuthorization
+                | IntoTransactionError::MissingAmountForRefund
+                | IntoTransactionError::MissingAmountForAdjustment => {
+                    RejectionReason::MissingAmount
+                }
+                IntoTransactionError::MissingReasonForAdjustment => RejectionReason::MissingReason,
+                IntoTransactionError::InvalidAmount(_) => RejectionReason::InvalidAmount,
+            },
+        }
+    }
+}
+
+impl Outcome {
+    /// Why the transaction wasn't applied, or `None` if it was.
+    pub fn reason(&self) -> Option<RejectionReason> {
+        match self {
+            Outcome::Applied => None,
+            Outcome::Ignored(IgnoreReason::RepeatedSettlement) => {
+                Some(RejectionReason::RepeatedSettlement)
+            }
+            Outcome::Ignored(IgnoreReason::Filtered) => Some(RejectionReason::Filtered),
+            Outcome::Ignored(IgnoreReason::Deferred) => Some(RejectionReason::Deferred),
+            Outcome::Ignored(IgnoreReason::Spec(error)) | Outcome::Rejected(error) => {
+                Some(error.reason())
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Run summary for the `report` subcommand: the one-pager wanted after a settlement cycle.
+
+use std::fmt;
+
+use rust_decimal::Decimal;
+
+use crate::{
+    domain::{Account, ClientId, DisputeState},
+    engine::PaymentsEngine,
+    parsing::ParseFailureCounts,
+};
+
+/// Totals over the final state of a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunReport {
+    pub clients: usize,
+    pub locked_accounts: usize,
+    pub total_available: Decimal,
+    pub total_held: Decimal,
+    /// Deposits whose dispute is still open at the end of the run.
+    pub open_disputes: usize,
+    /// Deposits that were charged back.
+    pub chargebacks: usize,
+    /// Disputes rejected for being opened outside the dispute window.
+    pub expired_disputes: usize,
+    /// Accounts with the highest total, largest first (ties by client ID).
+    pub largest_accounts: Vec<(ClientId, Account)>,
+    /// Input rows skipped for failing to parse, by category. Empty unless set with
+    /// [`RunReport::with_parse_failures`].
+    pub parse_failures: ParseFailureCounts,
+}
+
+impl RunReport {
+    /// Summarizes `engine`, listing at most `top` of the largest accounts.
+    pub fn new(engine: &PaymentsEngine, top: usize) -> Self {
+        let accounts = engine.client_accounts();
+        let mut largest_accounts: Vec<_> = accounts
+            .iter()
+            .map(|(client_id, account)| (client_id, account.clone()))
+            .collect();
+        largest_accounts.sort_unstable_by(|(a_id, a), (b_id, b)| {
+            b.balance()
+                .total()
+                .cmp(&a.balance().total())
+                .then(a_id.cmp(b_id))
+        });
+        largest_accounts.truncate(top);
+
+        let deposits = engine.deposit_history().as_map().values();
+        let count_where = |pred: fn(DisputeState) -> bool| {
+            deposits
+                .clone()
+                .filter(|deposit| pred(deposit.dispute_state()))
+                .count()
+        };
+
+        Self {
+            clients: accounts.len(),
+            locked_accounts: accounts.iter().filter(|(_, a)| a.is_locked()).count(),
+            total_available: accounts.iter().map(|(_, a)| a.balance().available()).sum(),
+            total_held: accounts.iter().map(|(_, a)| a.balance().held()).sum(),
+            open_disputes: count_where(DisputeState::is_open),
+            chargebacks: count_where(|state| state == DisputeState::ChargedBack),
+            expired_disputes: engine.expired_disputes(),
+            largest_accounts,
+            parse_failures: ParseFailureCounts::default(),
+        }
+    }
+    /// Adds the parse failures counted while reading the run's input.
+    pub fn with_parse_failures(self, parse_failures: ParseFailureCounts) -> Self {
+        Self {
+            parse_failures,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for RunReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Clients:          {}", self.clients)?;
+        writeln!(f, "Locked accounts:  {}", self.locked_accounts)?;
+        writeln!(f, "Total available:  {}", self.total_available)?;
+        writeln!(f, "Total held:       {}", self.total_held)?;
+        writeln!(f, "Open disputes:    {}", self.open_disputes)?;
+        writeln!(f, "Chargebacks:      {}", self.chargebacks)?;
+        writeln!(f, "Expired disputes: {}", self.expired_disputes)?;
+        writeln!(f, "Skipped rows:     {}", self.parse_failures.total())?;
+        for (failure, count) in self.parse_failures.iter() {
+            writeln!(f, "  {failure}: {count}")?;
+        }
+        if self.largest_accounts.is_empty() {
+            return Ok(());
+        }
+        writeln!(f)?;
+        writeln!(f, "Largest accounts:")?;
+        writeln!(
+            f,
+            "{:>7}  {:>16}  {:>16}  {:>16}  locked",
+            "client", "available", "held", "total"
+        )?;
+        for (client_id, account) in &self.largest_accounts {
+            let balance = account.balance();
+            writeln!(
+                f,
+                "{:>7}  {:>16}  {:>16}  {:>16}  {}",
+                client_id,
+                balance.available(),
+                balance.held(),
+                balance.total(),
+                if account.is_locked() { "yes" } else { "no" }
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+//! Per-client statements: every applied transaction and settled deposit with the running
+//! balance after it, and the final account state, rendered as text or HTML.
+//!
+//! The engine keeps no ledger, so [`StatementRecorder`] wraps it and records the lines
+//! while transactions are applied - only for the requested clients, to keep memory flat.
+
+use std::{collections::BTreeMap, fmt};
+
+use derive_more::From;
+
+use crate::{
+    domain::{Account, ClientId, Transaction, TransactionId},
+    engine::{
+        ApplyTransactions, EngineConfig, Outcome, PaymentsEngine, Settlement, SettlementKind,
+    },
+};
+
+/// One applied transaction or settlement and the client's account right after it.
+/// Settlements due at the same point all carry the account after the last of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementLine {
+    pub entry: StatementEntry,
+    pub account: Account,
+}
+
+/// What a [`StatementLine`] records.
+#[derive(Debug, Clone, PartialEq, From)]
+pub enum StatementEntry {
+    Transaction(Transaction),
+    /// A deposit's funds moving to available once its settlement or reserve delay passed.
+    Settlement(Settlement),
+}
+
+impl StatementEntry {
+    /// The transaction's [`Transaction::parent_tx`], or the settled deposit.
+    pub fn parent_tx(&self) -> Option<TransactionId> {
+        match self {
+            StatementEntry::Transaction(transaction) => transaction.parent_tx(),
+            StatementEntry::Settlement(settlement) => Some(settlement.tx),
+        }
+    }
+}
+
+impl fmt::Display for StatementEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatementEntry::Transaction(transaction) => write!(f, "{transaction}"),
+            StatementEntry::Settlement(settlement) => {
+                let kind = match settlement.kind {
+                    SettlementKind::Pending => "settled deposit",
+                    SettlementKind::Reserve => "released reserve of",
+                };
+                write!(
+                    f,
+                    "{kind} tx {} (client {}, amount {})",
+                    settlement.tx, settlement.client, settlement.amount
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub client: ClientId,
+    pub lines: Vec<StatementLine>,
+    /// Final state, also present for clients without applied transactions.
+    pub account: Account,
+}
+
+/// Runs a [`PaymentsEngine`] and records statement lines for a set of clients.
+#[derive(Debug, Clone)]
+pub struct StatementRecorder {
+    engine: PaymentsEngine,
+    lines: BTreeMap<ClientId, Vec<StatementLine>>,
+}
+
+impl StatementRecorder {
+    pub fn new(config: EngineConfig, clients: impl IntoIterator<Item = ClientId>) -> Self {
+        Self {
+            engine: PaymentsEngine::with_config(config),
+            lines: clients
+                .into_iter()
+                .map(|client| (client, Vec::new()))
+                .collect(),
+        }
+    }
+    pub fn engine(&self) -> &PaymentsEngine {
+        &self.engine
+    }
+    fn settle_due(&mut self) {
+        for settlement in self.engine.settle_due() {
+            let Some(lines) = self.lines.get_mut(&settlement.client) else {
+                continue;
+            };
+            let account = self
+                .engine
+                .client_accounts()
+                .get(settlement.client)
+                .expect("settled deposits have an account")
+                .clone();
+            lines.push(StatementLine {
+                entry: settlement.into(),
+                account,
+            });
+        }
+    }
+    /// Records the settlements due after the last transaction.
+    pub fn finish(&mut self) {
+        self.settle_due();
+    }
+    /// Statements of the requested clients, in client ID order.
+    pub fn statements(&self) -> impl Iterator<Item = Statement> + '_ {
+        self.lines.iter().map(|(client, lines)| Statement {
+            client: *client,
+            lines: lines.clone(),
+            account: self
+                .engine
+                .client_accounts()
+                .get(*client)
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl ApplyTransactions for StatementRecorder {
+    type Transaction = Transaction;
+
+    /// Applies `transaction`, recording a line if it was applied for a requested client,
+    /// after those of the settlements due before it.
+    fn apply(&mut self, transaction: Transaction) -> Outcome {
+        self.settle_due();
+        let client = transaction.client_id();
+        let Some(lines) = self.lines.get_mut(&client) else {
+            return self.engine.apply(transaction);
+        };
+        let outcome = self.engine.apply(transaction.clone());
+        if outcome == Outcome::Applied {
+            let account = self
+                .engine
+                .client_accounts()
+                .get(client)
+                .expect("applied transactions create the account")
+                .clone();
+            lines.push(StatementLine {
+                entry: transaction.into(),
+                account,
+            });
+        }
+        outcome
+    }
+}
+
+impl Statement {
+    /// HTML rendering: a `<section>` with one table row per applied transaction or settlement.
+    pub fn html(&self) -> Html<'_> {
+        Html(self)
+    }
+    /// Lines linked to `parent_tx`, e.g. the withdrawals paying out a deposit or its
+    /// settlement. See [`StatementEntry::parent_tx`].
+    pub fn linked_to(&self, parent_tx: TransactionId) -> impl Iterator<Item = &StatementLine> {
+        self.lines
+            .iter()
+            .filter(move |line| line.entry.parent_tx() == Some(parent_tx))
+    }
+}
+
+/// See [`Statement::html`].
+#[derive(Debug)]
+pub struct Html<'a>(&'a Statement);
+
+impl fmt::Display for Html<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let statement = self.0;
+        writeln!(f, "<section class=\"statement\">")?;
+        writeln!(f, "<h2>Statement for client {}</h2>", statement.client)?;
+        writeln!(f, "<table>")?;
+        writeln!(
+            f,
+            "<tr><th>Transaction</th><th>Available</th><th>Held</th><th>Total</th><th>Locked</th></tr>"
+        )?;
+        for line in &statement.lines {
+            writeln!(
+                f,
+                "<tr><td>{}</td>{}</tr>",
+                escape_html(&line.entry.to_string()),
+                html_balance_cells(&line.account)
+            )?;
+        }
+        writeln!(
+            f,
+            "<tr class=\"final\"><th>Final balance</th>{}</tr>",
+            html_balance_cells(&statement.account)
+        )?;
+        writeln!(f, "</table>")?;
+        writeln!(f, "</section>")
+    }
+}
+
+fn html_balance_cells(account: &Account) -> String {
+    let balance = account.balance();
+    format!(
+        "<td>{}</td><td>{}</td><td>{}</td><td>{}</td>",
+        balance.available(),
+        balance.held(),
+        balance.total(),
+        if account.is_locked() { "yes" } else { "no" }
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Plain-text rendering, one line per applied transaction or settlement.
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Statement for client {}", self.client)?;
+        writeln!(
+            f,
+            "{:<44}  {:>16}  {:>16}  {:>16}  locked",
+            "transaction", "available", "held", "total"
+        )?;
+        for line in &self.lines {
+            write_text_row(f, &line.entry.to_string(), &line.account)?;
+        }
+        write_text_row(f, "final balance", &self.account)
+    }
+}
+
+fn write_text_row(f: &mut fmt::Formatter<'_>, label: &str, account: &Account) -> fmt::Result {
+    let balance = account.balance();
+    writeln!(
+        f,
+        "{:<44}  {:>16}  {:>16}  {:>16}  {}",
+        label,
+        balance.available(),
+        balance.held(),
+        balance.total(),
+        if account.is_locked() { "yes" } else { "no" }
+    )
+}
@@ -0,0 +1,148 @@
+//! Suspicious-activity report for the `sar` subcommand: every account frozen during a run,
+//! the chargeback that froze it and a summary of the client's activity before, as JSON for
+//! compliance filings.
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{
+    domain::{Account, ClientId, Transaction, TransactionId},
+    engine::{ApplyTransactions, ClientHasher, EngineConfig, Outcome, PaymentsEngine},
+};
+
+/// Applied transactions of one client, counted and summed by kind.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ActivitySummary {
+    pub deposits: usize,
+    pub deposited: Decimal,
+    pub withdrawals: usize,
+    pub withdrawn: Decimal,
+    pub disputes: usize,
+    pub resolves: usize,
+    pub chargebacks: usize,
+}
+
+/// The chargeback that locked an account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TriggeringChargeback {
+    /// The charged-back deposit.
+    pub tx: TransactionId,
+    pub amount: Decimal,
+    /// Position of the chargeback among the applied transactions, starting at 1.
+    pub sequence: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrozenAccount {
+    pub client: ClientId,
+    pub trigger: TriggeringChargeback,
+    /// Activity up to, not including, the triggering chargeback.
+    pub prior_activity: ActivitySummary,
+    /// State at the end of the run.
+    pub account: Account,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SuspiciousActivityReport {
+    /// In the order the accounts were frozen.
+    pub frozen_accounts: Vec<FrozenAccount>,
+}
+
+/// Runs a [`PaymentsEngine`], keeping an [`ActivitySummary`] per client and recording the
+/// chargebacks that freeze accounts.
+#[derive(Debug, Clone)]
+pub struct SuspiciousActivityRecorder {
+    engine: PaymentsEngine,
+    applied: u64,
+    activity: HashMap<ClientId, ActivitySummary, ClientHasher>,
+    /// Frozen clients with their trigger and prior activity, in freezing order.
+    frozen: Vec<(ClientId, TriggeringChargeback, ActivitySummary)>,
+}
+
+impl SuspiciousActivityRecorder {
+    pub fn new(config: EngineConfig) -> Self {
+        Self {
+            engine: PaymentsEngine::with_config(config),
+            applied: 0,
+            activity: HashMap::default(),
+            frozen: Vec::new(),
+        }
+    }
+    pub fn engine(&self) -> &PaymentsEngine {
+        &self.engine
+    }
+    pub fn report(&self) -> SuspiciousActivityReport {
+        let frozen_accounts = self
+            .frozen
+            .iter()
+            .map(|(client, trigger, prior_activity)| FrozenAccount {
+                client: *client,
+                trigger: trigger.clone(),
+                prior_activity: prior_activity.clone(),
+                account: self
+                    .engine
+                    .client_accounts()
+                    .get(*client)
+                    .expect("frozen accounts exist")
+                    .clone(),
+            })
+            .collect();
+        SuspiciousActivityReport { frozen_accounts }
+    }
+}
+
+impl ApplyTransactions for SuspiciousActivityRecorder {
+    type Transaction = Transaction;
+
+    fn apply(&mut self, transaction: Transaction) -> Outcome {
+        let client = transaction.client_id();
+        let was_locked = self
+            .engine
+            .client_accounts()
+            .get(client)
+            .is_some_and(Account::is_locked);
+
+        let outcome = self.engine.apply(transaction.clone());
+        if outcome != Outcome::Applied {
+            return outcome;
+        }
+        self.applied += 1;
+
+        let activity = self.activity.entry(client).or_default();
+        if let Transaction::Chargeback(chargeback) = &transaction
+            && !was_locked
+        {
+            let tx = chargeback.disputed_tx_id();
+            let deposit = &self.engine.deposit_history().as_map()[&tx];
+            let trigger = TriggeringChargeback {
+                tx,
+                amount: deposit.amount().value(),
+                sequence: self.applied,
+            };
+            self.frozen.push((client, trigger, activity.clone()));
+        }
+        match &transaction {
+            Transaction::Deposit(deposit) => {
+                activity.deposits += 1;
+                activity.deposited += deposit.amount().value();
+            }
+            Transaction::Withdrawal(withdrawal) => {
+                activity.withdrawals += 1;
+                activity.withdrawn += withdrawal.amount().value();
+            }
+            Transaction::Dispute(_) => activity.disputes += 1,
+            Transaction::Resolve(_) => activity.resolves += 1,
+            Transaction::Chargeback(_) => activity.chargebacks += 1,
+            Transaction::Authorization(_)
+            | Transaction::Capture(_)
+            | Transaction::Void(_)
+            | Transaction::Refund(_)
+            | Transaction::Adjustment(_)
+            | Transaction::Evidence(_)
+            | Transaction::PreArbitration(_) => {}
+        }
+        outcome
+    }
+}
@@ -0,0 +1,253 @@
+//! Deterministic synthetic workloads, for benchmarks, property tests and the `generate`
+//! subcommand.
+//!
+//! A [`Workload`] describes the stream; [`Workload::rows`] generates it lazily, and
+//! [`Workload::transactions`] / [`Workload::to_vec`] keep only the valid rows as domain
+//! transactions, so tests don't need temp files. [`Workload::write_csv`] writes the
+//! input format.
+//!
+//! Rows are drawn from a seeded SplitMix64 generator, so a seed always produces the same
+//! stream, independent of any RNG crate's version. Deposits and withdrawals make up most of
+//! the stream; disputes reference earlier deposits of the same client, and open disputes
+//! are settled at the same rate, a `chargeback_share` of them by chargeback.
+
+use std::io::Write;
+
+use rust_decimal::Decimal;
+
+use crate::domain::{
+    Chargeback, ClientId, Deposit, Dispute, Resolve, Transaction, TransactionId, Withdrawal,
+};
+
+/// Parameters of a generated workload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workload {
+    /// Client IDs are drawn from `1..=clients`.
+    pub clients: u16,
+    /// Rows to generate, valid or not. Each row takes at most one tx ID.
+    pub rows: u32,
+    /// Probability of a row opening a dispute (and, separately, of settling one).
+    pub dispute_rate: f64,
+    /// Share of settlements that are chargebacks rather than resolves.
+    pub chargeback_share: f64,
+    /// Probability of a row being invalid (unknown type, bad amount, ...).
+    pub invalid_rate: f64,
+    pub seed: u64,
+}
+
+impl Default for Workload {
+    fn default() -> Self {
+        Self {
+            clients: 1000,
+            rows: 100_000,
+            dispute_rate: 0.01,
+            chargeback_share: 0.2,
+            invalid_rate: 0.0,
+            seed: 0,
+        }
+    }
+}
+
+/// A generated row: a valid transaction, or the CSV line of an invalid one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeneratedRow {
+    Valid(Transaction),
+    Invalid(String),
+}
+
+impl Workload {
+    /// Generates the rows lazily, in O(deposits) memory for dispute targets.
+    pub fn rows(&self) -> Rows {
+        Rows {
+            workload: self.clone(),
+            rng: Rng(self.seed),
+            generated: 0,
+            deposits: Vec::new(),
+            disputes: Vec::new(),
+            next_tx: 1,
+        }
+    }
+    /// The valid rows only.
+    pub fn transactions(&self) -> impl Iterator<Item = Transaction> + use<> {
+        self.rows().filter_map(|row| match row {
+            GeneratedRow::Valid(transaction) => Some(transaction),
+            GeneratedRow::Invalid(_) => None,
+        })
+    }
+    pub fn to_vec(&self) -> Vec<Transaction> {
+        self.transactions().collect()
+    }
+    /// Writes every row as CSV, with header.
+    pub fn write_csv(&self, writer: impl Write) -> std::io::Result<()> {
+        let mut writer = std::io::BufWriter::new(writer);
+        writeln!(writer, "type,client,tx,amount")?;
+        for row in self.rows() {
+            match row {
+                GeneratedRow::Valid(transaction) => write_transaction(&mut writer, &transaction)?,
+                GeneratedRow::Invalid(line) => writeln!(writer, "{line}")?,
+            }
+        }
+        writer.flush()
+    }
+}
+
+fn write_transaction(writer: &mut impl Write, transaction: &Transaction) -> std::io::Result<()> {
+    match transaction {
+        Transaction::Deposit(deposit) => writeln!(
+            writer,
+            "deposit,{},{},{}",
+            deposit.client_id(),
+            deposit.transaction_id(),
+            deposit.amount().value()
+        ),
+        Transaction::Withdrawal(withdrawal) => writeln!(
+            writer,
+            "withdrawal,{},{},{}",
+            withdrawal.client_id(),
+            withdrawal.transaction_id(),
+            withdrawal.amount().value()
+        ),
+        Transaction::Dispute(dispute) => writeln!(
+            writer,
+            "dispute,{},{},",
+            dispute.client_id(),
+            dispute.disputed_tx_id()
+        ),
+        Transaction::Resolve(resolve) => writeln!(
+            writer,
+            "resolve,{},{},",
+            resolve.client_id(),
+            resolve.disputed_tx_id()
+        ),
+        Transaction::Chargeback(chargeback) => writeln!(
+            writer,
+            "chargeback,{},{},",
+            chargeback.client_id(),
+            chargeback.disputed_tx_id()
+        ),
+        Transaction::Authorization(_)
+        | Transaction::Capture(_)
+        | Transaction::Void(_)
+        | Transaction::Refund(_)
+        | Transaction::Adjustment(_)
+        | Transaction::Evidence(_)
+        | Transaction::PreArbitration(_) => {
+            unreachable!("workloads only contain deposits, withdrawals, disputes and settlements")
+        }
+    }
+}
+
+/// SplitMix64: tiny, fast and good enough for workloads.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    /// Uniform in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+    /// Uniform in `[0, bound)`.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+const INVALID_ROWS: [&str; 5] = [
+    "transfer,{client},{tx},1.0",
+    "deposit,{client},{tx},",
+    "deposit,{client},{tx},-5",
+    "withdrawal,{client},{tx},1.00001",
+    "deposit,client,{tx},1.0",
+];
+
+/// Iterator over the rows of a [`Workload`], see [`Workload::rows`].
+#[derive(Debug, Clone)]
+pub struct Rows {
+    workload: Workload,
+    rng: Rng,
+    generated: u32,
+    /// Deposits that can still be disputed, and open disputes.
+    deposits: Vec<(ClientId, TransactionId)>,
+    disputes: Vec<(ClientId, TransactionId)>,
+    /// Wider than tx IDs, so it can step past the last one.
+    next_tx: u64,
+}
+
+impl Rows {
+    fn take_random(
+        rng: &mut Rng,
+        from: &mut Vec<(ClientId, TransactionId)>,
+    ) -> (ClientId, TransactionId) {
+        from.swap_remove(rng.below(from.len() as u64) as usize)
+    }
+}
+
+impl Iterator for Rows {
+    type Item = GeneratedRow;
+
+    fn next(&mut self) -> Option<GeneratedRow> {
+        if self.generated == self.workload.rows {
+            return None;
+        }
+        self.generated += 1;
+        let rng = &mut self.rng;
+        let workload = &self.workload;
+
+        let roll = rng.next_f64();
+        let client = ClientId::from(rng.below(u64::from(workload.clients.max(1))) as u16 + 1);
+        if roll < workload.invalid_rate {
+            let template = INVALID_ROWS[rng.below(INVALID_ROWS.len() as u64) as usize];
+            let line = template
+                .replace("{client}", &client.to_string())
+                .replace("{tx}", &self.next_tx.to_string());
+            self.next_tx += 1;
+            return Some(GeneratedRow::Invalid(line));
+        }
+        let roll = roll - workload.invalid_rate;
+        let transaction = if roll < workload.dispute_rate && !self.deposits.is_empty() {
+            let (client, tx) = Self::take_random(rng, &mut self.deposits);
+            self.disputes.push((client, tx));
+            Dispute::new(client, tx).into()
+        } else if roll < 2.0 * workload.dispute_rate && !self.disputes.is_empty() {
+            let (client, tx) = Self::take_random(rng, &mut self.disputes);
+            if rng.next_f64() < workload.chargeback_share {
+                Chargeback::new(client, tx).into()
+            } else {
+                self.deposits.push((client, tx));
+                Resolve::new(client, tx).into()
+            }
+        } else {
+            // Amounts spread over several orders of magnitude, with up to 4 decimals.
+            let scale = 10u64.pow(rng.below(4) as u32 + 2);
+            let units = rng.below(scale * 10_000) + 1;
+            let amount = Decimal::new(units as i64, 4);
+            let tx = TransactionId::from(self.next_tx as u32);
+            self.next_tx += 1;
+            if rng.below(10) < 7 {
+                self.deposits.push((client, tx));
+                Deposit::try_new(client, tx, amount)
+                    .expect("generated amounts are valid")
+                    .into()
+            } else {
+                Withdrawal::try_new(client, tx, amount)
+                    .expect("generated amounts are valid")
+                    .into()
+            }
+        };
+        Some(GeneratedRow::Valid(transaction))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.workload.rows - self.generated) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Rows {}
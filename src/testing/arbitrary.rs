@@ -0,0 +1,64 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+use rust_decimal::Decimal;
+
+use crate::{
+    domain::{
+        AMOUNT_MAX_SCALE, Adjustment, Amount, Authorization, Capture, Chargeback, ClientId,
+        Deposit, Dispute, Evidence, PreArbitration, Refund, Resolve, SignedAmount, Transaction,
+        TransactionId, Void, Withdrawal,
+    },
+    testing::MAX_AMOUNT_MANTISSA,
+};
+
+impl<'a> Arbitrary<'a> for ClientId {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u16::arbitrary(u).map(ClientId::from)
+    }
+}
+
+impl<'a> Arbitrary<'a> for TransactionId {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u32::arbitrary(u).map(TransactionId::from)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Amount {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_amount(u, 0)
+    }
+}
+
+/// Picks a mantissa in `min_mantissa..=MAX_AMOUNT_MANTISSA` and a valid scale.
+fn arbitrary_amount(u: &mut Unstructured<'_>, min_mantissa: i64) -> Result<Amount> {
+    let mantissa = u.int_in_range(min_mantissa..=MAX_AMOUNT_MANTISSA)?;
+    let scale = u.int_in_range(0..=AMOUNT_MAX_SCALE)?;
+    Amount::try_from(Decimal::new(mantissa, scale)).map_err(|_| arbitrary::Error::IncorrectFormat)
+}
+
+impl<'a> Arbitrary<'a> for Transaction {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let client = ClientId::arbitrary(u)?;
+        let tx = TransactionId::arbitrary(u)?;
+        let transaction = match u.int_in_range(0..=11u8)? {
+            0 => Deposit::new(client, tx, arbitrary_amount(u, 1)?).into(),
+            1 => Withdrawal::new(client, tx, arbitrary_amount(u, 1)?).into(),
+            2 => Dispute::new(client, tx).into(),
+            3 => Resolve::new(client, tx).into(),
+            4 => Chargeback::new(client, tx).into(),
+            5 => Authorization::new(client, tx, arbitrary_amount(u, 1)?).into(),
+            6 => Capture::new(client, tx).into(),
+            7 => Void::new(client, tx).into(),
+            8 => Refund::new(client, tx, arbitrary_amount(u, 1)?).into(),
+            9 => Evidence::new(client, tx).into(),
+            10 => PreArbitration::new(client, tx).into(),
+            _ => {
+                let amount = arbitrary_amount(u, 1)?.value();
+                let amount = if bool::arbitrary(u)? { -amount } else { amount };
+                let amount = SignedAmount::try_from(amount)
+                    .map_err(|_| arbitrary::Error::IncorrectFormat)?;
+                Adjustment::new(client, tx, amount, u16::arbitrary(u)?).into()
+            }
+        };
+        Ok(transaction)
+    }
+}
@@ -0,0 +1,83 @@
+//! Replays the same transactions through [`PaymentsEngine`] and the
+//! [`ReferenceEngine`] and reports every place where they disagree.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    domain::{Account, ClientId, Transaction},
+    engine::{DisputeHistoryStore, PaymentsEngine, errors::EngineError},
+    testing::reference::ReferenceEngine,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum Divergence {
+    /// One engine accepted the transaction and the other rejected it.
+    Outcome {
+        index: usize,
+        transaction: Transaction,
+        engine: Result<(), EngineError>,
+        reference_accepted: bool,
+    },
+    /// The final state of an account differs. `None` means the engine has no account for the client.
+    Account {
+        client: ClientId,
+        engine: Option<Account>,
+        reference: Option<Account>,
+    },
+}
+
+/// Runs `transactions` through a default [`PaymentsEngine`] and the reference engine.
+/// An empty result means they agree on every outcome and on every final account.
+pub fn diff_against_reference(transactions: &[Transaction]) -> Vec<Divergence> {
+    diff_engine(&mut PaymentsEngine::new(), transactions)
+}
+
+/// Same as [`diff_against_reference`], for an engine built elsewhere (e.g. over a custom
+/// [`DisputeHistoryStore`]). It should be fresh and use the default config, since that's
+/// all the reference models.
+pub fn diff_engine<H: DisputeHistoryStore>(
+    engine: &mut PaymentsEngine<H>,
+    transactions: &[Transaction],
+) -> Vec<Divergence> {
+    let mut reference = ReferenceEngine::new();
+    let mut divergences = Vec::new();
+
+    for (index, transaction) in transactions.iter().enumerate() {
+        let engine_result = engine.process_transaction(transaction.clone());
+        let reference_accepted = reference.process_transaction(transaction.clone());
+        if engine_result.is_ok() != reference_accepted {
+            divergences.push(Divergence::Outcome {
+                index,
+                transaction: transaction.clone(),
+                engine: engine_result,
+                reference_accepted,
+            });
+        }
+    }
+
+    let engine_accounts = engine
+        .client_accounts()
+        .iter()
+        .map(|(client, account)| (client, account.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let mut reference_accounts = reference.accounts();
+    let clients = engine_accounts
+        .keys()
+        .chain(reference_accounts.keys())
+        .copied()
+        .collect::<BTreeSet<_>>();
+
+    for client in clients {
+        let engine_account = engine_accounts.get(&client).cloned();
+        let reference_account = reference_accounts.remove(&client);
+        if engine_account != reference_account {
+            divergences.push(Divergence::Account {
+                client,
+                engine: engine_account,
+                reference: reference_account,
+            });
+        }
+    }
+
+    divergences
+}
@@ -0,0 +1,25 @@
+//! Generators for fuzzing and property-testing integrations, behind the `testing` feature.
+//!
+//! - [`arbitrary::Arbitrary`] is implemented for [`ClientId`](crate::domain::ClientId),
+//!   [`TransactionId`](crate::domain::TransactionId), [`Amount`](crate::domain::Amount)
+//!   and [`Transaction`](crate::domain::Transaction), for cargo-fuzz style targets.
+//! - [`strategies`] has the equivalent `proptest` strategies.
+//! - [`reference`] is a naive, obviously-correct engine, and [`differential`] diffs
+//!   [`PaymentsEngine`](crate::engine::PaymentsEngine) against it.
+//! - [`assert_accounts_snapshot`] compares final accounts against golden files.
+//!
+//! Generated transactions always satisfy the domain's validation rules (e.g. movement
+//! amounts are positive with at most 4 decimal places), but aren't necessarily
+//! accepted by the engine - a dispute may reference a tx that doesn't exist.
+
+mod arbitrary;
+pub mod differential;
+pub mod reference;
+mod snapshot;
+pub mod strategies;
+
+pub use snapshot::{UPDATE_SNAPSHOTS_VAR, accounts_snapshot, assert_accounts_snapshot};
+
+/// Largest generated amount mantissa: amounts stay at or below 10^12 (10^16 units with
+/// the `fixed-point` feature), so summing a long stream of them can't overflow a balance.
+const MAX_AMOUNT_MANTISSA: i64 = 1_000_000_000_000;
@@ -0,0 +1,283 @@
+//! Deliberately naive engine used as an oracle in differential tests.
+//!
+//! It keeps every transaction it has seen and recomputes everything from that log:
+//! there's no per-deposit state, no account map, and every lookup is a linear scan
+//! (quadratic overall). Slow, but each rule reads like the README describes it.
+//! Only the default [`EngineConfig`](crate::engine::EngineConfig) behavior is modeled.
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+
+use crate::domain::{Account, Balance, ClientId, DisputeState, Transaction, TransactionId};
+
+#[derive(Debug, Default)]
+pub struct ReferenceEngine {
+    /// Every transaction processed so far, with whether it was accepted.
+    log: Vec<(Transaction, bool)>,
+}
+
+impl ReferenceEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies a transaction and returns whether it was accepted.
+    pub fn process_transaction(&mut self, transaction: Transaction) -> bool {
+        let accepted = self.accepts(&transaction);
+        self.log.push((transaction, accepted));
+        accepted
+    }
+
+    /// Every client that appeared in any transaction, accepted or not - like the real
+    /// engine, a rejected transaction still creates an empty account.
+    pub fn accounts(&self) -> BTreeMap<ClientId, Account> {
+        self.log
+            .iter()
+            .map(|(transaction, _)| transaction.client_id())
+            .map(|client| (client, self.account(client)))
+            .collect()
+    }
+
+    /// Replays the client's accepted transactions from scratch.
+    pub fn account(&self, client: ClientId) -> Account {
+        let (mut available, mut held, mut authorized) =
+            (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO);
+        let mut locked = false;
+        for (index, transaction) in self.accepted() {
+            if transaction.client_id() != client {
+                continue;
+            }
+            match transaction {
+                Transaction::Deposit(deposit) => available += deposit.amount().value(),
+                Transaction::Withdrawal(withdrawal) => available -= withdrawal.amount().value(),
+                Transaction::Dispute(dispute) => {
+                    let amount = self.deposit_amount_at(index, dispute.disputed_tx_id());
+                    available -= amount;
+                    held += amount;
+                }
+                Transaction::Resolve(resolve) => {
+                    let amount = self.deposit_amount_at(index, resolve.disputed_tx_id());
+                    held -= amount;
+                    available += amount;
+                }
+                Transaction::Chargeback(chargeback) => {
+                    held -= self.deposit_amount_at(index, chargeback.disputed_tx_id());
+                    locked = true;
+                }
+                Transaction::Authorization(authorization) => {
+                    available -= authorization.amount().value();
+                    authorized += authorization.amount().value();
+                }
+                Transaction::Capture(capture) => {
+                    authorized -=
+                        self.authorization_amount_at(index, capture.authorization_tx_id());
+                }
+                Transaction::Void(void) => {
+                    let amount = self.authorization_amount_at(index, void.authorization_tx_id());
+                    authorized -= amount;
+                    available += amount;
+                }
+                Transaction::Refund(refund) => available -= refund.amount().value(),
+                Transaction::Adjustment(adjustment) => available += adjustment.amount().value(),
+                Transaction::Evidence(_) | Transaction::PreArbitration(_) => {}
+            }
+        }
+        Account::new(
+            Balance::new(available, held).with_authorized(authorized),
+            locked,
+        )
+    }
+
+    fn accepts(&self, transaction: &Transaction) -> bool {
+        if has_own_tx_id(transaction)
+            && self.log.iter().any(|(earlier, _)| {
+                has_own_tx_id(earlier) && earlier.tx_id() == transaction.tx_id()
+            })
+        {
+            return false;
+        }
+        let client = transaction.client_id();
+        let account = self.account(client);
+        match transaction {
+            Transaction::Deposit(_) => !account.is_locked(),
+            Transaction::Withdrawal(withdrawal) => {
+                !account.is_locked() && account.balance().available() >= withdrawal.amount().value()
+            }
+            Transaction::Authorization(authorization) => {
+                !account.is_locked()
+                    && account.balance().available() >= authorization.amount().value()
+            }
+            Transaction::Dispute(dispute) => {
+                matches!(
+                    self.dispute_state(client, dispute.disputed_tx_id()),
+                    Some(DisputeState::None | DisputeState::Resolved)
+                ) && self.deposit_amount_at(self.log.len(), dispute.disputed_tx_id())
+                    > Decimal::ZERO
+            }
+            Transaction::Resolve(resolve) => self
+                .dispute_state(client, resolve.disputed_tx_id())
+                .is_some_and(DisputeState::is_open),
+            Transaction::Chargeback(chargeback) => self
+                .dispute_state(client, chargeback.disputed_tx_id())
+                .is_some_and(DisputeState::is_open),
+            Transaction::Evidence(evidence) => {
+                self.dispute_state(client, evidence.disputed_tx_id()) == Some(DisputeState::Open)
+            }
+            Transaction::PreArbitration(pre_arbitration) => {
+                self.dispute_state(client, pre_arbitration.disputed_tx_id())
+                    == Some(DisputeState::EvidenceSubmitted)
+            }
+            Transaction::Capture(capture) => {
+                self.is_pending_authorization(client, capture.authorization_tx_id())
+            }
+            Transaction::Void(void) => {
+                self.is_pending_authorization(client, void.authorization_tx_id())
+            }
+            Transaction::Adjustment(_) => true,
+            Transaction::Refund(refund) => {
+                !account.is_locked()
+                    && matches!(
+                        self.dispute_state(client, refund.deposit_tx_id()),
+                        Some(DisputeState::None | DisputeState::Resolved)
+                    )
+                    && self.deposit_amount_at(self.log.len(), refund.deposit_tx_id())
+                        >= refund.amount().value()
+                    && account.balance().available() >= refund.amount().value()
+            }
+        }
+    }
+
+    fn accepted(&self) -> impl Iterator<Item = (usize, &Transaction)> {
+        self.log
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, accepted))| *accepted)
+            .map(|(index, (transaction, _))| (index, transaction))
+    }
+
+    /// Index of the accepted deposit with this tx ID. IDs are unique, so there's at most
+    /// one: a later transaction reusing the ID is never accepted.
+    fn latest_deposit(&self, tx: TransactionId) -> Option<(usize, ClientId)> {
+        self.accepted()
+            .filter_map(|(index, transaction)| match transaction {
+                Transaction::Deposit(deposit) if deposit.transaction_id() == tx => {
+                    Some((index, deposit.client_id()))
+                }
+                _ => None,
+            })
+            .last()
+    }
+
+    /// Same as [`Self::latest_deposit`], for authorizations.
+    fn latest_authorization(&self, tx: TransactionId) -> Option<(usize, ClientId)> {
+        self.accepted()
+            .filter_map(|(index, transaction)| match transaction {
+                Transaction::Authorization(authorization)
+                    if authorization.transaction_id() == tx =>
+                {
+                    Some((index, authorization.client_id()))
+                }
+                _ => None,
+            })
+            .last()
+    }
+
+    /// Amount of the deposit a dispute-family transaction at `index` referred to, minus
+    /// what was refunded of it before that.
+    fn deposit_amount_at(&self, index: usize, tx: TransactionId) -> Decimal {
+        let mut amount = None;
+        for (_, transaction) in self.accepted().take_while(|(i, _)| *i < index) {
+            match transaction {
+                Transaction::Deposit(deposit) if deposit.transaction_id() == tx => {
+                    amount = Some(deposit.amount().value());
+                }
+                Transaction::Refund(refund) if refund.deposit_tx_id() == tx => {
+                    amount = amount.map(|amount| amount - refund.amount().value());
+                }
+                _ => {}
+            }
+        }
+        amount.expect("accepted dispute-family transactions reference a deposit")
+    }
+
+    /// Amount of the authorization a capture or void at `index` referred to.
+    fn authorization_amount_at(&self, index: usize, tx: TransactionId) -> Decimal {
+        self.accepted()
+            .take_while(|(i, _)| *i < index)
+            .filter_map(|(_, transaction)| match transaction {
+                Transaction::Authorization(authorization)
+                    if authorization.transaction_id() == tx =>
+                {
+                    Some(authorization.amount().value())
+                }
+                _ => None,
+            })
+            .last()
+            .expect("accepted captures and voids reference an authorization")
+    }
+
+    /// Dispute state of the client's current deposit `tx`, derived from the accepted
+    /// dispute-family transactions since that deposit. `None` if there's no such deposit.
+    fn dispute_state(&self, client: ClientId, tx: TransactionId) -> Option<DisputeState> {
+        let (deposit_index, owner) = self.latest_deposit(tx)?;
+        if owner != client {
+            return None;
+        }
+        let state = self
+            .accepted()
+            .filter(|(index, _)| *index > deposit_index)
+            .fold(
+                DisputeState::None,
+                |state, (_, transaction)| match transaction {
+                    Transaction::Dispute(dispute) if dispute.disputed_tx_id() == tx => {
+                        DisputeState::Open
+                    }
+                    Transaction::Evidence(evidence) if evidence.disputed_tx_id() == tx => {
+                        DisputeState::EvidenceSubmitted
+                    }
+                    Transaction::PreArbitration(pre_arbitration)
+                        if pre_arbitration.disputed_tx_id() == tx =>
+                    {
+                        DisputeState::PreArbitration
+                    }
+                    Transaction::Resolve(resolve) if resolve.disputed_tx_id() == tx => {
+                        DisputeState::Resolved
+                    }
+                    Transaction::Chargeback(chargeback) if chargeback.disputed_tx_id() == tx => {
+                        DisputeState::ChargedBack
+                    }
+                    _ => state,
+                },
+            );
+        Some(state)
+    }
+
+    /// Whether the client's current authorization `tx` hasn't been captured or voided yet.
+    fn is_pending_authorization(&self, client: ClientId, tx: TransactionId) -> bool {
+        let Some((authorization_index, owner)) = self.latest_authorization(tx) else {
+            return false;
+        };
+        owner == client
+            && !self
+                .accepted()
+                .filter(|(index, _)| *index > authorization_index)
+                .any(|(_, transaction)| match transaction {
+                    Transaction::Capture(capture) => capture.authorization_tx_id() == tx,
+                    Transaction::Void(void) => void.authorization_tx_id() == tx,
+                    _ => false,
+                })
+    }
+}
+
+/// Deposits, withdrawals, authorizations and adjustments introduce their tx ID; every
+/// other transaction references one.
+fn has_own_tx_id(transaction: &Transaction) -> bool {
+    matches!(
+        transaction,
+        Transaction::Deposit(_)
+            | Transaction::Withdrawal(_)
+            | Transaction::Authorization(_)
+            | Transaction::Adjustment(_)
+    )
+}
@@ -0,0 +1,106 @@
+//! Golden-file snapshots of final account state.
+//!
+//! A snapshot has the required output columns followed by the authorized, pending and
+//! reserve funds, so it covers every balance bucket. It's ordered by client, with amounts
+//! normalized (`1.5`, `0`) so golden files don't depend on the scale the arithmetic happened
+//! to produce or on the `fixed-point` feature. Set `UPDATE_SNAPSHOTS=1` to rewrite the
+//! golden files instead of comparing against them.
+
+use std::{fs, path::Path};
+
+use crate::engine::{DisputeHistoryStore, PaymentsEngine};
+
+/// Environment variable that makes [`assert_accounts_snapshot`] write instead of compare.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// The accounts of `engine` as CSV in the snapshot's columns, sorted by client.
+pub fn accounts_snapshot<H: DisputeHistoryStore>(engine: &PaymentsEngine<H>) -> String {
+    let mut snapshot =
+        String::from("client,available,held,total,locked,authorized,pending,reserve\n");
+    for (client, account) in engine.client_accounts().iter_sorted() {
+        let balance = account.balance();
+        snapshot.push_str(&format!(
+            "{client},{},{},{},{},{},{},{}\n",
+            balance.available().normalize(),
+            balance.held().normalize(),
+            balance.total().normalize(),
+            account.is_locked(),
+            balance.authorized().normalize(),
+            balance.pending().normalize(),
+            balance.reserve().normalize(),
+        ));
+    }
+    snapshot
+}
+
+/// Compares the accounts of `engine` against the golden file at `path`.
+///
+/// # Panics
+///
+/// If the snapshot differs, listing the differing lines. A missing golden file is written
+/// and still fails the assertion, so new snapshots get reviewed before they pass. With
+/// `UPDATE_SNAPSHOTS` set, the file is (re)written and the assertion passes.
+#[track_caller]
+pub fn assert_accounts_snapshot<H: DisputeHistoryStore>(
+    engine: &PaymentsEngine<H>,
+    path: impl AsRef<Path>,
+) {
+    let path = path.as_ref();
+    let actual = accounts_snapshot(engine);
+    let update = std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some_and(|value| !value.is_empty());
+
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) if !update => expected,
+        Ok(_) => return write(path, &actual),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            write(path, &actual);
+            if update {
+                return;
+            }
+            panic!(
+                "No snapshot at {}; wrote the current accounts there, review it and re-run",
+                path.display()
+            );
+        }
+        Err(e) => panic!("Failed to read snapshot {}: {e}", path.display()),
+    };
+    // Golden files checked out on Windows may have CRLF line endings.
+    let expected = expected.replace("\r\n", "\n");
+    if expected != actual {
+        panic!(
+            "Accounts differ from snapshot {} (re-run with {UPDATE_SNAPSHOTS_VAR}=1 to update):\n{}",
+            path.display(),
+            line_diff(&expected, &actual)
+        );
+    }
+}
+
+fn write(path: &Path, snapshot: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .unwrap_or_else(|e| panic!("Failed to create {}: {e}", parent.display()));
+    }
+    fs::write(path, snapshot)
+        .unwrap_or_else(|e| panic!("Failed to write snapshot {}: {e}", path.display()));
+}
+
+/// Lines only in `expected` prefixed with `-`, lines only in `actual` with `+`, matched by
+/// position: rows are sorted by client, so a changed account shows up as a `-`/`+` pair.
+fn line_diff(expected: &str, actual: &str) -> String {
+    let (mut expected, mut actual) = (expected.lines(), actual.lines());
+    let mut diff = String::new();
+    loop {
+        match (expected.next(), actual.next()) {
+            (None, None) => return diff,
+            (Some(expected), Some(actual)) if expected == actual => {}
+            (expected, actual) => {
+                if let Some(expected) = expected {
+                    diff.push_str(&format!("-{expected}\n"));
+                }
+                if let Some(actual) = actual {
+                    diff.push_str(&format!("+{actual}\n"));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! `proptest` strategies for the domain types.
+
+use std::ops::RangeInclusive;
+
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+
+use crate::{
+    domain::{
+        AMOUNT_MAX_SCALE, Adjustment, Amount, Authorization, Capture, Chargeback, ClientId,
+        Deposit, Dispute, Evidence, PreArbitration, Refund, Resolve, SignedAmount, Transaction,
+        TransactionId, Void, Withdrawal,
+    },
+    testing::MAX_AMOUNT_MANTISSA,
+};
+
+pub fn client_id() -> impl Strategy<Value = ClientId> {
+    any::<u16>().prop_map(ClientId::from)
+}
+
+pub fn transaction_id() -> impl Strategy<Value = TransactionId> {
+    any::<u32>().prop_map(TransactionId::from)
+}
+
+/// Any valid [`Amount`], zero included.
+pub fn amount() -> impl Strategy<Value = Amount> {
+    amount_from(0)
+}
+
+/// Amounts accepted by deposits, withdrawals and authorizations.
+pub fn positive_amount() -> impl Strategy<Value = Amount> {
+    amount_from(1)
+}
+
+/// A positive or negative amount, never zero - what adjustments carry.
+pub fn nonzero_signed_amount() -> impl Strategy<Value = SignedAmount> {
+    (positive_amount(), any::<bool>()).prop_map(|(amount, negative)| {
+        let value = if negative {
+            -amount.value()
+        } else {
+            amount.value()
+        };
+        SignedAmount::try_from(value).expect("amounts are valid signed amounts")
+    })
+}
+
+fn amount_from(min_mantissa: i64) -> impl Strategy<Value = Amount> {
+    (min_mantissa..=MAX_AMOUNT_MANTISSA, 0..=AMOUNT_MAX_SCALE).prop_map(|(mantissa, scale)| {
+        Amount::try_from(Decimal::new(mantissa, scale)).expect("mantissa and scale are in range")
+    })
+}
+
+/// Any transaction kind, with IDs drawn from the full ID spaces.
+pub fn transaction() -> impl Strategy<Value = Transaction> {
+    transaction_in(0..=u16::MAX, 0..=u32::MAX)
+}
+
+/// Any transaction kind with IDs drawn from the given ranges. Narrow ranges make
+/// disputes, captures, voids and refunds likely to reference earlier transactions.
+pub fn transaction_in(
+    clients: RangeInclusive<u16>,
+    txs: RangeInclusive<u32>,
+) -> impl Strategy<Value = Transaction> {
+    let ids = (
+        clients.prop_map(ClientId::from),
+        txs.prop_map(TransactionId::from),
+    );
+    prop_oneof![
+        (ids.clone(), positive_amount())
+            .prop_map(|((client, tx), amount)| Deposit::new(client, tx, amount).into()),
+        (ids.clone(), positive_amount())
+            .prop_map(|((client, tx), amount)| Withdrawal::new(client, tx, amount).into()),
+        ids.clone()
+            .prop_map(|(client, tx)| Dispute::new(client, tx).into()),
+        ids.clone()
+            .prop_map(|(client, tx)| Resolve::new(client, tx).into()),
+        ids.clone()
+            .prop_map(|(client, tx)| Chargeback::new(client, tx).into()),
+        (ids.clone(), positive_amount())
+            .prop_map(|((client, tx), amount)| Authorization::new(client, tx, amount).into()),
+        ids.clone()
+            .prop_map(|(client, tx)| Capture::new(client, tx).into()),
+        ids.clone()
+            .prop_map(|(client, tx)| Void::new(client, tx).into()),
+        (ids.clone(), positive_amount())
+            .prop_map(|((client, tx), amount)| Refund::new(client, tx, amount).into()),
+        ids.clone()
+            .prop_map(|(client, tx)| Evidence::new(client, tx).into()),
+        ids.clone()
+            .prop_map(|(client, tx)| PreArbitration::new(client, tx).into()),
+        (ids, nonzero_signed_amount(), any::<u16>()).prop_map(|((client, tx), amount, reason)| {
+            Adjustment::new(client, tx, amount, reason).into()
+        }),
+    ]
+}
@@ -0,0 +1,149 @@
+//! Time-bucketed volume for the `volume` subcommand: applied deposits and withdrawals per
+//! client per hour or day, for inputs with a `timestamp` column (Unix seconds).
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{
+    domain::{ClientId, Transaction},
+    engine::{ApplyTransactions, EngineConfig, Outcome, PaymentsEngine},
+    parsing::TimestampedTransaction,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketWidth {
+    Hourly,
+    Daily,
+}
+
+impl BucketWidth {
+    pub fn seconds(self) -> u64 {
+        match self {
+            BucketWidth::Hourly => 60 * 60,
+            BucketWidth::Daily => 24 * 60 * 60,
+        }
+    }
+    /// Start of the bucket containing `timestamp`, in Unix seconds (UTC).
+    pub fn bucket_start(self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.seconds()
+    }
+}
+
+/// Applied deposits and withdrawals of one client within one bucket.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Volume {
+    pub deposits: Decimal,
+    pub deposit_count: usize,
+    pub withdrawals: Decimal,
+    pub withdrawal_count: usize,
+}
+
+/// A row of the volume report.
+#[derive(Debug, Serialize)]
+struct VolumeRow {
+    bucket_start: u64,
+    client: ClientId,
+    deposits: Decimal,
+    deposit_count: usize,
+    withdrawals: Decimal,
+    withdrawal_count: usize,
+}
+
+/// Runs a [`PaymentsEngine`] and sums the deposits and withdrawals it applies per client
+/// and bucket. Transactions without a timestamp are applied but not aggregated.
+#[derive(Debug, Clone)]
+pub struct VolumeAggregator {
+    engine: PaymentsEngine,
+    width: BucketWidth,
+    /// Keyed by bucket start, then client, which is also the report order.
+    buckets: BTreeMap<(u64, ClientId), Volume>,
+    untimestamped: usize,
+}
+
+impl VolumeAggregator {
+    pub fn new(config: EngineConfig, width: BucketWidth) -> Self {
+        Self {
+            engine: PaymentsEngine::with_config(config),
+            width,
+            buckets: BTreeMap::new(),
+            untimestamped: 0,
+        }
+    }
+    pub fn engine(&self) -> &PaymentsEngine {
+        &self.engine
+    }
+    pub fn buckets(&self) -> &BTreeMap<(u64, ClientId), Volume> {
+        &self.buckets
+    }
+    /// Applied deposits and withdrawals that had no timestamp.
+    pub fn untimestamped(&self) -> usize {
+        self.untimestamped
+    }
+    /// Writes one CSV row per client and bucket with volume, ordered by bucket then client.
+    /// The header is written even without rows.
+    pub fn write_report(&self, writer: impl std::io::Write) -> anyhow::Result<()> {
+        let mut wtr = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(writer);
+        wtr.write_record([
+            "bucket_start",
+            "client",
+            "deposits",
+            "deposit_count",
+            "withdrawals",
+            "withdrawal_count",
+        ])?;
+        for ((bucket_start, client), volume) in &self.buckets {
+            wtr.serialize(VolumeRow {
+                bucket_start: *bucket_start,
+                client: *client,
+                deposits: volume.deposits,
+                deposit_count: volume.deposit_count,
+                withdrawals: volume.withdrawals,
+                withdrawal_count: volume.withdrawal_count,
+            })?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl ApplyTransactions for VolumeAggregator {
+    type Transaction = TimestampedTransaction;
+
+    fn apply(&mut self, timestamped: TimestampedTransaction) -> Outcome {
+        let TimestampedTransaction {
+            timestamp,
+            transaction,
+        } = timestamped;
+        let client = transaction.client_id();
+        let movement = match &transaction {
+            Transaction::Deposit(deposit) => Some((true, deposit.amount().value())),
+            Transaction::Withdrawal(withdrawal) => Some((false, withdrawal.amount().value())),
+            _ => None,
+        };
+
+        let outcome = self.engine.apply(transaction);
+        let Some((is_deposit, amount)) = movement.filter(|_| outcome == Outcome::Applied) else {
+            return outcome;
+        };
+        let Some(timestamp) = timestamp else {
+            self.untimestamped += 1;
+            return outcome;
+        };
+        let volume = self
+            .buckets
+            .entry((self.width.bucket_start(timestamp), client))
+            .or_default();
+        if is_deposit {
+            volume.deposits += amount;
+            volume.deposit_count += 1;
+        } else {
+            volume.withdrawals += amount;
+            volume.withdrawal_count += 1;
+        }
+        outcome
+    }
+}
@@ -0,0 +1,124 @@
+use std::process::Command;
+
+use rust_coding_test::{
+    domain::{Adjustment, Chargeback, Deposit, Dispute, DomainError, Transaction},
+    engine::{PaymentsEngine, errors::EngineError},
+    parsing::{self, ParseError},
+    rejection::RejectionReason,
+};
+use rust_decimal::{Decimal, dec};
+
+fn adjustment(client: u16, tx: u32, value: Decimal, reason: u16) -> Transaction {
+    Adjustment::try_new(client.into(), tx.into(), value, reason)
+        .unwrap()
+        .into()
+}
+
+#[test]
+fn adjustments_correct_available_even_on_locked_accounts() {
+    let mut engine = PaymentsEngine::new();
+    engine.process_transactions(
+        [
+            Deposit::try_new(1.into(), 1.into(), dec!(1.5))
+                .unwrap()
+                .into(),
+            adjustment(1, 2, dec!(2.25), 7),
+            Dispute::new(1.into(), 1.into()).into(),
+            Chargeback::new(1.into(), 1.into()).into(),
+            adjustment(1, 3, dec!(-3.75), 7),
+        ]
+        .into_iter(),
+    );
+
+    let account = engine.client_accounts().get(1.into()).unwrap();
+    assert!(account.is_locked());
+    assert_eq!(account.balance().available(), dec!(-1.5));
+    assert_eq!(account.balance().total(), dec!(-1.5));
+}
+
+#[test]
+fn adjustments_cannot_be_disputed() {
+    let mut engine = PaymentsEngine::new();
+    engine
+        .process_transaction(adjustment(1, 1, dec!(1.5), 7))
+        .unwrap();
+
+    assert_eq!(
+        engine.process_transaction(Dispute::new(1.into(), 1.into()).into()),
+        Err(EngineError::TransactionNotFound {
+            client: 1.into(),
+            tx: 1.into(),
+        })
+    );
+}
+
+#[test]
+fn zero_adjustments_are_invalid() {
+    assert!(matches!(
+        Adjustment::try_new(1.into(), 1.into(), dec!(0), 7),
+        Err(DomainError::ZeroAdjustment)
+    ));
+    assert!(matches!(
+        Adjustment::try_new(1.into(), 1.into(), dec!(-0.00001), 7),
+        Err(DomainError::ExcessivePrecision)
+    ));
+}
+
+#[test]
+fn adjustment_rows_are_parsed() {
+    let input = b"type,client,tx,amount,reason\n\
+                  adjustment,1,1,-2.5,7\n\
+                  adjustment,1,2,1.5,\n\
+                  adjustment,1,3,0,7\n\
+                  deposit,1,4,-1.5,\n\
+                  deposit,1,5,1.5,\n";
+
+    let (transactions, errors) = parsing::parse_bytes(input);
+
+    assert_eq!(
+        transactions,
+        vec![
+            adjustment(1, 1, dec!(-2.5), 7),
+            Deposit::try_new(1.into(), 5.into(), dec!(1.5))
+                .unwrap()
+                .into(),
+        ]
+    );
+    let reasons = errors.iter().map(ParseError::reason).collect::<Vec<_>>();
+    assert_eq!(
+        reasons,
+        [
+            RejectionReason::MissingReason,
+            RejectionReason::InvalidAmount,
+            RejectionReason::InvalidAmount,
+        ]
+    );
+}
+
+#[test]
+fn cli_writes_adjustments_to_the_audit_log() {
+    let input = std::env::temp_dir().join(format!("adjustment_tests_{}.csv", std::process::id()));
+    std::fs::write(
+        &input,
+        "type,client,tx,amount,reason\ndeposit,1,1,1.5,\nadjustment,1,2,2.25,7\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg(&input)
+        .arg("INFO")
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    std::fs::remove_file(&input).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let audit = stderr
+        .lines()
+        .filter(|line| line.contains("audit:"))
+        .collect::<Vec<_>>();
+
+    assert!(output.status.success());
+    assert_eq!(audit.len(), 1, "{stderr}");
+    assert!(audit[0].contains("Adjustment applied"), "{}", audit[0]);
+    assert!(audit[0].contains("reason=7"), "{}", audit[0]);
+}
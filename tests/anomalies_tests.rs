@@ -0,0 +1,161 @@
+use rust_coding_test::{
+    anomalies::{Anomaly, AnomalyDetector, AnomalyKind, AnomalyThresholds, write_anomalies},
+    domain::{Deposit, Dispute, Resolve, Transaction, Withdrawal},
+    engine::{ApplyTransactions, EngineConfig},
+};
+use rust_decimal::{Decimal, dec};
+
+fn deposit(client: u16, tx: u32, amount: Decimal) -> Transaction {
+    Deposit::try_new(client.into(), tx.into(), amount)
+        .unwrap()
+        .into()
+}
+
+fn dispute(client: u16, tx: u32) -> Transaction {
+    Dispute::new(client.into(), tx.into()).into()
+}
+
+fn detect(thresholds: AnomalyThresholds, transactions: Vec<Transaction>) -> Vec<Anomaly> {
+    let mut detector = AnomalyDetector::new(EngineConfig::default(), thresholds);
+    detector.apply_all(transactions.into_iter());
+    detector.anomalies().to_vec()
+}
+
+/// Only the kind under test can trigger.
+fn quiet() -> AnomalyThresholds {
+    AnomalyThresholds {
+        large_deposit_sigmas: 4.0,
+        large_deposit_min_samples: u64::MAX,
+        balance_swing_ratio: Decimal::MAX,
+        dispute_burst: usize::MAX,
+        dispute_window: 1,
+    }
+}
+
+#[test]
+fn large_deposit_after_enough_samples() {
+    let thresholds = AnomalyThresholds {
+        large_deposit_min_samples: 4,
+        ..quiet()
+    };
+    // Each client deposits once, so no account is involved twice.
+    let mut transactions: Vec<_> = (1..=4)
+        .map(|n| deposit(n, n.into(), dec!(10) + Decimal::from(n)))
+        .collect();
+    transactions.push(deposit(5, 5, dec!(12.5)));
+    transactions.push(deposit(6, 6, dec!(500.25)));
+
+    assert_eq!(
+        detect(thresholds, transactions),
+        vec![Anomaly {
+            sequence: 6,
+            client: 6.into(),
+            kind: AnomalyKind::LargeDeposit,
+            value: dec!(500.25),
+        }]
+    );
+}
+
+#[test]
+fn no_large_deposit_below_min_samples() {
+    let thresholds = AnomalyThresholds {
+        large_deposit_min_samples: 10,
+        ..quiet()
+    };
+    let transactions = vec![
+        deposit(1, 1, dec!(1)),
+        deposit(2, 2, dec!(1.5)),
+        deposit(3, 3, dec!(1000)),
+    ];
+
+    assert!(detect(thresholds, transactions).is_empty());
+}
+
+#[test]
+fn balance_swings_in_both_directions() {
+    let thresholds = AnomalyThresholds {
+        balance_swing_ratio: dec!(0.5),
+        ..quiet()
+    };
+    let transactions = vec![
+        // Skipped: nothing before.
+        deposit(1, 1, dec!(10)),
+        deposit(1, 2, dec!(4.5)),
+        deposit(1, 3, dec!(10)),
+        Withdrawal::try_new(1.into(), 4.into(), dec!(20.25))
+            .unwrap()
+            .into(),
+        // Rejected, so not a swing.
+        Withdrawal::try_new(1.into(), 5.into(), dec!(1000))
+            .unwrap()
+            .into(),
+    ];
+
+    let swings: Vec<_> = detect(thresholds, transactions)
+        .into_iter()
+        .map(|anomaly| (anomaly.sequence, anomaly.kind, anomaly.value))
+        .collect();
+    assert_eq!(
+        swings,
+        vec![
+            (3, AnomalyKind::BalanceSwing, dec!(10)),
+            (4, AnomalyKind::BalanceSwing, dec!(-20.25)),
+        ]
+    );
+}
+
+#[test]
+fn dispute_bursts_within_window() {
+    let thresholds = AnomalyThresholds {
+        dispute_burst: 2,
+        dispute_window: 3,
+        ..quiet()
+    };
+    let transactions = vec![
+        deposit(1, 1, dec!(1.5)),
+        deposit(1, 2, dec!(1.5)),
+        deposit(1, 3, dec!(1.5)),
+        dispute(1, 1),
+        Resolve::new(1.into(), 1.into()).into(),
+        deposit(2, 4, dec!(1.5)),
+        // Three transactions after the first dispute: outside the window.
+        dispute(1, 2),
+        dispute(1, 3),
+    ];
+
+    assert_eq!(
+        detect(thresholds, transactions),
+        vec![Anomaly {
+            sequence: 8,
+            client: 1.into(),
+            kind: AnomalyKind::DisputeBurst,
+            value: dec!(2),
+        }]
+    );
+}
+
+#[test]
+fn report_csv() {
+    let mut output = Vec::new();
+    write_anomalies(
+        &[Anomaly {
+            sequence: 3,
+            client: 7.into(),
+            kind: AnomalyKind::BalanceSwing,
+            value: dec!(-2.25),
+        }],
+        &mut output,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "sequence,client,kind,value\n3,7,balance_swing,-2.25\n"
+    );
+
+    let mut output = Vec::new();
+    write_anomalies(&[], &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "sequence,client,kind,value\n"
+    );
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "async")]
+
+use rust_coding_test::{async_pipeline, engine::EngineConfig, output, parsing};
+use std::fmt::Write;
+use tokio::io::AsyncWriteExt;
+
+/// Enough rows to span several chunks, with disputes crossing chunk boundaries.
+fn large_input() -> String {
+    let mut input = String::from("type, client, tx, amount\n");
+    for tx in 1..=40_000u32 {
+        let client = tx % 50;
+        writeln!(input, "deposit, {client}, {tx}, {}.25", tx % 100).unwrap();
+        if tx % 7 == 0 {
+            writeln!(input, "dispute, {client}, {}, ", tx - 3).unwrap();
+        }
+    }
+    input
+}
+
+fn sync_output(input: &str) -> String {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+    let mut engine = rust_coding_test::engine::PaymentsEngine::new();
+    engine.process_transactions(parsing::deserialize_csv(&mut rdr));
+    let mut output = Vec::new();
+    output::print_accounts(engine.client_accounts(), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+async fn async_output(input: String) -> String {
+    let mut output = Vec::new();
+    async_pipeline::run(
+        std::io::Cursor::new(input.into_bytes()),
+        &mut output,
+        EngineConfig::default(),
+    )
+    .await
+    .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[tokio::test]
+async fn matches_sync_pipeline() {
+    let input = large_input();
+    assert!(input.len() > 3 * 256 * 1024);
+
+    assert_eq!(async_output(input.clone()).await, sync_output(&input));
+}
+
+#[tokio::test]
+async fn missing_trailing_newline_and_empty_input() {
+    let input = "type,client,tx,amount\ndeposit,1,1,2.5\nwithdrawal,1,2,1.0".to_string();
+
+    assert_eq!(async_output(input.clone()).await, sync_output(&input));
+    assert_eq!(async_output(String::new()).await, "");
+}
+
+#[tokio::test]
+async fn cancelled_run_stops_reading() {
+    let (mut writer, reader) = tokio::io::duplex(64 * 1024);
+    let run = tokio::spawn(async_pipeline::process(reader, EngineConfig::default()));
+    writer
+        .write_all(b"type,client,tx,amount\ndeposit,1,1,1.0\n")
+        .await
+        .unwrap();
+
+    run.abort();
+    assert!(run.await.unwrap_err().is_cancelled());
+
+    // The reader task notices at its next chunk and drops the input, closing the pipe.
+    let row = b"deposit,1,2,1.0\n";
+    let mut written = 0;
+    while writer.write_all(row).await.is_ok() {
+        written += row.len();
+        assert!(written < 16 * 1024 * 1024, "input still being read");
+    }
+}
+
+#[test]
+fn cli_rejects_options_the_async_pipeline_ignores() {
+    let input = std::env::temp_dir().join("async_tests_conflicts.csv");
+    std::fs::write(&input, "type,client,tx,amount\ndeposit,1,1,5\n").unwrap();
+    let run = |flag: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+            .arg(&input)
+            .args(["--async", flag])
+            .output()
+            .unwrap()
+    };
+    let outputs = ["--multi-tenant", "--two-pass", "--pipelined", "--presize"].map(run);
+    std::fs::remove_file(&input).unwrap();
+
+    for output in outputs {
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+        assert!(
+            String::from_utf8(output.stderr)
+                .unwrap()
+                .contains("cannot be used with")
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use rust_coding_test::authenticity::{AuthenticityError, HMAC_KEY_VAR, sign, verify};
+
+const INPUT: &[u8] = include_bytes!("io_tests/test_input.csv");
+const KEY: &[u8] = b"partner secret";
+
+#[test]
+fn signature_verifies() {
+    let signature = sign(INPUT, KEY).unwrap();
+
+    assert_eq!(signature.len(), 64);
+    verify(INPUT, KEY, &signature).unwrap();
+    verify(INPUT, KEY, &format!("{signature}\n")).unwrap();
+}
+
+#[test]
+fn tampered_input_or_other_key_rejected() {
+    let signature = sign(INPUT, KEY).unwrap();
+    let mut tampered = INPUT.to_vec();
+    tampered.extend_from_slice(b"deposit,1,99,1000.0\n");
+
+    assert!(matches!(
+        verify(&tampered[..], KEY, &signature),
+        Err(AuthenticityError::SignatureMismatch)
+    ));
+    assert!(matches!(
+        verify(INPUT, b"other key", &signature),
+        Err(AuthenticityError::SignatureMismatch)
+    ));
+}
+
+#[test]
+fn malformed_signature_rejected() {
+    let signature = sign(INPUT, KEY).unwrap();
+
+    for malformed in ["", "not hex", &signature[..62]] {
+        assert!(
+            matches!(
+                verify(INPUT, KEY, malformed),
+                Err(AuthenticityError::MalformedSignature)
+            ),
+            "{malformed}"
+        );
+    }
+}
+
+/// Copies the input to a temp file, returning its path.
+fn temp_input(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "authenticity_tests_{name}_{}.csv",
+        std::process::id()
+    ));
+    fs::write(&path, INPUT).unwrap();
+    path
+}
+
+fn run(input: &PathBuf, key: Option<&str>, args: &[&str]) -> std::process::Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"));
+    command.arg(input).args(args).env_remove(HMAC_KEY_VAR);
+    if let Some(key) = key {
+        command.env(HMAC_KEY_VAR, key);
+    }
+    command.output().unwrap()
+}
+
+#[test]
+fn cli_refuses_unsigned_or_tampered_input_when_keyed() {
+    let input = temp_input("cli");
+    let signature_path = PathBuf::from(format!("{}.sig", input.display()));
+    let key = std::str::from_utf8(KEY).unwrap();
+
+    let unsigned = run(&input, Some(key), &[]);
+    assert!(!unsigned.status.success());
+    assert!(unsigned.stdout.is_empty());
+
+    fs::write(&signature_path, sign(INPUT, KEY).unwrap()).unwrap();
+    let signed = run(&input, Some(key), &[]);
+    assert!(signed.status.success(), "{signed:?}");
+    assert!(signed.stdout.starts_with(b"client,"));
+
+    fs::write(&signature_path, sign(&b"other batch"[..], KEY).unwrap()).unwrap();
+    let tampered = run(&input, Some(key), &[]);
+    assert!(!tampered.status.success());
+    assert!(String::from_utf8_lossy(&tampered.stderr).contains("doesn't match"));
+
+    fs::remove_file(&signature_path).unwrap();
+    fs::remove_file(&input).unwrap();
+}
+
+#[test]
+fn cli_two_pass_reads_signed_input_in_both_passes() {
+    let input = temp_input("two_pass");
+    let signature_path = PathBuf::from(format!("{}.sig", input.display()));
+    fs::write(&signature_path, sign(INPUT, KEY).unwrap()).unwrap();
+    let key = std::str::from_utf8(KEY).unwrap();
+
+    let single = run(&input, Some(key), &[]);
+    let two_pass = run(&input, Some(key), &["--two-pass"]);
+    fs::remove_file(&signature_path).unwrap();
+    fs::remove_file(&input).unwrap();
+
+    assert!(two_pass.status.success(), "{two_pass:?}");
+    assert_eq!(two_pass.stdout, single.stdout);
+}
+
+#[test]
+fn cli_without_key() {
+    let input = temp_input("unkeyed");
+
+    assert!(run(&input, None, &[]).status.success());
+    assert!(
+        !run(&input, None, &["--signature", "missing.sig"])
+            .status
+            .success()
+    );
+
+    fs::remove_file(&input).unwrap();
+}
+
+#[test]
+fn cli_subcommands_refuse_tampered_input_when_keyed() {
+    let input = temp_input("subcommand");
+    let signature_path = PathBuf::from(format!("{}.sig", input.display()));
+    fs::write(&signature_path, sign(&b"other batch"[..], KEY).unwrap()).unwrap();
+
+    let outputs = ["report", "ledger", "sar"].map(|subcommand| {
+        Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+            .args([subcommand, input.to_str().unwrap()])
+            .env(HMAC_KEY_VAR, std::str::from_utf8(KEY).unwrap())
+            .output()
+            .unwrap()
+    });
+    fs::remove_file(&signature_path).unwrap();
+    fs::remove_file(&input).unwrap();
+
+    for output in outputs {
+        assert!(!output.status.success());
+        assert!(output.stdout.is_empty());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("doesn't match"));
+    }
+}
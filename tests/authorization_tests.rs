@@ -0,0 +1,251 @@
+mod common;
+
+use common::{account, run};
+use rust_coding_test::{
+    domain::{
+        Account, Authorization, Balance, Capture, Chargeback, ClientId, Deposit, Dispute,
+        Transaction, Void, Withdrawal,
+    },
+    output::{self, OutputColumns},
+    parsing,
+};
+use rust_decimal::dec;
+use std::collections::HashMap;
+
+fn authorized_account(
+    available: rust_decimal::Decimal,
+    authorized: rust_decimal::Decimal,
+    locked: bool,
+) -> Account {
+    Account::new(
+        Balance::new(available, dec!(0.0)).with_authorized(authorized),
+        locked,
+    )
+}
+
+/// An authorization moves funds out of available into authorized: total drops, the gross
+/// total is unchanged.
+#[test]
+fn authorization_reserves_funds() {
+    let engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Authorization::try_new(1.into(), 2.into(), dec!(40.0))
+            .unwrap()
+            .into(),
+    ]);
+
+    let expected = HashMap::from_iter([(
+        ClientId::from(1),
+        authorized_account(dec!(60.0), dec!(40.0), false),
+    )]);
+
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+    let balance = engine.client_accounts().as_map()[&ClientId::from(1)].balance();
+    assert_eq!(
+        (balance.total(), balance.gross_total()),
+        (dec!(60.0), dec!(100.0))
+    );
+}
+
+#[test]
+fn authorization_with_insufficient_funds_is_ignored() {
+    let engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+            .unwrap()
+            .into(),
+        Authorization::try_new(1.into(), 2.into(), dec!(40.0))
+            .unwrap()
+            .into(),
+        Capture::new(1.into(), 2.into()).into(), // nothing to capture
+    ]);
+
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(10.0), dec!(0.0), false))]);
+
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+/// Authorized funds can't be withdrawn a second time.
+#[test]
+fn authorized_funds_are_not_available_for_withdrawal() {
+    let engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Authorization::try_new(1.into(), 2.into(), dec!(80.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 3.into(), dec!(30.0))
+            .unwrap()
+            .into(),
+    ]);
+
+    let expected = HashMap::from_iter([(
+        ClientId::from(1),
+        authorized_account(dec!(20.0), dec!(80.0), false),
+    )]);
+
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+#[test]
+fn capture_settles_authorized_funds() {
+    let engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Authorization::try_new(1.into(), 2.into(), dec!(40.0))
+            .unwrap()
+            .into(),
+        Capture::new(1.into(), 2.into()).into(),
+    ]);
+
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(60.0), dec!(0.0), false))]);
+
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+#[test]
+fn void_returns_authorized_funds() {
+    let engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Authorization::try_new(1.into(), 2.into(), dec!(40.0))
+            .unwrap()
+            .into(),
+        Void::new(1.into(), 2.into()).into(),
+    ]);
+
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
+
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+/// Once captured or voided, an authorization can't be settled again.
+#[test]
+fn settled_authorization_cannot_be_settled_again() {
+    let engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Authorization::try_new(1.into(), 2.into(), dec!(40.0))
+            .unwrap()
+            .into(),
+        Capture::new(1.into(), 2.into()).into(),
+        Void::new(1.into(), 2.into()).into(),
+        Capture::new(1.into(), 2.into()).into(),
+    ]);
+
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(60.0), dec!(0.0), false))]);
+
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+#[test]
+fn capture_of_other_clients_authorization_is_ignored() {
+    let engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Authorization::try_new(1.into(), 2.into(), dec!(40.0))
+            .unwrap()
+            .into(),
+        Capture::new(2.into(), 2.into()).into(),
+    ]);
+
+    let expected = HashMap::from_iter([
+        (
+            ClientId::from(1),
+            authorized_account(dec!(60.0), dec!(40.0), false),
+        ),
+        (ClientId::from(2), account(dec!(0.0), dec!(0.0), false)),
+    ]);
+
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+/// A locked account can't authorize new payouts, but pending ones can still be settled.
+#[test]
+fn locked_account_blocks_authorization_but_settles_pending_ones() {
+    let engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(50.0))
+            .unwrap()
+            .into(),
+        Authorization::try_new(1.into(), 3.into(), dec!(40.0))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 2.into()).into(),
+        Chargeback::new(1.into(), 2.into()).into(), // locks the account
+        Authorization::try_new(1.into(), 4.into(), dec!(10.0))
+            .unwrap()
+            .into(), // rejected
+        Capture::new(1.into(), 3.into()).into(),
+    ]);
+
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(60.0), dec!(0.0), true))]);
+
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+#[test]
+fn authorization_rows_are_parsed() {
+    let input = "type,client,tx,amount\n\
+                 authorization,1,1,2.5\n\
+                 capture,1,1,\n\
+                 void,1,1,\n\
+                 authorization,1,2,\n";
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+
+    let transactions = parsing::deserialize_csv(&mut rdr).collect::<Vec<_>>();
+
+    // the last authorization is missing its amount and is skipped
+    let expected: Vec<Transaction> = vec![
+        Authorization::try_new(1.into(), 1.into(), dec!(2.5))
+            .unwrap()
+            .into(),
+        Capture::new(1.into(), 1.into()).into(),
+        Void::new(1.into(), 1.into()).into(),
+    ];
+    assert_eq!(transactions, expected);
+}
+
+/// The default output keeps the required columns; `authorized` and the gross total are
+/// opt-in.
+#[test]
+fn authorized_funds_are_only_written_when_selected() -> anyhow::Result<()> {
+    let engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(100))?.into(),
+        Authorization::try_new(1.into(), 2.into(), dec!(40))?.into(),
+    ]);
+    let print = |columns: OutputColumns| -> anyhow::Result<String> {
+        let mut output = Vec::new();
+        output::print_accounts_with_columns(engine.client_accounts(), &columns, &mut output)?;
+        Ok(String::from_utf8(output)?)
+    };
+
+    let default = print(OutputColumns::default())?;
+    assert!(default.starts_with("client,available,held,total,locked"));
+    assert!(!default.contains("authorized"));
+    assert!(default.contains("\n1,60,0,60,false"), "{default}");
+    assert_eq!(
+        print("client,total,authorized".parse()?)?,
+        "client,total,authorized\n1,60,40\n"
+    );
+    assert_eq!(
+        print(
+            "client,total"
+                .parse::<OutputColumns>()?
+                .with_gross_total(true)
+        )?,
+        "client,total\n1,100\n"
+    );
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use rust_coding_test::{
+    domain::{Account, ClientId, Deposit, Dispute, Resolve, Transaction, Withdrawal},
+    engine::{EngineConfig, PaymentsEngine},
+};
+use rust_decimal::dec;
+
+/// Three clients interleaved, with disputes referencing earlier deposits of their client.
+fn interleaved() -> Vec<Transaction> {
+    vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(10))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 2.into(), dec!(5))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 3.into(), dec!(4))
+            .unwrap()
+            .into(),
+        Dispute::new(2.into(), 2.into()).into(),
+        Deposit::try_new(3.into(), 4.into(), dec!(1))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(2.into(), 5.into(), dec!(1))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 1.into()).into(),
+        Resolve::new(2.into(), 2.into()).into(),
+        Dispute::new(3.into(), 99.into()).into(),
+    ]
+}
+
+fn accounts(engine: &PaymentsEngine) -> Vec<(ClientId, Account)> {
+    engine
+        .client_accounts()
+        .iter_sorted()
+        .map(|(client, account)| (client, account.clone()))
+        .collect()
+}
+
+fn assert_same_as_sequential(config: EngineConfig) {
+    let transactions = interleaved();
+    let mut sequential = PaymentsEngine::with_config(config.clone());
+    let expected = sequential.process_transactions_reporting(transactions.clone().into_iter());
+
+    let mut batched = PaymentsEngine::with_config(config);
+    let report = batched.process_batch(&transactions);
+
+    assert_eq!(report, expected);
+    assert_eq!(accounts(&batched), accounts(&sequential));
+}
+
+#[test]
+fn grouped_batch_matches_sequential_application() {
+    assert_same_as_sequential(EngineConfig::default());
+}
+
+/// With a settlement delay the order across clients matters, so the batch is applied as is.
+#[test]
+fn clocked_batch_matches_sequential_application() {
+    assert_same_as_sequential(EngineConfig {
+        settlement_delay: 2,
+        dispute_window: Some(4),
+        ..EngineConfig::default()
+    });
+}
@@ -0,0 +1,61 @@
+use rust_coding_test::{
+    domain::{Account, ClientId, Dispute, Transaction},
+    engine::{BloomFilter, BloomFilteredHistory, DepositHistory, EngineConfig, PaymentsEngine},
+    testgen::Workload,
+};
+
+fn accounts<H>(engine: &PaymentsEngine<H>) -> Vec<(ClientId, Account)> {
+    engine
+        .client_accounts()
+        .iter_sorted()
+        .map(|(client, account)| (client, account.clone()))
+        .collect()
+}
+
+#[test]
+fn no_false_negatives_and_about_the_target_rate() {
+    let mut filter = BloomFilter::new(10_000, 0.01);
+    for tx in (0..20_000u32).step_by(2) {
+        filter.insert(tx.into());
+    }
+    assert!(
+        (0..20_000u32)
+            .step_by(2)
+            .all(|tx| filter.may_contain(tx.into()))
+    );
+
+    let false_positives = (1..20_000u32)
+        .step_by(2)
+        .filter(|tx| filter.may_contain((*tx).into()))
+        .count();
+    assert!(false_positives < 200, "{false_positives} false positives");
+}
+
+#[test]
+fn filtered_engine_matches_and_skips_unknown_txs() {
+    let workload = Workload {
+        rows: 20_000,
+        clients: 50,
+        dispute_rate: 0.05,
+        ..Default::default()
+    };
+    // Disputes of txs that were never deposited, as in noisy inputs.
+    let noise = (0..1_000u32).map(|i| {
+        Transaction::from(Dispute::new(
+            ((i % 50) as u16 + 1).into(),
+            (900_000 + i).into(),
+        ))
+    });
+    let transactions = || workload.transactions().chain(noise.clone());
+
+    let mut plain = PaymentsEngine::new();
+    plain.process_transactions(transactions());
+    let mut engine = PaymentsEngine::with_store(
+        EngineConfig::default(),
+        BloomFilteredHistory::new(DepositHistory::new(), 20_000, 0.01),
+    );
+    engine.process_transactions(transactions());
+
+    assert_eq!(accounts(&engine), accounts(&plain));
+    assert!(engine.deposit_history().skipped_lookups() > 950);
+}
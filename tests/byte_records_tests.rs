@@ -0,0 +1,102 @@
+use rust_coding_test::{
+    domain::{Deposit, Dispute, Transaction, Withdrawal},
+    parsing::{self, ByteRecordParser, ParseError, TenantTransaction},
+};
+use rust_decimal::dec;
+
+const INPUT: &[u8] = include_bytes!("io_tests/test_input.csv");
+
+fn reader(input: &[u8]) -> csv::Reader<&[u8]> {
+    csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input)
+}
+
+#[test]
+fn matches_serde_pipeline() {
+    let via_serde = parsing::deserialize_tenant_csv(&mut reader(INPUT)).collect::<Vec<_>>();
+    let via_byte_records =
+        parsing::deserialize_tenant_byte_records(&mut reader(INPUT)).collect::<Vec<_>>();
+
+    assert!(!via_serde.is_empty());
+    assert_eq!(via_byte_records, via_serde);
+}
+
+/// Columns are found by header name; the tenant column is optional.
+#[test]
+fn columns_in_any_order() {
+    let input = b"amount,tx,tenant,client,type\n\
+                  1.50,1,3,2,deposit\n\
+                  ,1,,2,dispute\n";
+
+    let transactions =
+        parsing::deserialize_tenant_byte_records(&mut reader(input)).collect::<Vec<_>>();
+
+    assert_eq!(
+        transactions,
+        vec![
+            TenantTransaction {
+                tenant: Some(3.into()),
+                transaction: Deposit::try_new(2.into(), 1.into(), dec!(1.5))
+                    .unwrap()
+                    .into(),
+            },
+            TenantTransaction {
+                tenant: None,
+                transaction: Dispute::new(2.into(), 1.into()).into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn invalid_rows_are_reported() {
+    let input = b"type,client,tx,amount\n\
+                  transfer,1,1,1.0\n\
+                  deposit,70000,2,1.0\n\
+                  deposit,1,3,abc\n\
+                  deposit,1,4,1.23456\n\
+                  withdrawal,1,5,\n\
+                  deposit,1,6\n\
+                  withdrawal,1,7,2.0\n";
+
+    let results = ByteRecordParser::new(&mut reader(input)).collect::<Vec<_>>();
+
+    assert!(matches!(
+        results.as_slice(),
+        [
+            Err(ParseError::InvalidField {
+                row: 1,
+                column: "type"
+            }),
+            Err(ParseError::InvalidField {
+                row: 2,
+                column: "client"
+            }),
+            Err(ParseError::InvalidField {
+                row: 3,
+                column: "amount"
+            }),
+            Err(ParseError::Invalid { row: 4, .. }),
+            Err(ParseError::Invalid { row: 5, .. }),
+            Err(ParseError::Malformed(_)),
+            Ok(_),
+        ]
+    ));
+    let expected: Transaction = Withdrawal::try_new(1.into(), 7.into(), dec!(2.0))
+        .unwrap()
+        .into();
+    assert_eq!(results[6].as_ref().unwrap().transaction, expected);
+}
+
+#[test]
+fn missing_required_column_stops_parsing() {
+    let input = b"type,client,amount\ndeposit,1,1.0\ndeposit,1,2.0\n";
+
+    let results = ByteRecordParser::new(&mut reader(input)).collect::<Vec<_>>();
+
+    assert!(matches!(
+        results.as_slice(),
+        [Err(ParseError::MissingColumn("tx"))]
+    ));
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::account;
+use rust_coding_test::{
+    domain::{ClientId, Deposit, Dispute},
+    engine::{CapacityHint, EngineConfig, PaymentsEngine},
+};
+use rust_decimal::dec;
+use std::collections::HashMap;
+
+#[test]
+fn hint_from_input_size() {
+    assert_eq!(CapacityHint::from_input_size(0), CapacityHint::default());
+    assert_eq!(
+        CapacityHint::from_input_size(2_400),
+        CapacityHint {
+            clients: 100,
+            deposits: 100,
+        }
+    );
+}
+
+/// There are only 65536 possible client IDs, however large the input.
+#[test]
+fn client_estimate_is_capped() {
+    let hint = CapacityHint::from_input_size(24_000_000);
+
+    assert_eq!(hint.clients, 65_536);
+    assert_eq!(hint.deposits, 1_000_000);
+}
+
+#[test]
+fn presized_engine_allocates_up_front_and_behaves_the_same() {
+    let mut engine = PaymentsEngine::with_capacity(
+        EngineConfig::default(),
+        CapacityHint {
+            clients: 10,
+            deposits: 1_000,
+        },
+    );
+
+    assert!(engine.client_accounts().capacity() >= 10);
+    assert!(engine.deposit_history().as_map().capacity() >= 1_000);
+
+    engine
+        .process_transaction(
+            Deposit::try_new(1.into(), 1.into(), dec!(3.0))
+                .unwrap()
+                .into(),
+        )
+        .unwrap();
+    engine
+        .process_transaction(Dispute::new(1.into(), 1.into()).into())
+        .unwrap();
+
+    assert_eq!(
+        engine.client_accounts().as_map(),
+        &HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(3.0), false))])
+    );
+}
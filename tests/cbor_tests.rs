@@ -0,0 +1,160 @@
+#![cfg(feature = "cbor")]
+
+use std::process::Command;
+
+use ciborium::{Value, cbor};
+use rust_coding_test::{
+    cbor::{CborSink, deserialize_cbor, print_accounts_cbor},
+    domain::{Chargeback, Deposit, Dispute, Transaction, Withdrawal},
+    engine::PaymentsEngine,
+};
+use rust_decimal::dec;
+
+fn encode(items: &[Value]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for item in items {
+        ciborium::into_writer(item, &mut bytes).unwrap();
+    }
+    bytes
+}
+
+fn decode(mut bytes: &[u8]) -> Vec<Value> {
+    let mut items = Vec::new();
+    while !bytes.is_empty() {
+        items.push(ciborium::from_reader(&mut bytes).unwrap());
+    }
+    items
+}
+
+/// Transactions use the same tagged representation as their JSON form.
+#[test]
+fn transactions_round_trip() {
+    let transactions: Vec<Transaction> = vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 2.into(), dec!(0.5))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 1.into()).into(),
+        Chargeback::new(1.into(), 1.into()).into(),
+    ];
+    let items: Vec<Value> = transactions
+        .iter()
+        .map(|transaction| Value::serialized(transaction).unwrap())
+        .collect();
+
+    let read: Vec<Transaction> = deserialize_cbor(encode(&items).as_slice()).collect();
+
+    assert_eq!(read, transactions);
+    assert_eq!(
+        items[0],
+        cbor!({"type" => "deposit", "client" => 1, "tx" => 1, "amount" => "1.5"}).unwrap()
+    );
+}
+
+/// Invalid items are skipped; malformed CBOR ends the input.
+#[test]
+fn invalid_items_are_skipped() {
+    let mut bytes = encode(&[
+        cbor!({"type" => "transfer", "client" => 1, "tx" => 1}).unwrap(),
+        cbor!({"type" => "deposit", "client" => 1, "tx" => 2, "amount" => "-1"}).unwrap(),
+        cbor!({"type" => "deposit", "client" => 1, "tx" => 3, "amount" => 2}).unwrap(),
+    ]);
+    bytes.push(0xff);
+    bytes.extend(encode(&[
+        cbor!({"type" => "deposit", "client" => 1, "tx" => 4, "amount" => "1"}).unwrap(),
+    ]));
+
+    let read: Vec<Transaction> = deserialize_cbor(bytes.as_slice()).collect();
+
+    assert_eq!(
+        read,
+        vec![
+            Deposit::try_new(1.into(), 3.into(), dec!(2))
+                .unwrap()
+                .into()
+        ]
+    );
+}
+
+#[test]
+fn accounts_are_written_as_maps() {
+    let mut engine = PaymentsEngine::new();
+    engine.process_transactions(
+        [
+            Deposit::try_new(2.into(), 1.into(), dec!(3))
+                .unwrap()
+                .into(),
+            Deposit::try_new(1.into(), 2.into(), dec!(1.5))
+                .unwrap()
+                .into(),
+        ]
+        .into_iter(),
+    );
+    let mut bytes = Vec::new();
+    print_accounts_cbor(engine.client_accounts(), &mut bytes).unwrap();
+    let mut sink = CborSink::new(Vec::new());
+    engine.flush_accounts(&mut sink).unwrap();
+
+    let items = decode(&bytes);
+    assert_eq!(items.len(), 2);
+    assert_eq!(
+        items[0],
+        cbor!({
+            "client" => 1,
+            "available" => "1.5",
+            "held" => "0",
+            "total" => "1.5",
+            "locked" => false,
+            "authorized" => "0",
+            "pending" => "0",
+            "reserve" => "0",
+        })
+        .unwrap()
+    );
+    let snapshot = decode(&sink.into_inner());
+    let Value::Map(entries) = &snapshot[1] else {
+        panic!("not a map: {:?}", snapshot[1]);
+    };
+    assert_eq!(entries[0], (Value::from("sequence"), Value::from(1)));
+    assert_eq!(entries[1], (Value::from("client"), Value::from(2)));
+}
+
+#[test]
+fn cli_reads_and_writes_cbor() {
+    let path = std::env::temp_dir().join(format!("cbor_tests_{}.cbor", std::process::id()));
+    std::fs::write(
+        &path,
+        encode(&[
+            cbor!({"type" => "deposit", "client" => 1, "tx" => 1, "amount" => "2"}).unwrap(),
+            cbor!({"type" => "withdrawal", "client" => 1, "tx" => 2, "amount" => "0.5"}).unwrap(),
+        ]),
+    )
+    .unwrap();
+
+    let csv = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg(&path)
+        .output()
+        .unwrap();
+    let cbor = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg(&path)
+        .arg("--cbor")
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(csv.status.success());
+    assert_eq!(
+        String::from_utf8(csv.stdout).unwrap(),
+        "client,available,held,total,locked\n\
+         1,1.5,0,1.5,false\n"
+    );
+    assert!(cbor.status.success());
+    let items = decode(&cbor.stdout);
+    assert_eq!(items.len(), 1);
+    let Value::Map(entries) = &items[0] else {
+        panic!("not a map: {:?}", items[0]);
+    };
+    assert_eq!(entries[1], (Value::from("available"), Value::from("1.5")));
+}
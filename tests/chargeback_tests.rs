@@ -11,12 +11,14 @@ use std::collections::HashMap;
 #[test]
 fn chargeback_removes_funds_and_locks_account() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Chargeback::new(1.into(), 1.into()).into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(0.0), dec!(0.0), true))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(0.0), true))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -25,11 +27,14 @@ fn chargeback_removes_funds_and_locks_account() {
 #[test]
 fn chargeback_without_prior_dispute_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Chargeback::new(1.into(), 1.into()).into(), // no dispute open
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -38,11 +43,14 @@ fn chargeback_without_prior_dispute_is_ignored() {
 #[test]
 fn chargeback_on_nonexistent_transaction_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Chargeback::new(1.into(), 99.into()).into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -51,13 +59,17 @@ fn chargeback_on_nonexistent_transaction_is_ignored() {
 #[test]
 fn chargeback_preserves_remaining_balance_for_other_deposits() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(50.0)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(50.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(50.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(50.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Chargeback::new(1.into(), 1.into()).into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(50.0), dec!(0.0), true))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(50.0), dec!(0.0), true))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -67,12 +79,14 @@ fn chargeback_preserves_remaining_balance_for_other_deposits() {
 #[test]
 fn chargeback_on_wrong_client_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Chargeback::new(2.into(), 1.into()).into(), // client 2 tries to chargeback client 1's tx
     ]);
 
-    let expected = HashMap::from([
+    let expected = HashMap::from_iter([
         (ClientId::from(1), account(dec!(0.0), dec!(100.0), false)),
         (ClientId::from(2), account(dec!(0.0), dec!(0.0), false)),
     ]);
@@ -85,13 +99,16 @@ fn chargeback_on_wrong_client_is_ignored() {
 #[test]
 fn chargeback_after_resolve_without_redispute_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Resolve::new(1.into(), 1.into()).into(),
         Chargeback::new(1.into(), 1.into()).into(), // no active dispute → ignored
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -100,13 +117,15 @@ fn chargeback_after_resolve_without_redispute_is_ignored() {
 #[test]
 fn redispute_after_chargeback_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Chargeback::new(1.into(), 1.into()).into(),
         Dispute::new(1.into(), 1.into()).into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(0.0), dec!(0.0), true))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(0.0), true))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -0,0 +1,113 @@
+mod common;
+
+use common::run;
+use rust_coding_test::{
+    chargebacks::{ChargebackReport, ClientChargebacks},
+    domain::{Chargeback, Deposit, Dispute, Transaction, Withdrawal},
+    engine::PaymentsEngine,
+};
+use rust_decimal::dec;
+
+fn deposit(client: u16, tx: u32) -> Transaction {
+    Deposit::try_new(client.into(), tx.into(), dec!(1.5))
+        .unwrap()
+        .into()
+}
+
+fn charged_back(client: u16, tx: u32) -> [Transaction; 3] {
+    [
+        deposit(client, tx),
+        Dispute::new(client.into(), tx.into()).into(),
+        Chargeback::new(client.into(), tx.into()).into(),
+    ]
+}
+
+fn engine() -> PaymentsEngine {
+    let mut transactions = vec![deposit(1, 1), deposit(1, 2), deposit(1, 3)];
+    transactions.extend(charged_back(1, 4));
+    transactions.extend(charged_back(2, 5));
+    transactions.push(deposit(3, 6));
+    // Still open, so not a chargeback.
+    transactions.push(Dispute::new(3.into(), 6.into()).into());
+    transactions.push(deposit(4, 7));
+    transactions.extend(charged_back(4, 8));
+    // No deposits, so no ratio.
+    transactions.push(
+        Withdrawal::try_new(5.into(), 9.into(), dec!(1))
+            .unwrap()
+            .into(),
+    );
+    run(transactions)
+}
+
+#[test]
+fn ratios_ranked_and_flagged() {
+    let report = ChargebackReport::new(&engine(), dec!(0.3));
+
+    assert_eq!(
+        report.clients,
+        vec![
+            ClientChargebacks {
+                client: 2.into(),
+                deposits: 1,
+                chargebacks: 1,
+                ratio: dec!(1),
+                flagged: true,
+            },
+            ClientChargebacks {
+                client: 4.into(),
+                deposits: 2,
+                chargebacks: 1,
+                ratio: dec!(0.5),
+                flagged: true,
+            },
+            ClientChargebacks {
+                client: 1.into(),
+                deposits: 4,
+                chargebacks: 1,
+                ratio: dec!(0.25),
+                flagged: false,
+            },
+            ClientChargebacks {
+                client: 3.into(),
+                deposits: 1,
+                chargebacks: 0,
+                ratio: dec!(0),
+                flagged: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn ratio_equal_to_threshold_is_not_flagged() {
+    let report = ChargebackReport::new(&engine(), dec!(0.5));
+
+    let flagged: Vec<u16> = report
+        .flagged()
+        .map(|client| u16::from(client.client))
+        .collect();
+    assert_eq!(flagged, vec![2]);
+}
+
+#[test]
+fn renders_as_text() {
+    let report = ChargebackReport::new(&run(charged_back(7, 1).to_vec()), dec!(0.01));
+
+    assert_eq!(
+        report.to_string(),
+        "Threshold:  0.01\n\
+         Flagged:    1\n\
+         \n \
+         client    deposits  chargebacks     ratio  flagged\n      \
+         7           1            1         1  yes\n"
+    );
+}
+
+#[test]
+fn empty_run() {
+    let report = ChargebackReport::new(&PaymentsEngine::new(), dec!(0.01));
+
+    assert!(report.clients.is_empty());
+    assert_eq!(report.to_string(), "Threshold:  0.01\nFlagged:    0\n");
+}
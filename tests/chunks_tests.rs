@@ -0,0 +1,79 @@
+use rust_coding_test::parsing::{self, split_rows};
+
+const INPUT: &[u8] = b"type,client,tx,amount\n\
+                       deposit,1,1,1.0\n\
+                       deposit,2,2,2.0\n\
+                       withdrawal,1,3,0.5\n\
+                       dispute,2,2,\n\
+                       deposit,3,4,4.0";
+
+fn parse_whole(input: &[u8]) -> Vec<parsing::TenantTransaction> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input);
+    parsing::deserialize_tenant_csv(&mut reader).collect()
+}
+
+#[test]
+fn chunks_cover_body_in_order() {
+    for max_chunks in [0, 1, 2, 3, 100] {
+        let rows = split_rows(INPUT, max_chunks);
+
+        assert_eq!(rows.header(), b"type,client,tx,amount\n");
+        assert_eq!(
+            rows.chunks().collect::<Vec<_>>().concat(),
+            &INPUT[rows.header().len()..]
+        );
+        assert!(rows.chunks().count() <= max_chunks.max(1));
+        // every chunk but the last ends a row
+        let chunks = rows.chunks().collect::<Vec<_>>();
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert_eq!(chunk.last(), Some(&b'\n'));
+        }
+    }
+}
+
+#[test]
+fn parsing_chunks_matches_parsing_whole_input() {
+    let rows = split_rows(INPUT, 3);
+
+    let chunked = rows
+        .readers()
+        .flat_map(|mut reader| parsing::deserialize_tenant_csv(&mut reader).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    assert_eq!(chunked.len(), 5);
+    assert_eq!(chunked, parse_whole(INPUT));
+}
+
+#[test]
+fn header_only_input_has_no_chunks() {
+    assert_eq!(
+        split_rows(b"type,client,tx,amount\n", 4).chunks().count(),
+        0
+    );
+    assert_eq!(split_rows(b"", 4).chunks().count(), 0);
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn mapped_file_parses_like_in_memory_input() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("chunks_tests_{}.csv", std::process::id()));
+    std::fs::write(&path, INPUT)?;
+
+    let file = std::fs::File::open(&path)?;
+    // SAFETY: the file is private to this test and isn't modified while mapped.
+    let mapped = unsafe { parsing::MappedInput::map(&file)? };
+
+    assert_eq!(mapped.bytes(), INPUT);
+    let chunked = mapped
+        .split_rows(2)
+        .readers()
+        .flat_map(|mut reader| parsing::deserialize_tenant_csv(&mut reader).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(chunked, parse_whole(INPUT));
+
+    drop(mapped);
+    std::fs::remove_file(path)?;
+    Ok(())
+}
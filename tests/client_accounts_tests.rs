@@ -0,0 +1,125 @@
+mod common;
+
+use common::{account, run};
+use rust_coding_test::{
+    domain::{ClientId, Deposit, TenantId, TransactionId},
+    engine::ClientAccounts,
+    output,
+};
+use rust_decimal::dec;
+use std::collections::{BTreeSet, HashMap};
+
+#[test]
+fn empty_accounts() {
+    let accounts = ClientAccounts::new();
+
+    assert!(accounts.is_empty());
+    assert_eq!(accounts.len(), 0);
+    assert_eq!(accounts.iter().count(), 0);
+}
+
+#[test]
+fn iter_sorted_orders_by_client_id() {
+    let engine = run(vec![
+        Deposit::try_new(300.into(), 1.into(), dec!(3.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 2.into(), dec!(2.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(10.into(), 3.into(), dec!(1.0))
+            .unwrap()
+            .into(),
+    ]);
+    let accounts = engine.client_accounts();
+
+    assert_eq!(accounts.len(), 3);
+    assert!(!accounts.is_empty());
+    assert_eq!(
+        accounts
+            .iter_sorted()
+            .map(|(client_id, account)| (u16::from(client_id), account))
+            .collect::<Vec<_>>(),
+        vec![
+            (2, &account(dec!(2.0), dec!(0.0), false)),
+            (10, &account(dec!(1.0), dec!(0.0), false)),
+            (300, &account(dec!(3.0), dec!(0.0), false)),
+        ]
+    );
+}
+
+#[test]
+fn output_is_ordered_by_client_id() -> anyhow::Result<()> {
+    let engine = run(vec![
+        Deposit::try_new(3.into(), 1.into(), dec!(3.5))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 3.into(), dec!(2.5))
+            .unwrap()
+            .into(),
+    ]);
+
+    let mut output = Vec::new();
+    output::print_accounts(engine.client_accounts(), &mut output)?;
+
+    assert_eq!(
+        String::from_utf8(output)?,
+        "client,available,held,total,locked\n\
+         1,1.5,0,1.5,false\n\
+         2,2.5,0,2.5,false\n\
+         3,3.5,0,3.5,false\n"
+    );
+
+    Ok(())
+}
+
+/// IDs order numerically, so they can key ordered collections directly.
+#[test]
+fn ids_order_numerically() {
+    let clients = BTreeSet::from([ClientId::from(10), ClientId::from(2), ClientId::from(1)]);
+
+    assert_eq!(
+        clients.into_iter().map(u16::from).collect::<Vec<_>>(),
+        vec![1, 2, 10]
+    );
+    assert!(TransactionId::from(9) < TransactionId::from(10));
+    assert!(Some(TenantId::from(0)) > None);
+}
+
+#[test]
+fn extreme_client_ids() {
+    let mut accounts = ClientAccounts::new();
+    accounts.get_or_create_account_mut(u16::MAX.into());
+    accounts.get_or_create_account_mut(0.into());
+    accounts.get_or_create_account_mut(u16::MAX.into());
+
+    assert_eq!(accounts.len(), 2);
+    assert_eq!(
+        accounts
+            .iter_sorted()
+            .map(|(client_id, _)| u16::from(client_id))
+            .collect::<Vec<_>>(),
+        vec![0, u16::MAX]
+    );
+}
+
+/// `as_map` may be a cached view (`dense-accounts`), so it must follow later changes.
+#[test]
+fn map_view_follows_mutations() {
+    let mut accounts = ClientAccounts::new();
+    accounts.get_or_create_account_mut(1.into());
+    assert_eq!(accounts.as_map().len(), 1);
+
+    accounts.get_or_create_account_mut(2.into());
+
+    assert_eq!(
+        accounts.as_map(),
+        &HashMap::from_iter([
+            (ClientId::from(1), account(dec!(0), dec!(0), false)),
+            (ClientId::from(2), account(dec!(0), dec!(0), false)),
+        ])
+    );
+}
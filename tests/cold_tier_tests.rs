@@ -0,0 +1,101 @@
+#![cfg(feature = "cold-tier")]
+
+use std::io::Cursor;
+
+use rust_coding_test::{
+    domain::{Account, ClientId, Deposit, DisputeState},
+    engine::{DisputeHistoryStore, EngineConfig, PaymentsEngine, TieredDepositHistory},
+    testgen::Workload,
+};
+use rust_decimal::dec;
+
+fn tiered(hot_capacity: usize, block_deposits: usize) -> TieredDepositHistory<Cursor<Vec<u8>>> {
+    TieredDepositHistory::new(Cursor::new(Vec::new()), hot_capacity)
+        .with_block_deposits(block_deposits)
+}
+
+fn accounts<H>(engine: &PaymentsEngine<H>) -> Vec<(ClientId, Account)> {
+    engine
+        .client_accounts()
+        .iter_sorted()
+        .map(|(client, account)| (client, account.clone()))
+        .collect()
+}
+
+#[test]
+fn tiered_engine_matches_in_memory_engine() {
+    let workload = Workload {
+        rows: 20_000,
+        clients: 50,
+        dispute_rate: 0.05,
+        ..Default::default()
+    };
+    let mut in_memory = PaymentsEngine::new();
+    in_memory.process_transactions(workload.transactions());
+    let mut engine = PaymentsEngine::with_store(EngineConfig::default(), tiered(100, 16));
+    engine.process_transactions(workload.transactions());
+
+    assert_eq!(accounts(&engine), accounts(&in_memory));
+    let store = engine.deposit_history();
+    assert!(store.hot_len() <= 100);
+    assert_eq!(
+        store.hot_len() + store.cold_len(),
+        in_memory.deposit_history().as_map().len()
+    );
+}
+
+#[test]
+fn cold_deposits_keep_their_dispute_state() {
+    let mut store = tiered(2, 2);
+    for tx in 1..=5 {
+        store.insert(Deposit::try_new(1.into(), tx.into(), dec!(1)).unwrap());
+    }
+    assert_eq!((store.hot_len(), store.cold_len()), (1, 4));
+
+    store
+        .get_mut(&1.into(), &1.into())
+        .unwrap()
+        .open_dispute()
+        .unwrap();
+    assert_eq!(store.cold_len(), 3);
+    for tx in 6..=9 {
+        store.insert(Deposit::try_new(1.into(), tx.into(), dec!(1)).unwrap());
+    }
+    assert!(store.cold_len() >= 7);
+
+    let deposit = store.get_mut(&1.into(), &1.into()).unwrap();
+    assert_eq!(deposit.dispute_state(), DisputeState::Open);
+    // Lookups are scoped to the deposit's client, hot or cold.
+    assert!(store.contains(&2.into()));
+    assert!(store.get_mut(&2.into(), &7.into()).is_none());
+    assert!(store.get_mut(&2.into(), &1.into()).is_some());
+    assert!(!store.contains(&10.into()));
+}
+
+#[test]
+fn cli_output_is_unchanged_with_a_cold_tier() {
+    let input = std::env::temp_dir().join(format!("cold_tier_tests_{}.csv", std::process::id()));
+    std::fs::write(
+        &input,
+        "type,client,tx,amount\n\
+         deposit,1,1,5\n\
+         deposit,2,2,3\n\
+         deposit,1,3,1\n\
+         dispute,1,1,\n\
+         chargeback,1,1,\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+            .arg(&input)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        output.stdout
+    };
+    let (plain, tiered) = (run(&[]), run(&["--hot-deposits", "1"]));
+    std::fs::remove_file(&input).unwrap();
+
+    assert_eq!(tiered, plain);
+}
@@ -1,8 +1,9 @@
 use rust_coding_test::{
-    domain::{Account, Balance},
+    domain::{Account, Amount, Balance},
     engine::PaymentsEngine,
 };
 
+#[allow(dead_code)]
 pub fn run(transactions: Vec<rust_coding_test::domain::Transaction>) -> PaymentsEngine {
     let mut engine = PaymentsEngine::new();
     engine.process_transactions(transactions.into_iter());
@@ -15,8 +16,10 @@ pub fn account(
     held: rust_decimal::Decimal,
     locked: bool,
 ) -> Account {
-    Account {
-        balance: Balance::new(available, held),
-        locked,
-    }
+    Account::new(Balance::new(available, held), locked)
+}
+
+#[allow(dead_code)]
+pub fn amount(value: rust_decimal::Decimal) -> Amount {
+    Amount::try_from(value).unwrap()
 }
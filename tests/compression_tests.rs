@@ -0,0 +1,84 @@
+#![cfg(feature = "compression")]
+
+use std::{
+    io::{Read, Write},
+    process::Command,
+};
+
+use flate2::write::GzEncoder;
+use rust_coding_test::parsing::{Compression, decompress};
+
+const CSV: &str = "type,client,tx,amount\ndeposit,1,1,5\nwithdrawal,1,2,4\n";
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn read_all(bytes: &[u8]) -> String {
+    let mut plain = String::new();
+    decompress(bytes)
+        .unwrap()
+        .read_to_string(&mut plain)
+        .unwrap();
+    plain
+}
+
+#[test]
+fn formats_are_detected_by_magic_bytes() {
+    assert_eq!(Compression::detect(&gzip(b"x")), Some(Compression::Gzip));
+    assert_eq!(
+        Compression::detect(&zstd::encode_all(&b"x"[..], 0).unwrap()),
+        Some(Compression::Zstd)
+    );
+    assert_eq!(Compression::detect(CSV.as_bytes()), None);
+    assert_eq!(Compression::detect(b""), None);
+}
+
+/// Concatenated gzip members and zstd frames, as written by appending to a dump, are read
+/// to the end; plain input passes through.
+#[test]
+fn every_member_or_frame_is_decompressed() {
+    let (head, tail) = CSV.split_at(30);
+
+    let gzipped = [gzip(head.as_bytes()), gzip(tail.as_bytes())].concat();
+    assert_eq!(read_all(&gzipped), CSV);
+    let zstd = [
+        zstd::encode_all(head.as_bytes(), 3).unwrap(),
+        zstd::encode_all(tail.as_bytes(), 3).unwrap(),
+    ]
+    .concat();
+    assert_eq!(read_all(&zstd), CSV);
+    assert_eq!(read_all(CSV.as_bytes()), CSV);
+}
+
+/// The CLI decompresses by content, whatever the file is called.
+#[test]
+fn cli_reads_compressed_input() {
+    let dir = std::env::temp_dir();
+    let gzipped = dir.join(format!("compression_tests_{}.csv.gz", std::process::id()));
+    let zstd = dir.join(format!("compression_tests_{}.csv", std::process::id()));
+    std::fs::write(&gzipped, gzip(CSV.as_bytes())).unwrap();
+    std::fs::write(&zstd, zstd::encode_all(CSV.as_bytes(), 3).unwrap()).unwrap();
+
+    let outputs: Vec<_> = [&gzipped, &zstd]
+        .map(|path| {
+            Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+                .arg(path)
+                .output()
+                .unwrap()
+        })
+        .into();
+    std::fs::remove_file(&gzipped).unwrap();
+    std::fs::remove_file(&zstd).unwrap();
+
+    for output in outputs {
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "client,available,held,total,locked\n\
+             1,1,0,1,false\n"
+        );
+    }
+}
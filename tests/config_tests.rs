@@ -0,0 +1,242 @@
+mod common;
+
+use common::{account, amount};
+use rust_coding_test::{
+    domain::{Chargeback, ClientId, Deposit, Dispute, Resolve, Transaction, Withdrawal},
+    engine::{
+        EngineConfig, NegativeAvailable, PaymentsEngine, RepeatedSettlement, errors::EngineError,
+    },
+};
+use rust_decimal::{Decimal, dec};
+use std::collections::HashMap;
+
+fn run_with(config: EngineConfig, transactions: Vec<Transaction>) -> PaymentsEngine {
+    let mut engine = PaymentsEngine::with_config(config);
+    engine.process_transactions(transactions.into_iter());
+    engine
+}
+
+fn ignore_repeats() -> EngineConfig {
+    EngineConfig {
+        repeated_settlement: RepeatedSettlement::Ignore,
+        ..Default::default()
+    }
+}
+
+/// Default: a second resolve for the same dispute is reported as an error.
+#[test]
+fn repeated_resolve_is_an_error_by_default() {
+    let mut engine = run_with(
+        EngineConfig::default(),
+        vec![
+            Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+                .unwrap()
+                .into(),
+            Dispute::new(1.into(), 1.into()).into(),
+            Resolve::new(1.into(), 1.into()).into(),
+        ],
+    );
+
+    let result = engine.process_transaction(Resolve::new(1.into(), 1.into()).into());
+
+    assert_eq!(
+        result,
+        Err(EngineError::TransactionNotDisputed {
+            client: 1.into(),
+            tx: 1.into(),
+            amount: amount(dec!(10.0)),
+        })
+    );
+}
+
+#[test]
+fn repeated_resolve_and_chargeback_are_ignored_when_idempotent() {
+    let mut engine = run_with(
+        ignore_repeats(),
+        vec![
+            Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+                .unwrap()
+                .into(),
+            Deposit::try_new(1.into(), 2.into(), dec!(20.0))
+                .unwrap()
+                .into(),
+            Dispute::new(1.into(), 1.into()).into(),
+            Resolve::new(1.into(), 1.into()).into(),
+            Dispute::new(1.into(), 2.into()).into(),
+            Chargeback::new(1.into(), 2.into()).into(),
+        ],
+    );
+
+    assert_eq!(
+        engine.process_transaction(Resolve::new(1.into(), 1.into()).into()),
+        Ok(())
+    );
+    assert_eq!(
+        engine.process_transaction(Chargeback::new(1.into(), 2.into()).into()),
+        Ok(())
+    );
+
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(10.0), dec!(0.0), true))]);
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+/// Idempotency only covers replays of the same outcome, not contradicting ones.
+#[test]
+fn chargeback_after_resolve_is_still_an_error_when_idempotent() {
+    let mut engine = run_with(
+        ignore_repeats(),
+        vec![
+            Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+                .unwrap()
+                .into(),
+            Dispute::new(1.into(), 1.into()).into(),
+            Resolve::new(1.into(), 1.into()).into(),
+        ],
+    );
+
+    let result = engine.process_transaction(Chargeback::new(1.into(), 1.into()).into());
+
+    assert!(matches!(
+        result,
+        Err(EngineError::TransactionNotDisputed { .. })
+    ));
+}
+
+#[test]
+fn chargeback_opens_missing_dispute_when_enabled() {
+    let engine = run_with(
+        EngineConfig {
+            chargeback_opens_dispute: true,
+            ..Default::default()
+        },
+        vec![
+            Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+                .unwrap()
+                .into(),
+            Deposit::try_new(1.into(), 2.into(), dec!(5.0))
+                .unwrap()
+                .into(),
+            Chargeback::new(1.into(), 1.into()).into(),
+        ],
+    );
+
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(5.0), dec!(0.0), true))]);
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+#[test]
+fn chargeback_does_not_open_dispute_by_default() {
+    let engine = run_with(
+        EngineConfig::default(),
+        vec![
+            Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+                .unwrap()
+                .into(),
+            Chargeback::new(1.into(), 1.into()).into(),
+        ],
+    );
+
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(10.0), dec!(0.0), false))]);
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+/// Deposit of 10, 7.5 of it withdrawn, then the deposit disputed.
+fn partially_withdrawn_dispute(negative_available: NegativeAvailable) -> PaymentsEngine {
+    run_with(
+        EngineConfig {
+            negative_available,
+            ..Default::default()
+        },
+        vec![
+            Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+                .unwrap()
+                .into(),
+            Withdrawal::try_new(1.into(), 2.into(), dec!(7.5))
+                .unwrap()
+                .into(),
+            Dispute::new(1.into(), 1.into()).into(),
+        ],
+    )
+}
+
+fn balance_and_shortfall(engine: &PaymentsEngine) -> (Decimal, Decimal, bool, Decimal) {
+    let account = engine.client_accounts().get(1.into()).unwrap();
+    (
+        account.balance().available(),
+        account.balance().held(),
+        account.is_locked(),
+        account.shortfall(),
+    )
+}
+
+#[test]
+fn negative_available_allowed_by_default() {
+    let engine = partially_withdrawn_dispute(NegativeAvailable::Allow);
+
+    assert_eq!(
+        balance_and_shortfall(&engine),
+        (dec!(-7.5), dec!(10), false, dec!(0))
+    );
+}
+
+#[test]
+fn capped_dispute_holds_available_and_tracks_shortfall() {
+    let mut engine = partially_withdrawn_dispute(NegativeAvailable::CapAtZero);
+    assert_eq!(
+        balance_and_shortfall(&engine),
+        (dec!(0), dec!(2.5), false, dec!(7.5))
+    );
+
+    let mut charged_back = engine.clone();
+    engine
+        .process_transaction(Resolve::new(1.into(), 1.into()).into())
+        .unwrap();
+    assert_eq!(
+        balance_and_shortfall(&engine),
+        (dec!(2.5), dec!(0), false, dec!(0))
+    );
+
+    // The shortfall stays as the client's debt once charged back.
+    charged_back
+        .process_transaction(Chargeback::new(1.into(), 1.into()).into())
+        .unwrap();
+    assert_eq!(
+        balance_and_shortfall(&charged_back),
+        (dec!(0), dec!(0), true, dec!(7.5))
+    );
+}
+
+#[test]
+fn uncovered_dispute_rejected_when_configured() {
+    let mut engine = partially_withdrawn_dispute(NegativeAvailable::Reject);
+    assert_eq!(
+        balance_and_shortfall(&engine),
+        (dec!(2.5), dec!(0), false, dec!(0))
+    );
+
+    assert_eq!(
+        engine.process_transaction(Dispute::new(1.into(), 1.into()).into()),
+        Err(EngineError::InsufficientFunds {
+            client: 1.into(),
+            tx: 1.into(),
+            requested: amount(dec!(10.0)),
+            available: dec!(2.5),
+        })
+    );
+
+    // Covered disputes apply, and replays keep their own error.
+    engine
+        .process_transaction(
+            Deposit::try_new(1.into(), 3.into(), dec!(1.5))
+                .unwrap()
+                .into(),
+        )
+        .unwrap();
+    engine
+        .process_transaction(Dispute::new(1.into(), 3.into()).into())
+        .unwrap();
+    assert!(matches!(
+        engine.process_transaction(Dispute::new(1.into(), 3.into()).into()),
+        Err(EngineError::TransactionAlreadyDisputed { .. })
+    ));
+}
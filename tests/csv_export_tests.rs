@@ -0,0 +1,90 @@
+use rust_coding_test::{
+    domain::{
+        Adjustment, Authorization, Capture, Chargeback, Deposit, Dispute, Evidence, PreArbitration,
+        Refund, Resolve, Transaction, Void, Withdrawal,
+    },
+    parsing,
+    testgen::Workload,
+};
+use rust_decimal::dec;
+
+fn export(transactions: impl IntoIterator<Item = Transaction>) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    parsing::serialize_csv(&mut writer, transactions).unwrap();
+    String::from_utf8(writer.into_inner().unwrap()).unwrap()
+}
+
+fn every_kind() -> Vec<Transaction> {
+    vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 2.into(), dec!(0.25))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 1.into()).into(),
+        Evidence::new(1.into(), 1.into()).into(),
+        PreArbitration::new(1.into(), 1.into()).into(),
+        Resolve::new(1.into(), 1.into()).into(),
+        Chargeback::new(1.into(), 1.into()).into(),
+        Authorization::try_new(2.into(), 3.into(), dec!(4))
+            .unwrap()
+            .into(),
+        Capture::new(2.into(), 3.into()).into(),
+        Void::new(2.into(), 3.into()).into(),
+        Refund::try_new(1.into(), 1.into(), dec!(0.5))
+            .unwrap()
+            .into(),
+        Adjustment::try_new(3.into(), 4.into(), dec!(-2.75), 9)
+            .unwrap()
+            .into(),
+        Transaction::from(Withdrawal::try_new(1.into(), 5.into(), dec!(1)).unwrap())
+            .with_parent_tx(1.into()),
+    ]
+}
+
+#[test]
+fn every_kind_of_transaction_round_trips() {
+    let transactions = every_kind();
+    let csv = export(transactions.clone());
+
+    let mut rdr = csv::Reader::from_reader(csv.as_bytes());
+    assert_eq!(
+        parsing::deserialize_csv(&mut rdr).collect::<Vec<_>>(),
+        transactions
+    );
+    assert_eq!(parsing::parse_bytes(csv.as_bytes()).0, transactions);
+}
+
+#[test]
+fn writes_the_input_columns() {
+    let csv = export(every_kind().into_iter().skip(10));
+
+    assert_eq!(
+        csv,
+        "type,client,tx,amount,reason,parent_tx\n\
+         refund,1,1,0.5,,\n\
+         adjustment,3,4,-2.75,9,\n\
+         withdrawal,1,5,1,,1\n"
+    );
+    assert_eq!(export([]), "");
+}
+
+#[test]
+fn filtered_workloads_round_trip() {
+    let workload = Workload {
+        rows: 2_000,
+        clients: 50,
+        dispute_rate: 0.1,
+        ..Default::default()
+    };
+    let deposits = workload
+        .transactions()
+        .filter(|transaction| matches!(transaction, Transaction::Deposit(_)))
+        .collect::<Vec<_>>();
+
+    let csv = export(deposits.clone());
+    let (parsed, errors) = parsing::parse_bytes(csv.as_bytes());
+    assert_eq!(parsed, deposits);
+    assert!(errors.is_empty());
+}
@@ -9,9 +9,13 @@ use std::collections::HashMap;
 /// increase the available and total funds of the client account"
 #[test]
 fn single_deposit_creates_account_with_correct_balance() {
-    let engine = run(vec![Deposit::new(1.into(), 1.into(), dec!(50.0)).into()]);
+    let engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(50.0))
+            .unwrap()
+            .into(),
+    ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(50.0), dec!(0.0), false))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(50.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -20,13 +24,21 @@ fn single_deposit_creates_account_with_correct_balance() {
 #[test]
 fn multiple_deposits_accumulate() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(10.0)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(10.0)).into(),
-        Deposit::new(1.into(), 3.into(), dec!(10.0)).into(),
-        Deposit::new(1.into(), 4.into(), dec!(10.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(10.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 3.into(), dec!(10.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 4.into(), dec!(10.0))
+            .unwrap()
+            .into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(40.0), dec!(0.0), false))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(40.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -35,11 +47,15 @@ fn multiple_deposits_accumulate() {
 #[test]
 fn deposits_for_multiple_clients_are_independent() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
-        Deposit::new(2.into(), 2.into(), dec!(200.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 2.into(), dec!(200.0))
+            .unwrap()
+            .into(),
     ]);
 
-    let expected = HashMap::from([
+    let expected = HashMap::from_iter([
         (ClientId::from(1), account(dec!(100.0), dec!(0.0), false)),
         (ClientId::from(2), account(dec!(200.0), dec!(0.0), false)),
     ]);
@@ -51,11 +67,16 @@ fn deposits_for_multiple_clients_are_independent() {
 #[test]
 fn deposit_with_decimal_precision() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(0.1234)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(0.8766)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(0.1234))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(0.8766))
+            .unwrap()
+            .into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(1.0000), dec!(0.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(1.0000), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
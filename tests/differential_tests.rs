@@ -0,0 +1,144 @@
+#![cfg(feature = "testing")]
+
+use proptest::prelude::*;
+use rust_coding_test::{
+    domain::{
+        Authorization, Capture, Chargeback, Deposit, Dispute, Resolve, Transaction, Void,
+        Withdrawal,
+    },
+    engine::{EngineConfig, PaymentsEngine, ReferencedDepositHistory},
+    testing::{
+        differential::{Divergence, diff_against_reference, diff_engine},
+        reference::ReferenceEngine,
+        strategies,
+    },
+};
+use rust_decimal::dec;
+use std::collections::HashSet;
+
+mod common;
+use common::account;
+
+#[test]
+fn reference_follows_dispute_lifecycle() {
+    let mut reference = ReferenceEngine::new();
+
+    assert!(
+        reference.process_transaction(
+            Deposit::try_new(1.into(), 1.into(), dec!(5.0))
+                .unwrap()
+                .into()
+        )
+    );
+    assert!(reference.process_transaction(Dispute::new(1.into(), 1.into()).into()));
+    assert!(!reference.process_transaction(Dispute::new(1.into(), 1.into()).into()));
+    assert!(reference.process_transaction(Chargeback::new(1.into(), 1.into()).into()));
+    assert!(
+        !reference.process_transaction(
+            Deposit::try_new(1.into(), 2.into(), dec!(1.0))
+                .unwrap()
+                .into()
+        )
+    );
+
+    assert_eq!(
+        reference.account(1.into()),
+        account(dec!(0.0), dec!(0.0), true)
+    );
+}
+
+#[test]
+fn engines_agree_on_mixed_stream() {
+    let transactions: Vec<Transaction> = vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 2.into(), dec!(3.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 3.into(), dec!(4.0))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 1.into()).into(),
+        Resolve::new(1.into(), 1.into()).into(),
+        Dispute::new(2.into(), 1.into()).into(),
+        Authorization::try_new(2.into(), 4.into(), dec!(2.0))
+            .unwrap()
+            .into(),
+        Capture::new(2.into(), 4.into()).into(),
+        Void::new(2.into(), 4.into()).into(),
+        Dispute::new(1.into(), 1.into()).into(),
+        Chargeback::new(1.into(), 1.into()).into(),
+    ];
+
+    assert_eq!(diff_against_reference(&transactions), vec![]);
+}
+
+/// The reference only models the default config, so an engine that auto-opens
+/// disputes on chargeback disagrees with it.
+#[test]
+fn divergences_are_reported() {
+    let mut engine = PaymentsEngine::with_config(EngineConfig {
+        chargeback_opens_dispute: true,
+        ..EngineConfig::default()
+    });
+    let transactions: Vec<Transaction> = vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(5.0))
+            .unwrap()
+            .into(),
+        Chargeback::new(1.into(), 1.into()).into(),
+    ];
+
+    let divergences = diff_engine(&mut engine, &transactions);
+
+    assert_eq!(
+        divergences,
+        vec![
+            Divergence::Outcome {
+                index: 1,
+                transaction: Chargeback::new(1.into(), 1.into()).into(),
+                engine: Ok(()),
+                reference_accepted: false,
+            },
+            Divergence::Account {
+                client: 1.into(),
+                engine: Some(account(dec!(0.0), dec!(0.0), true)),
+                reference: Some(account(dec!(5.0), dec!(0.0), false)),
+            },
+        ]
+    );
+}
+
+proptest! {
+    /// Small ID ranges so most disputes, captures and voids hit earlier transactions.
+    #[test]
+    fn engine_matches_reference(
+        transactions in prop::collection::vec(strategies::transaction_in(1..=3, 1..=8), 0..96)
+    ) {
+        prop_assert_eq!(diff_against_reference(&transactions), vec![]);
+    }
+
+    /// The two-pass store only drops deposits nothing refers to, so it can't change results
+    /// of inputs without reused tx IDs, which it doesn't reject.
+    #[test]
+    fn two_pass_engine_matches_reference(
+        transactions in prop::collection::vec(strategies::transaction_in(1..=3, 1..=8), 0..96)
+    ) {
+        let mut introduced = HashSet::new();
+        let transactions: Vec<Transaction> = transactions
+            .into_iter()
+            .filter(|transaction| match transaction {
+                Transaction::Deposit(_)
+                | Transaction::Withdrawal(_)
+                | Transaction::Authorization(_)
+                | Transaction::Adjustment(_) => introduced.insert(transaction.tx_id()),
+                _ => true,
+            })
+            .collect();
+        let mut engine = PaymentsEngine::with_store(
+            EngineConfig::default(),
+            ReferencedDepositHistory::scan(transactions.iter().cloned()),
+        );
+        prop_assert_eq!(diff_engine(&mut engine, &transactions), vec![]);
+    }
+}
@@ -0,0 +1,53 @@
+use rust_coding_test::domain::{
+    Chargeback, ClientId, Deposit, Dispute, Resolve, Transaction, TransactionId, Withdrawal,
+};
+use rust_decimal::dec;
+
+#[test]
+fn ids_display_as_plain_numbers() {
+    assert_eq!(ClientId::from(7).to_string(), "7");
+    assert_eq!(TransactionId::from(42).to_string(), "42");
+}
+
+#[test]
+fn ids_parse_from_str() {
+    assert_eq!("7".parse::<ClientId>().unwrap(), ClientId::from(7));
+    assert_eq!(
+        "42".parse::<TransactionId>().unwrap(),
+        TransactionId::from(42)
+    );
+    // ClientId is a u16 per spec
+    assert!("70000".parse::<ClientId>().is_err());
+    assert!("abc".parse::<TransactionId>().is_err());
+}
+
+#[test]
+fn transactions_display_summary() {
+    let transactions: Vec<Transaction> = vec![
+        Deposit::try_new(1.into(), 2.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 3.into(), dec!(0.5))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 2.into()).into(),
+        Resolve::new(1.into(), 2.into()).into(),
+        Chargeback::new(1.into(), 2.into()).into(),
+    ];
+
+    let summaries = transactions
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        summaries,
+        vec![
+            "deposit tx 2 (client 1, amount 1.5)",
+            "withdrawal tx 3 (client 1, amount 0.5)",
+            "dispute of tx 2 (client 1)",
+            "resolve of tx 2 (client 1)",
+            "chargeback of tx 2 (client 1)",
+        ]
+    );
+}
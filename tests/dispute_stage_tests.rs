@@ -0,0 +1,142 @@
+use rust_coding_test::{
+    domain::{
+        Chargeback, Deposit, Dispute, DisputeState, Evidence, PreArbitration, Resolve, Transaction,
+    },
+    engine::{Outcome, PaymentsEngine, errors::EngineError},
+    parsing,
+};
+use rust_decimal::dec;
+
+mod common;
+use common::amount;
+
+fn deposit(client: u16, tx: u32) -> Transaction {
+    Deposit::try_new(client.into(), tx.into(), dec!(1.5))
+        .unwrap()
+        .into()
+}
+
+fn state(engine: &PaymentsEngine, tx: u32) -> DisputeState {
+    engine.deposit_history().as_map()[&tx.into()].dispute_state()
+}
+
+#[test]
+fn staged_dispute_keeps_funds_held_until_charged_back() {
+    let mut engine = PaymentsEngine::new();
+    engine.process_transactions(
+        [
+            deposit(1, 1),
+            Dispute::new(1.into(), 1.into()).into(),
+            Evidence::new(1.into(), 1.into()).into(),
+        ]
+        .into_iter(),
+    );
+    assert_eq!(state(&engine, 1), DisputeState::EvidenceSubmitted);
+
+    engine
+        .process_transaction(PreArbitration::new(1.into(), 1.into()).into())
+        .unwrap();
+    assert_eq!(state(&engine, 1), DisputeState::PreArbitration);
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    assert_eq!((balance.available(), balance.held()), (dec!(0), dec!(1.5)));
+
+    engine
+        .process_transaction(Chargeback::new(1.into(), 1.into()).into())
+        .unwrap();
+    let account = engine.client_accounts().get(1.into()).unwrap();
+    assert!(account.is_locked());
+    assert_eq!(account.balance().total(), dec!(0));
+}
+
+#[test]
+fn any_open_stage_can_be_resolved() {
+    let mut engine = PaymentsEngine::new();
+    engine.process_transactions(
+        [
+            deposit(1, 1),
+            Dispute::new(1.into(), 1.into()).into(),
+            Evidence::new(1.into(), 1.into()).into(),
+            Resolve::new(1.into(), 1.into()).into(),
+        ]
+        .into_iter(),
+    );
+    assert_eq!(state(&engine, 1), DisputeState::Resolved);
+
+    // A resolved dispute can be reopened and walks the stages again.
+    engine.process_transactions(
+        [
+            Dispute::new(1.into(), 1.into()).into(),
+            Evidence::new(1.into(), 1.into()).into(),
+            PreArbitration::new(1.into(), 1.into()).into(),
+            Resolve::new(1.into(), 1.into()).into(),
+        ]
+        .into_iter(),
+    );
+    assert_eq!(state(&engine, 1), DisputeState::Resolved);
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    assert_eq!((balance.available(), balance.held()), (dec!(1.5), dec!(0)));
+}
+
+#[test]
+fn out_of_order_stages_are_ignored() {
+    let mut engine = PaymentsEngine::new();
+    engine.process_transaction(deposit(1, 1)).unwrap();
+    assert!(matches!(
+        engine.process_transaction(Evidence::new(1.into(), 1.into()).into()),
+        Err(EngineError::TransactionNotDisputed { .. })
+    ));
+
+    engine
+        .process_transaction(Dispute::new(1.into(), 1.into()).into())
+        .unwrap();
+    let error = EngineError::DisputeStageOutOfOrder {
+        client: 1.into(),
+        tx: 1.into(),
+        amount: amount(dec!(1.5)),
+        stage: DisputeState::Open,
+    };
+    assert_eq!(
+        engine.apply(PreArbitration::new(1.into(), 1.into()).into()),
+        Outcome::from(error)
+    );
+
+    engine
+        .process_transaction(Evidence::new(1.into(), 1.into()).into())
+        .unwrap();
+    assert!(matches!(
+        engine.process_transaction(Evidence::new(1.into(), 1.into()).into()),
+        Err(EngineError::DisputeStageOutOfOrder {
+            stage: DisputeState::EvidenceSubmitted,
+            ..
+        })
+    ));
+    assert!(matches!(
+        engine.process_transaction(Dispute::new(1.into(), 1.into()).into()),
+        Err(EngineError::TransactionAlreadyDisputed { .. })
+    ));
+}
+
+#[test]
+fn stage_rows_are_parsed() {
+    let input = "type,client,tx,amount\n\
+                 evidence,1,1,\n\
+                 pre_arbitration,1,1,\n";
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+
+    let transactions = parsing::deserialize_csv(&mut rdr).collect::<Vec<_>>();
+
+    assert_eq!(
+        transactions,
+        vec![
+            Evidence::new(1.into(), 1.into()).into(),
+            PreArbitration::new(1.into(), 1.into()).into(),
+        ]
+    );
+    assert_eq!(
+        parsing::parse_bytes(input.as_bytes()).0,
+        transactions,
+        "byte records parse the same rows"
+    );
+}
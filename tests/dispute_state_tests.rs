@@ -0,0 +1,50 @@
+use rust_coding_test::domain::{DisputeState, DisputeTransitionError};
+
+#[test]
+fn open_then_resolve() {
+    let mut state = DisputeState::default();
+
+    assert_eq!(state.open(), Ok(()));
+    assert_eq!(state, DisputeState::Open);
+    assert_eq!(state.resolve(), Ok(()));
+    assert_eq!(state, DisputeState::Resolved);
+}
+
+/// Assumption 2: a resolved transaction can be disputed again.
+#[test]
+fn resolved_can_be_reopened() {
+    let mut state = DisputeState::Resolved;
+
+    assert_eq!(state.open(), Ok(()));
+    assert_eq!(state, DisputeState::Open);
+}
+
+/// Assumption 2: a charged back transaction cannot be disputed again.
+#[test]
+fn charged_back_is_final() {
+    let mut state = DisputeState::Open;
+
+    assert_eq!(state.charge_back(), Ok(()));
+    assert_eq!(state.open(), Err(DisputeTransitionError::AlreadyDisputed));
+    assert_eq!(state.resolve(), Err(DisputeTransitionError::NotDisputed));
+    assert_eq!(
+        state.charge_back(),
+        Err(DisputeTransitionError::NotDisputed)
+    );
+    assert_eq!(state, DisputeState::ChargedBack);
+}
+
+#[test]
+fn illegal_transitions_leave_state_unchanged() {
+    let mut state = DisputeState::None;
+    assert_eq!(state.resolve(), Err(DisputeTransitionError::NotDisputed));
+    assert_eq!(
+        state.charge_back(),
+        Err(DisputeTransitionError::NotDisputed)
+    );
+    assert_eq!(state, DisputeState::None);
+
+    let mut state = DisputeState::Open;
+    assert_eq!(state.open(), Err(DisputeTransitionError::AlreadyDisputed));
+    assert_eq!(state, DisputeState::Open);
+}
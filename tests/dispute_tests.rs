@@ -10,11 +10,14 @@ use std::collections::HashMap;
 #[test]
 fn dispute_moves_funds_to_held() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(0.0), dec!(100.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(100.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -23,11 +26,14 @@ fn dispute_moves_funds_to_held() {
 #[test]
 fn dispute_on_nonexistent_transaction_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 99.into()).into(), // tx 99 doesn't exist
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -37,11 +43,13 @@ fn dispute_on_nonexistent_transaction_is_ignored() {
 #[test]
 fn dispute_on_wrong_client_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(2.into(), 1.into()).into(),
     ]);
 
-    let expected = HashMap::from([
+    let expected = HashMap::from_iter([
         (ClientId::from(1), account(dec!(100.0), dec!(0.0), false)),
         (ClientId::from(2), account(dec!(0.0), dec!(0.0), false)),
     ]);
@@ -54,12 +62,15 @@ fn dispute_on_wrong_client_is_ignored() {
 fn duplicate_dispute_on_same_transaction_is_ignored() {
     // The second dispute on the same tx must be rejected; held stays at 100, not 200.
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Dispute::new(1.into(), 1.into()).into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(0.0), dec!(100.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(100.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -68,12 +79,17 @@ fn duplicate_dispute_on_same_transaction_is_ignored() {
 #[test]
 fn dispute_partial_deposit_leaves_remaining_available() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(30.0)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(70.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(30.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(70.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(), // only dispute the first deposit
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(70.0), dec!(30.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(70.0), dec!(30.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -82,12 +98,16 @@ fn dispute_partial_deposit_leaves_remaining_available() {
 #[test]
 fn dispute_on_withdrawal_tx_id_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
-        Withdrawal::new(1.into(), 2.into(), dec!(40.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 2.into(), dec!(40.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 2.into()).into(), // tx 2 is a withdrawal
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(60.0), dec!(0.0), false))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(60.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -96,15 +116,22 @@ fn dispute_on_withdrawal_tx_id_is_ignored() {
 #[test]
 fn multiple_disputes_on_different_txs() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(30.0)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(70.0)).into(),
-        Deposit::new(1.into(), 3.into(), dec!(50.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(30.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(70.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 3.into(), dec!(50.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(), // dispute 30
         Dispute::new(1.into(), 2.into()).into(), // dispute 70
     ]);
 
     // available = 150 - 30 - 70 = 50, held = 30 + 70 = 100
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(50.0), dec!(100.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(50.0), dec!(100.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -117,10 +144,18 @@ fn multiple_disputes_on_different_txs() {
 #[test]
 fn interleaved_disputes_with_mixed_outcomes() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(10.0)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(20.0)).into(),
-        Deposit::new(1.into(), 3.into(), dec!(30.0)).into(),
-        Deposit::new(1.into(), 4.into(), dec!(40.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(20.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 3.into(), dec!(30.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 4.into(), dec!(40.0))
+            .unwrap()
+            .into(),
         // total deposited = 100, available = 100
         Dispute::new(1.into(), 1.into()).into(), // hold 10
         Dispute::new(1.into(), 2.into()).into(), // hold 20
@@ -132,7 +167,7 @@ fn interleaved_disputes_with_mixed_outcomes() {
                                                     // available = 50, held = 30, total = 80, locked = true
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(50.0), dec!(30.0), true))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(50.0), dec!(30.0), true))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -142,12 +177,17 @@ fn interleaved_disputes_with_mixed_outcomes() {
 fn balance_total_equals_available_plus_held() {
     // 100 + 50 deposited; tx 1 (100) disputed → available = 50, held = 100, total = 150.
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(50.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(50.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(50.0), dec!(100.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(50.0), dec!(100.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -159,12 +199,17 @@ fn dispute_after_partial_withdrawal_allows_negative_available() {
     // Deposit 100, withdraw 60, dispute the original deposit of 100.
     // available = 100 - 60 - 100 = -60, held = 100, total = 40.
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
-        Withdrawal::new(1.into(), 2.into(), dec!(60.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 2.into(), dec!(60.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(-60.0), dec!(100.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(-60.0), dec!(100.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -175,13 +220,17 @@ fn dispute_and_chargeback_after_partial_withdrawal_allows_negative_available() {
     // Deposit 100, withdraw 60, dispute the original deposit of 100.
     // available = 100 - 60 - 100 = -60, held = 100, total = 40.
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
-        Withdrawal::new(1.into(), 2.into(), dec!(60.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 2.into(), dec!(60.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Chargeback::new(1.into(), 1.into()).into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(-60.0), dec!(0.0), true))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(-60.0), dec!(0.0), true))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
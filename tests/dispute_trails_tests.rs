@@ -0,0 +1,130 @@
+use rust_coding_test::{
+    dispute_trails::{
+        DiagramFormat, DisputeEvent, DisputeTrail, DisputeTrailRecorder, TrailFilter,
+    },
+    domain::{Chargeback, Deposit, Dispute, Resolve, Transaction},
+    engine::{ApplyTransactions, EngineConfig},
+};
+use rust_decimal::dec;
+
+fn transactions() -> Vec<Transaction> {
+    vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(2.25))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 3.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 1.into()).into(),
+        Resolve::new(1.into(), 1.into()).into(),
+        Dispute::new(1.into(), 1.into()).into(),
+        Chargeback::new(1.into(), 1.into()).into(),
+        // Rejected: already charged back.
+        Dispute::new(1.into(), 1.into()).into(),
+        Chargeback::new(1.into(), 2.into()).into(),
+        Dispute::new(2.into(), 3.into()).into(),
+    ]
+}
+
+fn record(config: EngineConfig, filter: TrailFilter) -> DisputeTrailRecorder {
+    let mut recorder = DisputeTrailRecorder::new(config, filter);
+    recorder.apply_all(transactions().into_iter());
+    recorder
+}
+
+#[test]
+fn applied_transitions_per_deposit() {
+    let recorder = record(EngineConfig::default(), TrailFilter::All);
+
+    let trails: Vec<_> = recorder.trails().cloned().collect();
+    assert_eq!(
+        trails,
+        vec![
+            DisputeTrail {
+                client: 1.into(),
+                tx: 1.into(),
+                amount: dec!(1.5),
+                events: vec![
+                    DisputeEvent::Opened,
+                    DisputeEvent::Resolved,
+                    DisputeEvent::Opened,
+                    DisputeEvent::ChargedBack,
+                ],
+            },
+            DisputeTrail {
+                client: 2.into(),
+                tx: 3.into(),
+                amount: dec!(1.5),
+                events: vec![DisputeEvent::Opened],
+            },
+        ]
+    );
+}
+
+#[test]
+fn chargeback_opening_its_dispute_records_both() {
+    let config = EngineConfig {
+        chargeback_opens_dispute: true,
+        ..EngineConfig::default()
+    };
+    let recorder = record(config, TrailFilter::Transaction(2.into()));
+
+    let trails: Vec<_> = recorder.trails().collect();
+    assert_eq!(trails.len(), 1);
+    assert_eq!(
+        trails[0].events,
+        vec![DisputeEvent::Opened, DisputeEvent::ChargedBack]
+    );
+}
+
+#[test]
+fn filtered_by_client() {
+    let recorder = record(EngineConfig::default(), TrailFilter::Client(2.into()));
+
+    let txs: Vec<u32> = recorder.trails().map(|trail| u32::from(trail.tx)).collect();
+    assert_eq!(txs, vec![3]);
+    // Other clients are still processed.
+    assert_eq!(recorder.engine().client_accounts().len(), 2);
+}
+
+#[test]
+fn renders_mermaid() {
+    let recorder = record(EngineConfig::default(), TrailFilter::Client(1.into()));
+
+    assert_eq!(
+        recorder.diagram(DiagramFormat::Mermaid).to_string(),
+        "flowchart LR\n    \
+         subgraph tx1[\"tx 1 (client 1, amount 1.5)\"]\n        \
+         tx1_0[\"deposited\"]\n        \
+         tx1_1[\"open\"]\n        \
+         tx1_2[\"resolved\"]\n        \
+         tx1_3[\"open\"]\n        \
+         tx1_4[\"charged back\"]\n        \
+         tx1_0 -->|dispute| tx1_1\n        \
+         tx1_1 -->|resolve| tx1_2\n        \
+         tx1_2 -->|re-dispute| tx1_3\n        \
+         tx1_3 -->|chargeback| tx1_4\n    \
+         end\n"
+    );
+}
+
+#[test]
+fn renders_graphviz() {
+    let recorder = record(EngineConfig::default(), TrailFilter::Transaction(3.into()));
+
+    assert_eq!(
+        recorder.diagram(DiagramFormat::Graphviz).to_string(),
+        "digraph disputes {\n    \
+         rankdir=LR;\n    \
+         subgraph cluster_tx3 {\n        \
+         label=\"tx 3 (client 2, amount 1.5)\";\n        \
+         tx3_0 [label=\"deposited\"];\n        \
+         tx3_1 [label=\"open\"];\n        \
+         tx3_0 -> tx3_1 [label=\"dispute\"];\n    \
+         }\n\
+         }\n"
+    );
+}
@@ -0,0 +1,109 @@
+use rust_coding_test::{
+    domain::{Chargeback, Deposit, Dispute, Transaction},
+    engine::{EngineConfig, Outcome, PaymentsEngine, errors::EngineError},
+    rejection::RejectionReason,
+    report::RunReport,
+};
+use rust_decimal::dec;
+
+mod common;
+use common::amount;
+
+fn windowed(window: u64) -> PaymentsEngine {
+    PaymentsEngine::with_config(EngineConfig {
+        dispute_window: Some(window),
+        ..Default::default()
+    })
+}
+
+fn deposit(client: u16, tx: u32) -> Transaction {
+    Deposit::try_new(client.into(), tx.into(), dec!(1.5))
+        .unwrap()
+        .into()
+}
+
+#[test]
+fn disputes_within_the_window_apply() {
+    let mut engine = windowed(2);
+    engine.process_transactions([deposit(1, 1), deposit(2, 2)].into_iter());
+
+    // The dispute is the second transaction after the deposit.
+    engine
+        .process_transaction(Dispute::new(1.into(), 1.into()).into())
+        .unwrap();
+    assert_eq!(
+        engine
+            .client_accounts()
+            .get(1.into())
+            .unwrap()
+            .balance()
+            .held(),
+        dec!(1.5)
+    );
+}
+
+#[test]
+fn stale_disputes_are_rejected_without_holding_funds() {
+    let mut engine = windowed(2);
+    engine.process_transactions([deposit(1, 1), deposit(2, 2), deposit(2, 3)].into_iter());
+
+    let error = EngineError::DisputeWindowExpired {
+        client: 1.into(),
+        tx: 1.into(),
+        amount: amount(dec!(1.5)),
+        age: 3,
+        window: 2,
+    };
+    assert_eq!(error.reason(), RejectionReason::DisputeWindowExpired);
+    assert_eq!(
+        engine.apply(Dispute::new(1.into(), 1.into()).into()),
+        Outcome::Rejected(error)
+    );
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    assert_eq!((balance.available(), balance.held()), (dec!(1.5), dec!(0)));
+
+    let report = RunReport::new(&engine, 0);
+    assert_eq!(report.expired_disputes, 1);
+    assert!(report.to_string().contains("Expired disputes: 1\n"));
+}
+
+#[test]
+fn chargebacks_opening_a_stale_dispute_are_rejected() {
+    let mut engine = PaymentsEngine::with_config(EngineConfig {
+        dispute_window: Some(1),
+        chargeback_opens_dispute: true,
+        ..Default::default()
+    });
+    engine.process_transactions([deposit(1, 1), deposit(2, 2)].into_iter());
+
+    assert!(matches!(
+        engine.process_transaction(Chargeback::new(1.into(), 1.into()).into()),
+        Err(EngineError::DisputeWindowExpired { age: 2, .. })
+    ));
+    assert!(!engine.client_accounts().get(1.into()).unwrap().is_locked());
+}
+
+#[test]
+fn open_disputes_settle_after_the_window() {
+    let mut engine = windowed(1);
+    engine.process_transactions(
+        [
+            deposit(1, 1),
+            Dispute::new(1.into(), 1.into()).into(),
+            deposit(2, 2),
+            deposit(2, 3),
+        ]
+        .into_iter(),
+    );
+
+    // The window only limits opening disputes; settling an open one still applies.
+    engine
+        .process_transaction(Chargeback::new(1.into(), 1.into()).into())
+        .unwrap();
+    // And an open dispute keeps its own error when repeated.
+    assert!(matches!(
+        engine.process_transaction(Dispute::new(1.into(), 1.into()).into()),
+        Err(EngineError::TransactionAlreadyDisputed { .. })
+    ));
+    assert_eq!(engine.expired_disputes(), 0);
+}
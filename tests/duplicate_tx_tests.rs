@@ -0,0 +1,100 @@
+use rust_coding_test::{
+    domain::{Deposit, Dispute, Transaction, Withdrawal},
+    engine::{Outcome, PaymentsEngine, errors::EngineError},
+    rejection::RejectionReason,
+};
+use rust_decimal::dec;
+
+fn deposit(client: u16, tx: u32, amount: rust_decimal::Decimal) -> Transaction {
+    Deposit::try_new(client.into(), tx.into(), amount)
+        .unwrap()
+        .into()
+}
+
+/// A reused ID no longer replaces the first deposit, so disputes still find it.
+#[test]
+fn duplicate_deposit_is_rejected_and_the_original_kept() {
+    let mut engine = PaymentsEngine::new();
+    engine.apply(deposit(1, 1, dec!(10)));
+
+    let outcome = engine.apply(deposit(2, 1, dec!(3)));
+    assert_eq!(
+        outcome,
+        Outcome::Rejected(EngineError::DuplicateTransactionId {
+            client: 2.into(),
+            tx: 1.into(),
+        })
+    );
+    assert_eq!(
+        outcome.reason(),
+        Some(RejectionReason::DuplicateTransactionId)
+    );
+
+    engine
+        .process_transaction(Dispute::new(1.into(), 1.into()).into())
+        .unwrap();
+    let accounts = engine.client_accounts();
+    assert_eq!(accounts.get(1.into()).unwrap().balance().held(), dec!(10));
+    assert_eq!(accounts.get(2.into()).unwrap().balance().total(), dec!(0));
+}
+
+#[test]
+fn withdrawal_reusing_any_earlier_id_is_rejected() {
+    let mut engine = PaymentsEngine::new();
+    engine.apply(deposit(1, 1, dec!(10)));
+    // Rejected for insufficient funds, but its ID is taken all the same.
+    engine.apply(
+        Withdrawal::try_new(1.into(), 2.into(), dec!(50))
+            .unwrap()
+            .into(),
+    );
+
+    for tx in [1, 2] {
+        assert!(matches!(
+            engine.apply(
+                Withdrawal::try_new(1.into(), tx.into(), dec!(1))
+                    .unwrap()
+                    .into()
+            ),
+            Outcome::Rejected(EngineError::DuplicateTransactionId { .. })
+        ));
+    }
+    assert_eq!(
+        engine
+            .client_accounts()
+            .get(1.into())
+            .unwrap()
+            .balance()
+            .available(),
+        dec!(10)
+    );
+}
+
+/// Grouping by client would reject the other one of the two.
+#[test]
+fn batch_rejects_the_same_duplicate_as_sequential_application() {
+    let transactions = [deposit(2, 1, dec!(1)), deposit(1, 1, dec!(2))];
+    let mut sequential = PaymentsEngine::new();
+    let expected = sequential.process_transactions_reporting(transactions.clone().into_iter());
+
+    let mut batched = PaymentsEngine::new();
+    assert_eq!(batched.process_batch(&transactions), expected);
+    assert_eq!(expected.rejected(), 1);
+    assert!(matches!(expected.outcome(1), Some(Outcome::Rejected(_))));
+}
+
+/// The largest tx ID is tracked like any other, without sizing anything by its value.
+#[test]
+fn max_tx_id_is_tracked() {
+    let mut engine = PaymentsEngine::new();
+    assert_eq!(
+        engine.apply(deposit(1, u32::MAX, dec!(1))),
+        Outcome::Applied
+    );
+
+    assert!(matches!(
+        engine.apply(deposit(2, u32::MAX, dec!(1))),
+        Outcome::Rejected(EngineError::DuplicateTransactionId { .. })
+    ));
+    assert_eq!(engine.apply(deposit(2, 0, dec!(1))), Outcome::Applied);
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::amount;
+use rust_coding_test::{
+    domain::{Chargeback, Deposit, Dispute, Resolve, Withdrawal},
+    engine::{PaymentsEngine, errors::EngineError},
+};
+use rust_decimal::dec;
+
+#[test]
+fn insufficient_funds_reports_requested_and_available() {
+    let mut engine = PaymentsEngine::new();
+    engine
+        .process_transaction(
+            Deposit::try_new(1.into(), 1.into(), dec!(10.5))
+                .unwrap()
+                .into(),
+        )
+        .unwrap();
+
+    let error = engine
+        .process_transaction(
+            Withdrawal::try_new(1.into(), 2.into(), dec!(15.25))
+                .unwrap()
+                .into(),
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        error,
+        EngineError::InsufficientFunds {
+            client: 1.into(),
+            tx: 2.into(),
+            requested: amount(dec!(15.25)),
+            available: dec!(10.5),
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Insufficient funds for client 1: tx 2 requested 15.25, available 10.5"
+    );
+}
+
+#[test]
+fn not_found_reports_client_and_tx() {
+    let mut engine = PaymentsEngine::new();
+
+    let error = engine
+        .process_transaction(Dispute::new(3.into(), 99.into()).into())
+        .unwrap_err();
+
+    assert_eq!(
+        error,
+        EngineError::TransactionNotFound {
+            client: 3.into(),
+            tx: 99.into(),
+        }
+    );
+    assert_eq!(error.to_string(), "Transaction 99 not found for client 3");
+}
+
+#[test]
+fn dispute_errors_report_the_disputed_deposit() {
+    let mut engine = PaymentsEngine::new();
+    engine
+        .process_transaction(
+            Deposit::try_new(1.into(), 1.into(), dec!(5.0))
+                .unwrap()
+                .into(),
+        )
+        .unwrap();
+
+    let error = engine
+        .process_transaction(Resolve::new(1.into(), 1.into()).into())
+        .unwrap_err();
+    assert_eq!(
+        error,
+        EngineError::TransactionNotDisputed {
+            client: 1.into(),
+            tx: 1.into(),
+            amount: amount(dec!(5.0)),
+        }
+    );
+
+    engine
+        .process_transaction(Dispute::new(1.into(), 1.into()).into())
+        .unwrap();
+    let error = engine
+        .process_transaction(Dispute::new(1.into(), 1.into()).into())
+        .unwrap_err();
+    assert_eq!(
+        error,
+        EngineError::TransactionAlreadyDisputed {
+            client: 1.into(),
+            tx: 1.into(),
+            amount: amount(dec!(5.0)),
+        }
+    );
+}
+
+#[test]
+fn locked_account_reports_rejected_movement() {
+    let mut engine = PaymentsEngine::new();
+    for transaction in [
+        Deposit::try_new(1.into(), 1.into(), dec!(5.0))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 1.into()).into(),
+        Chargeback::new(1.into(), 1.into()).into(),
+    ] {
+        engine.process_transaction(transaction).unwrap();
+    }
+
+    let error = engine
+        .process_transaction(
+            Deposit::try_new(1.into(), 2.into(), dec!(7.0))
+                .unwrap()
+                .into(),
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        error,
+        EngineError::AccountLocked {
+            client: 1.into(),
+            tx: 2.into(),
+            amount: amount(dec!(7.0)),
+        }
+    );
+}
@@ -0,0 +1,153 @@
+#![cfg(feature = "fixed-point")]
+
+mod common;
+
+use common::{account, amount, run};
+use rust_coding_test::{
+    domain::{Adjustment, Amount, ClientId, Deposit, Dispute, DomainError, Withdrawal},
+    engine::{AccountTiers, EngineConfig, Outcome, PaymentsEngine, errors::EngineError},
+    parsing::{ByteRecordParser, IntoTransactionError, ParseError},
+    rejection::RejectionReason,
+};
+use rust_decimal::{Decimal, dec};
+use std::collections::HashMap;
+
+#[test]
+fn sums_are_exact() {
+    let engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(0.1))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(0.2))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 3.into(), dec!(0.0003))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 2.into()).into(),
+    ]);
+
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(0.0997), dec!(0.2), false))]);
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+#[test]
+fn values_read_back_normalized() {
+    assert_eq!(amount(dec!(1.5000)).value().to_string(), "1.5");
+    assert_eq!(amount(dec!(20)).to_string(), "20");
+}
+
+#[test]
+fn amounts_beyond_i64_units_are_rejected() {
+    // 10^15 whole units is 10^19 fixed-point units, past i64::MAX.
+    let result = Amount::try_from(Decimal::from(1_000_000_000_000_000_i64));
+
+    assert!(matches!(result, Err(DomainError::AmountTooLarge)));
+}
+
+#[test]
+fn too_large_amount_is_an_invalid_amount_row() {
+    let input = "type,client,tx,amount\ndeposit,1,1,1000000000000000\n";
+    let mut rdr = csv::ReaderBuilder::new().from_reader(input.as_bytes());
+
+    let results: Vec<_> = ByteRecordParser::new(&mut rdr).collect();
+
+    let [Err(error)] = results.as_slice() else {
+        panic!("expected a single error, got {results:?}");
+    };
+    assert!(matches!(
+        error,
+        ParseError::Invalid {
+            source: IntoTransactionError::InvalidAmount(DomainError::AmountTooLarge),
+            ..
+        }
+    ));
+    assert_eq!(error.reason(), RejectionReason::InvalidAmount);
+}
+
+#[test]
+fn deposit_overflowing_the_balance_is_rejected() {
+    let mut engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(900000000000000))
+            .unwrap()
+            .into(),
+    ]);
+
+    assert_eq!(
+        engine.apply(
+            Deposit::try_new(1.into(), 2.into(), dec!(900000000000000))
+                .unwrap()
+                .into()
+        ),
+        Outcome::Rejected(EngineError::AmountTooLarge {
+            client: 1.into(),
+            tx: 2.into(),
+        })
+    );
+    let expected = HashMap::from_iter([(
+        ClientId::from(1),
+        account(dec!(900000000000000), dec!(0), false),
+    )]);
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+#[test]
+fn dispute_overflowing_held_is_rejected() {
+    let mut engine = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(900000000000000))
+            .unwrap()
+            .into(),
+        Adjustment::try_new(1.into(), 2.into(), dec!(-900000000000000), 1)
+            .unwrap()
+            .into(),
+        Adjustment::try_new(1.into(), 3.into(), dec!(-900000000000000), 1)
+            .unwrap()
+            .into(),
+    ]);
+
+    let outcome = engine.apply(Dispute::new(1.into(), 1.into()).into());
+
+    assert_eq!(outcome.reason(), Some(RejectionReason::AmountTooLarge));
+    let expected = HashMap::from_iter([(
+        ClientId::from(1),
+        account(dec!(-900000000000000), dec!(0), false),
+    )]);
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
+
+#[test]
+fn withdrawal_overflowing_the_overdraft_headroom_is_rejected() {
+    let tiers = r#"{
+        "tiers": { "gold": { "overdraft": "900000000000000" } },
+        "clients": { "1": "gold" }
+    }"#;
+    let mut engine = PaymentsEngine::with_config(EngineConfig {
+        tiers: AccountTiers::from_reader(tiers.as_bytes()).unwrap(),
+        ..Default::default()
+    });
+    engine
+        .process_transaction(
+            Deposit::try_new(1.into(), 1.into(), dec!(900000000000000))
+                .unwrap()
+                .into(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        engine.apply(
+            Withdrawal::try_new(1.into(), 2.into(), dec!(1))
+                .unwrap()
+                .into()
+        ),
+        Outcome::Rejected(EngineError::AmountTooLarge {
+            client: 1.into(),
+            tx: 2.into(),
+        })
+    );
+    let expected = HashMap::from_iter([(
+        ClientId::from(1),
+        account(dec!(900000000000000), dec!(0), false),
+    )]);
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
@@ -0,0 +1,83 @@
+use std::process::Command;
+
+use rust_coding_test::parsing::parse_bytes;
+
+fn generate(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg("generate")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    output.stdout
+}
+
+#[test]
+fn same_seed_same_workload() {
+    let args = [
+        "--transactions",
+        "2000",
+        "--dispute-rate",
+        "0.05",
+        "--seed",
+        "42",
+    ];
+    assert_eq!(generate(&args), generate(&args));
+
+    let other_seed = [
+        "--transactions",
+        "2000",
+        "--dispute-rate",
+        "0.05",
+        "--seed",
+        "43",
+    ];
+    assert_ne!(generate(&args), generate(&other_seed));
+}
+
+#[test]
+fn rows_and_clients_as_requested() {
+    let (transactions, errors) = parse_bytes(&generate(&[
+        "--clients",
+        "7",
+        "--transactions",
+        "1000",
+        "--dispute-rate",
+        "0.1",
+    ]));
+
+    assert!(errors.is_empty());
+    assert_eq!(transactions.len(), 1000);
+    assert!(
+        transactions
+            .iter()
+            .all(|transaction| (1..=7).contains(&u16::from(transaction.client_id())))
+    );
+}
+
+#[test]
+fn invalid_rows_on_request() {
+    let (transactions, errors) = parse_bytes(&generate(&[
+        "--transactions",
+        "1000",
+        "--invalid-rate",
+        "0.2",
+    ]));
+
+    assert_eq!(transactions.len() + errors.len(), 1000);
+    assert!(
+        (100..300).contains(&errors.len()),
+        "{} invalid rows",
+        errors.len()
+    );
+}
+
+#[test]
+fn rates_must_be_probabilities() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .args(["generate", "--dispute-rate", "1.5"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+}
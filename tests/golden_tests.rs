@@ -0,0 +1,101 @@
+#![cfg(feature = "testing")]
+
+use std::{fs, path::PathBuf};
+
+use rust_coding_test::{
+    domain::{Chargeback, Deposit, Dispute, Transaction, Withdrawal},
+    engine::PaymentsEngine,
+    testing::{UPDATE_SNAPSHOTS_VAR, accounts_snapshot, assert_accounts_snapshot},
+};
+use rust_decimal::dec;
+
+mod common;
+use common::run;
+
+fn lifecycle() -> PaymentsEngine {
+    let transactions: Vec<Transaction> = vec![
+        Deposit::try_new(2.into(), 1.into(), dec!(3.75))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(2.25))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 3.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Dispute::new(2.into(), 1.into()).into(),
+        Deposit::try_new(3.into(), 4.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Dispute::new(3.into(), 4.into()).into(),
+        Chargeback::new(3.into(), 4.into()).into(),
+    ];
+    run(transactions)
+}
+
+/// Failing assertions can't be checked while golden files are being updated.
+fn updating() -> bool {
+    std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("golden_tests_{name}_{}.csv", std::process::id()))
+}
+
+#[test]
+fn matches_golden_file() {
+    assert_accounts_snapshot(&lifecycle(), "tests/golden/lifecycle.csv");
+}
+
+#[test]
+fn snapshot_is_the_sorted_output() {
+    let snapshot = accounts_snapshot(&lifecycle());
+    let clients: Vec<_> = snapshot
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').next().unwrap())
+        .collect();
+
+    assert!(snapshot.starts_with("client,available,held,total,locked"));
+    assert_eq!(clients, ["1", "2", "3"]);
+}
+
+#[test]
+fn mismatch_lists_differing_lines() {
+    if updating() {
+        return;
+    }
+    let path = temp_path("mismatch");
+    fs::write(
+        &path,
+        accounts_snapshot(&lifecycle()).replace("0.75", "0.5"),
+    )
+    .unwrap();
+
+    let panic =
+        std::panic::catch_unwind(|| assert_accounts_snapshot(&lifecycle(), &path)).unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(message.contains("-1,0.5,"), "{message}");
+    assert!(message.contains("+1,0.75,"), "{message}");
+    assert!(!message.contains("2,"), "{message}");
+}
+
+#[test]
+fn missing_snapshot_is_written_but_fails() {
+    if updating() {
+        return;
+    }
+    let path = temp_path("missing");
+    let _ = fs::remove_file(&path);
+
+    assert!(std::panic::catch_unwind(|| assert_accounts_snapshot(&lifecycle(), &path)).is_err());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        accounts_snapshot(&lifecycle())
+    );
+
+    assert_accounts_snapshot(&lifecycle(), &path);
+    fs::remove_file(&path).unwrap();
+}
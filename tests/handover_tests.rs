@@ -0,0 +1,81 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use rust_coding_test::{
+    domain::{Deposit, Transaction, Withdrawal},
+    handover::{HandoverError, skip_overlap},
+};
+use rust_decimal::dec;
+
+fn deposit(tx: u32) -> Transaction {
+    Deposit::try_new(1.into(), tx.into(), dec!(1))
+        .unwrap()
+        .into()
+}
+
+#[test]
+fn overlap_up_to_last_historical_transaction_is_dropped() {
+    let live = (2..6).map(deposit);
+
+    let rest: Vec<_> = skip_overlap(live.clone(), Some(&deposit(3)), 3)
+        .unwrap()
+        .collect();
+    assert_eq!(rest, vec![deposit(4), deposit(5)]);
+
+    let all: Vec<_> = skip_overlap(live.clone(), Some(&deposit(3)), 0)
+        .unwrap()
+        .collect();
+    assert_eq!(all.len(), 4);
+    assert_eq!(skip_overlap(live, None, 3).unwrap().count(), 4);
+}
+
+#[test]
+fn missing_handover_point_is_an_error() {
+    let last: Transaction = Withdrawal::try_new(1.into(), 3.into(), dec!(1))
+        .unwrap()
+        .into();
+
+    assert_eq!(
+        skip_overlap((2..6).map(deposit), Some(&last), 3).err(),
+        Some(HandoverError::NoOverlap { last, window: 3 })
+    );
+    assert!(skip_overlap((4..10).map(deposit), Some(&deposit(3)), 3).is_err());
+}
+
+/// The history's snapshot comes first, then the live source picks up after the overlap.
+#[test]
+fn cli_hands_over_to_stdin() {
+    let path = std::env::temp_dir().join(format!("handover_tests_{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "type,client,tx,amount\ndeposit,1,1,5\ndeposit,2,2,1\n",
+    )
+    .unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg(&path)
+        .args(["--live", "-", "--overlap", "2", "--snapshot-every", "1"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\ndeposit,2,2,1\nwithdrawal,1,3,2\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "sequence,client,available,held,total,locked\n\
+         1,1,5,0,5,false\n\
+         1,2,1,0,1,false\n\
+         2,1,3,0,3,false\n"
+    );
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use common::amount;
+use rust_coding_test::domain::{Account, Balance};
+use rust_decimal::dec;
+
+/// Resolving more than is held would drive held negative.
+#[test]
+#[should_panic(expected = "held negative after resolve")]
+fn release_more_than_held_panics_in_debug() {
+    let mut account = Account::new(Balance::new(dec!(10.0), dec!(5.0)), false);
+    account.release(amount(dec!(6.0)));
+}
+
+#[test]
+#[should_panic(expected = "held negative after chargeback")]
+fn chargeback_more_than_held_panics_in_debug() {
+    let mut account = Account::new(Balance::new(dec!(10.0), dec!(5.0)), false);
+    account.charge_back(amount(dec!(6.0)));
+}
+
+#[test]
+fn valid_dispute_cycle_keeps_invariants() {
+    let mut account = Account::new(Balance::new(dec!(10.0), dec!(0.0)), false);
+    account.hold(amount(dec!(4.0))).unwrap();
+    account.release(amount(dec!(4.0)));
+    account.hold(amount(dec!(4.0))).unwrap();
+    account.charge_back(amount(dec!(4.0)));
+
+    assert_eq!(
+        account,
+        Account::new(Balance::new(dec!(6.0), dec!(0.0)), true)
+    );
+}
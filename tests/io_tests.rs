@@ -15,9 +15,15 @@ const INPUT: &[u8] = include_bytes!("io_tests/test_input.csv");
 #[test]
 fn test_output() -> anyhow::Result<()> {
     let transactions = vec![
-        Deposit::new(1.into(), 1.into(), dec!(1.0)).into(),
-        Deposit::new(1.into(), 3.into(), dec!(2.0)).into(),
-        Withdrawal::new(1.into(), 4.into(), dec!(1.5)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(1.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 3.into(), dec!(2.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 4.into(), dec!(1.5))
+            .unwrap()
+            .into(),
     ];
 
     let engine = run(transactions);
@@ -28,7 +34,7 @@ fn test_output() -> anyhow::Result<()> {
     output::print_accounts(client_accounts, &mut output)?;
 
     assert_eq!(output, OUTPUT);
-    
+
     Ok(())
 }
 
@@ -42,9 +48,15 @@ fn test_input() {
     let transactions = parsing::deserialize_csv(&mut rdr).collect::<Vec<_>>();
 
     let expected = vec![
-        Deposit::new(1.into(), 1.into(), dec!(1.0)).into(),
-        Deposit::new(1.into(), 3.into(), dec!(2.0)).into(),
-        Withdrawal::new(1.into(), 4.into(), dec!(1.5)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(1.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 3.into(), dec!(2.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 4.into(), dec!(1.5))
+            .unwrap()
+            .into(),
     ];
 
     assert_eq!(transactions, expected);
@@ -0,0 +1,103 @@
+use std::process::Command;
+
+use rust_coding_test::{
+    domain::TenantId,
+    parsing::{
+        ParseError, ParseFailure, deserialize_jsonl, deserialize_tenant_jsonl, parse_bytes,
+        try_deserialize_jsonl,
+    },
+};
+
+/// The same rows as JSON Lines and as CSV give the same transactions.
+#[test]
+fn lines_parse_like_csv_rows() {
+    let jsonl = r#"{"type": "deposit", "client": 1, "tx": 1, "amount": "1.5"}
+{"type": "withdrawal", "client": 1, "tx": 2, "amount": 0.25}
+
+{"type": "dispute", "client": 1, "tx": 1, "amount": null}
+{"type": "adjustment", "client": 2, "tx": 3, "amount": "-1", "reason": 7}
+"#;
+    let (expected, errors) = parse_bytes(
+        b"type,client,tx,amount,reason\n\
+          deposit,1,1,1.5,\n\
+          withdrawal,1,2,0.25,\n\
+          dispute,1,1,,\n\
+          adjustment,2,3,-1,7\n",
+    );
+    assert!(errors.is_empty());
+
+    assert_eq!(
+        deserialize_jsonl(jsonl.as_bytes()).collect::<Vec<_>>(),
+        expected
+    );
+}
+
+#[test]
+fn invalid_lines_are_reported_with_their_number() {
+    let jsonl = "{\"type\": \"deposit\", \"client\": 1, \"tx\": 1, \"amount\": \"1\"}\n\
+                 {\"type\": \"bogus\", \"client\": 1, \"tx\": 2}\n\
+                 not json\n\
+                 {\"type\": \"deposit\", \"client\": 1, \"tx\": 3}\n\
+                 {\"type\": \"deposit\", \"client\": 1, \"tx\": 4, \"amount\": \"2\"}";
+
+    let results: Vec<_> = try_deserialize_jsonl(jsonl.as_bytes()).collect();
+    assert_eq!(results.len(), 5);
+    assert!(results[0].is_ok() && results[4].is_ok());
+    assert!(matches!(results[1], Err(ParseError::Json { line: 2, .. })));
+    assert!(matches!(
+        results[3],
+        Err(ParseError::Invalid { row: 4, .. })
+    ));
+    assert_eq!(
+        results[1..4]
+            .iter()
+            .map(|result| result.as_ref().unwrap_err().failure())
+            .collect::<Vec<_>>(),
+        [
+            ParseFailure::UnknownType,
+            ParseFailure::Other,
+            ParseFailure::MissingAmount
+        ]
+    );
+}
+
+#[test]
+fn tenants_are_kept() {
+    let jsonl = r#"{"type": "deposit", "client": 1, "tx": 1, "amount": "1", "tenant": 7}
+{"type": "deposit", "client": 1, "tx": 2, "amount": "1"}"#;
+
+    let tenants: Vec<_> = deserialize_tenant_jsonl(jsonl.as_bytes())
+        .map(|transaction| transaction.tenant)
+        .collect();
+    assert_eq!(tenants, [Some(TenantId::from(7)), None]);
+}
+
+#[test]
+fn cli_reads_json_lines_with_the_format_flag() {
+    let path = std::env::temp_dir().join(format!("jsonl_tests_{}.jsonl", std::process::id()));
+    std::fs::write(
+        &path,
+        "{\"type\": \"deposit\", \"client\": 1, \"tx\": 1, \"amount\": \"5\"}\n\
+         {\"type\": \"withdrawal\", \"client\": 1, \"tx\": 2, \"amount\": \"4\"}\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg(&path)
+        .args(["--format", "jsonl"])
+        .output()
+        .unwrap();
+    let two_pass = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg(&path)
+        .args(["--format", "jsonl", "--two-pass"])
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "client,available,held,total,locked\n\
+         1,1,0,1,false\n"
+    );
+    assert!(!two_pass.status.success());
+}
@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+
+use rust_coding_test::{
+    domain::{
+        Adjustment, Authorization, Capture, Chargeback, Deposit, Dispute, Resolve, Transaction,
+        Void, Withdrawal,
+    },
+    engine::{EngineConfig, RollingReserve},
+    ledger::LedgerWriter,
+};
+use rust_decimal::{Decimal, dec};
+
+fn transactions() -> Vec<Transaction> {
+    vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(10.5))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 2.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        // Rejected: insufficient funds.
+        Withdrawal::try_new(1.into(), 3.into(), dec!(100))
+            .unwrap()
+            .into(),
+        Authorization::try_new(1.into(), 4.into(), dec!(2.25))
+            .unwrap()
+            .into(),
+        Capture::new(1.into(), 4.into()).into(),
+        Authorization::try_new(1.into(), 5.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Void::new(1.into(), 5.into()).into(),
+        Deposit::try_new(2.into(), 6.into(), dec!(3.75))
+            .unwrap()
+            .into(),
+        Dispute::new(2.into(), 6.into()).into(),
+        Resolve::new(2.into(), 6.into()).into(),
+        Dispute::new(2.into(), 6.into()).into(),
+        Chargeback::new(2.into(), 6.into()).into(),
+    ]
+}
+
+/// Rows as (entry, account, debit, credit), without the header and descriptions.
+fn journal(transactions: Vec<Transaction>) -> Vec<(u64, String, Decimal, Decimal)> {
+    journal_with(EngineConfig::default(), transactions)
+}
+
+fn journal_with(
+    config: EngineConfig,
+    transactions: Vec<Transaction>,
+) -> Vec<(u64, String, Decimal, Decimal)> {
+    let mut output = Vec::new();
+    let mut ledger = LedgerWriter::new(config, &mut output).unwrap();
+    ledger.apply_all(transactions.into_iter()).unwrap();
+    ledger.finish().unwrap();
+
+    let mut reader = csv::Reader::from_reader(output.as_slice());
+    reader
+        .records()
+        .map(|record| {
+            let record = record.unwrap();
+            (
+                record[0].parse().unwrap(),
+                record[1].to_string(),
+                record[2].parse().unwrap(),
+                record[3].parse().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn every_entry_balances() {
+    let mut sums: BTreeMap<u64, (Decimal, Decimal)> = BTreeMap::new();
+    for (entry, _, debit, credit) in journal(transactions()) {
+        let sum = sums.entry(entry).or_default();
+        sum.0 += debit;
+        sum.1 += credit;
+    }
+
+    // One entry per applied transaction: the rejected withdrawal is left out.
+    assert_eq!(sums.len(), 11);
+    for (entry, (debit, credit)) in sums {
+        assert_eq!(debit, credit, "entry {entry}");
+    }
+}
+
+#[test]
+fn client_accounts_sum_to_final_balances() {
+    let mut balances: BTreeMap<String, Decimal> = BTreeMap::new();
+    for (_, account, debit, credit) in journal(transactions()) {
+        *balances.entry(account).or_default() += debit - credit;
+    }
+    balances.retain(|_, balance| !balance.is_zero());
+
+    assert_eq!(
+        balances,
+        BTreeMap::from([
+            ("client:1:available".to_string(), dec!(6.75)),
+            ("partner".to_string(), dec!(-6.75)),
+        ])
+    );
+}
+
+#[test]
+fn deposit_and_dispute_lines() {
+    let journal = journal(transactions());
+
+    assert_eq!(
+        &journal[..2],
+        &[
+            (1, "client:1:available".to_string(), dec!(10.5), dec!(0)),
+            (1, "partner".to_string(), dec!(0), dec!(10.5)),
+        ]
+    );
+    let dispute: Vec<_> = journal.iter().filter(|line| line.0 == 8).collect();
+    assert_eq!(
+        dispute,
+        vec![
+            &(8, "client:2:available".to_string(), dec!(0), dec!(3.75)),
+            &(8, "client:2:held".to_string(), dec!(3.75), dec!(0)),
+        ]
+    );
+}
+
+#[test]
+fn settlements_journaled_as_entries() {
+    let config = EngineConfig {
+        settlement_delay: 1,
+        ..Default::default()
+    };
+    let journal = journal_with(
+        config,
+        vec![
+            Deposit::try_new(1.into(), 1.into(), dec!(1.5))
+                .unwrap()
+                .into(),
+            Deposit::try_new(2.into(), 2.into(), dec!(2.25))
+                .unwrap()
+                .into(),
+        ],
+    );
+
+    assert_eq!(
+        journal,
+        vec![
+            (1, "client:1:pending".to_string(), dec!(1.5), dec!(0)),
+            (1, "partner".to_string(), dec!(0), dec!(1.5)),
+            (2, "client:2:pending".to_string(), dec!(2.25), dec!(0)),
+            (2, "partner".to_string(), dec!(0), dec!(2.25)),
+            // The first deposit settled once the second was processed; the second is
+            // still pending at the end.
+            (3, "client:1:available".to_string(), dec!(1.5), dec!(0)),
+            (3, "client:1:pending".to_string(), dec!(0), dec!(1.5)),
+        ]
+    );
+}
+
+#[test]
+fn reserve_releases_journaled_as_entries() {
+    let config = EngineConfig {
+        rolling_reserve: RollingReserve {
+            rate: dec!(0.1),
+            delay: 0,
+        },
+        ..Default::default()
+    };
+    let journal = journal_with(
+        config,
+        vec![
+            Deposit::try_new(1.into(), 1.into(), dec!(12.5))
+                .unwrap()
+                .into(),
+        ],
+    );
+
+    assert_eq!(
+        journal,
+        vec![
+            (1, "client:1:available".to_string(), dec!(11.25), dec!(0)),
+            (1, "client:1:reserve".to_string(), dec!(1.25), dec!(0)),
+            (1, "partner".to_string(), dec!(0), dec!(12.5)),
+            (2, "client:1:available".to_string(), dec!(1.25), dec!(0)),
+            (2, "client:1:reserve".to_string(), dec!(0), dec!(1.25)),
+        ]
+    );
+}
+
+#[test]
+fn adjustments_offset_against_their_own_account() {
+    let journal = journal(vec![
+        Adjustment::try_new(1.into(), 1.into(), dec!(2.25), 7)
+            .unwrap()
+            .into(),
+        Adjustment::try_new(1.into(), 2.into(), dec!(-1.5), 7)
+            .unwrap()
+            .into(),
+    ]);
+
+    assert_eq!(
+        journal,
+        vec![
+            (1, "client:1:available".to_string(), dec!(2.25), dec!(0)),
+            (1, "adjustments".to_string(), dec!(0), dec!(2.25)),
+            (2, "client:1:available".to_string(), dec!(0), dec!(1.5)),
+            (2, "adjustments".to_string(), dec!(1.5), dec!(0)),
+        ]
+    );
+}
+
+#[test]
+fn empty_journal_has_header() {
+    let mut output = Vec::new();
+    LedgerWriter::new(EngineConfig::default(), &mut output)
+        .unwrap()
+        .finish()
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "entry,account,debit,credit,description,parent_tx\n"
+    );
+}
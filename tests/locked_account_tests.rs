@@ -1,7 +1,12 @@
 mod common;
 
-use common::{account, run};
-use rust_coding_test::domain::{Chargeback, ClientId, Deposit, Dispute, Resolve, Withdrawal};
+use common::{account, amount, run};
+use rust_coding_test::{
+    domain::{Chargeback, ClientId, Deposit, Dispute, Resolve, Transaction, Withdrawal},
+    engine::{
+        ChargebackFreeze, EngineConfig, LockedAccountDisputes, PaymentsEngine, errors::EngineError,
+    },
+};
 use rust_decimal::dec;
 use std::collections::HashMap;
 
@@ -10,13 +15,17 @@ use std::collections::HashMap;
 #[test]
 fn locked_account_ignores_further_deposits() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Chargeback::new(1.into(), 1.into()).into(),
-        Deposit::new(1.into(), 2.into(), dec!(500.0)).into(), // must be ignored
+        Deposit::try_new(1.into(), 2.into(), dec!(500.0))
+            .unwrap()
+            .into(), // must be ignored
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(0.0), dec!(0.0), true))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(0.0), true))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -25,14 +34,20 @@ fn locked_account_ignores_further_deposits() {
 #[test]
 fn locked_account_ignores_withdrawals() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(50.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(50.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Chargeback::new(1.into(), 1.into()).into(),
-        Withdrawal::new(1.into(), 3.into(), dec!(50.0)).into(), // must be ignored
+        Withdrawal::try_new(1.into(), 3.into(), dec!(50.0))
+            .unwrap()
+            .into(), // must be ignored
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(50.0), dec!(0.0), true))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(50.0), dec!(0.0), true))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -41,14 +56,18 @@ fn locked_account_ignores_withdrawals() {
 #[test]
 fn locked_account_allows_disputes() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(50.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(50.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Chargeback::new(1.into(), 1.into()).into(),
         Dispute::new(1.into(), 2.into()).into(), // allowed on locked account
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(0.0), dec!(50.0), true))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(50.0), true))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -57,15 +76,19 @@ fn locked_account_allows_disputes() {
 #[test]
 fn locked_account_allows_resolves() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(50.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(50.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Dispute::new(1.into(), 2.into()).into(),
         Chargeback::new(1.into(), 1.into()).into(), // locks account
         Resolve::new(1.into(), 2.into()).into(), // allowed - locked only blocks deposits/withdrawals
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(50.0), dec!(0.0), true))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(50.0), dec!(0.0), true))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -74,15 +97,180 @@ fn locked_account_allows_resolves() {
 #[test]
 fn locked_account_allows_chargeback() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(50.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(50.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Dispute::new(1.into(), 2.into()).into(),
         Chargeback::new(1.into(), 1.into()).into(), // locks account
         Chargeback::new(1.into(), 2.into()).into(), // allowed - locked only blocks deposits/withdrawals
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(0.0), dec!(0.0), true))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(0.0), true))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
+
+/// Client 1 locked by a chargeback of tx 1, with tx 2 under dispute and tx 3 undisputed.
+fn locked_engine(config: EngineConfig) -> PaymentsEngine {
+    let mut engine = PaymentsEngine::with_config(config);
+    engine.process_transactions(
+        vec![
+            Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+                .unwrap()
+                .into(),
+            Deposit::try_new(1.into(), 2.into(), dec!(50.0))
+                .unwrap()
+                .into(),
+            Deposit::try_new(1.into(), 3.into(), dec!(25.0))
+                .unwrap()
+                .into(),
+            Dispute::new(1.into(), 1.into()).into(),
+            Dispute::new(1.into(), 2.into()).into(),
+            Chargeback::new(1.into(), 1.into()).into(),
+        ]
+        .into_iter(),
+    );
+    engine
+}
+
+/// Every dispute-family transaction under every locked-account policy: `true` if it
+/// applies, otherwise it must be rejected as `AccountLocked` without touching the account.
+#[test]
+fn locked_account_dispute_policies() {
+    use LockedAccountDisputes::{Allow, Reject, SettleOpen};
+
+    let cases: [(Transaction, u32, [bool; 3]); 3] = [
+        (
+            Dispute::new(1.into(), 3.into()).into(),
+            3,
+            [true, false, false],
+        ),
+        (
+            Resolve::new(1.into(), 2.into()).into(),
+            2,
+            [true, true, false],
+        ),
+        (
+            Chargeback::new(1.into(), 2.into()).into(),
+            2,
+            [true, true, false],
+        ),
+    ];
+    for (transaction, tx, applies) in cases {
+        for (policy, applies) in [Allow, SettleOpen, Reject].into_iter().zip(applies) {
+            let mut engine = locked_engine(EngineConfig {
+                locked_account_disputes: policy,
+                ..Default::default()
+            });
+            let before = engine.client_accounts().as_map().clone();
+
+            let result = engine.process_transaction(transaction.clone());
+
+            if applies {
+                assert_eq!(result, Ok(()), "{transaction} under {policy:?}");
+            } else {
+                let deposit_amount = if tx == 2 { dec!(50.0) } else { dec!(25.0) };
+                assert_eq!(
+                    result,
+                    Err(EngineError::AccountLocked {
+                        client: 1.into(),
+                        tx: tx.into(),
+                        amount: amount(deposit_amount),
+                    }),
+                    "{transaction} under {policy:?}"
+                );
+                assert_eq!(engine.client_accounts().as_map(), &before);
+            }
+        }
+    }
+}
+
+/// With `chargeback_opens_dispute`, a chargeback of an undisputed deposit opens a dispute,
+/// which `SettleOpen` forbids on a locked account.
+#[test]
+fn settle_open_rejects_chargeback_opening_a_dispute() {
+    let mut engine = locked_engine(EngineConfig {
+        chargeback_opens_dispute: true,
+        locked_account_disputes: LockedAccountDisputes::SettleOpen,
+        ..Default::default()
+    });
+
+    assert_eq!(
+        engine.process_transaction(Chargeback::new(1.into(), 3.into()).into()),
+        Err(EngineError::AccountLocked {
+            client: 1.into(),
+            tx: 3.into(),
+            amount: amount(dec!(25.0)),
+        })
+    );
+    assert_eq!(
+        engine.process_transaction(Chargeback::new(1.into(), 2.into()).into()),
+        Ok(())
+    );
+}
+
+fn deposit(engine: &mut PaymentsEngine, tx: u32) -> Result<(), EngineError> {
+    engine.process_transaction(
+        Deposit::try_new(1.into(), tx.into(), dec!(1.0))
+            .unwrap()
+            .into(),
+    )
+}
+
+/// A temporary freeze rejects deposits until `horizon` more transactions were processed.
+#[test]
+fn temporary_freeze_expires_after_horizon() {
+    let mut engine = locked_engine(EngineConfig {
+        chargeback_freeze: ChargebackFreeze::Temporary { horizon: 3 },
+        ..Default::default()
+    });
+
+    for tx in 4..7 {
+        assert!(matches!(
+            deposit(&mut engine, tx),
+            Err(EngineError::AccountLocked { .. })
+        ));
+    }
+    assert_eq!(deposit(&mut engine, 7), Ok(()));
+    assert!(!engine.client_accounts().get(1.into()).unwrap().is_locked());
+}
+
+#[test]
+fn longest_temporary_freeze_is_never_lifted() {
+    let mut engine = locked_engine(EngineConfig {
+        chargeback_freeze: ChargebackFreeze::Temporary { horizon: u64::MAX },
+        ..Default::default()
+    });
+
+    for tx in 4..8 {
+        assert!(matches!(
+            deposit(&mut engine, tx),
+            Err(EngineError::AccountLocked { .. })
+        ));
+    }
+    assert!(engine.client_accounts().get(1.into()).unwrap().is_locked());
+}
+
+#[test]
+fn another_chargeback_extends_temporary_freeze() {
+    let mut engine = locked_engine(EngineConfig {
+        chargeback_freeze: ChargebackFreeze::Temporary { horizon: 2 },
+        ..Default::default()
+    });
+
+    assert_eq!(
+        engine.process_transaction(Chargeback::new(1.into(), 2.into()).into()),
+        Ok(())
+    );
+    for tx in 4..6 {
+        assert!(matches!(
+            deposit(&mut engine, tx),
+            Err(EngineError::AccountLocked { .. })
+        ));
+    }
+    assert_eq!(deposit(&mut engine, 6), Ok(()));
+}
@@ -0,0 +1,62 @@
+use std::{path::PathBuf, process::Command, process::Output};
+
+const INPUT: &str = "type,client,tx,amount\ndeposit,1,1,10\nwithdrawal,1,2,100\n";
+const ACCOUNTS: &str = "client,available,held,total,locked\n\
+                        1,10,0,10,false\n";
+
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("logging_tests_{}_{name}", std::process::id()))
+}
+
+fn run(name: &str, args: &[&str]) -> Output {
+    let input = temp_file(name);
+    std::fs::write(&input, INPUT).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg(&input)
+        .args(args)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    std::fs::remove_file(&input).unwrap();
+    output
+}
+
+#[test]
+fn logs_go_to_stderr_only() {
+    for args in [&["WARN"][..], &["-v"], &["-vvvv"]] {
+        let output = run("stderr", args);
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), ACCOUNTS);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("Insufficient funds"), "{args:?}: {stderr}");
+    }
+}
+
+#[test]
+fn verbosity_selects_the_level() {
+    let quiet = run("quiet", &["-q"]);
+    assert!(quiet.status.success());
+    assert_eq!(String::from_utf8(quiet.stdout).unwrap(), ACCOUNTS);
+    assert!(quiet.stderr.is_empty());
+
+    // Nothing is logged by default, and an explicit level can't be combined with --quiet.
+    assert!(run("default", &[]).stderr.is_empty());
+    assert!(!run("conflict", &["WARN", "--quiet"]).status.success());
+    // -v only logs warnings; the explicit level overrides it.
+    let error_only = run("override", &["ERROR", "-v"]);
+    assert!(error_only.stderr.is_empty());
+}
+
+#[test]
+fn log_file_replaces_stderr() {
+    let log = temp_file("log");
+    let output = run("file", &["-v", "--log-file", log.to_str().unwrap()]);
+    let logged = std::fs::read_to_string(&log).unwrap();
+    std::fs::remove_file(&log).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), ACCOUNTS);
+    assert!(output.stderr.is_empty());
+    assert!(logged.contains("Insufficient funds"), "{logged}");
+}
@@ -0,0 +1,162 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rust_coding_test::{
+    domain::{Deposit, Dispute, Transaction, Withdrawal},
+    engine::{
+        IgnoreReason, Middleware, MiddlewareEngine, Outcome, PaymentsEngine, Verdict,
+        errors::EngineError,
+    },
+    rejection::RejectionReason,
+};
+use rust_decimal::{Decimal, dec};
+
+fn deposit(client: u16, tx: u32, value: Decimal) -> Transaction {
+    Deposit::try_new(client.into(), tx.into(), value)
+        .unwrap()
+        .into()
+}
+
+fn withdrawal(client: u16, tx: u32, value: Decimal) -> Transaction {
+    Withdrawal::try_new(client.into(), tx.into(), value)
+        .unwrap()
+        .into()
+}
+
+/// Records every hook call as `<name> before/after <tx>`.
+struct Tracer {
+    name: &'static str,
+    calls: Rc<RefCell<Vec<String>>>,
+}
+
+impl Middleware for Tracer {
+    fn before(&mut self, transaction: &mut Transaction, _: &PaymentsEngine) -> Verdict {
+        let call = format!("{} before {}", self.name, transaction.tx_id());
+        self.calls.borrow_mut().push(call);
+        Verdict::Continue
+    }
+    fn after(&mut self, transaction: &Transaction, outcome: &Outcome, _: &PaymentsEngine) {
+        let reason = outcome
+            .reason()
+            .map_or("applied".to_string(), |r| r.to_string());
+        let call = format!("{} after {} {reason}", self.name, transaction.tx_id());
+        self.calls.borrow_mut().push(call);
+    }
+}
+
+#[test]
+fn stages_validate_and_filter() {
+    let mut engine = MiddlewareEngine::new(PaymentsEngine::new())
+        // Policy check: no withdrawals above 100.
+        .with(
+            |transaction: &mut Transaction, _: &PaymentsEngine| match transaction {
+                Transaction::Withdrawal(withdrawal) if withdrawal.amount().value() > dec!(100) => {
+                    Verdict::Reject("withdrawal above 100".to_string())
+                }
+                _ => Verdict::Continue,
+            },
+        )
+        // Filter: client 9 is handled elsewhere.
+        .with(|transaction: &mut Transaction, _: &PaymentsEngine| {
+            if transaction.client_id() == 9.into() {
+                Verdict::Filter
+            } else {
+                Verdict::Continue
+            }
+        });
+
+    assert_eq!(engine.apply(deposit(1, 1, dec!(500))), Outcome::Applied);
+    assert_eq!(
+        engine.apply(withdrawal(1, 2, dec!(150))),
+        Outcome::Rejected(EngineError::RejectedByMiddleware {
+            client: 1.into(),
+            tx: 2.into(),
+            reason: "withdrawal above 100".to_string(),
+        })
+    );
+    assert_eq!(
+        engine.apply(deposit(9, 3, dec!(1))),
+        Outcome::Ignored(IgnoreReason::Filtered)
+    );
+    assert_eq!(engine.process_transaction(deposit(9, 4, dec!(1))), Ok(()));
+    engine
+        .process_transaction(withdrawal(1, 5, dec!(50)))
+        .unwrap();
+
+    let engine = engine.into_engine();
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    assert_eq!(balance.available(), dec!(450));
+    assert!(engine.client_accounts().get(9.into()).is_none());
+}
+
+#[test]
+fn stages_can_enrich_transactions() {
+    // Normalizes deposits given in cents to units.
+    let mut engine = MiddlewareEngine::new(PaymentsEngine::new()).with(
+        |transaction: &mut Transaction, _: &PaymentsEngine| {
+            if let Transaction::Deposit(deposit) = transaction {
+                *transaction = Deposit::try_new(
+                    deposit.client_id(),
+                    deposit.transaction_id(),
+                    deposit.amount().value() / dec!(100),
+                )
+                .unwrap()
+                .into();
+            }
+            Verdict::Continue
+        },
+    );
+    engine.process_transactions([deposit(1, 1, dec!(1250))].into_iter());
+
+    let balance = engine
+        .engine()
+        .client_accounts()
+        .get(1.into())
+        .unwrap()
+        .balance();
+    assert_eq!(balance.available(), dec!(12.5));
+}
+
+#[test]
+fn hooks_wrap_in_order_and_see_every_outcome() {
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let tracer = |name| Tracer {
+        name,
+        calls: calls.clone(),
+    };
+    let mut engine = MiddlewareEngine::new(PaymentsEngine::new())
+        .with(tracer("outer"))
+        .with(|transaction: &mut Transaction, _: &PaymentsEngine| {
+            if transaction.tx_id() == 2.into() {
+                Verdict::Filter
+            } else {
+                Verdict::Continue
+            }
+        });
+    engine.push(tracer("inner"));
+
+    engine.apply(deposit(1, 1, dec!(1)));
+    engine.apply(deposit(1, 2, dec!(1)));
+    engine.apply(Dispute::new(1.into(), 7.into()).into());
+
+    assert_eq!(
+        *calls.borrow(),
+        [
+            "outer before 1",
+            "inner before 1",
+            "inner after 1 applied",
+            "outer after 1 applied",
+            // The filter stops the chain; every stage still sees the outcome.
+            "outer before 2",
+            "inner after 2 filtered",
+            "outer after 2 filtered",
+            "outer before 7",
+            "inner before 7",
+            "inner after 7 transaction_not_found",
+            "outer after 7 transaction_not_found",
+        ]
+    );
+    assert_eq!(
+        Outcome::Ignored(IgnoreReason::Filtered).reason(),
+        Some(RejectionReason::Filtered)
+    );
+}
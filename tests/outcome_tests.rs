@@ -0,0 +1,190 @@
+mod common;
+
+use common::amount;
+use rust_coding_test::{
+    domain::{Capture, Chargeback, Deposit, Dispute, Resolve, Withdrawal},
+    engine::{
+        BatchReport, EngineConfig, IgnoreReason, Outcome, PaymentsEngine, RepeatedSettlement,
+        errors::EngineError,
+    },
+    rejection::RejectionReason,
+};
+use rust_decimal::dec;
+
+fn engine_with_deposit(config: EngineConfig) -> PaymentsEngine {
+    let mut engine = PaymentsEngine::with_config(config);
+    let outcome = engine.apply(
+        Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+            .unwrap()
+            .into(),
+    );
+    assert_eq!(outcome, Outcome::Applied);
+    engine
+}
+
+/// Spec: disputes, resolves and chargebacks that don't match an existing (disputed)
+/// transaction are ignored.
+#[test]
+fn spec_ignores_are_reported_as_ignored() {
+    let mut engine = engine_with_deposit(EngineConfig::default());
+
+    assert_eq!(
+        engine.apply(Dispute::new(1.into(), 99.into()).into()),
+        Outcome::Ignored(IgnoreReason::Spec(EngineError::TransactionNotFound {
+            client: 1.into(),
+            tx: 99.into(),
+        }))
+    );
+    assert_eq!(
+        engine.apply(Resolve::new(1.into(), 1.into()).into()),
+        Outcome::Ignored(IgnoreReason::Spec(EngineError::TransactionNotDisputed {
+            client: 1.into(),
+            tx: 1.into(),
+            amount: amount(dec!(10.0)),
+        }))
+    );
+    assert!(matches!(
+        engine.apply(Capture::new(1.into(), 1.into()).into()),
+        Outcome::Ignored(IgnoreReason::Spec(_))
+    ));
+}
+
+#[test]
+fn invalid_movements_are_rejected() {
+    let mut engine = engine_with_deposit(EngineConfig::default());
+
+    assert_eq!(
+        engine.apply(
+            Withdrawal::try_new(1.into(), 2.into(), dec!(15.0))
+                .unwrap()
+                .into()
+        ),
+        Outcome::Rejected(EngineError::InsufficientFunds {
+            client: 1.into(),
+            tx: 2.into(),
+            requested: amount(dec!(15.0)),
+            available: dec!(10.0),
+        })
+    );
+
+    engine.apply(Dispute::new(1.into(), 1.into()).into());
+    engine.apply(Chargeback::new(1.into(), 1.into()).into());
+
+    assert!(matches!(
+        engine.apply(
+            Deposit::try_new(1.into(), 3.into(), dec!(1.0))
+                .unwrap()
+                .into()
+        ),
+        Outcome::Rejected(EngineError::AccountLocked { .. })
+    ));
+}
+
+#[test]
+fn repeated_settlements_ignored_by_config_are_not_errors() {
+    let mut engine = engine_with_deposit(EngineConfig {
+        repeated_settlement: RepeatedSettlement::Ignore,
+        ..Default::default()
+    });
+    engine.apply(Dispute::new(1.into(), 1.into()).into());
+    engine.apply(Resolve::new(1.into(), 1.into()).into());
+
+    let outcome = engine.apply(Resolve::new(1.into(), 1.into()).into());
+
+    assert_eq!(outcome, Outcome::Ignored(IgnoreReason::RepeatedSettlement));
+    assert_eq!(outcome.into_result(), Ok(()));
+}
+
+/// `process_transaction` keeps reporting spec ignores and rejections as errors.
+#[test]
+fn into_result_matches_process_transaction() {
+    let mut engine = engine_with_deposit(EngineConfig::default());
+
+    let via_apply = engine.apply(Dispute::new(1.into(), 99.into()).into());
+    let via_process = engine.process_transaction(Dispute::new(1.into(), 99.into()).into());
+
+    assert_eq!(via_apply.into_result(), via_process);
+    assert!(via_process.is_err());
+}
+
+#[test]
+fn batch_report_has_per_index_outcomes_and_counts() {
+    let mut engine = engine_with_deposit(EngineConfig {
+        repeated_settlement: RepeatedSettlement::Ignore,
+        ..Default::default()
+    });
+    let report = engine.process_transactions_reporting(
+        [
+            Withdrawal::try_new(1.into(), 2.into(), dec!(20))
+                .unwrap()
+                .into(),
+            Dispute::new(1.into(), 1.into()).into(),
+            Dispute::new(1.into(), 99.into()).into(),
+            Resolve::new(1.into(), 1.into()).into(),
+            Resolve::new(1.into(), 1.into()).into(),
+            Withdrawal::try_new(1.into(), 3.into(), dec!(4))
+                .unwrap()
+                .into(),
+        ]
+        .into_iter(),
+    );
+
+    assert_eq!(report.len(), 6);
+    assert_eq!(
+        (report.applied(), report.ignored(), report.rejected()),
+        (3, 2, 1)
+    );
+    assert!(matches!(
+        report.outcome(0),
+        Some(Outcome::Rejected(EngineError::InsufficientFunds { .. }))
+    ));
+    assert_eq!(report.outcome(1), Some(&Outcome::Applied));
+    assert_eq!(report.outcome(6), None);
+    assert_eq!(
+        report.dropped().collect::<Vec<_>>(),
+        [
+            (0, RejectionReason::InsufficientFunds),
+            (2, RejectionReason::TransactionNotFound),
+            (4, RejectionReason::RepeatedSettlement),
+        ]
+    );
+    assert_eq!(
+        report.reason_counts()[&RejectionReason::TransactionNotFound],
+        1
+    );
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    assert_eq!(balance.available(), dec!(6));
+}
+
+#[test]
+fn batch_report_matches_process_transactions() {
+    let transactions = || {
+        [
+            Deposit::try_new(2.into(), 5.into(), dec!(3))
+                .unwrap()
+                .into(),
+            Chargeback::new(2.into(), 5.into()).into(),
+            Withdrawal::try_new(2.into(), 6.into(), dec!(1))
+                .unwrap()
+                .into(),
+        ]
+        .into_iter()
+    };
+    let mut reporting = PaymentsEngine::new();
+    let report = reporting.process_transactions_reporting(transactions());
+    let mut plain = PaymentsEngine::new();
+    plain.process_transactions(transactions());
+
+    assert_eq!(
+        reporting
+            .client_accounts()
+            .iter_sorted()
+            .collect::<Vec<_>>(),
+        plain.client_accounts().iter_sorted().collect::<Vec<_>>()
+    );
+    assert_eq!(report.applied(), 2);
+    assert_eq!(
+        PaymentsEngine::new().process_transactions_reporting(std::iter::empty()),
+        BatchReport::default()
+    );
+}
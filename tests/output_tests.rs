@@ -0,0 +1,143 @@
+mod common;
+
+use common::run;
+use rust_coding_test::{
+    domain::{Deposit, Transaction, Withdrawal},
+    engine::{MultiTenantEngine, PaymentsEngine},
+    output::{self, Column, ColumnsError, OutputColumns},
+    parsing::TenantTransaction,
+};
+use rust_decimal::Decimal;
+use std::num::NonZeroUsize;
+
+fn engine_with_clients(clients: u16) -> PaymentsEngine {
+    let transactions: Vec<Transaction> = (0..clients)
+        .flat_map(|client| {
+            let tx = u32::from(client) * 2;
+            [
+                Deposit::try_new(
+                    client.into(),
+                    tx.into(),
+                    Decimal::new(i64::from(client) + 5, 1),
+                )
+                .unwrap()
+                .into(),
+                Withdrawal::try_new(client.into(), (tx + 1).into(), Decimal::new(3, 1))
+                    .unwrap()
+                    .into(),
+            ]
+        })
+        .collect();
+    run(transactions)
+}
+
+fn sequential(engine: &PaymentsEngine) -> anyhow::Result<String> {
+    let mut output = Vec::new();
+    output::print_accounts(engine.client_accounts(), &mut output)?;
+    Ok(String::from_utf8(output)?)
+}
+
+fn sharded(engine: &PaymentsEngine, shards: usize) -> anyhow::Result<String> {
+    let mut output = Vec::new();
+    output::print_accounts_sharded(
+        engine.client_accounts(),
+        &mut output,
+        NonZeroUsize::new(shards).unwrap(),
+    )?;
+    Ok(String::from_utf8(output)?)
+}
+
+#[test]
+fn sharded_output_matches_sequential() -> anyhow::Result<()> {
+    let engine = engine_with_clients(1000);
+    let expected = sequential(&engine)?;
+
+    for shards in [1, 3, 8] {
+        assert_eq!(sharded(&engine, shards)?, expected, "{shards} shards");
+    }
+    Ok(())
+}
+
+#[test]
+fn more_shards_than_rows() -> anyhow::Result<()> {
+    let engine = engine_with_clients(2);
+
+    assert_eq!(sharded(&engine, 16)?, sequential(&engine)?);
+    Ok(())
+}
+
+#[test]
+fn no_accounts_writes_nothing() -> anyhow::Result<()> {
+    let engine = PaymentsEngine::new();
+
+    assert_eq!(sequential(&engine)?, "");
+    assert_eq!(sharded(&engine, 4)?, "");
+    Ok(())
+}
+
+#[test]
+fn columns_are_selected_and_renamed() -> anyhow::Result<()> {
+    let engine = engine_with_clients(2);
+    let columns: OutputColumns = "client=client_id, total,locked=frozen".parse()?;
+
+    let mut output = Vec::new();
+    output::print_accounts_with_columns(engine.client_accounts(), &columns, &mut output)?;
+    assert_eq!(
+        String::from_utf8(output)?,
+        "client_id,total,frozen\n0,0.2,false\n1,0.3,false\n"
+    );
+
+    let mut output = Vec::new();
+    output::print_accounts_sharded_with_columns(
+        engine.client_accounts(),
+        &columns,
+        &mut output,
+        NonZeroUsize::new(2).unwrap(),
+    )?;
+    assert_eq!(
+        String::from_utf8(output)?,
+        "client_id,total,frozen\n0,0.2,false\n1,0.3,false\n"
+    );
+
+    // The default selection is the usual layout.
+    let mut output = Vec::new();
+    output::print_accounts_with_columns(
+        engine.client_accounts(),
+        &OutputColumns::default(),
+        &mut output,
+    )?;
+    assert_eq!(String::from_utf8(output)?, sequential(&engine)?);
+    Ok(())
+}
+
+#[test]
+fn tenant_column_can_be_selected() -> anyhow::Result<()> {
+    let mut engine = MultiTenantEngine::new();
+    engine.process_transactions(std::iter::once(TenantTransaction {
+        tenant: Some(3.into()),
+        transaction: Deposit::try_new(1.into(), 1.into(), Decimal::ONE)?.into(),
+    }));
+    let columns = OutputColumns::new([Column::Available, Column::Tenant])?;
+
+    let mut output = Vec::new();
+    output::print_tenant_accounts_with_columns(&engine, &columns, &mut output)?;
+    assert_eq!(String::from_utf8(output)?, "available,tenant\n1,3\n");
+    Ok(())
+}
+
+#[test]
+fn invalid_column_specs_are_rejected() {
+    assert_eq!(
+        "client,balance".parse::<OutputColumns>(),
+        Err(ColumnsError::UnknownColumn("balance".to_owned()))
+    );
+    assert_eq!(
+        "client,total,client=id".parse::<OutputColumns>(),
+        Err(ColumnsError::Duplicate(Column::Client))
+    );
+    assert_eq!(
+        "client=".parse::<OutputColumns>(),
+        Err(ColumnsError::EmptyHeader(Column::Client))
+    );
+    assert_eq!("".parse::<OutputColumns>(), Err(ColumnsError::Empty));
+}
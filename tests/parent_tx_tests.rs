@@ -0,0 +1,119 @@
+use rust_coding_test::{
+    domain::{Capture, Deposit, Refund, Transaction, Withdrawal},
+    engine::{ApplyTransactions, EngineConfig, PaymentsEngine},
+    ledger::LedgerWriter,
+    parsing,
+    statement::StatementRecorder,
+};
+use rust_decimal::dec;
+
+const INPUT: &str = "type,client,tx,amount,parent_tx\n\
+                     deposit,1,1,10,\n\
+                     withdrawal,1,2,2.5,1\n\
+                     withdrawal,1,3,1.5,\n";
+
+fn withdrawal(tx: u32) -> Transaction {
+    Withdrawal::try_new(1.into(), tx.into(), dec!(2.5))
+        .unwrap()
+        .into()
+}
+
+#[test]
+fn parent_tx_column_is_parsed() {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(INPUT.as_bytes());
+    let transactions = parsing::deserialize_csv(&mut rdr).collect::<Vec<_>>();
+
+    assert_eq!(transactions[0].parent_tx(), None);
+    assert_eq!(transactions[1], withdrawal(2).with_parent_tx(1.into()));
+    assert_eq!(transactions[2].parent_tx(), None);
+    assert_eq!(parsing::parse_bytes(INPUT.as_bytes()).0, transactions);
+
+    let (_, errors) = parsing::parse_bytes(b"type,client,tx,amount,parent_tx\ndeposit,1,1,1,x\n");
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn parent_tx_is_described_but_does_not_change_processing() {
+    let linked = Refund::try_new(1.into(), 1.into(), dec!(1))
+        .map(Transaction::from)
+        .unwrap()
+        .with_parent_tx(7.into());
+    assert_eq!(
+        linked.to_string(),
+        "refund of tx 1 (client 1, amount 1, parent tx 7)"
+    );
+    assert_eq!(
+        Transaction::from(Capture::new(1.into(), 4.into()))
+            .with_parent_tx(3.into())
+            .to_string(),
+        "capture of tx 4 (client 1, parent tx 3)"
+    );
+
+    let mut engine = PaymentsEngine::new();
+    engine
+        .process_transaction(
+            Transaction::from(Deposit::try_new(1.into(), 1.into(), dec!(10)).unwrap())
+                .with_parent_tx(9.into()),
+        )
+        .unwrap();
+    engine.process_transaction(linked).unwrap();
+    assert_eq!(
+        engine
+            .client_accounts()
+            .get(1.into())
+            .unwrap()
+            .balance()
+            .available(),
+        dec!(9)
+    );
+    let deposit = &engine.deposit_history().as_map()[&1.into()];
+    assert_eq!(deposit.parent_tx(), Some(9.into()));
+}
+
+#[test]
+fn ledger_entries_carry_the_parent_tx() {
+    let mut rdr = csv::Reader::from_reader(INPUT.as_bytes());
+    let mut output = Vec::new();
+    let mut ledger = LedgerWriter::new(EngineConfig::default(), &mut output).unwrap();
+    ledger
+        .apply_all(parsing::deserialize_csv(&mut rdr))
+        .unwrap();
+    ledger.finish().unwrap();
+
+    let mut reader = csv::Reader::from_reader(output.as_slice());
+    let parents = reader
+        .records()
+        .map(|record| {
+            let record = record.unwrap();
+            (record[0].to_string(), record[5].to_string())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        parents,
+        [
+            ("1", ""),
+            ("1", ""),
+            ("2", "1"),
+            ("2", "1"),
+            ("3", ""),
+            ("3", "")
+        ]
+        .map(|(entry, parent)| (entry.to_string(), parent.to_string()))
+    );
+}
+
+#[test]
+fn statements_find_linked_transactions() {
+    let mut rdr = csv::Reader::from_reader(INPUT.as_bytes());
+    let mut recorder = StatementRecorder::new(EngineConfig::default(), [1.into()]);
+    recorder.apply_all(parsing::deserialize_csv(&mut rdr));
+
+    let statement = recorder.statements().next().unwrap();
+    let linked = statement
+        .linked_to(1.into())
+        .map(|line| line.entry.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(linked, [withdrawal(2).with_parent_tx(1.into()).into()]);
+}
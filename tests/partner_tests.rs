@@ -0,0 +1,156 @@
+use rust_coding_test::{
+    domain::PartnerId,
+    engine::{ApplyTransactions, EngineConfig},
+    parsing::{self, PartnerTransaction},
+    partners::{PartnerBalance, PartnerBreakdown, PartnerLiability},
+};
+use rust_decimal::dec;
+
+const INPUT: &str = "type,client,tx,amount,partner\n\
+                     deposit,1,1,10,7\n\
+                     deposit,1,2,5,8\n\
+                     withdrawal,1,3,2.5,7\n\
+                     withdrawal,1,4,100,8\n\
+                     dispute,1,2,,\n\
+                     chargeback,1,2,,\n\
+                     deposit,2,5,1.5,\n";
+
+fn breakdown() -> PartnerBreakdown {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(INPUT.as_bytes());
+    let mut breakdown = PartnerBreakdown::new(EngineConfig::default());
+    breakdown.apply_all(parsing::deserialize_partner_csv(&mut rdr));
+    breakdown
+}
+
+fn partner(id: u16) -> Option<PartnerId> {
+    Some(id.into())
+}
+
+#[test]
+fn partner_column_is_optional() {
+    let mut rdr = csv::Reader::from_reader("type,client,tx,amount\ndeposit,1,1,1\n".as_bytes());
+    let rows = parsing::deserialize_partner_csv(&mut rdr).collect::<Vec<_>>();
+
+    assert!(matches!(
+        rows.as_slice(),
+        [PartnerTransaction { partner: None, .. }]
+    ));
+}
+
+#[test]
+fn balances_are_attributed_per_client_and_partner() {
+    let breakdown = breakdown();
+
+    assert_eq!(
+        breakdown.balances()[&(partner(7), 1.into())],
+        PartnerBalance {
+            available: dec!(7.5),
+            held: dec!(0),
+            total: dec!(7.5),
+            deposits: dec!(10),
+            deposit_count: 1,
+            withdrawals: dec!(2.5),
+            withdrawal_count: 1,
+            chargebacks: 0,
+        }
+    );
+    // The dispute and chargeback rows have no partner and inherit the deposit's; the
+    // rejected withdrawal isn't counted.
+    assert_eq!(
+        breakdown.balances()[&(partner(8), 1.into())],
+        PartnerBalance {
+            deposits: dec!(5),
+            deposit_count: 1,
+            chargebacks: 1,
+            ..Default::default()
+        }
+    );
+    assert_eq!(breakdown.balances()[&(None, 2.into())].total, dec!(1.5));
+}
+
+#[test]
+fn liability_accounts_are_kept_per_partner() {
+    let breakdown = breakdown();
+
+    assert_eq!(
+        breakdown.liabilities()[&partner(7)],
+        PartnerLiability {
+            deposited: dec!(10),
+            withdrawn: dec!(2.5),
+            charged_back: dec!(0),
+            balance: dec!(7.5),
+        }
+    );
+    assert_eq!(
+        breakdown.liabilities()[&partner(8)],
+        PartnerLiability {
+            deposited: dec!(5),
+            charged_back: dec!(5),
+            ..Default::default()
+        }
+    );
+    assert_eq!(breakdown.liabilities()[&None].balance, dec!(1.5));
+    let account = breakdown.engine().client_accounts().get(1.into()).unwrap();
+    assert_eq!(account.balance().total(), dec!(7.5));
+}
+
+#[test]
+fn settlements_are_attributed_to_the_deposit_partner() {
+    let input = "type,client,tx,amount,partner\n\
+                 deposit,1,1,10,7\n\
+                 deposit,1,2,5,8\n";
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+    let mut breakdown = PartnerBreakdown::new(EngineConfig {
+        settlement_delay: 1,
+        ..Default::default()
+    });
+    breakdown.apply_all(parsing::deserialize_partner_csv(&mut rdr));
+    breakdown.finish();
+
+    // Deposit 1 settled after the last transaction; deposit 2 is still pending.
+    for (id, deposited, settled) in [(7, dec!(10), dec!(10)), (8, dec!(5), dec!(0))] {
+        assert_eq!(
+            breakdown.balances()[&(partner(id), 1.into())],
+            PartnerBalance {
+                available: settled,
+                total: settled,
+                deposits: deposited,
+                deposit_count: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(breakdown.liabilities()[&partner(id)].balance, settled);
+    }
+}
+
+#[test]
+fn writes_csv_report() {
+    let mut output = Vec::new();
+    breakdown().write_report(&mut output).unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "partner,client,available,held,total,deposits,deposit_count,withdrawals,withdrawal_count,chargebacks\n\
+         ,2,1.5,0,1.5,1.5,1,0,0,0\n\
+         7,1,7.5,0,7.5,10,1,2.5,1,0\n\
+         8,1,0,0,0,5,1,0,0,1\n"
+    );
+}
+
+#[test]
+fn writes_csv_liability_table() {
+    let mut output = Vec::new();
+    breakdown().write_liabilities(&mut output).unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "partner,deposited,withdrawn,charged_back,balance\n\
+         ,1.5,0,0,1.5\n\
+         7,10,2.5,0,7.5\n\
+         8,5,0,5,0\n"
+    );
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::account;
+use rust_coding_test::{domain::ClientId, engine::PaymentsEngine, parsing, pipeline::Pipeline};
+use rust_decimal::dec;
+use std::collections::HashMap;
+
+#[test]
+fn items_arrive_in_order() {
+    let pipeline = Pipeline {
+        batch_size: 7,
+        depth: 2,
+    };
+
+    let received: Vec<u32> = pipeline.run(0..1000, |items| items.collect());
+
+    assert_eq!(received, (0..1000).collect::<Vec<_>>());
+}
+
+#[test]
+fn zero_batch_size_still_delivers() {
+    let pipeline = Pipeline {
+        batch_size: 0,
+        depth: 0,
+    };
+
+    assert_eq!(pipeline.run(0..10, |items| items.sum::<u32>()), 45);
+}
+
+#[test]
+fn consumer_can_stop_early() {
+    // The producer would never finish on its own; it must stop once the consumer is gone.
+    let first: Vec<u64> = Pipeline::default().run(0.., |items| items.take(3).collect());
+
+    assert_eq!(first, vec![0, 1, 2]);
+}
+
+#[test]
+fn pipelined_parsing_matches_sequential() {
+    let input = "type,client,tx,amount\n\
+                 deposit,1,1,10.0\n\
+                 deposit,2,2,5.0\n\
+                 withdrawal,1,3,4.0\n\
+                 dispute,2,2,\n\
+                 deposit,1,4,not-a-number\n\
+                 chargeback,2,2,\n";
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+
+    let engine = Pipeline::default().run(parsing::deserialize_csv(&mut rdr), |transactions| {
+        let mut engine = PaymentsEngine::new();
+        engine.process_transactions(transactions);
+        engine
+    });
+
+    let expected = HashMap::from_iter([
+        (ClientId::from(1), account(dec!(6.0), dec!(0.0), false)),
+        (ClientId::from(2), account(dec!(0.0), dec!(0.0), true)),
+    ]);
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+}
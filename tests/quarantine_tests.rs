@@ -0,0 +1,90 @@
+use std::process::Command;
+
+use rust_coding_test::{
+    domain::Deposit,
+    parsing::{Quarantine, deserialize_tenant_csv_quarantined},
+};
+use rust_decimal::dec;
+
+fn quarantine(input: &str) -> (Vec<rust_coding_test::domain::Transaction>, String) {
+    let mut reader = csv::ReaderBuilder::new().from_reader(input.as_bytes());
+    let mut output = Vec::new();
+    let transactions =
+        deserialize_tenant_csv_quarantined(&mut reader, Quarantine::new(&mut output))
+            .map(|tenant_transaction| tenant_transaction.transaction)
+            .collect();
+    (transactions, String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn skipped_rows_are_written_as_read_with_their_line_and_reason() {
+    let (transactions, quarantined) = quarantine(
+        "type, client, tx, amount\n\
+         deposit, 1, 1, 1.5\n\
+         bogus, 1, 2, 1\n\
+         withdrawal, 1, 3,\n\
+         deposit, 1\n\
+         deposit, x, 5, 1\n\
+         deposit, 2, 6, 2\n",
+    );
+
+    assert_eq!(
+        transactions,
+        [
+            Deposit::try_new(1.into(), 1.into(), dec!(1.5))
+                .unwrap()
+                .into(),
+            Deposit::try_new(2.into(), 6.into(), dec!(2))
+                .unwrap()
+                .into(),
+        ]
+    );
+    assert_eq!(
+        quarantined,
+        "line,reason,type, client, tx, amount\n\
+         3,malformed_row,bogus, 1, 2, 1\n\
+         4,missing_amount,withdrawal, 1, 3,\n\
+         5,malformed_row,deposit, 1\n\
+         6,malformed_row,deposit, x, 5, 1\n"
+    );
+}
+
+#[test]
+fn nothing_is_written_without_skipped_rows() {
+    let (transactions, quarantined) = quarantine("type,client,tx,amount\ndeposit,1,1,1\n");
+
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(quarantined, "");
+}
+
+#[test]
+fn cli_writes_the_quarantine_file() {
+    let dir = std::env::temp_dir();
+    let input = dir.join(format!("quarantine_tests_{}.csv", std::process::id()));
+    let quarantine = dir.join(format!("quarantine_tests_{}.bad.csv", std::process::id()));
+    std::fs::write(
+        &input,
+        "type,client,tx,amount\ndeposit,1,1,5\ndeposit,1,2,-1\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg(&input)
+        .arg("--quarantine")
+        .arg(&quarantine)
+        .output()
+        .unwrap();
+    let quarantined = std::fs::read_to_string(&quarantine).unwrap();
+    std::fs::remove_file(&input).unwrap();
+    std::fs::remove_file(&quarantine).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "client,available,held,total,locked\n\
+         1,5,0,5,false\n"
+    );
+    assert_eq!(
+        quarantined,
+        "line,reason,type,client,tx,amount\n3,invalid_amount,deposit,1,2,-1\n"
+    );
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use common::run;
+use rust_coding_test::{
+    domain::{Chargeback, Deposit, Dispute},
+    engine::PaymentsEngine,
+    output::print_accounts,
+    reconcile::{
+        ColumnMapping, Discrepancy, MappingError, compare, read_balances, read_expected, reconcile,
+        write_discrepancies,
+    },
+};
+use rust_decimal::dec;
+
+fn engine() -> PaymentsEngine {
+    run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 2.into(), dec!(2.25))
+            .unwrap()
+            .into(),
+        Deposit::try_new(3.into(), 3.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Dispute::new(3.into(), 3.into()).into(),
+        Chargeback::new(3.into(), 3.into()).into(),
+    ])
+}
+
+fn discrepancy(client: u16, field: &'static str, expected: &str, actual: &str) -> Discrepancy {
+    Discrepancy {
+        client: client.into(),
+        field,
+        expected: expected.to_string(),
+        actual: actual.to_string(),
+    }
+}
+
+#[test]
+fn own_output_reconciles() {
+    let engine = engine();
+    let mut output = Vec::new();
+    print_accounts(engine.client_accounts(), &mut output).unwrap();
+
+    let expected = read_expected(output.as_slice()).unwrap();
+    assert!(reconcile(&expected, engine.client_accounts()).is_empty());
+}
+
+#[test]
+fn differences_per_client_and_field() {
+    let expected = read_expected(
+        "client, available, held, total, locked\n\
+         1, 1.50, 0, 1.5, false\n\
+         2, 2.5, 0, 2.5, false\n\
+         3, 0, 0, 0, false\n\
+         4, 1.5, 0, 1.5, false\n"
+            .as_bytes(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        reconcile(&expected, engine().client_accounts()),
+        vec![
+            discrepancy(2, "available", "2.5", "2.25"),
+            discrepancy(2, "total", "2.5", "2.25"),
+            discrepancy(3, "locked", "false", "true"),
+            discrepancy(4, "account", "present", "missing"),
+        ]
+    );
+}
+
+#[test]
+fn accounts_missing_from_expected() {
+    let expected =
+        read_expected("client,available,held,total,locked\n1,1.5,0,1.5,false\n".as_bytes())
+            .unwrap();
+
+    let clients: Vec<_> = reconcile(&expected, engine().client_accounts())
+        .into_iter()
+        .map(|d| (u16::from(d.client), d.field, d.expected, d.actual))
+        .collect();
+    assert_eq!(
+        clients,
+        vec![
+            (2, "account", "missing".to_string(), "present".to_string()),
+            (3, "account", "missing".to_string(), "present".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn invalid_expected_files_rejected() {
+    let malformed = read_expected("client,available,held,total,locked\n1,x,0,0,false\n".as_bytes());
+    assert!(malformed.unwrap_err().to_string().contains("row 1"));
+
+    let repeated = read_expected(
+        "client,available,held,total,locked\n1,0,0,0,false\n1,0,0,0,false\n".as_bytes(),
+    );
+    assert!(repeated.unwrap_err().to_string().contains("more than once"));
+}
+
+#[test]
+fn report_csv() {
+    let mut output = Vec::new();
+    write_discrepancies(&[discrepancy(2, "held", "0", "2.25")], &mut output).unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "client,field,expected,actual\n2,held,0,2.25\n"
+    );
+}
+
+/// An export with its own headers and only some fields, compared with a tolerance.
+#[test]
+fn compare_maps_columns_within_tolerance() -> anyhow::Result<()> {
+    let engine = engine();
+    let mut output = Vec::new();
+    print_accounts(engine.client_accounts(), &mut output)?;
+    let actual = read_balances(output.as_slice(), &ColumnMapping::default())?;
+    let export = "account_id,balance,frozen\n\
+                  1,1.50004,0\n\
+                  2,2.2,0\n\
+                  4,0,0\n";
+    let mapping: ColumnMapping = "client=account_id, available=balance, locked=frozen".parse()?;
+    let expected = read_balances(export.as_bytes(), &mapping)?;
+
+    assert_eq!(
+        compare(&expected, &actual, dec!(0.0001)),
+        vec![
+            discrepancy(2, "available", "2.2", "2.25"),
+            discrepancy(3, "account", "missing", "present"),
+            discrepancy(4, "account", "present", "missing"),
+        ]
+    );
+    assert_eq!(
+        compare(&expected, &actual, dec!(0.05))
+            .iter()
+            .filter(|discrepancy| discrepancy.field != "account")
+            .count(),
+        0
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_mappings_and_balances_are_rejected() {
+    assert_eq!(
+        "client=id,balance=available".parse::<ColumnMapping>(),
+        Err(MappingError::UnknownField("balance".to_string()))
+    );
+    assert_eq!(
+        "held=a,held=b".parse::<ColumnMapping>(),
+        Err(MappingError::Duplicate("held"))
+    );
+    assert_eq!(
+        "total=".parse::<ColumnMapping>(),
+        Err(MappingError::EmptyHeader("total"))
+    );
+    let mapping = ColumnMapping::default();
+    assert!(read_balances("id,available\n1,1\n".as_bytes(), &mapping).is_err());
+    assert!(read_balances("client,locked\n1,maybe\n".as_bytes(), &mapping).is_err());
+    assert!(read_balances("client,held\n1,1\n1,2\n".as_bytes(), &mapping).is_err());
+}
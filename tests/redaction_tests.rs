@@ -0,0 +1,114 @@
+//! Redaction is process-wide, so every test here enables it with the same key.
+
+use std::process::Command;
+
+use rust_coding_test::{
+    domain::{ClientId, Deposit, Withdrawal},
+    engine::PaymentsEngine,
+    redaction::{self, REDACTION_KEY_VAR},
+};
+use rust_decimal::dec;
+
+const KEY: &[u8] = b"log pipeline key";
+
+fn enable() {
+    assert!(redaction::enable(KEY));
+}
+
+#[test]
+fn client_ids_hashed_consistently() {
+    enable();
+    let masked = redaction::client(ClientId::from(4242)).to_string();
+
+    assert!(redaction::is_enabled());
+    assert!(masked.starts_with("client#"));
+    assert!(!masked.contains("4242"));
+    assert_eq!(masked, redaction::client(ClientId::from(4242)).to_string());
+    assert_ne!(masked, redaction::client(ClientId::from(4243)).to_string());
+}
+
+#[test]
+fn amounts_bucketed() {
+    enable();
+    let cases = [
+        (dec!(0), "0"),
+        (dec!(0.0001), "[0.0001, 0.001)"),
+        (dec!(0.5), "[0.1, 1)"),
+        (dec!(10), "[10, 100)"),
+        (dec!(1234.5), "[1000, 10000)"),
+        (dec!(-3.75), "-[1, 10)"),
+    ];
+    for (amount, range) in cases {
+        assert_eq!(redaction::amount(amount).to_string(), range);
+    }
+}
+
+#[test]
+fn first_key_wins() {
+    enable();
+    assert!(!redaction::enable(b"other key"));
+    assert!(redaction::enable(KEY));
+}
+
+#[test]
+fn engine_errors_masked() {
+    enable();
+    let mut engine = PaymentsEngine::new();
+    engine
+        .process_transaction(
+            Deposit::try_new(4242.into(), 1.into(), dec!(12.5))
+                .unwrap()
+                .into(),
+        )
+        .unwrap();
+    let error = engine
+        .process_transaction(
+            Withdrawal::try_new(4242.into(), 2.into(), dec!(1234.5))
+                .unwrap()
+                .into(),
+        )
+        .unwrap_err()
+        .to_string();
+
+    let client = redaction::client(ClientId::from(4242)).to_string();
+    assert_eq!(
+        error,
+        format!(
+            "Insufficient funds for client {client}: tx 2 requested [1000, 10000), available [10, 100)"
+        )
+    );
+}
+
+#[test]
+fn cli_logs_masked_outputs_not() {
+    enable();
+    let input = std::env::temp_dir().join(format!("redaction_tests_{}.csv", std::process::id()));
+    std::fs::write(
+        &input,
+        "type,client,tx,amount\ndeposit,4242,1,12.5\nwithdrawal,4242,2,1234.5\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg(&input)
+        .arg("WARN")
+        .env(REDACTION_KEY_VAR, std::str::from_utf8(KEY).unwrap())
+        .output()
+        .unwrap();
+    std::fs::remove_file(&input).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let logs = stderr.lines().collect::<Vec<_>>();
+    let accounts = stdout.lines().collect::<Vec<_>>();
+
+    assert!(output.status.success());
+    assert_eq!(logs.len(), 1);
+    assert!(!logs[0].contains("4242"), "{}", logs[0]);
+    assert!(!logs[0].contains("1234.5"), "{}", logs[0]);
+    assert!(
+        logs[0].contains(&redaction::client(ClientId::from(4242)).to_string()),
+        "{}",
+        logs[0]
+    );
+    assert_eq!(accounts[1], "4242,12.5,0,12.5,false");
+}
@@ -0,0 +1,158 @@
+use rust_coding_test::{
+    domain::{Chargeback, Deposit, Dispute, Refund, Resolve, Transaction, Withdrawal},
+    engine::{Outcome, PaymentsEngine, errors::EngineError},
+    parsing,
+};
+use rust_decimal::{Decimal, dec};
+
+mod common;
+use common::amount;
+
+fn deposit(client: u16, tx: u32, value: Decimal) -> Transaction {
+    Deposit::try_new(client.into(), tx.into(), value)
+        .unwrap()
+        .into()
+}
+
+fn refund(client: u16, tx: u32, value: Decimal) -> Transaction {
+    Refund::try_new(client.into(), tx.into(), value)
+        .unwrap()
+        .into()
+}
+
+/// (available, held, total, locked) of client 1.
+fn funds(engine: &PaymentsEngine) -> (Decimal, Decimal, Decimal, bool) {
+    let account = engine.client_accounts().get(1.into()).unwrap();
+    let balance = account.balance();
+    (
+        balance.available(),
+        balance.held(),
+        balance.total(),
+        account.is_locked(),
+    )
+}
+
+#[test]
+fn partial_refunds_debit_available_without_locking() {
+    let mut engine = PaymentsEngine::new();
+    engine.process_transaction(deposit(1, 1, dec!(10))).unwrap();
+    engine.process_transaction(refund(1, 1, dec!(2.5))).unwrap();
+    engine
+        .process_transaction(refund(1, 1, dec!(3.75)))
+        .unwrap();
+    assert_eq!(funds(&engine), (dec!(3.75), dec!(0), dec!(3.75), false));
+
+    assert_eq!(
+        engine.process_transaction(refund(1, 1, dec!(5))),
+        Err(EngineError::RefundExceedsDeposit {
+            client: 1.into(),
+            tx: 1.into(),
+            requested: amount(dec!(5)),
+            refundable: amount(dec!(3.75)),
+        })
+    );
+    // A new deposit doesn't make the old one refundable beyond its amount.
+    engine.process_transaction(deposit(1, 2, dec!(10))).unwrap();
+    assert!(engine.process_transaction(refund(1, 1, dec!(5))).is_err());
+}
+
+#[test]
+fn disputes_only_hold_the_unrefunded_amount() {
+    let mut engine = PaymentsEngine::new();
+    engine.process_transactions(
+        [
+            deposit(1, 1, dec!(10)),
+            refund(1, 1, dec!(2.5)),
+            Dispute::new(1.into(), 1.into()).into(),
+        ]
+        .into_iter(),
+    );
+    assert_eq!(funds(&engine), (dec!(0), dec!(7.5), dec!(7.5), false));
+
+    engine
+        .process_transaction(Chargeback::new(1.into(), 1.into()).into())
+        .unwrap();
+    assert_eq!(funds(&engine), (dec!(0), dec!(0), dec!(0), true));
+}
+
+#[test]
+fn fully_refunded_deposits_cannot_be_disputed() {
+    let mut engine = PaymentsEngine::new();
+    engine
+        .process_transaction(deposit(1, 1, dec!(1.5)))
+        .unwrap();
+    engine.process_transaction(refund(1, 1, dec!(1.5))).unwrap();
+
+    let error = EngineError::DepositRefunded {
+        client: 1.into(),
+        tx: 1.into(),
+        amount: amount(dec!(1.5)),
+    };
+    assert_eq!(
+        engine.apply(Dispute::new(1.into(), 1.into()).into()),
+        Outcome::from(error)
+    );
+}
+
+#[test]
+fn disputed_locked_unknown_and_uncovered_refunds_rejected() {
+    let mut engine = PaymentsEngine::new();
+    engine.process_transaction(deposit(1, 1, dec!(10))).unwrap();
+    engine
+        .process_transaction(Dispute::new(1.into(), 1.into()).into())
+        .unwrap();
+    assert!(matches!(
+        engine.process_transaction(refund(1, 1, dec!(1))),
+        Err(EngineError::TransactionAlreadyDisputed { .. })
+    ));
+    assert!(matches!(
+        engine.process_transaction(refund(1, 9, dec!(1))),
+        Err(EngineError::TransactionNotFound { .. })
+    ));
+
+    // Resolved deposits can be refunded again, as far as available funds go.
+    engine
+        .process_transaction(Resolve::new(1.into(), 1.into()).into())
+        .unwrap();
+    engine
+        .process_transaction(
+            Withdrawal::try_new(1.into(), 2.into(), dec!(7.5))
+                .unwrap()
+                .into(),
+        )
+        .unwrap();
+    assert!(matches!(
+        engine.process_transaction(refund(1, 1, dec!(5))),
+        Err(EngineError::InsufficientFunds { .. })
+    ));
+    engine.process_transaction(refund(1, 1, dec!(2.5))).unwrap();
+
+    engine
+        .process_transaction(deposit(1, 3, dec!(1.5)))
+        .unwrap();
+    engine
+        .process_transaction(Dispute::new(1.into(), 3.into()).into())
+        .unwrap();
+    engine
+        .process_transaction(Chargeback::new(1.into(), 3.into()).into())
+        .unwrap();
+    assert!(matches!(
+        engine.process_transaction(refund(1, 3, dec!(1))),
+        Err(EngineError::AccountLocked { .. })
+    ));
+}
+
+#[test]
+fn refund_rows_are_parsed() {
+    let input = "type,client,tx,amount\n\
+                 refund,1,1,2.5\n\
+                 refund,1,1,\n";
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+
+    let transactions = parsing::deserialize_csv(&mut rdr).collect::<Vec<_>>();
+
+    // the second refund is missing its amount and is skipped
+    assert_eq!(transactions, vec![refund(1, 1, dec!(2.5))]);
+}
@@ -0,0 +1,102 @@
+use rust_coding_test::{
+    domain::{Deposit, Dispute, Resolve, Withdrawal},
+    engine::{EngineConfig, PaymentsEngine, RepeatedSettlement},
+    parsing,
+    rejection::{RejectionReason, TAXONOMY_VERSION},
+};
+use rust_decimal::dec;
+
+/// Names and codes are a public contract - changing one breaks downstream correlation.
+#[test]
+fn taxonomy_is_stable() {
+    let table = RejectionReason::ALL
+        .iter()
+        .map(|reason| (reason.code(), reason.to_string()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(TAXONOMY_VERSION, 10);
+    assert_eq!(
+        table,
+        [
+            (100, "malformed_row"),
+            (101, "missing_amount"),
+            (102, "invalid_amount"),
+            (103, "missing_reason"),
+            (200, "transaction_not_found"),
+            (201, "already_disputed"),
+            (202, "not_disputed"),
+            (203, "repeated_settlement"),
+            (204, "deposit_refunded"),
+            (205, "dispute_stage_out_of_order"),
+            (206, "filtered"),
+            (207, "deferred"),
+            (300, "account_locked"),
+            (301, "insufficient_funds"),
+            (302, "authorization_already_settled"),
+            (303, "refund_exceeds_deposit"),
+            (304, "dispute_window_expired"),
+            (305, "withdrawal_limit_exceeded"),
+            (306, "rejected_by_middleware"),
+            (307, "duplicate_transaction_id"),
+            (308, "amount_too_large"),
+        ]
+        .map(|(code, name)| (code, name.to_string()))
+    );
+}
+
+#[test]
+fn serialized_name_matches_display() {
+    for reason in RejectionReason::ALL {
+        assert_eq!(
+            serde_json::to_value(reason).unwrap(),
+            serde_json::json!(reason.to_string())
+        );
+    }
+}
+
+#[test]
+fn engine_outcomes_carry_reasons() {
+    let mut engine = PaymentsEngine::with_config(EngineConfig {
+        repeated_settlement: RepeatedSettlement::Ignore,
+        ..Default::default()
+    });
+
+    let deposit = engine.apply(
+        Deposit::try_new(1.into(), 1.into(), dec!(1.0))
+            .unwrap()
+            .into(),
+    );
+    let overdraft = engine.apply(
+        Withdrawal::try_new(1.into(), 2.into(), dec!(5.0))
+            .unwrap()
+            .into(),
+    );
+    let unknown = engine.apply(Dispute::new(1.into(), 9.into()).into());
+    let undisputed = engine.apply(Resolve::new(1.into(), 1.into()).into());
+    engine.apply(Dispute::new(1.into(), 1.into()).into());
+    engine.apply(Resolve::new(1.into(), 1.into()).into());
+    let repeated = engine.apply(Resolve::new(1.into(), 1.into()).into());
+
+    assert_eq!(deposit.reason(), None);
+    assert_eq!(overdraft.reason(), Some(RejectionReason::InsufficientFunds));
+    assert_eq!(unknown.reason(), Some(RejectionReason::TransactionNotFound));
+    assert_eq!(undisputed.reason(), Some(RejectionReason::NotDisputed));
+    assert_eq!(repeated.reason(), Some(RejectionReason::RepeatedSettlement));
+}
+
+#[test]
+fn parse_errors_carry_reasons() {
+    let input = b"type,client,tx,amount\n\
+                  deposit,1,1,oops\n\
+                  withdrawal,1,2,\n";
+
+    let (_, errors) = parsing::parse_bytes(input);
+
+    assert_eq!(
+        errors.iter().map(|e| e.reason()).collect::<Vec<_>>(),
+        vec![
+            RejectionReason::MalformedRow,
+            RejectionReason::MissingAmount
+        ]
+    );
+}
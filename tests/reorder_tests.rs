@@ -0,0 +1,82 @@
+use rust_coding_test::{
+    domain::{Chargeback, Deposit, Dispute, Transaction},
+    engine::{EngineConfig, IgnoreReason, Outcome, PaymentsEngine},
+    rejection::RejectionReason,
+};
+use rust_decimal::dec;
+
+fn reordering(window: u64) -> PaymentsEngine {
+    PaymentsEngine::with_config(EngineConfig {
+        reorder_window: Some(window),
+        ..Default::default()
+    })
+}
+
+fn deposit(client: u16, tx: u32) -> Transaction {
+    Deposit::try_new(client.into(), tx.into(), dec!(1.5))
+        .unwrap()
+        .into()
+}
+
+#[test]
+fn early_dispute_and_chargeback_apply_once_the_deposit_arrives() {
+    let mut engine = reordering(3);
+
+    let outcome = engine.apply(Dispute::new(1.into(), 1.into()).into());
+    assert_eq!(outcome, Outcome::Ignored(IgnoreReason::Deferred));
+    assert_eq!(outcome.reason(), Some(RejectionReason::Deferred));
+    engine
+        .process_transaction(Chargeback::new(1.into(), 1.into()).into())
+        .unwrap();
+    assert_eq!(engine.parked_transactions(), 2);
+
+    assert_eq!(engine.apply(deposit(1, 1)), Outcome::Applied);
+    assert_eq!(engine.parked_transactions(), 0);
+    let account = engine.client_accounts().get(1.into()).unwrap();
+    assert!(account.is_locked());
+    assert_eq!(account.balance().total(), dec!(0));
+}
+
+#[test]
+fn parked_transactions_expire_after_the_window() {
+    let mut engine = reordering(1);
+
+    engine.apply(Dispute::new(1.into(), 1.into()).into());
+    engine.apply(deposit(2, 2));
+    assert_eq!(engine.parked_transactions(), 1);
+    engine.apply(deposit(2, 3));
+    assert_eq!(engine.parked_transactions(), 0);
+
+    assert_eq!(engine.apply(deposit(1, 1)), Outcome::Applied);
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    assert_eq!(balance.available(), dec!(1.5));
+    assert_eq!(balance.held(), dec!(0));
+}
+
+#[test]
+fn longest_window_keeps_transactions_parked() {
+    let mut engine = reordering(u64::MAX);
+
+    engine.apply(Dispute::new(1.into(), 1.into()).into());
+    engine.apply(deposit(2, 2));
+    engine.apply(deposit(2, 3));
+    assert_eq!(engine.parked_transactions(), 1);
+
+    assert_eq!(engine.apply(deposit(1, 1)), Outcome::Applied);
+    assert_eq!(engine.parked_transactions(), 0);
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    assert_eq!(balance.held(), dec!(1.5));
+}
+
+#[test]
+fn references_to_known_deposits_are_not_parked() {
+    let mut engine = reordering(10);
+    engine.apply(deposit(1, 1));
+
+    // The deposit exists, but for another client: ignored as the spec says.
+    assert!(matches!(
+        engine.apply(Dispute::new(2.into(), 1.into()).into()),
+        Outcome::Ignored(IgnoreReason::Spec(_))
+    ));
+    assert_eq!(engine.parked_transactions(), 0);
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::{account, run};
+use rust_coding_test::{
+    domain::{Chargeback, Deposit, Dispute, Withdrawal},
+    engine::PaymentsEngine,
+    parsing::ParseFailureCounts,
+    report::RunReport,
+};
+use rust_decimal::dec;
+
+fn settled_engine() -> PaymentsEngine {
+    run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(10))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(5))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 2.into()).into(),
+        Withdrawal::try_new(1.into(), 3.into(), dec!(2))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 4.into(), dec!(100.25))
+            .unwrap()
+            .into(),
+        Deposit::try_new(3.into(), 5.into(), dec!(7))
+            .unwrap()
+            .into(),
+        Dispute::new(3.into(), 5.into()).into(),
+        Chargeback::new(3.into(), 5.into()).into(),
+    ])
+}
+
+#[test]
+fn totals_over_final_state() {
+    let report = RunReport::new(&settled_engine(), 10);
+
+    assert_eq!(
+        report,
+        RunReport {
+            clients: 3,
+            locked_accounts: 1,
+            total_available: dec!(108.25),
+            total_held: dec!(5),
+            open_disputes: 1,
+            chargebacks: 1,
+            expired_disputes: 0,
+            largest_accounts: vec![
+                (2.into(), account(dec!(100.25), dec!(0), false)),
+                (1.into(), account(dec!(8), dec!(5), false)),
+                (3.into(), account(dec!(0), dec!(0), true)),
+            ],
+            parse_failures: ParseFailureCounts::default(),
+        }
+    );
+}
+
+#[test]
+fn largest_accounts_are_truncated_and_ties_ordered_by_client() {
+    let engine = run(vec![
+        Deposit::try_new(9.into(), 1.into(), dec!(3))
+            .unwrap()
+            .into(),
+        Deposit::try_new(4.into(), 2.into(), dec!(3))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 3.into(), dec!(1))
+            .unwrap()
+            .into(),
+    ]);
+
+    let report = RunReport::new(&engine, 2);
+
+    let clients: Vec<u16> = report
+        .largest_accounts
+        .iter()
+        .map(|(client_id, _)| u16::from(*client_id))
+        .collect();
+    assert_eq!(clients, vec![4, 9]);
+}
+
+#[test]
+fn renders_as_text() {
+    let report = RunReport::new(&settled_engine(), 1);
+
+    assert_eq!(
+        report.to_string(),
+        "Clients:          3\n\
+         Locked accounts:  1\n\
+         Total available:  108.25\n\
+         Total held:       5\n\
+         Open disputes:    1\n\
+         Chargebacks:      1\n\
+         Expired disputes: 0\n\
+         Skipped rows:     0\n\
+         \n\
+         Largest accounts:\n \
+         client         available              held             total  locked\n      \
+         2            100.25                 0            100.25  no\n"
+    );
+}
+
+#[test]
+fn empty_run() {
+    let report = RunReport::new(&PaymentsEngine::new(), 10);
+
+    assert_eq!(report.clients, 0);
+    assert!(report.largest_accounts.is_empty());
+    assert!(!report.to_string().contains("Largest accounts"));
+}
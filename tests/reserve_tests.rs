@@ -0,0 +1,185 @@
+use rust_coding_test::{
+    domain::{Amount, Deposit, Dispute, Transaction},
+    engine::{
+        AccountTiers, EngineConfig, PaymentsEngine, RollingReserve, Settlement, SettlementKind,
+    },
+    output::{self, OutputColumns},
+};
+use rust_decimal::{Decimal, dec};
+
+mod common;
+use common::amount;
+
+fn reserving(rate: Decimal, delay: u64) -> PaymentsEngine {
+    PaymentsEngine::with_config(EngineConfig {
+        rolling_reserve: RollingReserve { rate, delay },
+        ..Default::default()
+    })
+}
+
+fn deposit(client: u16, tx: u32, value: Decimal) -> Transaction {
+    Deposit::try_new(client.into(), tx.into(), value)
+        .unwrap()
+        .into()
+}
+
+/// (available, reserve, gross total) of client 1.
+fn funds(engine: &PaymentsEngine) -> (Decimal, Decimal, Decimal) {
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    (
+        balance.available(),
+        balance.reserve(),
+        balance.gross_total(),
+    )
+}
+
+#[test]
+fn reserve_released_after_the_delay() {
+    let mut engine = reserving(dec!(0.1), 1);
+    engine
+        .process_transaction(deposit(1, 1, dec!(12.5)))
+        .unwrap();
+    assert_eq!(funds(&engine), (dec!(11.25), dec!(1.25), dec!(12.5)));
+
+    engine
+        .process_transaction(deposit(2, 2, dec!(1.5)))
+        .unwrap();
+    assert_eq!(
+        engine.settle_due(),
+        [Settlement {
+            client: 1.into(),
+            tx: 1.into(),
+            amount: amount(dec!(1.25)),
+            kind: SettlementKind::Reserve,
+        }]
+    );
+    assert_eq!(funds(&engine), (dec!(12.5), dec!(0), dec!(12.5)));
+}
+
+#[test]
+fn longest_delay_never_releases_the_reserve() {
+    let mut engine = reserving(dec!(0.1), u64::MAX);
+    engine
+        .process_transaction(deposit(1, 1, dec!(12.5)))
+        .unwrap();
+    engine
+        .process_transaction(deposit(2, 2, dec!(1.5)))
+        .unwrap();
+
+    assert!(engine.settle_due().is_empty());
+    assert_eq!(funds(&engine), (dec!(11.25), dec!(1.25), dec!(12.5)));
+}
+
+#[test]
+fn reserve_is_rounded_down() {
+    assert_eq!(
+        amount(dec!(0.0007)).split(dec!(0.5)),
+        (amount(dec!(0.0003)), amount(dec!(0.0004)))
+    );
+    assert_eq!(
+        amount(dec!(1.5)).split(dec!(1)),
+        (amount(dec!(1.5)), Amount::ZERO)
+    );
+
+    // A share too small to represent isn't reserved at all.
+    let mut engine = reserving(dec!(0.1), 5);
+    engine
+        .process_transaction(deposit(1, 1, dec!(0.0001)))
+        .unwrap();
+    assert_eq!(funds(&engine), (dec!(0.0001), dec!(0), dec!(0.0001)));
+}
+
+#[test]
+fn dispute_releases_reserve_first() {
+    let mut engine = reserving(dec!(0.5), 5);
+    engine
+        .process_transaction(deposit(1, 1, dec!(7.5)))
+        .unwrap();
+    engine
+        .process_transaction(Dispute::new(1.into(), 1.into()).into())
+        .unwrap();
+
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    assert_eq!(
+        (balance.available(), balance.held(), balance.reserve()),
+        (dec!(0), dec!(7.5), dec!(0))
+    );
+    // Nothing is left to release later.
+    engine.process_transactions((10..20).map(|tx| deposit(2, tx, dec!(1.5))));
+    assert_eq!(
+        engine
+            .client_accounts()
+            .get(1.into())
+            .unwrap()
+            .balance()
+            .held(),
+        dec!(7.5)
+    );
+}
+
+#[test]
+fn reserve_and_settlement_delays_are_independent() {
+    let mut engine = PaymentsEngine::with_config(EngineConfig {
+        settlement_delay: 1,
+        rolling_reserve: RollingReserve {
+            rate: dec!(0.2),
+            delay: 3,
+        },
+        ..Default::default()
+    });
+    engine
+        .process_transaction(deposit(1, 1, dec!(12.5)))
+        .unwrap();
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    assert_eq!(
+        (balance.pending(), balance.reserve()),
+        (dec!(10), dec!(2.5))
+    );
+
+    engine.process_transactions((10..13).map(|tx| deposit(2, tx, dec!(1.5))));
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    assert_eq!(
+        (balance.available(), balance.total()),
+        (dec!(12.5), dec!(12.5))
+    );
+}
+
+/// A reserve rate, global or of a tier, adds the `reserve` column to the default layout.
+#[test]
+fn reserve_column_only_with_a_reserve_rate() {
+    let header = |config: &EngineConfig| {
+        let mut engine = PaymentsEngine::with_config(config.clone());
+        engine.process_transaction(deposit(1, 1, dec!(1))).unwrap();
+        let mut csv = Vec::new();
+        output::print_accounts_with_columns(
+            engine.client_accounts(),
+            &OutputColumns::for_config(config),
+            &mut csv,
+        )
+        .unwrap();
+        String::from_utf8(csv)
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .to_owned()
+    };
+
+    assert_eq!(
+        header(&EngineConfig::default()),
+        "client,available,held,total,locked"
+    );
+    assert_eq!(
+        header(reserving(dec!(0.1), 1).config()),
+        "client,available,held,total,locked,reserve"
+    );
+    let tiers =
+        r#"{ "tiers": { "risky": { "reserve_rate": "0.5" } }, "clients": { "2": "risky" } }"#;
+    assert_eq!(
+        header(&EngineConfig {
+            tiers: AccountTiers::from_reader(tiers.as_bytes()).unwrap(),
+            ..Default::default()
+        }),
+        "client,available,held,total,locked,reserve"
+    );
+}
@@ -10,12 +10,15 @@ use std::collections::HashMap;
 #[test]
 fn resolve_releases_held_funds_back_to_available() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Resolve::new(1.into(), 1.into()).into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -24,11 +27,14 @@ fn resolve_releases_held_funds_back_to_available() {
 #[test]
 fn resolve_without_prior_dispute_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Resolve::new(1.into(), 1.into()).into(), // no dispute open
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -37,11 +43,14 @@ fn resolve_without_prior_dispute_is_ignored() {
 #[test]
 fn resolve_on_nonexistent_transaction_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Resolve::new(1.into(), 99.into()).into(), // tx 99 doesn't exist
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -51,13 +60,16 @@ fn resolve_on_nonexistent_transaction_is_ignored() {
 #[test]
 fn after_resolve_dispute_can_be_reopened() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Resolve::new(1.into(), 1.into()).into(),
         Dispute::new(1.into(), 1.into()).into(), // re-dispute
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(0.0), dec!(100.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(100.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -67,12 +79,14 @@ fn after_resolve_dispute_can_be_reopened() {
 #[test]
 fn resolve_on_wrong_client_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
         Resolve::new(2.into(), 1.into()).into(), // client 2 tries to resolve client 1's dispute
     ]);
 
-    let expected = HashMap::from([
+    let expected = HashMap::from_iter([
         (ClientId::from(1), account(dec!(0.0), dec!(100.0), false)),
         (ClientId::from(2), account(dec!(0.0), dec!(0.0), false)),
     ]);
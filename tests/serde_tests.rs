@@ -0,0 +1,94 @@
+use rust_coding_test::domain::{
+    Account, Balance, Chargeback, Deposit, Dispute, Resolve, Transaction, Withdrawal,
+};
+use rust_decimal::dec;
+use serde_json::json;
+
+/// Transactions serialize as internally tagged objects with the CSV field names.
+#[test]
+fn transaction_serializes_with_type_tag() -> anyhow::Result<()> {
+    let deposit: Transaction = Deposit::try_new(1.into(), 2.into(), dec!(1.5))
+        .unwrap()
+        .into();
+    let dispute: Transaction = Dispute::new(1.into(), 2.into()).into();
+
+    assert_eq!(
+        serde_json::to_value(&deposit)?,
+        json!({"type": "deposit", "client": 1, "tx": 2, "amount": "1.5"})
+    );
+    assert_eq!(
+        serde_json::to_value(&dispute)?,
+        json!({"type": "dispute", "client": 1, "tx": 2})
+    );
+
+    Ok(())
+}
+
+#[test]
+fn all_transaction_kinds_round_trip() -> anyhow::Result<()> {
+    let transactions: Vec<Transaction> = vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(1.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 2.into(), dec!(0.5))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 1.into()).into(),
+        Resolve::new(1.into(), 1.into()).into(),
+        Chargeback::new(1.into(), 1.into()).into(),
+    ];
+
+    let json = serde_json::to_string(&transactions)?;
+    let round_tripped: Vec<Transaction> = serde_json::from_str(&json)?;
+
+    assert_eq!(round_tripped, transactions);
+
+    Ok(())
+}
+
+#[test]
+fn unknown_transaction_type_is_rejected() {
+    let result =
+        serde_json::from_value::<Transaction>(json!({"type": "transfer", "client": 1, "tx": 1}));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn account_serializes_flat_with_total() -> anyhow::Result<()> {
+    let account = Account::new(Balance::new(dec!(1.5), dec!(2.25)), true);
+
+    let value = serde_json::to_value(&account)?;
+
+    assert_eq!(
+        value,
+        json!({"available": "1.5", "held": "2.25", "total": "3.75", "locked": true, "authorized": "0", "pending": "0", "reserve": "0"})
+    );
+    assert_eq!(serde_json::from_value::<Account>(value)?, account);
+
+    Ok(())
+}
+
+#[test]
+fn balance_total_is_optional_on_deserialize() -> anyhow::Result<()> {
+    let balance: Balance = serde_json::from_value(json!({"available": "1.0", "held": "2.0"}))?;
+
+    assert_eq!(balance, Balance::new(dec!(1.0), dec!(2.0)));
+    assert_eq!(balance.total(), dec!(3.0));
+
+    Ok(())
+}
+
+/// The total = available + held (+ authorized) invariant is checked on deserialize.
+#[test]
+fn inconsistent_balances_are_rejected() {
+    let wrong_total = json!({"available": "1.0", "held": "2.0", "total": "4.0"});
+    let negative_held = json!({"available": "1.0", "held": "-2.0"});
+
+    assert!(serde_json::from_value::<Balance>(wrong_total.clone()).is_err());
+    assert!(serde_json::from_value::<Balance>(negative_held).is_err());
+
+    let mut wrong_account = wrong_total;
+    wrong_account["locked"] = json!(false);
+    assert!(serde_json::from_value::<Account>(wrong_account).is_err());
+}
@@ -0,0 +1,143 @@
+use rust_coding_test::{
+    domain::{Deposit, Dispute, Withdrawal},
+    engine::{EngineConfig, PaymentsEngine, Settlement, SettlementKind, errors::EngineError},
+    output::{self, OutputColumns},
+};
+use rust_decimal::{Decimal, dec};
+
+mod common;
+use common::amount;
+
+fn delayed(settlement_delay: u64) -> PaymentsEngine {
+    PaymentsEngine::with_config(EngineConfig {
+        settlement_delay,
+        ..Default::default()
+    })
+}
+
+fn deposit(client: u16, tx: u32, value: Decimal) -> rust_coding_test::domain::Transaction {
+    Deposit::try_new(client.into(), tx.into(), value)
+        .unwrap()
+        .into()
+}
+
+/// (available, pending, gross total) of client 1.
+fn funds(engine: &PaymentsEngine) -> (Decimal, Decimal, Decimal) {
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    (
+        balance.available(),
+        balance.pending(),
+        balance.gross_total(),
+    )
+}
+
+#[test]
+fn deposits_settle_after_the_delay() {
+    let mut engine = delayed(2);
+    engine
+        .process_transaction(deposit(1, 1, dec!(1.5)))
+        .unwrap();
+    engine
+        .process_transaction(deposit(2, 2, dec!(2.25)))
+        .unwrap();
+    assert_eq!(funds(&engine), (dec!(0), dec!(1.5), dec!(1.5)));
+
+    engine
+        .process_transaction(deposit(2, 3, dec!(2.25)))
+        .unwrap();
+    assert_eq!(
+        engine.settle_due(),
+        [Settlement {
+            client: 1.into(),
+            tx: 1.into(),
+            amount: amount(dec!(1.5)),
+            kind: SettlementKind::Pending,
+        }]
+    );
+    assert_eq!(funds(&engine), (dec!(1.5), dec!(0), dec!(1.5)));
+    assert!(engine.settle_due().is_empty());
+}
+
+#[test]
+fn longest_delay_never_settles() {
+    let mut engine = delayed(u64::MAX);
+    engine
+        .process_transaction(deposit(1, 1, dec!(1.5)))
+        .unwrap();
+    engine
+        .process_transaction(deposit(2, 2, dec!(2.25)))
+        .unwrap();
+
+    assert!(engine.settle_due().is_empty());
+    assert_eq!(funds(&engine), (dec!(0), dec!(1.5), dec!(1.5)));
+}
+
+#[test]
+fn withdrawals_cannot_draw_on_pending_funds() {
+    let mut engine = delayed(5);
+    engine
+        .process_transaction(deposit(1, 1, dec!(1.5)))
+        .unwrap();
+
+    assert_eq!(
+        engine.process_transaction(
+            Withdrawal::try_new(1.into(), 2.into(), dec!(1))
+                .unwrap()
+                .into()
+        ),
+        Err(EngineError::InsufficientFunds {
+            client: 1.into(),
+            tx: 2.into(),
+            requested: amount(dec!(1)),
+            available: dec!(0),
+        })
+    );
+}
+
+#[test]
+fn dispute_settles_pending_deposit_first() {
+    let mut engine = delayed(5);
+    engine
+        .process_transaction(deposit(1, 1, dec!(1.5)))
+        .unwrap();
+    engine
+        .process_transaction(Dispute::new(1.into(), 1.into()).into())
+        .unwrap();
+
+    let balance = engine.client_accounts().get(1.into()).unwrap().balance();
+    assert_eq!(
+        (balance.available(), balance.held(), balance.pending()),
+        (dec!(0), dec!(1.5), dec!(0))
+    );
+
+    engine.process_transactions((10..20).map(|tx| deposit(2, tx, dec!(1.5))));
+    assert_eq!(
+        engine
+            .client_accounts()
+            .get(1.into())
+            .unwrap()
+            .balance()
+            .held(),
+        dec!(1.5)
+    );
+}
+
+#[test]
+fn pending_column_only_with_a_settlement_delay() {
+    let mut engine = delayed(1);
+    engine.process_transactions([deposit(1, 1, dec!(1.5)), deposit(2, 2, dec!(2.25))].into_iter());
+    let print = |columns: &OutputColumns| {
+        let mut csv = Vec::new();
+        output::print_accounts_with_columns(engine.client_accounts(), columns, &mut csv).unwrap();
+        String::from_utf8(csv).unwrap()
+    };
+
+    let without = print(&OutputColumns::for_config(&EngineConfig::default()));
+    assert!(without.starts_with("client,available,held,total,locked\n"));
+
+    let csv = print(&OutputColumns::for_config(engine.config()));
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines[0], "client,available,held,total,locked,pending");
+    assert!(lines[1].starts_with("1,1.5,0,1.5,false"), "{}", lines[1]);
+    assert_eq!(lines[2], "2,0,0,0,false,2.25");
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{account, run};
+use rust_coding_test::domain::{Chargeback, ClientId, Deposit, Dispute, Withdrawal};
+use rust_decimal::dec;
+use std::collections::HashMap;
+
+/// A cloned engine can be branched without affecting the live one.
+#[test]
+fn hypothetical_transactions_leave_original_untouched() {
+    let live = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+            .unwrap()
+            .into(),
+    ]);
+
+    let mut what_if = live.clone();
+    what_if
+        .process_transaction(Dispute::new(1.into(), 1.into()).into())
+        .unwrap();
+    what_if
+        .process_transaction(Chargeback::new(1.into(), 1.into()).into())
+        .unwrap();
+
+    assert_eq!(
+        what_if.client_accounts().as_map(),
+        &HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(0.0), true))])
+    );
+    assert_eq!(
+        live.client_accounts().as_map(),
+        &HashMap::from_iter([(ClientId::from(1), account(dec!(10.0), dec!(0.0), false))])
+    );
+}
+
+/// Dispute state lives in the deposit history, so it must be branched too.
+#[test]
+fn clone_branches_dispute_state() {
+    let mut live = run(vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+            .unwrap()
+            .into(),
+    ]);
+
+    let mut what_if = live.clone();
+    what_if
+        .process_transaction(Dispute::new(1.into(), 1.into()).into())
+        .unwrap();
+
+    // the dispute only happened in the branch, so the live engine can still open it
+    assert!(
+        live.process_transaction(Dispute::new(1.into(), 1.into()).into())
+            .is_ok()
+    );
+    assert!(
+        what_if
+            .process_transaction(
+                Withdrawal::try_new(1.into(), 2.into(), dec!(1.0))
+                    .unwrap()
+                    .into()
+            )
+            .is_err()
+    );
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use common::account;
+use rust_coding_test::{
+    domain::{Deposit, Dispute, Resolve, Transaction, Withdrawal},
+    engine::{ApplyTransactions, EngineConfig, Outcome},
+    statement::{StatementLine, StatementRecorder},
+};
+use rust_decimal::dec;
+
+fn transactions() -> Vec<Transaction> {
+    vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 2.into(), dec!(2.25))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 1.into()).into(),
+        // Rejected: only 0 available while the deposit is held.
+        Withdrawal::try_new(1.into(), 3.into(), dec!(0.5))
+            .unwrap()
+            .into(),
+        Resolve::new(1.into(), 1.into()).into(),
+    ]
+}
+
+#[test]
+fn lines_carry_the_running_balance() {
+    let mut recorder = StatementRecorder::new(EngineConfig::default(), [1.into()]);
+    recorder.apply_all(transactions().into_iter());
+
+    let statements: Vec<_> = recorder.statements().collect();
+    assert_eq!(statements.len(), 1);
+    let statement = &statements[0];
+    assert_eq!(statement.client, 1.into());
+    let transactions = transactions();
+    assert_eq!(
+        statement.lines,
+        vec![
+            StatementLine {
+                entry: transactions[0].clone().into(),
+                account: account(dec!(1.5), dec!(0), false),
+            },
+            StatementLine {
+                entry: transactions[2].clone().into(),
+                account: account(dec!(0), dec!(1.5), false),
+            },
+            StatementLine {
+                entry: transactions[4].clone().into(),
+                account: account(dec!(1.5), dec!(0), false),
+            },
+        ]
+    );
+    assert_eq!(statement.account, account(dec!(1.5), dec!(0), false));
+}
+
+#[test]
+fn every_client_is_processed_but_only_requested_ones_recorded() {
+    let mut recorder = StatementRecorder::new(EngineConfig::default(), [3.into()]);
+    let outcomes: Vec<_> = transactions()
+        .into_iter()
+        .map(|transaction| recorder.apply(transaction))
+        .collect();
+
+    assert_eq!(outcomes[1], Outcome::Applied);
+    assert_eq!(recorder.engine().client_accounts().len(), 2);
+
+    let statements: Vec<_> = recorder.statements().collect();
+    assert_eq!(statements.len(), 1);
+    assert_eq!(statements[0].client, 3.into());
+    assert!(statements[0].lines.is_empty());
+    assert_eq!(statements[0].account, account(dec!(0), dec!(0), false));
+}
+
+#[test]
+fn renders_as_text() {
+    let mut recorder = StatementRecorder::new(EngineConfig::default(), [2.into()]);
+    recorder.apply_all(transactions().into_iter());
+    let statement = recorder.statements().next().unwrap();
+
+    assert_eq!(
+        statement.to_string(),
+        "Statement for client 2\n\
+         transaction                                          available              held             total  locked\n\
+         deposit tx 2 (client 2, amount 2.25)                      2.25                 0              2.25  no\n\
+         final balance                                             2.25                 0              2.25  no\n"
+    );
+}
+
+#[test]
+fn settlements_get_their_own_line() {
+    let mut recorder = StatementRecorder::new(
+        EngineConfig {
+            settlement_delay: 1,
+            ..Default::default()
+        },
+        [2.into()],
+    );
+    recorder.apply_all(transactions().into_iter());
+    recorder.finish();
+    let statement = recorder.statements().next().unwrap();
+
+    assert_eq!(
+        statement.to_string(),
+        "Statement for client 2\n\
+         transaction                                          available              held             total  locked\n\
+         deposit tx 2 (client 2, amount 2.25)                         0                 0                 0  no\n\
+         settled deposit tx 2 (client 2, amount 2.25)              2.25                 0              2.25  no\n\
+         final balance                                             2.25                 0              2.25  no\n"
+    );
+}
+
+#[test]
+fn renders_as_html() {
+    let mut recorder = StatementRecorder::new(EngineConfig::default(), [2.into()]);
+    recorder.apply_all(transactions().into_iter());
+    let statement = recorder.statements().next().unwrap();
+
+    assert_eq!(
+        statement.html().to_string(),
+        "<section class=\"statement\">\n\
+         <h2>Statement for client 2</h2>\n\
+         <table>\n\
+         <tr><th>Transaction</th><th>Available</th><th>Held</th><th>Total</th><th>Locked</th></tr>\n\
+         <tr><td>deposit tx 2 (client 2, amount 2.25)</td><td>2.25</td><td>0</td><td>2.25</td><td>no</td></tr>\n\
+         <tr class=\"final\"><th>Final balance</th><td>2.25</td><td>0</td><td>2.25</td><td>no</td></tr>\n\
+         </table>\n\
+         </section>\n"
+    );
+}
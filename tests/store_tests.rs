@@ -0,0 +1,149 @@
+mod common;
+
+use common::account;
+use rust_coding_test::{
+    domain::{ClientId, Deposit, Dispute, Resolve, Transaction, TransactionId, Withdrawal},
+    engine::{
+        DepositHistory, DisputeHistoryStore, EngineConfig, Outcome, PaymentsEngine,
+        ReferencedDepositHistory,
+    },
+};
+use rust_decimal::dec;
+use std::collections::HashMap;
+
+/// Wraps the in-memory history and counts lookups, standing in for a custom backend.
+#[derive(Default)]
+struct CountingStore {
+    inner: DepositHistory,
+    lookups: usize,
+}
+
+impl DisputeHistoryStore for CountingStore {
+    fn insert(&mut self, deposit: Deposit) {
+        self.inner.insert(deposit);
+    }
+    fn contains(&self, tx_id: &TransactionId) -> bool {
+        self.inner.contains(tx_id)
+    }
+    fn get_mut(&mut self, tx_id: &TransactionId, client_id: &ClientId) -> Option<&mut Deposit> {
+        self.lookups += 1;
+        self.inner.get_mut(tx_id, client_id)
+    }
+}
+
+#[test]
+fn engine_runs_on_a_custom_store() {
+    let mut engine = PaymentsEngine::with_store(EngineConfig::default(), CountingStore::default());
+    engine.process_transactions(
+        vec![
+            Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+                .unwrap()
+                .into(),
+            Dispute::new(1.into(), 1.into()).into(),
+            Resolve::new(1.into(), 1.into()).into(),
+            Dispute::new(1.into(), 1.into()).into(),
+        ]
+        .into_iter(),
+    );
+
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(10.0), false))]);
+    assert_eq!(engine.client_accounts().as_map(), &expected);
+    assert_eq!(engine.deposit_history().lookups, 3);
+}
+
+#[test]
+fn in_memory_store_filters_by_client() {
+    let mut history = DepositHistory::new();
+    history.insert(Deposit::try_new(1.into(), 7.into(), dec!(1.0)).unwrap());
+
+    assert!(history.contains(&7.into()));
+    assert!(history.get_mut(&7.into(), &1.into()).is_some());
+    assert!(history.get_mut(&7.into(), &2.into()).is_none());
+    assert!(history.get_mut(&8.into(), &1.into()).is_none());
+}
+
+fn two_pass_stream() -> Vec<Transaction> {
+    vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(10.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(5.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 3.into(), dec!(7.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 4.into(), dec!(3.0))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 2.into()).into(),
+        Resolve::new(1.into(), 2.into()).into(),
+        Dispute::new(2.into(), 3.into()).into(),
+        // Disputes a tx that doesn't exist - referenced, but never stored.
+        Dispute::new(2.into(), 9.into()).into(),
+    ]
+}
+
+#[test]
+fn two_pass_store_keeps_only_referenced_deposits() {
+    let transactions = two_pass_stream();
+    let mut engine = PaymentsEngine::with_store(
+        EngineConfig::default(),
+        ReferencedDepositHistory::scan(transactions.iter().cloned()),
+    );
+    engine.process_transactions(transactions.into_iter());
+
+    let stored = engine.deposit_history().deposits().as_map();
+    assert_eq!(stored.len(), 2);
+    assert!(stored.contains_key(&2.into()));
+    assert!(stored.contains_key(&3.into()));
+}
+
+#[test]
+fn two_pass_store_matches_full_history() {
+    let transactions = two_pass_stream();
+    let mut two_pass = PaymentsEngine::with_store(
+        EngineConfig::default(),
+        ReferencedDepositHistory::scan(transactions.iter().cloned()),
+    );
+    let mut full = PaymentsEngine::new();
+    let outcomes: Vec<_> = transactions
+        .iter()
+        .map(|transaction| {
+            (
+                two_pass.apply(transaction.clone()),
+                full.apply(transaction.clone()),
+            )
+        })
+        .collect();
+
+    for (two_pass, full) in outcomes {
+        assert_eq!(two_pass, full);
+    }
+    assert_eq!(
+        two_pass.client_accounts().as_map(),
+        full.client_accounts().as_map()
+    );
+}
+
+/// Keeping memory O(disputes) means not recording every tx ID, so reuses aren't caught.
+#[test]
+fn two_pass_store_does_not_reject_reused_tx_ids() {
+    let transactions: Vec<Transaction> = vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(1.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 1.into(), dec!(2.0))
+            .unwrap()
+            .into(),
+    ];
+    let mut engine = PaymentsEngine::with_store(
+        EngineConfig::default(),
+        ReferencedDepositHistory::scan(transactions.iter().cloned()),
+    );
+
+    for transaction in transactions {
+        assert_eq!(engine.apply(transaction), Outcome::Applied);
+    }
+    assert!(!engine.deposit_history().contains_tx_id(&1.into()));
+}
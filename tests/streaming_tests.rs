@@ -0,0 +1,190 @@
+use rust_coding_test::{
+    domain::{Account, ClientId, Deposit, Dispute, Transaction, Withdrawal},
+    engine::{EngineConfig, Outcome, PaymentsEngine, errors::EngineError},
+    output::{AccountSink, OutputColumns, SequencedCsv},
+};
+use rust_decimal::{Decimal, dec};
+
+fn deposit(client: u16, tx: u32, value: Decimal) -> Transaction {
+    Deposit::try_new(client.into(), tx.into(), value)
+        .unwrap()
+        .into()
+}
+
+fn available(snapshot: &[(ClientId, Account)]) -> Vec<(ClientId, Decimal)> {
+    snapshot
+        .iter()
+        .map(|(client, account)| (*client, account.balance().available()))
+        .collect()
+}
+
+#[test]
+fn snapshots_can_be_interleaved_with_feeding() {
+    let mut engine = PaymentsEngine::new();
+    assert_eq!(engine.feed(deposit(2, 1, dec!(5))), Outcome::Applied);
+
+    let mut first = Vec::new();
+    engine.flush_accounts(&mut first).unwrap();
+    assert_eq!(available(&first), [(2.into(), dec!(5))]);
+
+    engine.feed(deposit(1, 2, dec!(3)));
+    assert!(matches!(
+        engine.feed(
+            Withdrawal::try_new(2.into(), 3.into(), dec!(9))
+                .unwrap()
+                .into()
+        ),
+        Outcome::Rejected(EngineError::InsufficientFunds { .. })
+    ));
+    engine.feed(Dispute::new(2.into(), 1.into()).into());
+
+    let mut second = Vec::new();
+    engine.flush_accounts(&mut second).unwrap();
+    assert_eq!(
+        available(&second),
+        [(1.into(), dec!(3)), (2.into(), dec!(0))]
+    );
+    assert_eq!(second[1].1.balance().held(), dec!(5));
+}
+
+#[test]
+fn flushing_settles_what_is_due() {
+    let mut engine = PaymentsEngine::with_config(EngineConfig {
+        settlement_delay: 1,
+        ..Default::default()
+    });
+    engine.feed(deposit(1, 1, dec!(4)));
+    // The deposit is due once this one was processed, but only settles with the next.
+    engine.feed(deposit(2, 2, dec!(1)));
+    assert_eq!(
+        engine
+            .client_accounts()
+            .get(1.into())
+            .unwrap()
+            .balance()
+            .pending(),
+        dec!(4)
+    );
+
+    let mut snapshot = Vec::new();
+    engine.flush_accounts(&mut snapshot).unwrap();
+    let balance = snapshot[0].1.balance();
+    assert_eq!((balance.available(), balance.pending()), (dec!(4), dec!(0)));
+}
+
+#[test]
+fn csv_sinks_write_the_header_once() {
+    let mut engine = PaymentsEngine::new();
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    engine.feed(deposit(1, 1, dec!(1.5)));
+    engine.flush_accounts(&mut writer).unwrap();
+    engine.feed(deposit(1, 2, dec!(1)));
+    engine.flush_accounts(&mut writer).unwrap();
+
+    assert_eq!(
+        String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+        "client,available,held,total,locked\n\
+         1,1.5,0,1.5,false\n\
+         1,2.5,0,2.5,false\n"
+    );
+}
+
+/// Counts snapshots and accounts, as a custom sink would forward them.
+#[derive(Default)]
+struct Counter {
+    accounts: usize,
+    snapshots: usize,
+}
+
+impl AccountSink for Counter {
+    type Error = std::convert::Infallible;
+
+    fn account(&mut self, _: ClientId, _: &Account) -> Result<(), Self::Error> {
+        self.accounts += 1;
+        Ok(())
+    }
+    fn end_snapshot(&mut self) -> Result<(), Self::Error> {
+        self.snapshots += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn custom_sinks_get_every_snapshot() {
+    let mut engine = PaymentsEngine::new();
+    let mut counter = Counter::default();
+    engine.flush_accounts(&mut counter).unwrap();
+    engine.feed(deposit(1, 1, dec!(1)));
+    engine.feed(deposit(2, 2, dec!(1)));
+    engine.flush_accounts(&mut counter).unwrap();
+
+    assert_eq!((counter.accounts, counter.snapshots), (2, 2));
+}
+
+fn clients(snapshot: &[(ClientId, Account)]) -> Vec<ClientId> {
+    snapshot.iter().map(|(client, _)| *client).collect()
+}
+
+#[test]
+fn deltas_only_send_changed_accounts() {
+    let mut engine = PaymentsEngine::new();
+    for client in 1..=3 {
+        engine.feed(deposit(client, client.into(), dec!(10)));
+    }
+    let mut first = Vec::new();
+    engine.flush_changed_accounts(&mut first).unwrap();
+    assert_eq!(clients(&first), [1.into(), 2.into(), 3.into()]);
+
+    engine.feed(
+        Withdrawal::try_new(1.into(), 4.into(), dec!(1))
+            .unwrap()
+            .into(),
+    );
+    // Neither the rejected withdrawal nor the ignored dispute changes an account.
+    engine.feed(
+        Withdrawal::try_new(2.into(), 5.into(), dec!(99))
+            .unwrap()
+            .into(),
+    );
+    engine.feed(Dispute::new(3.into(), 42.into()).into());
+    engine.feed(deposit(4, 6, dec!(1)));
+    let mut second = Vec::new();
+    engine.flush_changed_accounts(&mut second).unwrap();
+    assert_eq!(clients(&second), [1.into(), 4.into()]);
+    assert_eq!(second[0].1.balance().available(), dec!(9));
+
+    let mut unchanged = Vec::new();
+    engine.flush_changed_accounts(&mut unchanged).unwrap();
+    assert!(unchanged.is_empty());
+
+    // A full snapshot is a checkpoint too.
+    engine.feed(deposit(2, 7, dec!(1)));
+    engine.flush_accounts(&mut Vec::new()).unwrap();
+    let mut after_full = Vec::new();
+    engine.flush_changed_accounts(&mut after_full).unwrap();
+    assert!(after_full.is_empty());
+    assert_eq!(engine.checkpoint(), 5);
+}
+
+#[test]
+fn delta_rows_carry_their_sequence_number() {
+    let config = EngineConfig {
+        settlement_delay: 1,
+        ..Default::default()
+    };
+    let mut sink = SequencedCsv::with_columns(Vec::new(), OutputColumns::for_config(&config));
+    let mut engine = PaymentsEngine::with_config(config);
+    engine.feed(deposit(1, 1, dec!(4)));
+    engine.flush_changed_accounts(&mut sink).unwrap();
+    // Settling client 1's deposit on flush counts as a change.
+    engine.feed(deposit(2, 2, dec!(1)));
+    engine.flush_changed_accounts(&mut sink).unwrap();
+
+    assert_eq!(
+        String::from_utf8(sink.into_inner().unwrap()).unwrap(),
+        "sequence,client,available,held,total,locked,pending\n\
+         1,1,0,0,0,false,4\n\
+         2,1,4,0,4,false,0\n\
+         2,2,0,0,0,false,1\n"
+    );
+}
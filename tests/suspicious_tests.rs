@@ -0,0 +1,87 @@
+mod common;
+
+use common::account;
+use rust_coding_test::{
+    domain::{Chargeback, Deposit, Dispute, Resolve, Transaction, Withdrawal},
+    engine::{ApplyTransactions, EngineConfig},
+    suspicious::{
+        ActivitySummary, FrozenAccount, SuspiciousActivityRecorder, TriggeringChargeback,
+    },
+};
+use rust_decimal::dec;
+
+fn transactions() -> Vec<Transaction> {
+    vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(2.25))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 3.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Deposit::try_new(2.into(), 4.into(), dec!(1.5))
+            .unwrap()
+            .into(),
+        Dispute::new(1.into(), 2.into()).into(),
+        Resolve::new(1.into(), 2.into()).into(),
+        Dispute::new(1.into(), 1.into()).into(),
+        Chargeback::new(1.into(), 1.into()).into(),
+        // Already frozen: not a new trigger.
+        Dispute::new(1.into(), 2.into()).into(),
+        Chargeback::new(1.into(), 2.into()).into(),
+        // Open dispute only: not frozen.
+        Dispute::new(2.into(), 4.into()).into(),
+    ]
+}
+
+#[test]
+fn frozen_accounts_with_trigger_and_prior_activity() {
+    let mut recorder = SuspiciousActivityRecorder::new(EngineConfig::default());
+    recorder.apply_all(transactions().into_iter());
+
+    assert_eq!(
+        recorder.report().frozen_accounts,
+        vec![FrozenAccount {
+            client: 1.into(),
+            trigger: TriggeringChargeback {
+                tx: 1.into(),
+                amount: dec!(1.5),
+                sequence: 8,
+            },
+            prior_activity: ActivitySummary {
+                deposits: 2,
+                deposited: dec!(3.75),
+                withdrawals: 1,
+                withdrawn: dec!(1.5),
+                disputes: 2,
+                resolves: 1,
+                chargebacks: 0,
+            },
+            account: account(dec!(-1.5), dec!(0), true),
+        }]
+    );
+}
+
+#[test]
+fn nothing_frozen() {
+    let mut recorder = SuspiciousActivityRecorder::new(EngineConfig::default());
+    recorder.apply_all(transactions().into_iter().take(7));
+
+    assert!(recorder.report().frozen_accounts.is_empty());
+}
+
+#[test]
+fn serializes_as_json() {
+    let mut recorder = SuspiciousActivityRecorder::new(EngineConfig::default());
+    recorder.apply_all(transactions().into_iter());
+
+    let json = serde_json::to_value(recorder.report()).unwrap();
+    let frozen = &json["frozen_accounts"][0];
+    assert_eq!(frozen["client"], 1);
+    assert_eq!(frozen["trigger"]["tx"], 1);
+    assert_eq!(frozen["trigger"]["amount"], "1.5");
+    assert_eq!(frozen["prior_activity"]["deposited"], "3.75");
+    assert_eq!(frozen["account"]["locked"], true);
+}
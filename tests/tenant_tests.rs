@@ -0,0 +1,158 @@
+mod common;
+
+use common::account;
+use rust_coding_test::{
+    domain::{ClientId, Deposit, Dispute, TenantId, Withdrawal},
+    engine::MultiTenantEngine,
+    output,
+    parsing::{self, TenantTransaction},
+};
+use rust_decimal::dec;
+use std::collections::HashMap;
+
+fn tenant_tx(
+    tenant: Option<u16>,
+    transaction: rust_coding_test::domain::Transaction,
+) -> TenantTransaction {
+    TenantTransaction {
+        tenant: tenant.map(TenantId::from),
+        transaction,
+    }
+}
+
+/// The same client under two tenants has two independent accounts.
+#[test]
+fn same_client_is_isolated_between_tenants() {
+    let mut engine = MultiTenantEngine::new();
+    engine.process_transactions(
+        vec![
+            tenant_tx(
+                Some(1),
+                Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+                    .unwrap()
+                    .into(),
+            ),
+            tenant_tx(
+                Some(2),
+                Deposit::try_new(1.into(), 2.into(), dec!(50.0))
+                    .unwrap()
+                    .into(),
+            ),
+            tenant_tx(
+                Some(2),
+                Withdrawal::try_new(1.into(), 3.into(), dec!(20.0))
+                    .unwrap()
+                    .into(),
+            ),
+        ]
+        .into_iter(),
+    );
+
+    let tenant_1 = engine.engine(Some(1.into())).unwrap();
+    let tenant_2 = engine.engine(Some(2.into())).unwrap();
+
+    assert_eq!(
+        tenant_1.client_accounts().as_map(),
+        &HashMap::from_iter([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))])
+    );
+    assert_eq!(
+        tenant_2.client_accounts().as_map(),
+        &HashMap::from_iter([(ClientId::from(1), account(dec!(30.0), dec!(0.0), false))])
+    );
+}
+
+/// A dispute only sees the deposit history of its own tenant.
+#[test]
+fn dispute_does_not_reach_other_tenant_history() {
+    let mut engine = MultiTenantEngine::new();
+    engine.process_transactions(
+        vec![
+            tenant_tx(
+                Some(1),
+                Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+                    .unwrap()
+                    .into(),
+            ),
+            tenant_tx(Some(2), Dispute::new(1.into(), 1.into()).into()),
+        ]
+        .into_iter(),
+    );
+
+    let tenant_1 = engine.engine(Some(1.into())).unwrap();
+    let tenant_2 = engine.engine(Some(2.into())).unwrap();
+
+    assert_eq!(
+        tenant_1.client_accounts().as_map(),
+        &HashMap::from_iter([(ClientId::from(1), account(dec!(100.0), dec!(0.0), false))])
+    );
+    assert_eq!(
+        tenant_2.client_accounts().as_map(),
+        &HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(0.0), false))])
+    );
+}
+
+/// The tenant column is optional; rows without it land in the default namespace.
+#[test]
+fn tenant_column_is_optional() {
+    let input = "type,client,tx,amount,tenant\ndeposit,1,1,1.0,7\ndeposit,1,2,2.0,\n";
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+
+    let transactions = parsing::deserialize_tenant_csv(&mut rdr).collect::<Vec<_>>();
+
+    let expected = vec![
+        tenant_tx(
+            Some(7),
+            Deposit::try_new(1.into(), 1.into(), dec!(1.0))
+                .unwrap()
+                .into(),
+        ),
+        tenant_tx(
+            None,
+            Deposit::try_new(1.into(), 2.into(), dec!(2.0))
+                .unwrap()
+                .into(),
+        ),
+    ];
+    assert_eq!(transactions, expected);
+
+    let input = "type,client,tx,amount\ndeposit,1,1,1.0\n";
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+
+    let transactions = parsing::deserialize_tenant_csv(&mut rdr).collect::<Vec<_>>();
+
+    let expected = vec![tenant_tx(
+        None,
+        Deposit::try_new(1.into(), 1.into(), dec!(1.0))
+            .unwrap()
+            .into(),
+    )];
+    assert_eq!(transactions, expected);
+}
+
+#[test]
+fn tenant_output_has_tenant_column() -> anyhow::Result<()> {
+    let mut engine = MultiTenantEngine::new();
+    engine.process_transactions(
+        vec![tenant_tx(
+            Some(3),
+            Deposit::try_new(1.into(), 1.into(), dec!(1.5))
+                .unwrap()
+                .into(),
+        )]
+        .into_iter(),
+    );
+
+    let mut output = Vec::new();
+    output::print_tenant_accounts(&engine, &mut output)?;
+
+    assert_eq!(
+        String::from_utf8(output)?,
+        "tenant,client,available,held,total,locked\n3,1,1.5,0,1.5,false\n"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use rust_coding_test::{
+    domain::Transaction,
+    parsing::parse_bytes,
+    testgen::{GeneratedRow, Workload},
+};
+
+fn workload() -> Workload {
+    Workload {
+        clients: 20,
+        rows: 5000,
+        dispute_rate: 0.05,
+        invalid_rate: 0.02,
+        seed: 11,
+        ..Workload::default()
+    }
+}
+
+#[test]
+fn same_seed_same_transactions() {
+    assert_eq!(workload().to_vec(), workload().to_vec());
+
+    let other_seed = Workload {
+        seed: 12,
+        ..workload()
+    };
+    assert_ne!(workload().to_vec(), other_seed.to_vec());
+}
+
+#[test]
+fn rows_are_streamed_lazily() {
+    let huge = Workload {
+        rows: u32::MAX,
+        ..workload()
+    };
+    let rows = huge.rows();
+    assert_eq!(rows.len(), u32::MAX as usize);
+    assert_eq!(rows.take(10).count(), 10);
+}
+
+#[test]
+fn invalid_rows_only_in_rows() {
+    let rows: Vec<_> = workload().rows().collect();
+    let invalid = rows
+        .iter()
+        .filter(|row| matches!(row, GeneratedRow::Invalid(_)))
+        .count();
+
+    assert_eq!(rows.len(), 5000);
+    assert!(invalid > 0);
+    assert_eq!(workload().to_vec().len(), 5000 - invalid);
+}
+
+#[test]
+fn disputes_reference_earlier_deposits_of_the_client() {
+    let mut deposits = HashMap::new();
+    let mut disputes = 0;
+    for transaction in workload().transactions() {
+        match transaction {
+            Transaction::Deposit(deposit) => {
+                deposits.insert(deposit.transaction_id(), deposit.client_id());
+            }
+            Transaction::Dispute(dispute) => {
+                disputes += 1;
+                assert_eq!(
+                    deposits.get(&dispute.disputed_tx_id()),
+                    Some(&dispute.client_id())
+                );
+            }
+            _ => {}
+        }
+    }
+    assert!(disputes > 0);
+}
+
+#[test]
+fn csv_parses_back_to_the_same_transactions() {
+    let mut csv = Vec::new();
+    workload().write_csv(&mut csv).unwrap();
+
+    let (transactions, errors) = parse_bytes(&csv);
+    assert_eq!(transactions, workload().to_vec());
+    assert_eq!(transactions.len() + errors.len(), 5000);
+}
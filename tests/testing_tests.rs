@@ -0,0 +1,111 @@
+#![cfg(feature = "testing")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use proptest::prelude::*;
+use rust_coding_test::{
+    domain::{AMOUNT_MAX_SCALE, Amount, Transaction},
+    engine::PaymentsEngine,
+    parsing,
+    testing::strategies,
+};
+
+/// Movement amounts must be positive, so a valid generated transaction survives a
+/// serde round trip (deserialization re-runs the domain validation).
+fn assert_valid(transaction: &Transaction) {
+    let json = serde_json::to_string(transaction).unwrap();
+    assert_eq!(
+        &serde_json::from_str::<Transaction>(&json).unwrap(),
+        transaction
+    );
+}
+
+#[test]
+fn arbitrary_transactions_are_valid() {
+    let bytes = (0..4096u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect::<Vec<_>>();
+    let mut u = Unstructured::new(&bytes);
+
+    while !u.is_empty() {
+        let Ok(transaction) = Transaction::arbitrary(&mut u) else {
+            break;
+        };
+        assert_valid(&transaction);
+    }
+}
+
+#[test]
+fn arbitrary_amount_from_exhausted_input_is_zero() {
+    let mut u = Unstructured::new(&[]);
+
+    assert_eq!(Amount::arbitrary(&mut u).unwrap(), Amount::ZERO);
+}
+
+proptest! {
+    #[test]
+    fn generated_amounts_are_valid(amount in strategies::amount()) {
+        prop_assert!(amount.value() >= rust_decimal::Decimal::ZERO);
+        prop_assert!(amount.value().scale() <= AMOUNT_MAX_SCALE);
+    }
+
+    #[test]
+    fn generated_transactions_are_valid(transaction in strategies::transaction()) {
+        assert_valid(&transaction);
+    }
+
+    /// Streams over a handful of IDs hit every engine path without panicking.
+    #[test]
+    fn engine_accepts_generated_streams(
+        transactions in prop::collection::vec(strategies::transaction_in(1..=3, 1..=10), 0..64)
+    ) {
+        let mut engine = PaymentsEngine::new();
+        engine.process_transactions(transactions.into_iter());
+
+        for (_, account) in engine.client_accounts().iter() {
+            let balance = account.balance();
+            prop_assert_eq!(balance.total(), balance.available() + balance.held());
+            prop_assert_eq!(balance.gross_total(), balance.total() + balance.authorized());
+        }
+    }
+}
+
+proptest! {
+    /// `parse_bytes` is a fuzzing entry point: it must not panic on any input.
+    #[test]
+    fn parse_bytes_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+        let _ = parsing::parse_bytes(&bytes);
+    }
+
+    #[test]
+    fn parse_bytes_never_panics_on_csv_like_input(
+        input in "type,client,tx,amount(,tenant)?\n([a-z]{0,10},[-0-9]{0,8},[-0-9]{0,12},[-0-9eE.+ ]{0,32}(,[0-9]{0,6})?\n){0,8}"
+    ) {
+        let _ = parsing::parse_bytes(input.as_bytes());
+    }
+}
+
+proptest! {
+    /// Both parsing pipelines accept the same rows and produce identical transactions
+    /// (compared through `Display`, so amounts must also have the same scale).
+    #[test]
+    fn byte_record_parser_matches_serde(
+        input in "type,client,tx,amount,tenant\n((deposit|withdrawal|dispute|resolve|chargeback|authorization|capture|void|refund),[0-9]{1,5},[0-9]{1,3},(-?[0-9]{1,6}(\\.[0-9]{0,5})?)?,[0-9]{0,2}\n){0,10}"
+    ) {
+        let parse = |byte_records: bool| {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(input.as_bytes());
+            let transactions: Vec<_> = if byte_records {
+                parsing::deserialize_tenant_byte_records(&mut reader).collect()
+            } else {
+                parsing::deserialize_tenant_csv(&mut reader).collect()
+            };
+            transactions
+                .into_iter()
+                .map(|t| format!("{:?} {}", t.tenant, t.transaction))
+                .collect::<Vec<_>>()
+        };
+
+        prop_assert_eq!(parse(true), parse(false));
+    }
+}
@@ -0,0 +1,125 @@
+use rust_coding_test::{
+    domain::{Deposit, Transaction, Withdrawal},
+    engine::{
+        AccountTiers, EngineConfig, PaymentsEngine, RollingReserve, TierError, TierLimits,
+        errors::EngineError,
+    },
+};
+use rust_decimal::{Decimal, dec};
+
+mod common;
+use common::amount;
+
+const TIERS: &str = r#"{
+    "tiers": {
+        "basic": { "max_withdrawal": "10" },
+        "gold": { "overdraft": "5", "reserve_rate": "0.5" }
+    },
+    "clients": { "1": "basic", "2": "gold" }
+}"#;
+
+fn tiered() -> PaymentsEngine {
+    PaymentsEngine::with_config(EngineConfig {
+        tiers: AccountTiers::from_reader(TIERS.as_bytes()).unwrap(),
+        rolling_reserve: RollingReserve {
+            rate: dec!(0),
+            delay: 10,
+        },
+        ..Default::default()
+    })
+}
+
+fn deposit(client: u16, tx: u32, value: Decimal) -> Transaction {
+    Deposit::try_new(client.into(), tx.into(), value)
+        .unwrap()
+        .into()
+}
+
+fn withdrawal(client: u16, tx: u32, value: Decimal) -> Transaction {
+    Withdrawal::try_new(client.into(), tx.into(), value)
+        .unwrap()
+        .into()
+}
+
+#[test]
+fn withdrawals_above_the_tier_cap_are_rejected() {
+    let mut engine = tiered();
+    engine.process_transaction(deposit(1, 1, dec!(50))).unwrap();
+
+    assert_eq!(
+        engine.process_transaction(withdrawal(1, 2, dec!(10.5))),
+        Err(EngineError::WithdrawalLimitExceeded {
+            client: 1.into(),
+            tx: 2.into(),
+            requested: amount(dec!(10.5)),
+            limit: amount(dec!(10)),
+        })
+    );
+    engine
+        .process_transaction(withdrawal(1, 3, dec!(10)))
+        .unwrap();
+    // Clients without a tier have no cap.
+    engine.process_transaction(deposit(3, 4, dec!(50))).unwrap();
+    engine
+        .process_transaction(withdrawal(3, 5, dec!(50)))
+        .unwrap();
+}
+
+#[test]
+fn overdraft_lets_withdrawals_go_negative_up_to_the_limit() {
+    let mut engine = tiered();
+    engine.process_transaction(deposit(2, 1, dec!(4))).unwrap();
+    let balance = engine.client_accounts().get(2.into()).unwrap().balance();
+    assert_eq!((balance.available(), balance.reserve()), (dec!(2), dec!(2)));
+
+    engine
+        .process_transaction(withdrawal(2, 2, dec!(6.5)))
+        .unwrap();
+    assert!(matches!(
+        engine.process_transaction(withdrawal(2, 3, dec!(1))),
+        Err(EngineError::InsufficientFunds { .. })
+    ));
+    let balance = engine.client_accounts().get(2.into()).unwrap().balance();
+    assert_eq!(balance.available(), dec!(-4.5));
+}
+
+#[test]
+fn tiers_can_be_changed_through_the_engine() {
+    let mut engine = tiered();
+    engine.process_transaction(deposit(1, 1, dec!(50))).unwrap();
+
+    engine.set_client_tier(1.into(), "gold").unwrap();
+    engine
+        .process_transaction(withdrawal(1, 2, dec!(52)))
+        .unwrap();
+    assert_eq!(engine.config().tiers.tier(1.into()), Some("gold"));
+
+    assert!(matches!(
+        engine.set_client_tier(1.into(), "platinum"),
+        Err(TierError::UnknownTier(tier)) if tier == "platinum"
+    ));
+}
+
+#[test]
+fn invalid_tier_files_are_rejected() {
+    assert!(matches!(
+        AccountTiers::from_reader(r#"{ "clients": { "1": "gold" } }"#.as_bytes()),
+        Err(TierError::UnknownTier(_))
+    ));
+    assert!(matches!(
+        AccountTiers::from_reader(
+            r#"{ "tiers": { "gold": { "reserve_rate": "1.5" } } }"#.as_bytes()
+        ),
+        Err(TierError::InvalidReserveRate { .. })
+    ));
+    assert!(matches!(
+        AccountTiers::from_reader(r#"{ "tiers": { "gold": { "overdraft": "-1" } } }"#.as_bytes()),
+        Err(TierError::Json(_))
+    ));
+
+    let mut tiers = AccountTiers::new();
+    tiers.define("gold", TierLimits::default()).unwrap();
+    tiers.assign(7.into(), "gold").unwrap();
+    assert_eq!(tiers.limits(7.into()), Some(&TierLimits::default()));
+    assert_eq!(tiers.limits(8.into()), None);
+}
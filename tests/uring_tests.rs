@@ -0,0 +1,77 @@
+#![cfg(feature = "io-uring")]
+
+use rust_coding_test::parsing;
+use std::io::Read;
+
+/// Writes `bytes` to a file private to this test, reads it back with `read` and cleans up.
+fn round_trip<T>(
+    name: &str,
+    bytes: &[u8],
+    read: impl FnOnce(Box<dyn Read + Send>) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let path = std::env::temp_dir().join(format!("uring_tests_{name}_{}.csv", std::process::id()));
+    std::fs::write(&path, bytes)?;
+    let result = read(parsing::open_uring(&path)?);
+    std::fs::remove_file(path)?;
+    result
+}
+
+fn read_all(mut input: Box<dyn Read + Send>) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Not a multiple of any block size, so the last block is partial.
+fn patterned(len: usize) -> Vec<u8> {
+    (0..len).map(|index| (index % 251) as u8).collect()
+}
+
+#[test]
+fn reads_files_of_any_size() -> anyhow::Result<()> {
+    for (name, len) in [
+        ("empty", 0),
+        ("small", 10),
+        ("one_block", 1 << 20),
+        ("many_blocks", (11 << 19) + 7),
+    ] {
+        let bytes = patterned(len);
+        assert_eq!(round_trip(name, &bytes, read_all)?, bytes, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn small_reads_see_every_byte() -> anyhow::Result<()> {
+    let bytes = patterned((3 << 20) + 1);
+
+    let read = round_trip("small_reads", &bytes, |mut input| {
+        let mut read = Vec::new();
+        let mut buffer = [0; 4093];
+        loop {
+            let len = input.read(&mut buffer)?;
+            if len == 0 {
+                return Ok(read);
+            }
+            read.extend_from_slice(&buffer[..len]);
+        }
+    })?;
+
+    assert_eq!(read, bytes);
+    Ok(())
+}
+
+#[test]
+fn parses_like_in_memory_input() -> anyhow::Result<()> {
+    let input = b"type,client,tx,amount\ndeposit,1,1,2.5\ndispute,1,1,\nwithdrawal,2,2,1.0\n";
+
+    let transactions = round_trip("parse", input, |input| {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(input);
+        Ok(parsing::deserialize_csv(&mut reader).collect::<Vec<_>>())
+    })?;
+
+    assert_eq!(transactions, parsing::parse_bytes(input).0);
+    Ok(())
+}
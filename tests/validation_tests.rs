@@ -0,0 +1,225 @@
+use rust_coding_test::{
+    domain::{Amount, Deposit, DomainError, Transaction, Withdrawal},
+    parsing::{self, ParseError, ParseFailure, ParseFailureCounts},
+};
+use rust_decimal::dec;
+
+#[test]
+fn zero_and_negative_amounts_are_rejected() {
+    assert!(matches!(
+        Deposit::try_new(1.into(), 1.into(), dec!(0.0)),
+        Err(DomainError::NonPositiveAmount)
+    ));
+    assert!(matches!(
+        Withdrawal::try_new(1.into(), 1.into(), dec!(-1.0)),
+        Err(DomainError::NonPositiveAmount)
+    ));
+}
+
+/// Spec: "You can assume a precision of four places past the decimal".
+#[test]
+fn more_than_four_decimal_places_is_rejected() {
+    assert!(matches!(
+        Deposit::try_new(1.into(), 1.into(), dec!(1.00001)),
+        Err(DomainError::ExcessivePrecision)
+    ));
+    assert!(matches!(
+        Withdrawal::try_new(1.into(), 1.into(), dec!(0.12345)),
+        Err(DomainError::ExcessivePrecision)
+    ));
+}
+
+#[test]
+fn four_decimal_places_and_trailing_zeros_are_accepted() {
+    assert!(Deposit::try_new(1.into(), 1.into(), dec!(0.1234)).is_ok());
+    // trailing zeros don't add precision
+    assert!(Withdrawal::try_new(1.into(), 1.into(), dec!(1.500000)).is_ok());
+}
+
+/// Rows with invalid amounts are skipped by the CSV parser like any other malformed row.
+#[test]
+fn parser_skips_rows_with_invalid_amounts() {
+    let input = "type,client,tx,amount\n\
+                 deposit,1,1,-5.0\n\
+                 deposit,1,2,1.23456\n\
+                 withdrawal,1,3,0\n\
+                 deposit,1,4,2.0\n";
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+
+    let transactions = parsing::deserialize_csv(&mut rdr).collect::<Vec<_>>();
+
+    let expected: Vec<Transaction> = vec![
+        Deposit::try_new(1.into(), 4.into(), dec!(2.0))
+            .unwrap()
+            .into(),
+    ];
+    assert_eq!(transactions, expected);
+}
+
+#[test]
+fn deserialized_transactions_are_validated() {
+    let result = serde_json::from_str::<Transaction>(
+        r#"{"type":"deposit","client":1,"tx":1,"amount":"-1.0"}"#,
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn amount_allows_zero_but_not_negative() {
+    assert_eq!(Amount::try_from(dec!(0.0)).unwrap(), Amount::ZERO);
+    assert!(matches!(
+        Amount::try_from(dec!(-0.0001)),
+        Err(DomainError::NegativeAmount)
+    ));
+    assert!(matches!(
+        Amount::try_from(dec!(0.00001)),
+        Err(DomainError::ExcessivePrecision)
+    ));
+    assert_eq!(
+        Amount::try_from(dec!(1.2345)).unwrap().value(),
+        dec!(1.2345)
+    );
+}
+
+#[test]
+fn parse_bytes_reports_skipped_rows() {
+    let input = b"type,client,tx,amount\n\
+                  deposit,1,1,2.0\n\
+                  deposit,1,2,\n\
+                  transfer,1,3,1.0\n\
+                  withdrawal,1,4,1.23456\n\
+                  authorization,1,5,\n";
+
+    let (transactions, errors) = parsing::parse_bytes(input);
+
+    let expected: Vec<Transaction> = vec![
+        Deposit::try_new(1.into(), 1.into(), dec!(2.0))
+            .unwrap()
+            .into(),
+    ];
+    assert_eq!(transactions, expected);
+    // unknown types and over-precise amounts already fail while deserializing the row
+    assert!(matches!(
+        errors.as_slice(),
+        [
+            ParseError::Invalid { row: 2, .. },
+            ParseError::Malformed(_),
+            ParseError::Malformed(_),
+            ParseError::Invalid { row: 5, .. },
+        ]
+    ));
+}
+
+#[test]
+fn parse_failures_are_categorized() {
+    let input = b"type,client,tx,amount\n\
+                  deposit,1,1,2.0\n\
+                  transfer,1,2,1.0\n\
+                  deposit,x,3,1.0\n\
+                  deposit,1,4,abc\n\
+                  deposit,1,5,\n\
+                  withdrawal,1,6,1.23456\n\
+                  deposit,1,7,-1.0\n\
+                  deposit,1,8\n\
+                  deposit,1,9,1.0,extra\n";
+
+    let (_, errors) = parsing::parse_bytes(input);
+
+    let failures: Vec<_> = errors.iter().map(ParseError::failure).collect();
+    assert_eq!(
+        failures,
+        vec![
+            ParseFailure::UnknownType,
+            ParseFailure::MalformedNumber,
+            ParseFailure::MalformedNumber,
+            ParseFailure::MissingAmount,
+            ParseFailure::InvalidAmount,
+            ParseFailure::InvalidAmount,
+            ParseFailure::WrongColumnCount,
+            ParseFailure::WrongColumnCount,
+        ]
+    );
+}
+
+#[test]
+fn parse_failure_counts_skip_and_tally_rows() {
+    let input = "type,client,tx,amount\n\
+                 deposit,1,1,2.0\n\
+                 transfer,1,2,1.0\n\
+                 bogus,1,3,1.0\n\
+                 deposit,1,4,\n";
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+    let mut counts = ParseFailureCounts::default();
+
+    let transactions: Vec<_> = parsing::try_deserialize_csv(&mut reader)
+        .filter_map(|row| counts.count(row))
+        .collect();
+
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(counts.total(), 3);
+    assert_eq!(counts.get(ParseFailure::UnknownType), 2);
+    assert_eq!(counts.get(ParseFailure::MissingAmount), 1);
+    assert_eq!(counts.get(ParseFailure::WrongColumnCount), 0);
+    assert_eq!(
+        counts.iter().collect::<Vec<_>>(),
+        vec![
+            (ParseFailure::UnknownType, 2),
+            (ParseFailure::MissingAmount, 1)
+        ]
+    );
+}
+
+/// Garbage input yields errors, never a panic.
+#[test]
+fn parse_bytes_survives_pathological_input() {
+    let inputs: [&[u8]; 6] = [
+        b"",
+        b"\xff\xfe\x00",
+        b"type,client,tx,amount\n\"deposit,1,1,1.0\n",
+        b"type,client,tx,amount\ndeposit,1,1\ndeposit,1,1,1.0,2,3\n",
+        b"type,client,tx,amount\ndeposit,99999999,-1,NaN\ndeposit,1,1,1e308\n",
+        b"type,client,tx,amount\ndeposit,1,1,\xc3\x28\n",
+    ];
+
+    for input in inputs {
+        let (transactions, _) = parsing::parse_bytes(input);
+        assert!(transactions.is_empty());
+    }
+}
+
+#[test]
+fn try_deserialize_yields_an_error_per_invalid_row() {
+    let input = "type,client,tx,amount\n\
+                 deposit,1,1,-5.0\n\
+                 deposit,1,2,2.0\n\
+                 deposit,x,3,1.0\n\
+                 withdrawal,1,4,\n";
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+
+    let results = parsing::try_deserialize_csv(&mut rdr).collect::<Vec<_>>();
+
+    assert_eq!(results.len(), 4);
+    assert!(matches!(
+        results[0],
+        Err(ParseError::Invalid { row: 1, .. })
+    ));
+    assert_eq!(
+        results[1].as_ref().unwrap(),
+        &Transaction::from(Deposit::try_new(1.into(), 2.into(), dec!(2.0)).unwrap())
+    );
+    assert!(matches!(
+        &results[2],
+        Err(ParseError::Malformed(e)) if e.position().is_some_and(|position| position.line() == 4)
+    ));
+    assert!(matches!(
+        results[3],
+        Err(ParseError::Invalid { row: 4, .. })
+    ));
+}
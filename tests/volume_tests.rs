@@ -0,0 +1,134 @@
+use rust_coding_test::{
+    engine::{ApplyTransactions, EngineConfig},
+    parsing::{TimestampedTransaction, deserialize_timestamped_csv},
+    volume::{BucketWidth, Volume, VolumeAggregator},
+};
+use rust_decimal::dec;
+
+const INPUT: &str = "\
+type,client,tx,amount,timestamp
+deposit,1,1,1.5,3600
+deposit,1,2,2.25,7199
+withdrawal,1,3,1.5,7200
+withdrawal,1,4,100,7300
+deposit,2,5,1.5,90000
+deposit,2,6,1.5,
+dispute,2,5,,90001
+";
+
+fn transactions(input: &str) -> Vec<TimestampedTransaction> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+    deserialize_timestamped_csv(&mut reader).collect()
+}
+
+fn aggregate(width: BucketWidth) -> VolumeAggregator {
+    let mut aggregator = VolumeAggregator::new(EngineConfig::default(), width);
+    aggregator.apply_all(transactions(INPUT).into_iter());
+    aggregator
+}
+
+#[test]
+fn timestamp_column_is_optional() {
+    let timestamps: Vec<_> = transactions(INPUT).iter().map(|t| t.timestamp).collect();
+    assert_eq!(
+        timestamps,
+        vec![
+            Some(3600),
+            Some(7199),
+            Some(7200),
+            Some(7300),
+            Some(90000),
+            None,
+            Some(90001)
+        ]
+    );
+
+    let without_column = transactions("type,client,tx,amount\ndeposit,1,1,1.5\n");
+    assert_eq!(without_column.len(), 1);
+    assert_eq!(without_column[0].timestamp, None);
+}
+
+#[test]
+fn hourly_buckets_per_client() {
+    let aggregator = aggregate(BucketWidth::Hourly);
+
+    let buckets: Vec<_> = aggregator
+        .buckets()
+        .iter()
+        .map(|((start, client), volume)| (*start, u16::from(*client), volume.clone()))
+        .collect();
+    assert_eq!(
+        buckets,
+        vec![
+            (
+                3600,
+                1,
+                Volume {
+                    deposits: dec!(3.75),
+                    deposit_count: 2,
+                    ..Volume::default()
+                }
+            ),
+            (
+                7200,
+                1,
+                Volume {
+                    withdrawals: dec!(1.5),
+                    withdrawal_count: 1,
+                    ..Volume::default()
+                }
+            ),
+            (
+                90000,
+                2,
+                Volume {
+                    deposits: dec!(1.5),
+                    deposit_count: 1,
+                    ..Volume::default()
+                }
+            ),
+        ]
+    );
+    // The rejected withdrawal is left out; the untimestamped deposit is still applied.
+    assert_eq!(aggregator.untimestamped(), 1);
+    assert_eq!(
+        aggregator
+            .engine()
+            .client_accounts()
+            .get(2.into())
+            .unwrap()
+            .balance()
+            .held(),
+        dec!(1.5)
+    );
+}
+
+#[test]
+fn daily_report_csv() {
+    let mut output = Vec::new();
+    aggregate(BucketWidth::Daily)
+        .write_report(&mut output)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "bucket_start,client,deposits,deposit_count,withdrawals,withdrawal_count\n\
+         0,1,3.75,2,1.5,1\n\
+         86400,2,1.5,1,0,0\n"
+    );
+}
+
+#[test]
+fn empty_report_has_header() {
+    let mut output = Vec::new();
+    VolumeAggregator::new(EngineConfig::default(), BucketWidth::Daily)
+        .write_report(&mut output)
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "bucket_start,client,deposits,deposit_count,withdrawals,withdrawal_count\n"
+    );
+}
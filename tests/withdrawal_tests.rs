@@ -10,11 +10,15 @@ use std::collections::HashMap;
 #[test]
 fn withdrawal_reduces_available_balance() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(100.0)).into(),
-        Withdrawal::new(1.into(), 2.into(), dec!(40.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(100.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 2.into(), dec!(40.0))
+            .unwrap()
+            .into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(60.0), dec!(0.0), false))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(60.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -23,11 +27,15 @@ fn withdrawal_reduces_available_balance() {
 #[test]
 fn withdrawal_of_exact_balance_leaves_zero() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(50.0)).into(),
-        Withdrawal::new(1.into(), 2.into(), dec!(50.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(50.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 2.into(), dec!(50.0))
+            .unwrap()
+            .into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(0.0), dec!(0.0), false))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -37,11 +45,15 @@ fn withdrawal_of_exact_balance_leaves_zero() {
 #[test]
 fn withdrawal_exceeding_balance_is_ignored() {
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(30.0)).into(),
-        Withdrawal::new(1.into(), 2.into(), dec!(100.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(30.0))
+            .unwrap()
+            .into(),
+        Withdrawal::try_new(1.into(), 2.into(), dec!(100.0))
+            .unwrap()
+            .into(),
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(30.0), dec!(0.0), false))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(30.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -50,9 +62,13 @@ fn withdrawal_exceeding_balance_is_ignored() {
 /// The withdrawal itself fails (insufficient funds), but the account exists.
 #[test]
 fn withdrawal_without_prior_deposit_is_ignored() {
-    let engine = run(vec![Withdrawal::new(1.into(), 1.into(), dec!(10.0)).into()]);
+    let engine = run(vec![
+        Withdrawal::try_new(1.into(), 1.into(), dec!(10.0))
+            .unwrap()
+            .into(),
+    ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(0.0), dec!(0.0), false))]);
+    let expected = HashMap::from_iter([(ClientId::from(1), account(dec!(0.0), dec!(0.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -64,13 +80,20 @@ fn withdrawal_fails_when_available_reduced_by_held_funds() {
     // Deposit 100, dispute 80 → available = 20, held = 80, total = 100.
     // Withdraw 50 must fail because available (20) < 50.
     let engine = run(vec![
-        Deposit::new(1.into(), 1.into(), dec!(80.0)).into(),
-        Deposit::new(1.into(), 2.into(), dec!(20.0)).into(),
+        Deposit::try_new(1.into(), 1.into(), dec!(80.0))
+            .unwrap()
+            .into(),
+        Deposit::try_new(1.into(), 2.into(), dec!(20.0))
+            .unwrap()
+            .into(),
         Dispute::new(1.into(), 1.into()).into(),
-        Withdrawal::new(1.into(), 3.into(), dec!(50.0)).into(), // must be rejected
+        Withdrawal::try_new(1.into(), 3.into(), dec!(50.0))
+            .unwrap()
+            .into(), // must be rejected
     ]);
 
-    let expected = HashMap::from([(ClientId::from(1), account(dec!(20.0), dec!(80.0), false))]);
+    let expected =
+        HashMap::from_iter([(ClientId::from(1), account(dec!(20.0), dec!(80.0), false))]);
 
     assert_eq!(engine.client_accounts().as_map(), &expected);
 }
@@ -0,0 +1,94 @@
+#![cfg(feature = "zip")]
+
+use std::{
+    io::{Cursor, Write},
+    process::Command,
+};
+
+use rust_coding_test::{
+    domain::{Deposit, Dispute, Transaction},
+    parsing::ZipInput,
+};
+use rust_decimal::dec;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+fn archive(members: &[(&str, &str)]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, contents) in members {
+        writer.start_file(*name, options).unwrap();
+        writer.write_all(contents.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn members_are_read_in_name_order() {
+    let bytes = archive(&[
+        ("2024-01-02.csv", "type,client,tx,amount\ndispute,1,1,\n"),
+        ("readme.txt", "not transactions"),
+        (
+            "2024-01-01.CSV",
+            "type, client, tx, amount, tenant\ndeposit, 1, 1, 2.5, 7\nbogus,1,2,1,\n",
+        ),
+        ("old/", ""),
+    ]);
+    let input = ZipInput::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(input.members(), ["2024-01-01.CSV", "2024-01-02.csv"]);
+
+    let transactions = input
+        .tenant_transactions()
+        .map(|tenant_transaction| (tenant_transaction.tenant, tenant_transaction.transaction))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        transactions,
+        [
+            (
+                Some(7.into()),
+                Transaction::from(Deposit::try_new(1.into(), 1.into(), dec!(2.5)).unwrap())
+            ),
+            (None, Dispute::new(1.into(), 1.into()).into()),
+        ]
+    );
+}
+
+#[test]
+fn archives_without_csv_members_are_refused() {
+    assert!(ZipInput::new(Cursor::new(archive(&[("notes.txt", "")]))).is_err());
+    assert!(ZipInput::new(Cursor::new(b"type,client,tx,amount\n".to_vec())).is_err());
+}
+
+#[test]
+fn cli_processes_an_archive_as_one_run() {
+    let path = std::env::temp_dir().join(format!("zip_tests_{}.zip", std::process::id()));
+    std::fs::write(
+        &path,
+        archive(&[
+            ("b.csv", "type,client,tx,amount\nwithdrawal,1,3,4\n"),
+            (
+                "a.csv",
+                "type,client,tx,amount\ndeposit,1,1,5\ndeposit,2,2,1\n",
+            ),
+        ]),
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg(&path)
+        .output()
+        .unwrap();
+    let two_pass = Command::new(env!("CARGO_BIN_EXE_rust_coding_test"))
+        .arg(&path)
+        .arg("--two-pass")
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "client,available,held,total,locked\n\
+         1,1,0,1,false\n\
+         2,1,0,1,false\n"
+    );
+    assert!(!two_pass.status.success());
+}